@@ -11,9 +11,11 @@
 
 pub extern crate cpp_to_rust_common as common;
 use common::errors::{fancy_unwrap, ChainErr, Result};
-use common::cpp_build_config::{CppBuildConfig, CppBuildPaths, CppLibraryType};
+use common::cpp_build_config::{CppBuildConfig, CppBuildPaths, CppLibraryType, CppTypeSizesMode,
+                               TYPE_SIZES_MARKER};
 use common::BuildScriptData;
-use common::file_utils::{PathBufWithAdded, load_json, create_file, file_to_string, path_to_str};
+use common::file_utils::{PathBufWithAdded, load_json, create_file, file_to_string, path_to_str,
+                         open_file};
 use common::cpp_lib_builder::{CppLibBuilder, CMakeVar, BuildType};
 use common::target::current_target;
 use common::utils::{get_command_output, exe_suffix};
@@ -21,6 +23,7 @@ use common::log;
 
 use std::path::PathBuf;
 use std::process::Command;
+use std::io::Read;
 
 /// Configuration of the build script.
 #[derive(Debug)]
@@ -44,6 +47,60 @@ fn build_script_data() -> Result<BuildScriptData> {
   load_json(manifest_dir()?.with_added("build_script_data.json"))
 }
 
+/// Returns true if the crate is being built for a platform
+/// other than the host platform.
+fn is_cross_compiling() -> Result<bool> {
+  let target = std::env::var("TARGET")
+    .chain_err(|| "TARGET env var is missing")?;
+  let host = std::env::var("HOST")
+    .chain_err(|| "HOST env var is missing")?;
+  Ok(target != host)
+}
+
+/// Extracts type sizes data produced by the C++ code generated by
+/// `cpp_to_rust_generator::cpp_code_generator::generate_cpp_type_size_requester`
+/// from `data` and returns content of `type_sizes.rs`.
+/// `data` may be the output of the type sizes program or the content
+/// of the type sizes static library.
+fn type_sizes_code(data: &[u8]) -> Result<String> {
+  let marker = TYPE_SIZES_MARKER.as_bytes();
+  let start = data
+    .windows(marker.len())
+    .position(|window| window == marker)
+    .chain_err(|| "type sizes data not found")? + marker.len();
+  let end = data[start..]
+    .iter()
+    .position(|&byte| byte == 0)
+    .map(|pos| start + pos)
+    .unwrap_or(data.len());
+  let text = std::str::from_utf8(&data[start..end])
+    .chain_err(|| "type sizes data is not valid UTF-8")?;
+  let mut code = String::new();
+  for line in text.lines() {
+    let parts: Vec<_> = line.split(' ').collect();
+    if parts.len() != 4 {
+      return Err(format!("invalid type sizes line: {:?}", line).into());
+    }
+    let size = u64::from_str_radix(parts[2], 16)
+      .chain_err(|| format!("invalid size: {:?}", line))?;
+    let align = u64::from_str_radix(parts[3], 16)
+      .chain_err(|| format!("invalid alignment: {:?}", line))?;
+    // the alignment type is a braced struct because a unit struct
+    // would also define a value that could clash with a size constant
+    // (e.g. `FOO_ALIGN` of `Foo` and `FOO_ALIGN` of `FooAlign`)
+    code.push_str(&format!("pub const {}: usize = {};\n\
+                            #[allow(non_camel_case_types)]\n\
+                            #[derive(Clone, Copy, Debug)]\n\
+                            #[repr(C, align({}))]\n\
+                            pub struct {} {{}}\n",
+                           parts[0],
+                           size,
+                           align,
+                           parts[1]));
+  }
+  Ok(code)
+}
+
 impl Config {
   /// Constructs default configuration state based on
   /// information in the generated `build_script_data.json` file
@@ -100,6 +157,16 @@ impl Config {
       .build_script_data
       .cpp_build_config
       .eval(&current_target())?;
+    let type_sizes_mode = match cpp_build_config_data.type_sizes_mode() {
+      Some(mode) => mode,
+      None => {
+        if is_cross_compiling()? {
+          CppTypeSizesMode::ReadObjectFile
+        } else {
+          CppTypeSizesMode::RunBinary
+        }
+      }
+    };
     let mut cmake_vars = Vec::new();
     cmake_vars.push(CMakeVar::new("C2R_LIBRARY_TYPE",
                                   match cpp_build_config_data.library_type() {
@@ -117,6 +184,11 @@ impl Config {
                                        cpp_build_config_data.linked_frameworks())?);
     cmake_vars.push(CMakeVar::new("C2R_COMPILER_FLAGS",
                                   cpp_build_config_data.compiler_flags().join(" ")));
+    cmake_vars.push(CMakeVar::new("C2R_TYPE_SIZES_MODE",
+                                  match type_sizes_mode {
+                                    CppTypeSizesMode::RunBinary => "RUN_BINARY",
+                                    CppTypeSizesMode::ReadObjectFile => "READ_OBJECT_FILE",
+                                  }));
     let out_dir = out_dir()?;
     let c_lib_install_dir = out_dir.with_added("c_lib_install");
     let manifest_dir = manifest_dir()?;
@@ -155,11 +227,33 @@ impl Config {
     }
     {
       log::status("Requesting type sizes");
-      let mut command = Command::new(c_lib_install_dir
-                                       .with_added("lib")
-                                       .with_added(format!("type_sizes{}", exe_suffix())));
+      let lib_dir = c_lib_install_dir.with_added("lib");
+      let code = match type_sizes_mode {
+        CppTypeSizesMode::RunBinary => {
+          let mut command = Command::new(lib_dir.with_added(format!("type_sizes{}",
+                                                                    exe_suffix())));
+          type_sizes_code(get_command_output(&mut command)?.as_bytes())?
+        }
+        CppTypeSizesMode::ReadObjectFile => {
+          let path = ["libtype_sizes.a", "type_sizes.lib"]
+            .iter()
+            .map(|name| lib_dir.with_added(name))
+            .find(|path| path.exists())
+            .chain_err(|| "type sizes library not found")?;
+          let mut data = Vec::new();
+          open_file(&path)?
+            .into_file()
+            .read_to_end(&mut data)
+            .chain_err(|| format!("failed to read {}", path.display()))?;
+          type_sizes_code(&data).chain_err(|| {
+              format!("failed to read type sizes from {} (the library must not be \
+                       compiled with link-time optimization)",
+                      path.display())
+            })?
+        }
+      };
       let mut file = create_file(out_dir.with_added("type_sizes.rs"))?;
-      file.write(get_command_output(&mut command)?)?;
+      file.write(code)?;
     }
 
     for name in cpp_build_config_data.linked_libs() {
@@ -212,3 +306,6 @@ pub fn run() -> ! {
   let config = fancy_unwrap(Config::new());
   config.run()
 }
+
+#[cfg(test)]
+mod tests;
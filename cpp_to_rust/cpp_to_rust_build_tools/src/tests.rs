@@ -0,0 +1,141 @@
+use common::cpp_build_config::TYPE_SIZES_MARKER;
+use common::file_utils::{create_dir_all, create_file, PathBufWithAdded};
+use common::string_utils::JoinWithSeparator;
+use common::utils::get_command_output;
+use type_sizes_code;
+
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Creates an empty temporary directory for the test named `name`.
+fn temp_dir(name: &str) -> PathBuf {
+  let dir = ::std::env::temp_dir().with_added(format!("cpp_to_rust_build_tools_{}", name));
+  if dir.exists() {
+    ::std::fs::remove_dir_all(&dir).unwrap();
+  }
+  create_dir_all(&dir).unwrap();
+  dir
+}
+
+/// Output of the type sizes program generated by
+/// `cpp_to_rust_generator::cpp_code_generator::generate_cpp_type_size_requester`.
+fn program_output() -> String {
+  format!("{}NS_POINT NS_POINT_ALIGN 00000010 00000008\n\
+           BYTE BYTE_ALIGN 00000001 00000001\n",
+          TYPE_SIZES_MARKER)
+}
+
+#[test]
+fn missing_marker() {
+  let data = "NS_POINT NS_POINT_ALIGN 00000010 00000008\n\0";
+  assert!(type_sizes_code(data.as_bytes()).is_err());
+  assert!(type_sizes_code(&TYPE_SIZES_MARKER.as_bytes()[1..]).is_err());
+  assert!(type_sizes_code(b"").is_err());
+}
+
+#[test]
+fn invalid_field_count() {
+  for line in &["NS_POINT 00000010 00000008",
+                "NS_POINT NS_POINT_ALIGN 00000010 00000008 00000001",
+                "NS_POINT  NS_POINT_ALIGN 00000010 00000008"] {
+    let data = format!("{}{}\n\0", TYPE_SIZES_MARKER, line);
+    assert!(type_sizes_code(data.as_bytes()).is_err());
+  }
+}
+
+#[test]
+fn invalid_hex() {
+  for line in &["NS_POINT NS_POINT_ALIGN 0000001g 00000008",
+                "NS_POINT NS_POINT_ALIGN 00000010 -",
+                "NS_POINT NS_POINT_ALIGN  00000008"] {
+    let data = format!("{}{}\n\0", TYPE_SIZES_MARKER, line);
+    assert!(type_sizes_code(data.as_bytes()).is_err());
+  }
+}
+
+#[test]
+fn data_end() {
+  // the output of the program is not terminated
+  let code = type_sizes_code(program_output().as_bytes()).unwrap();
+  assert!(code.contains("pub const BYTE: usize = 1;\n"));
+  // the data in a library is followed by unrelated bytes
+  let mut data = b"\x7fELF\0\x01".to_vec();
+  data.extend_from_slice(program_output().as_bytes());
+  data.extend_from_slice(b"\0\x01\x02 \n");
+  assert_eq!(type_sizes_code(&data).unwrap(), code);
+  // empty data is allowed
+  assert_eq!(type_sizes_code(TYPE_SIZES_MARKER.as_bytes()).unwrap(), "");
+}
+
+#[test]
+fn round_trip() {
+  let code = type_sizes_code(program_output().as_bytes()).unwrap();
+  assert_eq!(code,
+             "pub const NS_POINT: usize = 16;\n\
+              #[allow(non_camel_case_types)]\n\
+              #[derive(Clone, Copy, Debug)]\n\
+              #[repr(C, align(8))]\n\
+              pub struct NS_POINT_ALIGN {}\n\
+              pub const BYTE: usize = 1;\n\
+              #[allow(non_camel_case_types)]\n\
+              #[derive(Clone, Copy, Debug)]\n\
+              #[repr(C, align(1))]\n\
+              pub struct BYTE_ALIGN {}\n");
+
+  // the same data compiled into an object file like the generated `type_sizes.cpp` does it
+  let dir = temp_dir("round_trip");
+  let cpp_path = dir.with_added("type_sizes.cpp");
+  create_file(&cpp_path)
+    .unwrap()
+    .write(format!("extern \"C\" const char cpp_to_rust_type_sizes[] = {{\n  {},\n  '\\0'\n}};\n",
+                   program_output()
+                     .chars()
+                     .map(|c| if c == '\n' {
+                            "'\\n'".to_string()
+                          } else {
+                            format!("'{}'", c)
+                          })
+                     .join(", ")))
+    .unwrap();
+  let object_path = dir.with_added("type_sizes.o");
+  // `-fno-lto` must override link-time optimization flags passed by the user
+  get_command_output(Command::new("c++")
+                       .arg("-c")
+                       .arg("-O2")
+                       .arg("-flto")
+                       .arg("-fno-lto")
+                       .arg(&cpp_path)
+                       .arg("-o")
+                       .arg(&object_path))
+      .unwrap();
+  let mut data = Vec::new();
+  File::open(&object_path)
+    .unwrap()
+    .read_to_end(&mut data)
+    .unwrap();
+  assert_eq!(type_sizes_code(&data).unwrap(), code);
+}
+
+#[test]
+fn align_type_doesnt_clash_with_size_constant() {
+  // `Foo` has alignment type `FOO_ALIGN`, and `FooAlign` has size constant `FOO_ALIGN`
+  let data = format!("{}FOO FOO_ALIGN 00000004 00000004\n\
+                      FOO_ALIGN FOO_ALIGN_ALIGN 00000008 00000008\n\0",
+                     TYPE_SIZES_MARKER);
+  let code = type_sizes_code(data.as_bytes()).unwrap();
+  assert!(code.contains("pub const FOO_ALIGN: usize = 8;\n"));
+  assert!(code.contains("pub struct FOO_ALIGN {}\n"));
+
+  let dir = temp_dir("align_type_clash");
+  let path = dir.with_added("type_sizes.rs");
+  create_file(&path).unwrap().write(code).unwrap();
+  get_command_output(Command::new("rustc")
+                       .arg("--crate-type")
+                       .arg("lib")
+                       .arg("--out-dir")
+                       .arg(&dir)
+                       .arg(&path))
+      .unwrap();
+}
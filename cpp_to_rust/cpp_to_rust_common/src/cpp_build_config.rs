@@ -42,6 +42,30 @@ pub enum CppLibraryType {
   Static,
 }
 
+/// Text preceding the type sizes data in the output of the type sizes program
+/// and in the compiled static library.
+pub const TYPE_SIZES_MARKER: &'static str = "cpp_to_rust_type_sizes_data\n";
+
+/// Method of determining sizes and alignments of C++ types
+/// in the build script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub enum CppTypeSizesMode {
+  /// Build and execute a C++ program that prints the sizes.
+  /// This doesn't work when cross-compiling because the program
+  /// can't be executed on the host.
+  RunBinary,
+  /// Compile the sizes into a static library and read them back
+  /// from the library file. No target code is executed, so this mode
+  /// works when cross-compiling.
+  ///
+  /// The data is searched for in the raw bytes of the library, so it can't be found
+  /// if the library contains link-time optimization bitcode instead of machine code.
+  /// For this reason, the build script disables link-time optimization
+  /// (`-flto` or MSVC's `/GL`) for this library regardless of the compiler flags.
+  ReadObjectFile,
+}
+
 /// Platform-specific information
 /// required to build the C++ wrapper library.
 /// This type contains one configuration item of `CppBuildConfig`.
@@ -52,6 +76,7 @@ pub struct CppBuildConfigData {
   linked_frameworks: Vec<String>,
   compiler_flags: Vec<String>,
   library_type: Option<CppLibraryType>,
+  type_sizes_mode: Option<CppTypeSizesMode>,
 }
 
 impl CppBuildConfigData {
@@ -91,6 +116,13 @@ impl CppBuildConfigData {
     self.library_type = Some(t);
   }
 
+  /// Sets method of determining C++ type sizes. By default,
+  /// `CppTypeSizesMode::ReadObjectFile` is used when cross-compiling,
+  /// and `CppTypeSizesMode::RunBinary` is used otherwise.
+  pub fn set_type_sizes_mode(&mut self, mode: CppTypeSizesMode) {
+    self.type_sizes_mode = Some(mode);
+  }
+
   /// Returns names of linked libraries.
   pub fn linked_libs(&self) -> &[String] {
     &self.linked_libs
@@ -111,6 +143,11 @@ impl CppBuildConfigData {
     self.library_type
   }
 
+  /// Returns method of determining C++ type sizes, if it was set.
+  pub fn type_sizes_mode(&self) -> Option<CppTypeSizesMode> {
+    self.type_sizes_mode
+  }
+
   fn add_from(&mut self, other: &CppBuildConfigData) -> Result<()> {
     self.linked_libs.append(&mut other.linked_libs.clone());
     self
@@ -126,6 +163,13 @@ impl CppBuildConfigData {
     } else {
       self.library_type = other.library_type;
     }
+    if self.type_sizes_mode.is_some() {
+      if other.type_sizes_mode.is_some() && other.type_sizes_mode != self.type_sizes_mode {
+        return Err("conflicting type sizes modes specified".into());
+      }
+    } else {
+      self.type_sizes_mode = other.type_sizes_mode;
+    }
     Ok(())
   }
 }
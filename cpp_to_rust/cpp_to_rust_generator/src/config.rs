@@ -3,7 +3,7 @@
 use std::path::PathBuf;
 use common::errors::Result;
 use cpp_method::CppMethod;
-use cpp_data::ParserCppData;
+use cpp_data::{ParserCppData, CppConstant};
 pub use cpp_data::CppTypeAllocationPlace;
 use common::cpp_build_config::CppBuildConfig;
 use std::collections::HashMap;
@@ -20,6 +20,17 @@ impl ::std::fmt::Debug for CppFfiGeneratorFilter {
   }
 }
 
+/// Function type used in `Config::add_constant_filter`.
+pub type CppConstantFilterFn = Fn(&CppConstant) -> Result<bool>;
+
+struct CppConstantFilter(Box<CppConstantFilterFn>);
+
+impl ::std::fmt::Debug for CppConstantFilter {
+  fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::result::Result<(), ::std::fmt::Error> {
+    write!(f, "CppConstantFilter")
+  }
+}
+
 /// Function type used in `Config::add_cpp_data_filter`.
 pub type CppDataFilterFn = Fn(&mut ParserCppData) -> Result<()>;
 
@@ -224,6 +235,87 @@ impl Default for DebugLoggingConfig {
   }
 }
 
+/// Ownership and nullability of a raw C++ pointer used as
+/// an argument or a return value of a method.
+/// See `Config::add_pointer_annotation`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PointerAnnotation {
+  /// The pointer may be null. `T*` is presented as
+  /// `Option<&mut T>` (or `Option<&T>` for `const T*`).
+  Nullable,
+  /// The pointer is never null, and the object is owned by someone else.
+  /// `T*` is presented as `&mut T` (or `&T` for `const T*`).
+  Borrowed,
+  /// Ownership of the object is transferred along with the pointer:
+  /// to the caller for return values and to the callee for arguments.
+  /// `T*` is presented as `CppBox<T>`. The class must have a public destructor.
+  Owned,
+}
+
+/// Position of the pointer a `PointerAnnotation` applies to.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum PointerAnnotationTarget {
+  /// Argument of the C++ method with the specified index (starting with 0).
+  /// `this` pointer is not counted.
+  Argument(usize),
+  /// Return value of the C++ method
+  ReturnValue,
+}
+
+/// Pointer annotations of a single C++ method.
+pub type MethodPointerAnnotations = HashMap<PointerAnnotationTarget, PointerAnnotation>;
+
+/// Source of the lifetime of a reference returned by a method.
+/// See `Config::set_return_value_lifetime`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ReturnValueLifetime {
+  /// The returned reference borrows from `self`.
+  OfSelf,
+  /// The returned reference borrows from the argument with the specified index
+  /// (starting with 0). `this` pointer is not counted.
+  OfArgument(usize),
+  /// The returned reference is valid for the whole run of the program (`'static`).
+  Static,
+}
+
+/// Policy of marking generated Rust methods as `unsafe`.
+/// See `Config::set_unsafe_policy`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum UnsafePolicy {
+  /// All methods are marked as unsafe.
+  AllUnsafe,
+  /// Methods taking raw pointers are marked as unsafe.
+  Heuristic,
+  /// Methods taking raw pointers and methods that weren't added
+  /// with `Config::add_safe_method` are marked as unsafe.
+  HeuristicWithAllowlist,
+}
+
+impl Default for UnsafePolicy {
+  fn default() -> UnsafePolicy {
+    UnsafePolicy::Heuristic
+  }
+}
+
+/// Traits generated for a container-like C++ class.
+/// See `Config::set_container_traits`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ContainerTraits {
+  /// The class is not treated as a container.
+  Disabled,
+  /// Only `cpp_utils::container` traits and `IntoIterator` are implemented.
+  IteratorsOnly,
+  /// `Extend` and `FromIterator` are also implemented if the class
+  /// has `append` or `push_back` method and a default constructor.
+  All,
+}
+
+impl Default for ContainerTraits {
+  fn default() -> ContainerTraits {
+    ContainerTraits::All
+  }
+}
+
 /// The starting point of `cpp_to_rust` API.
 /// Create a `Config` object, set its properties,
 /// add custom functions if necessary, and start
@@ -244,6 +336,22 @@ pub struct Config {
   cpp_parser_arguments: Vec<String>,
   cpp_parser_blocked_names: Vec<String>,
   cpp_ffi_generator_filters: Vec<CppFfiGeneratorFilter>,
+  catch_exceptions: bool,
+  catch_exceptions_filters: Vec<CppFfiGeneratorFilter>,
+  subclassing_classes: Vec<String>,
+  template_instantiations: Vec<String>,
+  enums_as_newtypes: bool,
+  flags_typedefs: HashMap<String, String>,
+  preserved_type_aliases: Vec<String>,
+  skip_deprecated: bool,
+  pointer_annotations: HashMap<String, MethodPointerAnnotations>,
+  return_value_lifetimes: HashMap<String, ReturnValueLifetime>,
+  unsafe_policy: UnsafePolicy,
+  safe_methods: Vec<String>,
+  debug_methods: Vec<String>,
+  eq_classes: Vec<String>,
+  container_traits: HashMap<String, ContainerTraits>,
+  constant_filters: Vec<CppConstantFilter>,
   cpp_data_filters: Vec<CppDataFilter>,
   cpp_filtered_namespaces: Vec<String>,
   cpp_build_config: CppBuildConfig, // TODO: add CppBuildPaths when needed
@@ -278,6 +386,22 @@ impl Config {
       cpp_parser_arguments: Default::default(),
       cpp_parser_blocked_names: Default::default(),
       cpp_ffi_generator_filters: Default::default(),
+      catch_exceptions: false,
+      catch_exceptions_filters: Default::default(),
+      subclassing_classes: Default::default(),
+      template_instantiations: Default::default(),
+      enums_as_newtypes: false,
+      flags_typedefs: Default::default(),
+      preserved_type_aliases: Default::default(),
+      skip_deprecated: false,
+      pointer_annotations: Default::default(),
+      return_value_lifetimes: Default::default(),
+      unsafe_policy: UnsafePolicy::default(),
+      safe_methods: Default::default(),
+      debug_methods: Default::default(),
+      eq_classes: Default::default(),
+      container_traits: Default::default(),
+      constant_filters: Default::default(),
       cpp_data_filters: Default::default(),
       cpp_filtered_namespaces: Default::default(),
       cpp_build_config: Default::default(),
@@ -428,6 +552,156 @@ impl Config {
     self.include_directives.push(path.into());
   }
 
+  /// Enables or disables generating C++ enums as newtype structs.
+  /// By default, each C++ enum is represented by a Rust enum,
+  /// and receiving a value that doesn't correspond to any of its variants
+  /// from C++ results in undefined behavior. If enabled, each C++ enum
+  /// is represented by a transparent struct containing the integer value,
+  /// and C++ enum variants are available as associated constants of the struct.
+  /// Any integer value is valid for such struct.
+  /// The generated crate requires Rust 1.28 or later because
+  /// the struct uses `#[repr(transparent)]`.
+  pub fn set_enums_as_newtypes(&mut self, value: bool) {
+    self.enums_as_newtypes = value;
+  }
+
+  /// Declares that the integer typedef `typedef_name` holds
+  /// an OR-combination of values of the C++ enum `enum_name`,
+  /// e.g. `config.add_flags_typedef("VkBufferUsageFlags", "VkBufferUsageFlagBits")`.
+  /// Arguments and return values of this type will be represented by
+  /// `cpp_utils::flags::Flags<E>` in the Rust API, where `E` is the Rust enum
+  /// corresponding to `enum_name`. Qt's `QFlags<E>` is converted the same way
+  /// without any configuration. `Flags<E>` stores values as `c_int`, so typedefs
+  /// and enums wider than 32 bits are left as plain integers.
+  pub fn add_flags_typedef<S1: Into<String>, S2: Into<String>>(&mut self,
+                                                               typedef_name: S1,
+                                                               enum_name: S2) {
+    self
+      .flags_typedefs
+      .insert(typedef_name.into(), enum_name.into());
+  }
+
+  /// Enables or disables skipping C++ types, methods, enum variants and other
+  /// entities marked as deprecated (e.g. with `[[deprecated]]` or
+  /// `Q_DECL_DEPRECATED`). By default, deprecated entities are processed
+  /// and marked with `#[deprecated]` attribute in the generated Rust code.
+  pub fn set_skip_deprecated(&mut self, value: bool) {
+    self.skip_deprecated = value;
+  }
+
+  /// Requests to use the C++ typedef or `using` alias `name`
+  /// (e.g. `"QStringList"`) in the Rust API instead of its target type.
+  /// All C++ type aliases are generated as Rust type aliases, but
+  /// method arguments and return values use target types
+  /// unless the alias was added with this function.
+  pub fn add_preserved_type_alias<S: Into<String>>(&mut self, name: S) {
+    self.preserved_type_aliases.push(name.into());
+  }
+
+  /// Sets ownership and nullability of a raw pointer argument or return value
+  /// of the C++ method with signature `method_signature`.
+  /// The signature consists of the method's full name and argument types,
+  /// e.g. `"QWidget::setLayout(QLayout*)"` or `"QObject::parent() const"`.
+  /// Without an annotation, raw pointers are presented as `*mut T` or `*const T`
+  /// in the Rust API, making the method unsafe.
+  pub fn add_pointer_annotation<S: Into<String>>(&mut self,
+                                                 method_signature: S,
+                                                 target: PointerAnnotationTarget,
+                                                 annotation: PointerAnnotation) {
+    self
+      .pointer_annotations
+      .entry(method_signature.into())
+      .or_insert_with(Default::default)
+      .insert(target, annotation);
+  }
+
+  /// Sets the lifetime of the reference returned by the C++ method with
+  /// signature `method_signature` (see `Config::add_pointer_annotation`
+  /// for the signature format).
+  ///
+  /// By default, references returned by class methods borrow from `self`,
+  /// and references returned by free functions and static methods borrow
+  /// from all reference arguments. If a free function or a static method
+  /// has no reference arguments, its return type is kept as a raw pointer
+  /// unless a lifetime is set using this function.
+  pub fn set_return_value_lifetime<S: Into<String>>(&mut self,
+                                                    method_signature: S,
+                                                    lifetime: ReturnValueLifetime) {
+    self
+      .return_value_lifetimes
+      .insert(method_signature.into(), lifetime);
+  }
+
+  /// Sets the policy of marking generated Rust methods as `unsafe`.
+  /// Default value is `UnsafePolicy::Heuristic`. Regardless of the policy,
+  /// documentation of each unsafe method explains why it's unsafe.
+  /// Trait implementations (e.g. `Drop`, casts or operators) are not affected
+  /// by the policy: they are generated from methods that don't take raw pointers,
+  /// and their methods are safe.
+  pub fn set_unsafe_policy(&mut self, value: UnsafePolicy) {
+    self.unsafe_policy = value;
+  }
+
+  /// Adds a C++ method that is considered safe when
+  /// `UnsafePolicy::HeuristicWithAllowlist` is used. `method` can be either
+  /// the method's full name (e.g. `"QString::size"`), which applies to all
+  /// overloads, or its signature (e.g. `"QString::size() const"`,
+  /// see `Config::add_pointer_annotation` for the signature format).
+  /// The method will still be unsafe if it takes a raw pointer.
+  pub fn add_safe_method<S: Into<String>>(&mut self, method: S) {
+    self.safe_methods.push(method.into());
+  }
+
+  /// Adds multiple safe methods. See `Config::add_safe_method`.
+  pub fn add_safe_methods<Item, Iter>(&mut self, items: Iter)
+    where Item: Into<String>,
+          Iter: IntoIterator<Item = Item>
+  {
+    for item in items {
+      self.safe_methods.push(item.into());
+    }
+  }
+
+  /// Adds a C++ method that should be used to implement `Debug`
+  /// for its class. `method_signature` has the same format as in
+  /// `Config::add_pointer_annotation` (e.g. `"QUrl::toString() const"`).
+  /// The method must be a const class method without arguments, and
+  /// the Rust type of its return value must implement `Debug`.
+  ///
+  /// Classes without a configured method implement `Debug` using
+  /// `operator<<(std::ostream&, const T&)` if the library declares it.
+  pub fn add_debug_method<S: Into<String>>(&mut self, method_signature: S) {
+    self.debug_methods.push(method_signature.into());
+  }
+
+  /// Adds a C++ class (e.g. `"QUrl"`) for which `Eq` should be implemented
+  /// in addition to `PartialEq` generated from its `operator==`.
+  /// The generator can't verify that `operator==` is an equivalence
+  /// relation, so `Eq` is not implemented for any class by default.
+  /// Classes containing floating point values are still skipped.
+  /// The value applies to all instantiations of a template class.
+  pub fn add_eq_class<S: Into<String>>(&mut self, class_name: S) {
+    self.eq_classes.push(class_name.into());
+  }
+
+  /// Sets traits generated for the C++ class `class_name` (e.g. `"QList"`;
+  /// the value applies to all instantiations of a template class).
+  ///
+  /// Classes that have `size()` and `at(index)` const methods are treated
+  /// as containers. They implement `cpp_utils::container::CppContainer`
+  /// and `IntoIterator` for references to the class. A non-const
+  /// `operator[]` or `at` allows to iterate over mutable references, and an `append`
+  /// or `push_back` method allows to implement `Extend` and `FromIterator`.
+  ///
+  /// Classes without these methods are treated as containers if they have
+  /// `begin()` and `end()` const methods returning a stack-allocated iterator class
+  /// with `operator*`, prefix `operator++` and `operator==`. They implement
+  /// `cpp_utils::container::CppIterable` and `IntoIterator` for const references.
+  /// By default, `ContainerTraits::All` is used for all classes.
+  pub fn set_container_traits<S: Into<String>>(&mut self, class_name: S, value: ContainerTraits) {
+    self.container_traits.insert(class_name.into(), value);
+  }
+
   /// Adds a custom function that decides whether a C++ method should be
   /// added to the C++ wrapper library. For each C++ method,
   /// each function will be run once. Filters are executed in the same order they
@@ -448,6 +722,103 @@ impl Config {
       .push(CppFfiGeneratorFilter(Box::new(f)));
   }
 
+  /// Enables or disables catching C++ exceptions in the wrapper library.
+  /// If enabled, each FFI function catches all exceptions thrown by the
+  /// wrapped C++ method and reports them to the Rust side, and
+  /// the corresponding Rust method returns `Result<T, cpp_utils::CppException>`.
+  /// Methods declared with `noexcept` or `throw()` are not affected.
+  /// Use `Config::add_catch_exceptions_filter` to enable catching exceptions
+  /// only for some methods. Catching exceptions is disabled by default.
+  pub fn set_catch_exceptions(&mut self, value: bool) {
+    self.catch_exceptions = value;
+  }
+
+  /// Adds a custom function that decides whether exceptions thrown
+  /// by a C++ method should be caught. Filters are only used if catching
+  /// exceptions is enabled with `Config::set_catch_exceptions`.
+  /// Filters are executed in the same order they were added.
+  ///
+  /// Interpretation of the function's output:
+  ///
+  /// - `Err` indicates an unexpected failure and terminates the processing.
+  /// - `Ok(true)` allows to continue processing of the method.
+  /// If all functions return `Ok(true)`, exceptions are caught for this method.
+  /// - `Ok(false)` disables catching exceptions for the method.
+  /// Remaining filter functions are not run on this method.
+  pub fn add_catch_exceptions_filter<F>(&mut self, f: F)
+    where F: Fn(&CppMethod) -> Result<bool> + 'static
+  {
+    self
+      .catch_exceptions_filters
+      .push(CppFfiGeneratorFilter(Box::new(f)));
+  }
+
+  /// Enables subclassing of a polymorphic C++ class from Rust.
+  ///
+  /// The generator will produce a C++ subclass of `class_name` that overrides
+  /// all its virtual methods and forwards them to Rust callbacks, and
+  /// a Rust trait that should be implemented to provide the overrides.
+  /// Protected methods of the class are made available on the subclass,
+  /// so they can be called from the overriding methods.
+  ///
+  /// If a pure virtual method of the class can't be overridden in Rust
+  /// (e.g. because of an unsupported argument type), the subclass can't
+  /// be created, so the class is skipped and an error is logged.
+  pub fn add_subclassing_class<S: Into<String>>(&mut self, class_name: S) {
+    self.subclassing_classes.push(class_name.into());
+  }
+
+  /// Enables subclassing of multiple classes. See `Config::add_subclassing_class`.
+  pub fn add_subclassing_classes<Item, Iter>(&mut self, items: Iter)
+    where Item: Into<String>,
+          Iter: IntoIterator<Item = Item>
+  {
+    for item in items {
+      self.subclassing_classes.push(item.into());
+    }
+  }
+
+  /// Requests an instantiation of a C++ template class, e.g. `"QVector<QPoint>"`
+  /// or `"QHash<QString, int>"`.
+  ///
+  /// The generator only finds template instantiations that are used in
+  /// the library's API. This method allows to add instantiations that are not
+  /// used anywhere in the library. Requested instantiations are processed the same way as
+  /// found instantiations: methods of the template class are instantiated, and
+  /// a Rust wrapper type is generated for it.
+  ///
+  /// `cpp_type` must be valid C++ code of the type in the global namespace.
+  /// All types used in it must be declared in headers added with
+  /// `Config::add_include_directive`.
+  pub fn add_template_instantiation<S: Into<String>>(&mut self, cpp_type: S) {
+    self.template_instantiations.push(cpp_type.into());
+  }
+
+  /// Requests multiple template instantiations.
+  /// See `Config::add_template_instantiation`.
+  pub fn add_template_instantiations<Item, Iter>(&mut self, items: Iter)
+    where Item: Into<String>,
+          Iter: IntoIterator<Item = Item>
+  {
+    for item in items {
+      self.template_instantiations.push(item.into());
+    }
+  }
+
+  /// Adds a custom function that decides whether a C++ constant should be
+  /// added to the generated crate as a Rust `const` item.
+  ///
+  /// The parser collects object-like macros and `constexpr` variables
+  /// that evaluate to an integer, a floating point number or a string literal.
+  /// Filters are executed in the same order they were added.
+  /// If any function returns `Ok(false)`, the constant is skipped.
+  /// `Err` terminates the processing.
+  pub fn add_constant_filter<F>(&mut self, f: F)
+    where F: Fn(&CppConstant) -> Result<bool> + 'static
+  {
+    self.constant_filters.push(CppConstantFilter(Box::new(f)));
+  }
+
   /// Adds a custom function that visits `&mut CppData` and can perform any changes
   /// in the output of the C++ parser. Filters are executed in the same order they
   /// were added. If the function returns `Err`, the processing is terminated.
@@ -623,6 +994,96 @@ impl Config {
       .collect()
   }
 
+  /// Returns value set by `Config::set_enums_as_newtypes`.
+  pub fn enums_as_newtypes(&self) -> bool {
+    self.enums_as_newtypes
+  }
+
+  /// Returns values added by `Config::add_flags_typedef`.
+  /// Keys of the hash map are typedef names, and values are enum names.
+  pub fn flags_typedefs(&self) -> &HashMap<String, String> {
+    &self.flags_typedefs
+  }
+
+  /// Returns value set by `Config::set_skip_deprecated`.
+  pub fn skip_deprecated(&self) -> bool {
+    self.skip_deprecated
+  }
+
+  /// Returns values added by `Config::add_preserved_type_alias`.
+  pub fn preserved_type_aliases(&self) -> &Vec<String> {
+    &self.preserved_type_aliases
+  }
+
+  /// Returns values added by `Config::add_pointer_annotation`.
+  /// Keys of the hash map are method signatures.
+  pub fn pointer_annotations(&self) -> &HashMap<String, MethodPointerAnnotations> {
+    &self.pointer_annotations
+  }
+
+  /// Returns values set by `Config::set_return_value_lifetime`.
+  /// Keys of the hash map are method signatures.
+  pub fn return_value_lifetimes(&self) -> &HashMap<String, ReturnValueLifetime> {
+    &self.return_value_lifetimes
+  }
+
+  /// Returns value set by `Config::set_unsafe_policy`.
+  pub fn unsafe_policy(&self) -> UnsafePolicy {
+    self.unsafe_policy
+  }
+
+  /// Returns values added by `Config::add_safe_method`.
+  pub fn safe_methods(&self) -> &Vec<String> {
+    &self.safe_methods
+  }
+
+  /// Returns values added by `Config::add_debug_method`.
+  pub fn debug_methods(&self) -> &Vec<String> {
+    &self.debug_methods
+  }
+
+  /// Returns values added by `Config::add_eq_class`.
+  pub fn eq_classes(&self) -> &Vec<String> {
+    &self.eq_classes
+  }
+
+  /// Returns values set by `Config::set_container_traits`.
+  /// Keys of the hash map are C++ class names.
+  pub fn container_traits(&self) -> &HashMap<String, ContainerTraits> {
+    &self.container_traits
+  }
+
+  /// Returns value set by `Config::set_catch_exceptions`.
+  pub fn catch_exceptions(&self) -> bool {
+    self.catch_exceptions
+  }
+
+  /// Returns values added by `Config::add_catch_exceptions_filter`.
+  pub fn catch_exceptions_filters(&self) -> Vec<&Box<CppFfiGeneratorFilterFn>> {
+    self
+      .catch_exceptions_filters
+      .iter()
+      .map(|x| &x.0)
+      .collect()
+  }
+
+  /// Returns names added with `Config::add_subclassing_class`
+  /// and similar methods.
+  pub fn subclassing_classes(&self) -> &[String] {
+    &self.subclassing_classes
+  }
+
+  /// Returns types added with `Config::add_template_instantiation`
+  /// and similar methods.
+  pub fn template_instantiations(&self) -> &[String] {
+    &self.template_instantiations
+  }
+
+  /// Returns values added by `Config::add_constant_filter`.
+  pub fn constant_filters(&self) -> Vec<&Box<CppConstantFilterFn>> {
+    self.constant_filters.iter().map(|x| &x.0).collect()
+  }
+
   pub fn has_cpp_data_filters(&self) -> bool {
     !self.cpp_data_filters.is_empty()
   }
@@ -1,12 +1,15 @@
 use cpp_ffi_data::{QtSlotWrapper, CppIndirectionChange, CppAndFfiMethod, CppFfiArgumentMeaning,
-                   CppFfiHeaderData, CppFfiType, CppFieldAccessorType, CppFfiMethodKind};
-use cpp_method::ReturnValueAllocationPlace;
+                   CppFfiHeaderData, CppFfiType, CppFieldAccessorType, CppFfiMethodKind,
+                   CppSubclassWrapper};
+use cpp_method::{ReturnValueAllocationPlace, CppMethodArgument};
+use cpp_operator::CppOperator;
 use cpp_type::{CppTypeIndirection, CppTypeBase, CppType};
 use common::errors::{Result, ChainErr, unexpected};
 use common::file_utils::{PathBufWithAdded, create_dir_all, create_file, path_to_str};
 use common::string_utils::JoinWithSeparator;
 use common::utils::MapIfOk;
 use common::utils::get_command_output;
+use common::cpp_build_config::TYPE_SIZES_MARKER;
 
 use std::path::PathBuf;
 use std::iter::once;
@@ -43,12 +46,7 @@ impl CppCodeGenerator {
     }
     let name_with_args = format!("{}({})", method.c_name, arg_texts.join(", "));
     let return_type = &method.c_signature.return_type.ffi_type;
-    let r = if let CppTypeBase::FunctionPointer(..) = return_type.base {
-      return_type.to_cpp_code(Some(&name_with_args))?
-    } else {
-      format!("{} {}", return_type.to_cpp_code(None)?, name_with_args)
-    };
-    Ok(r)
+    return_type.to_cpp_declaration(&name_with_args)
   }
 
   /// Generates method declaration for the header.
@@ -70,9 +68,7 @@ impl CppCodeGenerator {
       .arguments
       .iter()
       .enumerate()
-      .map_if_ok(|(num, t)| -> Result<_> {
-                   Ok(format!("{} arg{}", t.original_type.to_cpp_code(None)?, num))
-                 })?
+      .map_if_ok(|(num, t)| t.original_type.to_cpp_declaration(&format!("arg{}", num)))?
       .join(", ");
     let func_args = once("m_data".to_string())
       .chain(wrapper
@@ -92,6 +88,184 @@ impl CppCodeGenerator {
 
   }
 
+  /// Generates code for a subclass wrapper
+  fn subclass_wrapper(&self, wrapper: &CppSubclassWrapper) -> Result<String> {
+    let base_class_name = wrapper.base_class.to_cpp_code()?;
+    let arg_declarations = |arguments: &[CppMethodArgument]| -> Result<String> {
+      Ok(arguments
+           .iter()
+           .enumerate()
+           .map_if_ok(|(num, arg)| -> Result<_> {
+                        arg.argument_type.to_cpp_declaration(&format!("arg{}", num))
+                      })?
+           .join(", "))
+    };
+    let arg_names = |count: usize| (0..count).map(|num| format!("arg{}", num)).join(", ");
+
+    let field_initializers = once("m_data(0)".to_string())
+      .chain(once("m_deleter(0)".to_string()))
+      .chain(wrapper
+               .virtual_methods
+               .iter()
+               .map(|m| format!("m_{}(0)", m.key)))
+      .join(", ");
+    let mut constructors = String::new();
+    for arguments in &wrapper.constructors {
+      // a separate constructor is generated for each number of omitted arguments
+      // with default values, as for methods with omitted arguments
+      let mut count = arguments.len();
+      loop {
+        constructors.push_str(&format!("  {}({}) : {}({}), {} {{ }}\n",
+                                       wrapper.class_name,
+                                       arg_declarations(&arguments[0..count])?,
+                                       base_class_name,
+                                       arg_names(count),
+                                       field_initializers));
+        if count == 0 || !arguments[count - 1].has_default_value {
+          break;
+        }
+        count -= 1;
+      }
+    }
+    let using_declarations = wrapper
+      .protected_method_names
+      .iter()
+      .map(|name| format!("  using {}::{};\n", base_class_name, name))
+      .join("");
+
+    let mut methods = String::new();
+    let mut fields = String::new();
+    for virtual_method in &wrapper.virtual_methods {
+      let method = &virtual_method.method;
+      let (is_const, is_pure_virtual) = if let Some(ref info) = method.class_membership {
+        (info.is_const, info.is_pure_virtual)
+      } else {
+        return Err(unexpected("virtual method must be a class member").into());
+      };
+      let func_type = CppType {
+        base: CppTypeBase::FunctionPointer(virtual_method.function_type.clone()),
+        indirection: CppTypeIndirection::None,
+        is_const: false,
+        is_const2: false,
+      };
+      let output_arg = if virtual_method.return_allocation_place ==
+                          ReturnValueAllocationPlace::Stack {
+        Some("output".to_string())
+      } else {
+        None
+      };
+      let callback_args = once("m_data".to_string())
+        .chain(once("this".to_string()))
+        .chain(virtual_method
+                 .arguments
+                 .iter()
+                 .enumerate()
+                 .map_if_ok(|(num, t)| -> Result<_> {
+          let name = format!("arg{}", num);
+          Ok(match t.conversion {
+               CppIndirectionChange::NoChange => name,
+               // the argument lives until the callback returns,
+               // so it can be passed by pointer
+               CppIndirectionChange::ValueToPointer |
+               CppIndirectionChange::ReferenceToPointer => format!("&{}", name),
+               CppIndirectionChange::FlagsToInt { .. } |
+               CppIndirectionChange::EnumToInt => {
+                 format!("static_cast< {} >({})", t.ffi_type.to_cpp_code(None)?, name)
+               }
+               CppIndirectionChange::RValueReferenceToPointer => {
+                 return Err(unexpected("rvalue references can't be passed to callbacks").into());
+               }
+             })
+        })?)
+        .chain(output_arg)
+        .join(", ");
+      let return_type = method.return_type.to_cpp_code(None)?;
+      // a class object returned by value is moved out of the object
+      // created by the callback, and the latter is destroyed
+      let callback_call = match virtual_method.return_allocation_place {
+        ReturnValueAllocationPlace::NotApplicable => {
+          if virtual_method.return_type.conversion == CppIndirectionChange::EnumToInt {
+            format!("return static_cast< {} >(m_{}({}));",
+                    virtual_method.return_type.original_type.to_cpp_code(None)?,
+                    virtual_method.key,
+                    callback_args)
+          } else {
+            format!("return m_{}({});", virtual_method.key, callback_args)
+          }
+        }
+        ReturnValueAllocationPlace::Stack => {
+          format!("typedef {value_type} value_type;\n      \
+                   typename std::aligned_storage<sizeof(value_type), \
+                   alignof(value_type)>::type output_buffer;\n      \
+                   value_type* output = reinterpret_cast<value_type*>(&output_buffer);\n      \
+                   m_{key}({args});\n      \
+                   value_type result(std::move(*output));\n      \
+                   {lib_name}_call_destructor(output);\n      \
+                   return result;",
+                  value_type = virtual_method.return_type.original_type.to_cpp_code(None)?,
+                  key = virtual_method.key,
+                  args = callback_args,
+                  lib_name = self.lib_name)
+        }
+        ReturnValueAllocationPlace::Heap => {
+          format!("typedef {value_type} value_type;\n      \
+                   value_type* output = m_{key}({args});\n      \
+                   if (!output) {{\n        std::terminate();\n      }}\n      \
+                   value_type result(std::move(*output));\n      \
+                   delete output;\n      \
+                   return result;",
+                  value_type = virtual_method.return_type.original_type.to_cpp_code(None)?,
+                  key = virtual_method.key,
+                  args = callback_args)
+        }
+      };
+      let arguments = arg_declarations(&method.arguments)?;
+      let const_text = if is_const { " const" } else { "" };
+      let fallback = if is_pure_virtual {
+        "std::terminate();".to_string()
+      } else {
+        format!("return {}::{}({});",
+                base_class_name,
+                method.name,
+                arg_names(method.arguments.len()))
+      };
+      methods.push_str(&format!("  void set_{key}_callback({func_arg}) {{\n    \
+                                 m_{key} = func;\n  }}\n\n  \
+                                 {return_type} {name}({arguments}){const_text}{noexcept} \
+                                 override {{\n    if (m_{key}) {{\n      \
+                                 {callback_call}\n    }}\n    \
+                                 {fallback}\n  }}\n\n",
+                                key = virtual_method.key,
+                                func_arg = func_type.to_cpp_code(Some("func"))?,
+                                return_type = return_type,
+                                name = method.name,
+                                arguments = arguments,
+                                const_text = const_text,
+                                noexcept = if method.is_noexcept { " noexcept" } else { "" },
+                                callback_call = callback_call,
+                                fallback = fallback));
+      if !is_pure_virtual {
+        methods.push_str(&format!("  {} {}_base({}){} {{\n    return {}::{}({});\n  }}\n\n",
+                                  return_type,
+                                  virtual_method.key,
+                                  arguments,
+                                  const_text,
+                                  base_class_name,
+                                  method.name,
+                                  arg_names(method.arguments.len())));
+      }
+      fields.push_str(&format!("  {};\n",
+                               func_type.to_cpp_code(Some(&format!("m_{}", virtual_method.key)))?));
+    }
+    Ok(format!(include_str!("../templates/c_lib/subclass_wrapper.h"),
+               class_name = wrapper.class_name,
+               base_class_name = base_class_name,
+               constructors = constructors,
+               using_declarations = using_declarations,
+               methods = methods,
+               fields = fields))
+  }
+
   /// Generates code that wraps `expression` of type `type1.original_type` and
   /// converts it to type `type1.ffi_type`
   fn convert_type_to_ffi(&self, type1: &CppFfiType, expression: String) -> Result<String> {
@@ -103,7 +277,15 @@ impl CppCodeGenerator {
                    expression)
          }
          CppIndirectionChange::ReferenceToPointer => format!("&{}", expression),
-         CppIndirectionChange::QFlagsToUInt => format!("uint({})", expression),
+         CppIndirectionChange::FlagsToInt { .. } |
+         CppIndirectionChange::EnumToInt => {
+           format!("static_cast< {} >({})",
+                   type1.ffi_type.to_cpp_code(None)?,
+                   expression)
+         }
+         CppIndirectionChange::RValueReferenceToPointer => {
+           return Err("rvalue references can't be converted to FFI values".into());
+         }
        })
   }
 
@@ -136,8 +318,14 @@ impl CppCodeGenerator {
       CppIndirectionChange::ReferenceToPointer => {
         result = format!("&{}", result);
       }
-      CppIndirectionChange::QFlagsToUInt => {
-        result = format!("uint({})", result);
+      CppIndirectionChange::FlagsToInt { .. } |
+      CppIndirectionChange::EnumToInt => {
+        result = format!("static_cast< {} >({})",
+                         method.c_signature.return_type.ffi_type.to_cpp_code(None)?,
+                         result);
+      }
+      CppIndirectionChange::RValueReferenceToPointer => {
+        return Err(unexpected("rvalue reference return types are not supported").into());
       }
     }
 
@@ -170,8 +358,11 @@ impl CppCodeGenerator {
         match c_argument.argument_type.conversion {
           CppIndirectionChange::ValueToPointer |
           CppIndirectionChange::ReferenceToPointer => result = format!("*{}", result),
+          CppIndirectionChange::RValueReferenceToPointer => {
+            result = format!("std::move(*{})", result)
+          }
           CppIndirectionChange::NoChange => {}
-          CppIndirectionChange::QFlagsToUInt => {
+          CppIndirectionChange::FlagsToInt { .. } => {
             let type_text = if cpp_argument.argument_type.indirection == CppTypeIndirection::Ref &&
                                cpp_argument.argument_type.is_const {
               let mut fake_type = cpp_argument.argument_type.clone();
@@ -183,6 +374,11 @@ impl CppCodeGenerator {
             };
             result = format!("{}({})", type_text, result);
           }
+          CppIndirectionChange::EnumToInt => {
+            result = format!("static_cast< {} >({})",
+                             cpp_argument.argument_type.to_cpp_code(None)?,
+                             result);
+          }
         }
         filled_arguments.push(result);
       } else {
@@ -205,6 +401,19 @@ impl CppCodeGenerator {
       } else {
         return Err(unexpected("no this arg in destructor").into());
       }
+    } else if method.kind == CppFfiMethodKind::StreamOutput {
+      if let Some(arg) = method
+           .c_signature
+           .arguments
+           .iter()
+           .find(|x| x.meaning == CppFfiArgumentMeaning::This) {
+        format!("{}_write_to_buffer(*{}, {})",
+                self.lib_name,
+                arg.name,
+                self.arguments_values(method)?)
+      } else {
+        return Err(unexpected("no this arg in stream output method").into());
+      }
     } else {
       let mut is_field_accessor = false;
       let result_without_args = if let Some(info) = method.cpp_method.class_info_if_constructor() {
@@ -270,10 +479,16 @@ impl CppCodeGenerator {
             format!("{}{}", scope_specifier, field_name)
           }
         } else {
-          format!("{}{}{}",
-                  scope_specifier,
-                  method.cpp_method.name,
-                  template_args)
+          // the name of a conversion operator as reported by the parser
+          // may contain type aliases not accessible in the current scope,
+          // so the type's full name is used instead
+          let method_name = if let Some(CppOperator::Conversion(ref cpp_type)) =
+            method.cpp_method.operator {
+            format!("operator {}", cpp_type.to_cpp_code(None)?)
+          } else {
+            method.cpp_method.name.clone()
+          };
+          format!("{}{}{}", scope_specifier, method_name, template_args)
         }
       };
       if is_field_accessor {
@@ -301,13 +516,25 @@ impl CppCodeGenerator {
         panic!("Error: no this argument found\n{:?}", method);
       }
     } else {
-      Ok(format!("{}{};\n",
-                 if method.c_signature.return_type.ffi_type.is_void() {
-                   ""
-                 } else {
-                   "return "
-                 },
-                 self.returned_expression(&method)?))
+      let is_void = method.c_signature.return_type.ffi_type.is_void();
+      let call = format!("{}{};",
+                         if is_void { "" } else { "return " },
+                         self.returned_expression(&method)?);
+      if let Some(arg) = method
+           .c_signature
+           .arguments
+           .iter()
+           .find(|x| x.meaning == CppFfiArgumentMeaning::ExceptionInfo) {
+        Ok(format!("try {{\n    {call}\n  }} catch (const std::exception& e) {{\n    \
+                    cpp_to_rust_store_exception({arg}, typeid(e).name(), e.what());\n  \
+                    }} catch (...) {{\n    \
+                    cpp_to_rust_store_exception({arg}, \"unknown\", \"\");\n  }}\n{ret}",
+                   call = call,
+                   arg = arg.name,
+                   ret = if is_void { "" } else { "  return {};\n" }))
+      } else {
+        Ok(format!("{}\n", call))
+      }
     }
   }
 
@@ -414,6 +641,9 @@ impl CppCodeGenerator {
       for wrapper in &data.qt_slot_wrappers {
         h_file.write(self.qt_slot_wrapper(wrapper)?)?;
       }
+      for wrapper in &data.subclass_wrappers {
+        h_file.write(self.subclass_wrapper(wrapper)?)?;
+      }
       h_file.write("extern \"C\" {\n\n")?;
       for method in &data.methods {
         h_file.write(self.function_declaration(method)?)?;
@@ -436,19 +666,39 @@ impl CppCodeGenerator {
 }
 
 /// Entry about a Rust struct with a buffer that must have the exact same size
-/// as its corresponding C++ class. This information is required for the C++ program
-/// that is launched by the build script to determine type sizes and generate `type_sizes.rs`.
+/// and alignment as its corresponding C++ class. This information is required for the C++ program
+/// that is used by the build script to determine type sizes and generate `type_sizes.rs`.
 #[derive(Debug, Clone)]
 pub struct CppTypeSizeRequest {
-  /// C++ code representing the type. Used as argument to `sizeof`.
+  /// C++ code representing the type. Used as argument to `sizeof` and `alignof`.
   pub cpp_code: String,
-  /// Name of the constant in `type_sizes.rs`.
+  /// Name of the size constant in `type_sizes.rs`.
   pub size_const_name: String,
+  /// Name of the zero-sized type in `type_sizes.rs`
+  /// that has the same alignment as the C++ type.
+  pub align_type_name: String,
+}
+
+/// Returns C++ code of comma-separated character literals of `text`.
+fn cpp_char_literals(text: &str) -> String {
+  text
+    .chars()
+    .map(|c| if c == '\n' {
+           "'\\n'".to_string()
+         } else {
+           format!("'{}'", c)
+         })
+    .join(", ")
 }
 
-/// Generates a C++ program that determines sizes of target C++ types
-/// on the current platform and outputs the Rust code for `type_sizes.rs` module
-/// to the standard output.
+/// Generates C++ code that determines sizes and alignments of target C++ types
+/// on the current platform. The values are stored in `cpp_to_rust_type_sizes`
+/// character array that is computed at compile time, so the build script can either
+/// execute the program to print the array or find the array in the compiled
+/// static library. The array starts with `TYPE_SIZES_MARKER` and
+/// contains a line for each type: names of the size constant and the alignment type
+/// followed by the size and the alignment in hexadecimal format.
+/// The build script converts this data to the Rust code of `type_sizes.rs`.
 pub fn generate_cpp_type_size_requester(requests: &[CppTypeSizeRequest],
                                         include_directives: &[PathBuf])
                                         -> Result<String> {
@@ -456,12 +706,18 @@ pub fn generate_cpp_type_size_requester(requests: &[CppTypeSizeRequest],
   for dir in include_directives {
     result.push(format!("#include <{}>\n", path_to_str(dir)?));
   }
-  result.push("#include <iostream>\n\nint main() {\n".to_string());
+  result.push(include_str!("../templates/c_lib/type_sizes.h").to_string());
+  result.push(format!("extern \"C\" const char cpp_to_rust_type_sizes[] = {{\n  {},\n",
+                      cpp_char_literals(TYPE_SIZES_MARKER)));
   for request in requests {
-    result.push(format!("  std::cout << \"pub const {}: usize = \" << sizeof({}) << \";\\n\";\n",
-                        request.size_const_name,
-                        request.cpp_code));
+    result.push(format!("  {name}, CPP_TO_RUST_HEX((sizeof({cpp_type}))), ' ', \
+                         CPP_TO_RUST_HEX((alignof({cpp_type}))), '\\n',\n",
+                        name = cpp_char_literals(&format!("{} {} ",
+                                                          request.size_const_name,
+                                                          request.align_type_name)),
+                        cpp_type = request.cpp_code));
   }
-  result.push("}\n".to_string());
+  result.push("  '\\0'\n};\n\nint main() {\n  std::cout << cpp_to_rust_type_sizes;\n}\n"
+                .to_string());
   Ok(result.join(""))
 }
@@ -14,16 +14,29 @@ use std::io::{BufRead, BufReader};
 
 use regex::Regex;
 
+/// Information about a deprecation attribute
+/// (`[[deprecated]]`, `__attribute__((deprecated))`, `Q_DECL_DEPRECATED`, etc.)
+/// of a C++ entity
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[derive(Serialize, Deserialize)]
+pub struct CppDeprecation {
+  /// Message specified in the attribute, if any
+  pub message: Option<String>,
+}
+
 /// One item of a C++ enum declaration
 #[derive(Debug, PartialEq, Eq, Clone)]
 #[derive(Serialize, Deserialize)]
 pub struct CppEnumValue {
   /// Identifier
   pub name: String,
-  /// Corresponding value
+  /// Corresponding value. Values of enums with an unsigned
+  /// 64-bit underlying type are stored as their bit pattern.
   pub value: i64,
-  /// C++ documentation for this item in HTML
-  pub doc: Option<String>,
+  /// C++ documentation for this item
+  pub doc: Option<CppDocContent>,
+  /// Deprecation attribute of the item, if any
+  pub deprecation: Option<CppDeprecation>,
 }
 
 /// Member field of a C++ class declaration
@@ -38,6 +51,10 @@ pub struct CppClassField {
   pub visibility: CppVisibility,
   /// Size of type in bytes
   pub size: Option<usize>,
+  /// Width in bits if this field is a bitfield.
+  /// Bitfields are not addressable, so only copy getters
+  /// and setters can be generated for them.
+  pub bit_width: Option<usize>,
 }
 
 /// A "using" directive inside a class definition,
@@ -73,6 +90,12 @@ pub enum CppTypeKind {
   Enum {
     /// List of items
     values: Vec<CppEnumValue>,
+    /// Integer type used to store values of the enum,
+    /// either declared explicitly (`enum Foo : uint8_t`) or
+    /// selected by the compiler
+    underlying_type: CppType,
+    /// True if the enum is declared as `enum class` or `enum struct`
+    is_scoped: bool,
   },
   /// Class declaration
   Class {
@@ -109,12 +132,32 @@ pub enum CppVisibility {
   Private,
 }
 
+/// Content of C++ documentation
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[derive(Serialize, Deserialize)]
+pub enum CppDocContent {
+  /// HTML content (e.g. extracted from Qt documentation)
+  Html(String),
+  /// Markdown converted from a documentation comment in the C++ header
+  Markdown(String),
+}
+
+impl CppDocContent {
+  /// Returns the content as text regardless of its format.
+  pub fn text(&self) -> &str {
+    match *self {
+      CppDocContent::Html(ref text) |
+      CppDocContent::Markdown(ref text) => text,
+    }
+  }
+}
+
 /// C++ documentation for a type
 #[derive(Debug, PartialEq, Eq, Clone)]
 #[derive(Serialize, Deserialize)]
 pub struct CppTypeDoc {
-  /// HTML content
-  pub html: String,
+  /// Content of the documentation
+  pub content: CppDocContent,
   /// Absolute URL to online documentation page for this type
   pub url: String,
   /// Absolute documentation URLs encountered in the content
@@ -136,6 +179,8 @@ pub struct CppTypeData {
   pub kind: CppTypeKind,
   /// C++ documentation data for this type
   pub doc: Option<CppTypeDoc>,
+  /// Deprecation attribute of the type, if any
+  pub deprecation: Option<CppDeprecation>,
 }
 
 /// Information about template arguments of a C++ class type
@@ -157,6 +202,9 @@ pub struct TemplateArgumentsDeclaration {
   pub nested_level: usize,
   /// Names of template arguments. Names themselves are
   /// not particularly important, but their count is.
+  /// Non-type template parameters (like `N` in `template<class T, int N>`)
+  /// are included as well, so indexes of template parameters
+  /// match positions of template arguments.
   pub names: Vec<String>,
 }
 
@@ -195,6 +243,69 @@ pub enum CppTypeAllocationPlace {
   Stack,
 }
 
+/// Value of a C++ constant.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize)]
+pub enum CppConstantValue {
+  /// Value of an integer or boolean constant
+  Integer(i64),
+  /// Value of a floating point constant
+  /// represented as a Rust literal
+  Float(String),
+  /// Value of a string literal constant
+  String(String),
+}
+
+/// Information about a C++ compile-time constant:
+/// an object-like macro or a `constexpr` variable.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct CppConstant {
+  /// Name of the macro or full name of the variable,
+  /// including namespaces and class names
+  pub name: String,
+  /// True if the constant is a macro
+  pub is_macro: bool,
+  /// Type of the constant's value
+  pub value_type: CppType,
+  /// Value of the constant
+  pub value: CppConstantValue,
+  /// File name of the include file (without full path)
+  pub include_file: String,
+  /// Exact location of the declaration
+  pub origin_location: Option<CppOriginLocation>,
+}
+
+/// Information about a C++ `typedef` or a `using` type alias.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct CppTypeAlias {
+  /// Full name of the alias, including namespaces and class names
+  pub name: String,
+  /// Type referred to by the alias
+  pub target_type: CppType,
+  /// File name of the include file (without full path)
+  pub include_file: String,
+  /// Exact location of the declaration
+  pub origin_location: Option<CppOriginLocation>,
+}
+
+/// Information about a C++ global variable or a static data member of a class.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct CppGlobalVariable {
+  /// Full name of the variable, including namespaces and class names
+  pub name: String,
+  /// Type of the variable
+  pub variable_type: CppType,
+  /// Type of the class if the variable is a static data member
+  pub class_type: Option<CppTypeClassBase>,
+  /// File name of the include file (without full path)
+  pub include_file: String,
+  /// Exact location of the declaration
+  pub origin_location: Option<CppOriginLocation>,
+}
+
 /// C++ parser output
 #[derive(Debug, PartialEq, Eq, Clone, Default)]
 #[derive(Serialize, Deserialize)]
@@ -203,6 +314,15 @@ pub struct ParserCppData {
   pub types: Vec<CppTypeData>,
   /// List of found methods
   pub methods: Vec<CppMethod>,
+  /// Template class instantiations requested
+  /// with `Config::add_template_instantiation`
+  pub requested_template_instantiations: Vec<CppType>,
+  /// List of found constants
+  pub constants: Vec<CppConstant>,
+  /// List of found global variables and static data members
+  pub global_variables: Vec<CppGlobalVariable>,
+  /// List of found typedefs and `using` type aliases
+  pub type_aliases: Vec<CppTypeAlias>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Default)]
@@ -962,6 +1082,21 @@ impl<'a> CppDataWithDeps<'a> {
         result.insert(tp.include_file.clone());
       }
     }
+    for constant in &self.current.parser.constants {
+      if !result.contains(&constant.include_file) {
+        result.insert(constant.include_file.clone());
+      }
+    }
+    for variable in &self.current.parser.global_variables {
+      if !result.contains(&variable.include_file) {
+        result.insert(variable.include_file.clone());
+      }
+    }
+    for alias in &self.current.parser.type_aliases {
+      if !result.contains(&alias.include_file) {
+        result.insert(alias.include_file.clone());
+      }
+    }
     for instantiations in &self.current.processed.template_instantiations {
       let type_info =
         self
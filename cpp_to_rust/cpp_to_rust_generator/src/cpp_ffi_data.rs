@@ -1,7 +1,7 @@
 use caption_strategy::{ArgumentCaptionStrategy, MethodCaptionStrategy, TypeCaptionStrategy};
 use cpp_method::{CppMethod, ReturnValueAllocationPlace, CppMethodArgument};
 use cpp_operator::CppOperator;
-use cpp_type::{CppType, CppTypeBase, CppFunctionPointerType};
+use cpp_type::{CppType, CppTypeBase, CppFunctionPointerType, CppTypeClassBase, CppTypeIndirection};
 use common::errors::Result;
 use common::utils::MapIfOk;
 
@@ -78,6 +78,9 @@ pub enum CppFfiMethodKind {
   /// This is an instance of `static_cast`, `dynamic_cast` or
   /// `qobject_cast` function call.
   Cast(CppCast),
+  /// This is a non-existing `write_to_buffer` method that writes
+  /// the text printed by `operator<<(std::ostream&, const T&)` to a buffer.
+  StreamOutput,
 }
 
 /// Relation between original C++ method's argument value
@@ -93,9 +96,20 @@ pub enum CppIndirectionChange {
   /// C++ argument is a reference (like QPoint&)
   /// and FFI argument is a pointer (like QPoint*)
   ReferenceToPointer,
-  /// C++ argument is QFlags<T>
-  /// and FFI argument is uint
-  QFlagsToUInt,
+  /// C++ argument is a flags type (`QFlags<T>` or an integer typedef
+  /// configured with `Config::add_flags_typedef`)
+  /// and FFI argument is an integer
+  FlagsToInt {
+    /// Name of the C++ enum which values are combined in the flags type
+    enum_name: String,
+  },
+  /// C++ argument is an enum value and FFI argument is
+  /// the enum's underlying integer type
+  EnumToInt,
+  /// C++ argument is an rvalue reference (like QString&&)
+  /// and FFI argument is a pointer (like QString*).
+  /// The FFI function moves the value out of the pointed object.
+  RValueReferenceToPointer,
 }
 
 /// Information that indicates how an FFI function argument
@@ -112,6 +126,9 @@ pub enum CppFfiArgumentMeaning {
   /// This argument receives pointer to the buffer where
   /// the return value should be transferred to using placement new.
   ReturnValue,
+  /// This argument receives pointer to the structure where
+  /// information about a caught C++ exception is written.
+  ExceptionInfo,
 }
 
 impl CppFfiArgumentMeaning {
@@ -163,16 +180,7 @@ impl CppFfiMethodArgument {
   /// Generates C++ code for the part of FFI function signature
   /// corresponding to this argument
   pub fn to_cpp_code(&self) -> Result<String> {
-    if let CppTypeBase::FunctionPointer(..) = self.argument_type.ffi_type.base {
-      Ok(self
-           .argument_type
-           .ffi_type
-           .to_cpp_code(Some(&self.name))?)
-    } else {
-      Ok(format!("{} {}",
-                 self.argument_type.ffi_type.to_cpp_code(None)?,
-                 self.name))
-    }
+    self.argument_type.ffi_type.to_cpp_declaration(&self.name)
   }
 }
 
@@ -235,6 +243,40 @@ impl CppFfiMethodSignature {
     }
        })
   }
+
+  /// Returns true if this function reports caught C++ exceptions
+  /// through an additional argument.
+  pub fn has_exception_info(&self) -> bool {
+    self
+      .arguments
+      .iter()
+      .any(|arg| arg.meaning == CppFfiArgumentMeaning::ExceptionInfo)
+  }
+
+  /// Adds the last argument receiving information about
+  /// a C++ exception caught by the FFI function.
+  pub fn add_exception_info_argument(&mut self) {
+    let exception_info_type = CppType {
+      base: CppTypeBase::Class(CppTypeClassBase {
+                                 name: "cpp_to_rust_exception_info".to_string(),
+                                 template_arguments: None,
+                               }),
+      indirection: CppTypeIndirection::Ptr,
+      is_const: false,
+      is_const2: false,
+    };
+    self
+      .arguments
+      .push(CppFfiMethodArgument {
+              name: "exception_info".to_string(),
+              argument_type: CppFfiType {
+                original_type: exception_info_type.clone(),
+                ffi_type: exception_info_type,
+                conversion: CppIndirectionChange::NoChange,
+              },
+              meaning: CppFfiArgumentMeaning::ExceptionInfo,
+            });
+  }
 }
 
 /// FFI function type with attached information about
@@ -326,10 +368,7 @@ pub fn c_base_name(cpp_method: &CppMethod,
     }
   } else if let Some(ref operator) = cpp_method.operator {
     add_place_note(match *operator {
-                     CppOperator::Conversion(ref cpp_type) => {
-                       format!("convert_to_{}",
-                               cpp_type.caption(TypeCaptionStrategy::Full)?)
-                     }
+                     CppOperator::Conversion(..) => operator.c_name()?,
                      _ => format!("operator_{}", operator.c_name()?),
                    })
   } else {
@@ -381,6 +420,47 @@ pub struct QtSlotWrapper {
   pub receiver_id: String,
 }
 
+/// Information about a virtual method overridden in
+/// a subclass wrapper
+#[derive(Debug, Clone)]
+pub struct CppSubclassVirtualMethod {
+  /// Original virtual method of the base class
+  pub method: CppMethod,
+  /// Identifier of the method, unique within the subclass wrapper.
+  /// Used to construct names of the generated members.
+  pub key: String,
+  /// Types of the method's arguments passed to the callback
+  pub arguments: Vec<CppFfiType>,
+  /// Return type of the method as seen by the callback
+  pub return_type: CppFfiType,
+  /// If the method returns a class object by value, determines how the callback
+  /// passes it to C++: `Stack` means that the callback writes the object to
+  /// the "output" argument added after other arguments, and `Heap` means that
+  /// the callback returns an object allocated with `new`.
+  /// Otherwise, this is `NotApplicable`.
+  pub return_allocation_place: ReturnValueAllocationPlace,
+  /// The function pointer type of the callback
+  pub function_type: CppFunctionPointerType,
+}
+
+/// Information about a generated C++ subclass of a polymorphic class
+/// that forwards calls of virtual methods to callbacks
+#[derive(Debug, Clone)]
+pub struct CppSubclassWrapper {
+  /// Generated name of the subclass
+  pub class_name: String,
+  /// The class being subclassed
+  pub base_class: CppTypeClassBase,
+  /// Argument lists of the subclass constructors. Each constructor
+  /// calls a base class constructor with the same arguments.
+  pub constructors: Vec<Vec<CppMethodArgument>>,
+  /// Virtual methods overridden in the subclass
+  pub virtual_methods: Vec<CppSubclassVirtualMethod>,
+  /// Names of protected methods of the base class
+  /// made public in the subclass
+  pub protected_method_names: Vec<String>,
+}
+
 /// Information about a header of the generated C++ wrapper library
 #[derive(Debug, Clone)]
 pub struct CppFfiHeaderData {
@@ -390,4 +470,6 @@ pub struct CppFfiHeaderData {
   pub methods: Vec<CppAndFfiMethod>,
   /// Generated Qt slot wrappers
   pub qt_slot_wrappers: Vec<QtSlotWrapper>,
+  /// Generated subclass wrappers
+  pub subclass_wrappers: Vec<CppSubclassWrapper>,
 }
@@ -1,16 +1,19 @@
 use caption_strategy::{TypeCaptionStrategy, MethodCaptionStrategy};
-use cpp_data::{CppVisibility, CppTypeAllocationPlace, CppDataWithDeps, CppTypeKind,
-               CppTemplateInstantiation, CppOperator};
+use cpp_data::{CppVisibility, CppTypeAllocationPlace, CppDataWithDeps, CppTypeData,
+               CppTypeKind, CppTemplateInstantiation, CppOperator};
 use cpp_type::{CppTypeRole, CppType, CppTypeBase, CppTypeIndirection, CppTypeClassBase,
-               CppFunctionPointerType};
+               CppFunctionPointerType, CppSpecificNumericType, CppBuiltInNumericType};
 use cpp_ffi_data::{CppAndFfiMethod, c_base_name, CppFfiHeaderData, QtSlotWrapper,
-                   CppFfiMethodKind, CppFieldAccessorType, CppMethodWithFfiSignature, CppCast};
+                   CppFfiMethodKind, CppFieldAccessorType, CppMethodWithFfiSignature, CppCast,
+                   CppSubclassWrapper, CppSubclassVirtualMethod, CppIndirectionChange,
+                   CppFfiType};
 use cpp_method::{CppMethod, CppMethodKind, CppMethodArgument, CppMethodClassMembership,
                  ReturnValueAllocationPlace};
 use common::errors::{Result, ChainErr, unexpected};
 use common::log;
 use common::utils::{MapIfOk, add_to_multihash};
 use config::CppFfiGeneratorFilterFn;
+use cpp_template_checker::CppTemplateChecker;
 use std::collections::{HashSet, HashMap};
 use std::iter::once;
 
@@ -22,6 +25,14 @@ struct CppFfiGenerator<'a> {
   cpp_ffi_lib_name: String,
   /// FFI filters passed to `Config`
   filters: Vec<&'a Box<CppFfiGeneratorFilterFn>>,
+  /// If true, FFI functions catch C++ exceptions
+  catch_exceptions: bool,
+  /// Filters selecting methods that catch C++ exceptions
+  catch_exceptions_filters: Vec<&'a Box<CppFfiGeneratorFilterFn>>,
+  /// Names of classes that should be subclassable from Rust
+  subclassing_classes: &'a [String],
+  /// Integer typedefs representing flags mapped to names of their enums
+  flags_typedefs: &'a HashMap<String, String>,
 }
 
 #[derive(Debug, Clone)]
@@ -47,12 +58,21 @@ impl CppMethodWithKind {
 /// Runs the FFI generator
 pub fn run(cpp_data: &CppDataWithDeps,
            cpp_ffi_lib_name: String,
-           filters: Vec<&Box<CppFfiGeneratorFilterFn>>)
+           filters: Vec<&Box<CppFfiGeneratorFilterFn>>,
+           catch_exceptions: bool,
+           catch_exceptions_filters: Vec<&Box<CppFfiGeneratorFilterFn>>,
+           subclassing_classes: &[String],
+           flags_typedefs: &HashMap<String, String>,
+           template_checker: &mut CppTemplateChecker)
            -> Result<Vec<CppFfiHeaderData>> {
   let generator = CppFfiGenerator {
     cpp_data: cpp_data,
     cpp_ffi_lib_name: cpp_ffi_lib_name,
     filters: filters,
+    catch_exceptions: catch_exceptions,
+    catch_exceptions_filters: catch_exceptions_filters,
+    subclassing_classes: subclassing_classes,
+    flags_typedefs: flags_typedefs,
   };
 
   let mut c_headers = Vec::new();
@@ -64,9 +84,14 @@ pub fn run(cpp_data: &CppDataWithDeps,
   include_name_list.sort();
 
   let mut extra_methods = Vec::new();
-  extra_methods.append(&mut instantiate_templates(&generator.cpp_data)?);
+  let mut instantiated_methods = instantiate_templates(&generator.cpp_data)?;
+  instantiated_methods.append(&mut instantiate_template_methods(&generator.cpp_data)?);
+  extra_methods.append(&mut check_template_instantiations(instantiated_methods,
+                                                          template_checker)?);
   extra_methods.append(&mut generate_field_accessors(&generator.cpp_data)?);
+  extra_methods.append(&mut generate_variable_accessors(&generator.cpp_data)?);
   extra_methods.append(&mut generate_casts(&generator.cpp_data)?);
+  extra_methods.append(&mut generate_stream_output_methods(&generator.cpp_data)?);
 
   for include_file in &include_name_list {
     let mut include_file_base_name = include_file.clone();
@@ -97,12 +122,16 @@ pub fn run(cpp_data: &CppDataWithDeps,
                        include_file_base_name: include_file_base_name,
                        methods: methods,
                        qt_slot_wrappers: Vec::new(),
+                       subclass_wrappers: Vec::new(),
                      });
     }
   }
   if let Some(header) = generator.generate_slot_wrappers()? {
     c_headers.push(header);
   }
+  if let Some(header) = generator.generate_subclass_wrappers()? {
+    c_headers.push(header);
+  }
   if c_headers.is_empty() {
     return Err("No FFI headers generated".into());
   }
@@ -261,7 +290,185 @@ fn instantiate_templates(data: &CppDataWithDeps) -> Result<Vec<CppMethodWithKind
   Ok(new_methods)
 }
 
+/// Generates instantiations of template methods of non-template classes
+/// with a single template parameter, using a pointer to each class
+/// of the current library derived from the method's class (or the class itself)
+/// as the template argument, e.g. `QObject::findChild<QWidget*>`.
+/// Instantiations that can't be compiled are removed
+/// by `check_template_instantiations`.
+fn instantiate_template_methods(data: &CppDataWithDeps) -> Result<Vec<CppMethodWithKind>> {
+  log::status("Instantiating template methods");
+  let mut new_methods = Vec::new();
+  for cpp_data in data.dependencies.iter().chain(once(&&data.current)) {
+    for method in &cpp_data.parser.methods {
+      if let (&Some(ref template_arguments), &Some(ref info)) =
+        (&method.template_arguments, &method.class_membership) {
+        if template_arguments.count() != 1 || info.class_type.template_arguments.is_some() {
+          continue;
+        }
+        for type_info in &data.current.parser.types {
+          if !type_info.is_class() || type_info.default_template_arguments().is_some() {
+            continue;
+          }
+          if type_info.name != info.class_type.name &&
+             !data.inherits(&type_info.name, &info.class_type.name) {
+            continue;
+          }
+          let instantiation = CppTemplateInstantiation {
+            template_arguments: vec![CppType {
+                                       indirection: CppTypeIndirection::Ptr,
+                                       is_const: false,
+                                       is_const2: false,
+                                       base: CppTypeBase::Class(CppTypeClassBase {
+                                                                  name: type_info.name.clone(),
+                                                                  template_arguments: None,
+                                                                }),
+                                     }],
+          };
+          match apply_instantiations_to_method(method,
+                                               template_arguments.nested_level,
+                                               &[instantiation]) {
+            Ok(methods) => {
+              for mut new_method in methods {
+                if let Err(msg) = new_method
+                     .all_involved_types()
+                     .iter()
+                     .map_if_ok(|t| data.check_template_type(t)) {
+                  log::llog(log::DebugTemplateInstantiation, || {
+                    format!("method is not accepted: {}\n  {}",
+                            new_method.short_text(),
+                            msg)
+                  });
+                  continue;
+                }
+                // the instantiation belongs to the library
+                // that declares the template argument
+                new_method.include_file = type_info.include_file.clone();
+                new_methods.push(CppMethodWithKind {
+                                   method: new_method,
+                                   kind: CppFfiMethodKind::Real,
+                                 });
+              }
+            }
+            Err(msg) => {
+              log::llog(log::DebugTemplateInstantiation,
+                        || format!("failed: {}: {}", method.short_text(), msg))
+            }
+          }
+        }
+      }
+    }
+  }
+  Ok(new_methods)
+}
+
+/// Test-compiles instantiated template methods and
+/// returns the methods that compile successfully.
+fn check_template_instantiations(methods: Vec<CppMethodWithKind>,
+                                 template_checker: &mut CppTemplateChecker)
+                                 -> Result<Vec<CppMethodWithKind>> {
+  log::status("Checking template instantiations");
+  let results = template_checker
+    .check(&methods.iter().map(|m| &m.method).collect::<Vec<_>>())?;
+  let mut good_methods = Vec::new();
+  for (method, result) in methods.into_iter().zip(results.into_iter()) {
+    if let Some(error) = result {
+      log::llog(log::DebugFfiSkips, || {
+        format!("Skipping template instantiation that doesn't compile: {}\n{}\n",
+                method.method.short_text(),
+                error)
+      });
+    } else {
+      good_methods.push(method);
+    }
+  }
+  Ok(good_methods)
+}
+
+/// Returns names, types, return types and arguments of getter and setter
+/// methods for a field or a variable named `name` of type `value_type`.
+/// Mutable getters and setters are not generated for const values.
+/// If `is_bitfield` is true, only a copy getter and a setter are generated
+/// because bitfields can't be referenced.
+fn accessor_signatures(name: &str,
+                       value_type: &CppType,
+                       is_bitfield: bool)
+                       -> Vec<(String, CppFieldAccessorType, CppType, Vec<CppMethodArgument>)> {
+  let mut result = Vec::new();
+  let is_const_value = value_type.is_const && value_type.indirection == CppTypeIndirection::None;
+  let is_array = value_type.base.is_array();
+  if is_array && !value_type.with_const(true).is_const {
+    // `T* const (&)[N]` can't be represented, so a const getter
+    // can't be generated for an array of pointers
+    return result;
+  }
+  if !is_bitfield && value_type.indirection == CppTypeIndirection::None &&
+     (value_type.base.is_class() || is_array) {
+    let mut type2_const = value_type.with_const(true);
+    type2_const.indirection = CppTypeIndirection::Ref;
+    result.push((name.to_string(), CppFieldAccessorType::ConstRefGetter, type2_const, Vec::new()));
+    if !is_const_value {
+      let mut type2_mut = value_type.with_const(false);
+      type2_mut.indirection = CppTypeIndirection::Ref;
+      result.push((format!("{}_mut", name),
+                   CppFieldAccessorType::MutRefGetter,
+                   type2_mut,
+                   Vec::new()));
+    }
+  } else {
+    result.push((name.to_string(),
+                 CppFieldAccessorType::CopyGetter,
+                 value_type.clone(),
+                 Vec::new()));
+  }
+  // arrays can't be assigned, so their elements are changed through the mutable getter
+  if !is_const_value && !is_array {
+    let arg = CppMethodArgument {
+      argument_type: value_type.clone(),
+      name: "value".to_string(),
+      has_default_value: false,
+    };
+    result.push((format!("set_{}", name), CppFieldAccessorType::Setter, CppType::void(), vec![arg]));
+  }
+  result
+}
 
+/// Creates a fictional accessor method. `class_membership` should be `None`
+/// for a global variable, and `field_name` should be the variable's full name in this case.
+fn create_accessor_method(name: String,
+                          accessor_type: CppFieldAccessorType,
+                          return_type: CppType,
+                          arguments: Vec<CppMethodArgument>,
+                          class_membership: Option<CppMethodClassMembership>,
+                          field_name: String,
+                          include_file: String)
+                          -> CppMethodWithKind {
+  CppMethodWithKind {
+    method: CppMethod {
+      name: name,
+      class_membership: class_membership,
+      operator: None,
+      return_type: return_type,
+      arguments: arguments,
+      allows_variadic_arguments: false,
+      include_file: include_file,
+      origin_location: None,
+      template_arguments: None,
+      template_arguments_values: None,
+      declaration_code: None,
+      doc: None,
+      deprecation: None,
+      inheritance_chain: Vec::new(),
+      //is_fake_inherited_method: false,
+      is_ffi_whitelisted: false,
+      is_noexcept: false,
+    },
+    kind: CppFfiMethodKind::FieldAccessor {
+      accessor_type: accessor_type,
+      field_name: field_name,
+    },
+  }
+}
 
 /// Adds fictional getter and setter methods for each known public field of each class.
 fn generate_field_accessors(cpp_data: &CppDataWithDeps) -> Result<Vec<CppMethodWithKind>> {
@@ -271,80 +478,34 @@ fn generate_field_accessors(cpp_data: &CppDataWithDeps) -> Result<Vec<CppMethodW
   for type_info in &cpp_data.current.parser.types {
     if let CppTypeKind::Class { ref fields, .. } = type_info.kind {
       for field in fields {
-        let create_method =
-          |name, accessor_type, return_type, arguments| -> Result<CppMethodWithKind> {
-            Ok(CppMethodWithKind {
-                 method: CppMethod {
-                   name: name,
-                   class_membership: Some(CppMethodClassMembership {
-                                            class_type: type_info.default_class_type()?,
-                                            kind: CppMethodKind::Regular,
-                                            is_virtual: false,
-                                            is_pure_virtual: false,
-                                            is_const: match accessor_type {
-                                              CppFieldAccessorType::CopyGetter |
-                                              CppFieldAccessorType::ConstRefGetter => true,
-                                              CppFieldAccessorType::MutRefGetter |
-                                              CppFieldAccessorType::Setter => false,
-                                            },
-                                            is_static: false,
-                                            visibility: CppVisibility::Public,
-                                            is_signal: false,
-                                            is_slot: false,
-                                          }),
-                   operator: None,
-                   return_type: return_type,
-                   arguments: arguments,
-                   allows_variadic_arguments: false,
-                   include_file: type_info.include_file.clone(),
-                   origin_location: None,
-                   template_arguments: None,
-                   template_arguments_values: None,
-                   declaration_code: None,
-                   doc: None,
-                   inheritance_chain: Vec::new(),
-                   //is_fake_inherited_method: false,
-                   is_ffi_whitelisted: false,
-                 },
-                 kind: CppFfiMethodKind::FieldAccessor {
-                   accessor_type: accessor_type,
-                   field_name: field.name.clone(),
-                 },
-               })
-          };
-        if field.visibility == CppVisibility::Public {
-          if field.field_type.indirection == CppTypeIndirection::None &&
-             field.field_type.base.is_class() {
-
-            let mut type2_const = field.field_type.clone();
-            type2_const.is_const = true;
-            type2_const.indirection = CppTypeIndirection::Ref;
-            let mut type2_mut = field.field_type.clone();
-            type2_mut.is_const = false;
-            type2_mut.indirection = CppTypeIndirection::Ref;
-            new_methods.push(create_method(field.name.clone(),
-                                           CppFieldAccessorType::ConstRefGetter,
-                                           type2_const,
-                                           Vec::new())?);
-            new_methods.push(create_method(format!("{}_mut", field.name),
-                                           CppFieldAccessorType::MutRefGetter,
-                                           type2_mut,
-                                           Vec::new())?);
-          } else {
-            new_methods.push(create_method(field.name.clone(),
-                                           CppFieldAccessorType::CopyGetter,
-                                           field.field_type.clone(),
-                                           Vec::new())?);
-          }
-          let arg = CppMethodArgument {
-            argument_type: field.field_type.clone(),
-            name: "value".to_string(),
-            has_default_value: false,
+        if field.visibility != CppVisibility::Public {
+          continue;
+        }
+        for (name, accessor_type, return_type, arguments) in
+          accessor_signatures(&field.name, &field.field_type, field.bit_width.is_some()) {
+          let class_membership = CppMethodClassMembership {
+            class_type: type_info.default_class_type()?,
+            kind: CppMethodKind::Regular,
+            is_virtual: false,
+            is_pure_virtual: false,
+            is_const: match accessor_type {
+              CppFieldAccessorType::CopyGetter |
+              CppFieldAccessorType::ConstRefGetter => true,
+              CppFieldAccessorType::MutRefGetter |
+              CppFieldAccessorType::Setter => false,
+            },
+            is_static: false,
+            visibility: CppVisibility::Public,
+            is_signal: false,
+            is_slot: false,
           };
-          new_methods.push(create_method(format!("set_{}", field.name),
-                                         CppFieldAccessorType::Setter,
-                                         CppType::void(),
-                                         vec![arg])?);
+          new_methods.push(create_accessor_method(name,
+                                                  accessor_type,
+                                                  return_type,
+                                                  arguments,
+                                                  Some(class_membership),
+                                                  field.name.clone(),
+                                                  type_info.include_file.clone()));
         }
       }
     }
@@ -352,6 +513,48 @@ fn generate_field_accessors(cpp_data: &CppDataWithDeps) -> Result<Vec<CppMethodW
   Ok(new_methods)
 }
 
+/// Adds fictional getter and setter functions for each global variable
+/// and static getter and setter methods for each static data member.
+fn generate_variable_accessors(cpp_data: &CppDataWithDeps) -> Result<Vec<CppMethodWithKind>> {
+  log::status("Adding global variable accessors");
+  let mut new_methods = Vec::new();
+  for variable in &cpp_data.current.parser.global_variables {
+    let (scope, last_name) = match variable.name.rfind("::") {
+      Some(index) => (&variable.name[..index + 2], &variable.name[index + 2..]),
+      None => ("", variable.name.as_str()),
+    };
+    for (name, accessor_type, return_type, arguments) in
+      accessor_signatures(last_name, &variable.variable_type, false) {
+      let (name, class_membership, field_name) = match variable.class_type {
+        Some(ref class_type) => {
+          (name,
+           Some(CppMethodClassMembership {
+                  class_type: class_type.clone(),
+                  kind: CppMethodKind::Regular,
+                  is_virtual: false,
+                  is_pure_virtual: false,
+                  is_const: false,
+                  is_static: true,
+                  visibility: CppVisibility::Public,
+                  is_signal: false,
+                  is_slot: false,
+                }),
+           last_name.to_string())
+        }
+        None => (format!("{}{}", scope, name), None, variable.name.clone()),
+      };
+      new_methods.push(create_accessor_method(name,
+                                              accessor_type,
+                                              return_type,
+                                              arguments,
+                                              class_membership,
+                                              field_name,
+                                              variable.include_file.clone()));
+    }
+  }
+  Ok(new_methods)
+}
+
 /// Convenience function to create `CppMethod` object for
 /// `static_cast` or `dynamic_cast` from type `from` to type `to`.
 /// See `CppMethod`'s documentation for more information
@@ -379,8 +582,10 @@ fn create_cast_method(cast: CppCast,
       template_arguments_values: Some(vec![to.clone()]),
       declaration_code: None,
       doc: None,
+      deprecation: None,
       inheritance_chain: Vec::new(),
       is_ffi_whitelisted: true,
+      is_noexcept: false,
     },
     kind: CppFfiMethodKind::Cast(cast),
   }
@@ -468,11 +673,118 @@ fn generate_casts(cpp_data: &CppDataWithDeps) -> Result<Vec<CppMethodWithKind>>
   Ok(new_methods)
 }
 
+/// Adds a fictional const `write_to_buffer` method for each class of the library
+/// that has a free `operator<<(std::ostream&, const T&)` function.
+/// The method writes the text printed by the operator to a `char` buffer.
+fn generate_stream_output_methods(cpp_data: &CppDataWithDeps) -> Result<Vec<CppMethodWithKind>> {
+  log::status("Adding stream output methods");
+  let size_type = CppType {
+    base: CppTypeBase::PointerSizedInteger {
+      name: "size_t".to_string(),
+      is_signed: false,
+    },
+    indirection: CppTypeIndirection::None,
+    is_const: false,
+    is_const2: false,
+  };
+  let mut new_methods = Vec::new();
+  for method in &cpp_data.current.parser.methods {
+    let class_type = match method.stream_output_class() {
+      Some(class_type) => class_type,
+      None => continue,
+    };
+    if method.template_arguments.is_some() {
+      continue;
+    }
+    if !cpp_data
+          .current
+          .parser
+          .types
+          .iter()
+          .any(|t| t.name == class_type.name) {
+      log::llog(log::DebugFfiSkips, || {
+        format!("Skipping stream output operator for a class of another library: {}",
+                method.short_text())
+      });
+      continue;
+    }
+    new_methods.push(CppMethodWithKind {
+                       method: CppMethod {
+                         name: "write_to_buffer".to_string(),
+                         class_membership: Some(CppMethodClassMembership {
+                                                  class_type: class_type.clone(),
+                                                  kind: CppMethodKind::Regular,
+                                                  is_virtual: false,
+                                                  is_pure_virtual: false,
+                                                  is_const: true,
+                                                  is_static: false,
+                                                  visibility: CppVisibility::Public,
+                                                  is_signal: false,
+                                                  is_slot: false,
+                                                }),
+                         operator: None,
+                         return_type: size_type.clone(),
+                         arguments: vec![CppMethodArgument {
+                                           name: "buffer".to_string(),
+                                           argument_type: CppType {
+                                             base: CppTypeBase::BuiltInNumeric(
+                                               CppBuiltInNumericType::Char),
+                                             indirection: CppTypeIndirection::Ptr,
+                                             is_const: false,
+                                             is_const2: false,
+                                           },
+                                           has_default_value: false,
+                                         },
+                                         CppMethodArgument {
+                                           name: "buffer_size".to_string(),
+                                           argument_type: size_type.clone(),
+                                           has_default_value: false,
+                                         }],
+                         allows_variadic_arguments: false,
+                         include_file: method.include_file.clone(),
+                         origin_location: None,
+                         template_arguments: None,
+                         template_arguments_values: None,
+                         declaration_code: None,
+                         doc: None,
+                         deprecation: None,
+                         inheritance_chain: Vec::new(),
+                         is_ffi_whitelisted: false,
+                         is_noexcept: false,
+                       },
+                       kind: CppFfiMethodKind::StreamOutput,
+                     });
+  }
+  Ok(new_methods)
+}
+
+
+/// Changes `ffi_type` to pass an enum value as the enum's underlying integer type,
+/// so the FFI function has the same ABI on both sides regardless of
+/// the enum's representation in Rust.
+fn convert_enum_to_int(cpp_data: &CppDataWithDeps, ffi_type: &mut CppFfiType) -> Result<()> {
+  if ffi_type.conversion != CppIndirectionChange::NoChange ||
+     ffi_type.original_type.indirection != CppTypeIndirection::None {
+    return Ok(());
+  }
+  if let CppTypeBase::Enum { ref name } = ffi_type.original_type.base {
+    let underlying_type = match cpp_data.find_type_info(|x| &x.name == name) {
+      Some(&CppTypeData { kind: CppTypeKind::Enum { ref underlying_type, .. }, .. }) => {
+        underlying_type
+      }
+      _ => return Err(format!("enum not found: {}", name).into()),
+    };
+    ffi_type.ffi_type.base = underlying_type.base.clone();
+    ffi_type.conversion = CppIndirectionChange::EnumToInt;
+  }
+  Ok(())
+}
 
 /// Generates the FFI function signature for this method.
 fn method_to_ffi_signature<'a>(method: CppMethodRefWithKind<'a>,
                                cpp_data: &CppDataWithDeps,
-                               type_allocation_places_override: Option<CppTypeAllocationPlace>)
+                               type_allocation_places_override: Option<CppTypeAllocationPlace>,
+                               flags_typedefs: &HashMap<String, String>)
                                -> Result<CppMethodWithFfiSignature> {
   let get_place = |name| -> Result<ReturnValueAllocationPlace> {
     let v = if let Some(ref x) = type_allocation_places_override {
@@ -506,7 +818,31 @@ fn method_to_ffi_signature<'a>(method: CppMethodRefWithKind<'a>,
     ReturnValueAllocationPlace::NotApplicable
   };
 
-  let c_signature = method.method.c_signature(place.clone())?;
+  let mut c_signature = method.method.c_signature(place.clone())?;
+  for ffi_type in c_signature
+        .arguments
+        .iter_mut()
+        .map(|arg| &mut arg.argument_type)
+        .chain(once(&mut c_signature.return_type)) {
+    convert_enum_to_int(cpp_data, ffi_type)?;
+    if let CppTypeBase::SpecificNumeric(CppSpecificNumericType { ref name, ref bits, .. }) =
+      ffi_type.original_type.base {
+      if ffi_type.original_type.indirection == CppTypeIndirection::None {
+        if let Some(enum_name) = flags_typedefs.get(name) {
+          if *bits > 32 {
+            // `cpp_utils::flags::Flags` stores values as `c_int`
+            log::llog(log::DebugFfiSkips, || {
+              format!("Flags type is too wide and will be used as an integer: {}", name)
+            });
+          } else {
+            ffi_type.conversion = CppIndirectionChange::FlagsToInt {
+              enum_name: enum_name.clone(),
+            };
+          }
+        }
+      }
+    }
+  }
   Ok(CppMethodWithFfiSignature {
        cpp_method: method.method.clone(),
        kind: method.kind,
@@ -537,6 +873,11 @@ impl<'a> CppFfiGenerator<'a> {
     if class_name == "QFlags" {
       return Ok(false);
     }
+    if method.stream_output_class().is_some() {
+      // `std::ostream` can't be used in Rust, so the operator is only
+      // called by methods added in `generate_stream_output_methods`
+      return Ok(false);
+    }
     if let Some(ref membership) = method.class_membership {
       if membership.kind == CppMethodKind::Constructor &&
          self.cpp_data.has_pure_virtual_methods(&class_name) {
@@ -557,11 +898,6 @@ impl<'a> CppFfiGenerator<'a> {
     if method.template_arguments.is_some() {
       return Ok(false);
     }
-    if method.template_arguments_values.is_some() && !method.is_ffi_whitelisted {
-      // TODO: re-enable after template test compilation (#24) is implemented
-      // TODO: QObject::findChild and QObject::findChildren should be allowed
-      return Ok(false);
-    }
     if method
          .all_involved_types()
          .iter()
@@ -571,6 +907,30 @@ impl<'a> CppFfiGenerator<'a> {
     Ok(true)
   }
 
+  /// Returns true if the FFI function for `method` should catch
+  /// C++ exceptions and report them through an extra argument.
+  fn should_catch_exceptions(&self, method: &CppMethodRefWithKind) -> Result<bool> {
+    if !self.catch_exceptions {
+      return Ok(false);
+    }
+    match method.kind {
+      CppFfiMethodKind::Real |
+      CppFfiMethodKind::RealWithOmittedArguments { .. } => {}
+      // field accessors and casts can't throw
+      _ => return Ok(false),
+    }
+    if method.method.is_noexcept || method.method.is_destructor() {
+      return Ok(false);
+    }
+    for filter in &self.catch_exceptions_filters {
+      if !filter(method.method)
+            .chain_err(|| "catch_exceptions_filter failed")? {
+        return Ok(false);
+      }
+    }
+    Ok(true)
+  }
+
   /// Generates FFI wrappers for all specified methods,
   /// resolving all name conflicts using additional method captions.
   fn process_methods<'b, I>(&self,
@@ -584,10 +944,11 @@ impl<'a> CppFfiGenerator<'a> {
                         include_file_base_name));
     let mut hash_name_to_methods: HashMap<String, Vec<_>> = HashMap::new();
     {
-      let mut process_one = |method: CppMethodRefWithKind| {
+      let mut process_one = |method: CppMethodRefWithKind, catch_exceptions: bool| {
         match method_to_ffi_signature(method.clone(),
                                       &self.cpp_data,
-                                      type_allocation_places_override.clone()) {
+                                      type_allocation_places_override.clone(),
+                                      self.flags_typedefs) {
           Err(msg) => {
             log::llog(log::DebugFfiSkips, || {
               format!("Unable to produce C function for method:\n{}\nError:{}\n",
@@ -595,7 +956,10 @@ impl<'a> CppFfiGenerator<'a> {
                       msg)
             });
           }
-          Ok(result) => {
+          Ok(mut result) => {
+            if catch_exceptions {
+              result.c_signature.add_exception_info_argument();
+            }
             match c_base_name(&result.cpp_method,
                               &result.allocation_place,
                               include_file_base_name) {
@@ -621,7 +985,8 @@ impl<'a> CppFfiGenerator<'a> {
         if !self.should_process_method(&method.method)? {
           continue;
         }
-        process_one(method.clone());
+        let catch_exceptions = self.should_catch_exceptions(&method)?;
+        process_one(method.clone(), catch_exceptions);
         // generate methods with omitted arguments
         if let Some(last_arg) = method.method.arguments.last() {
           if last_arg.has_default_value {
@@ -635,7 +1000,8 @@ impl<'a> CppFfiGenerator<'a> {
                             kind: CppFfiMethodKind::RealWithOmittedArguments {
                               arguments_before_omitting: Some(method.method.arguments.clone()),
                             },
-                          });
+                          },
+                          catch_exceptions);
             }
           }
         }
@@ -718,7 +1084,11 @@ impl<'a> CppFfiGenerator<'a> {
     let mut methods = Vec::new();
     for types in &self.cpp_data.current.processed.signal_argument_types {
       let ffi_types = types
-        .map_if_ok(|t| t.to_cpp_ffi_type(CppTypeRole::NotReturnType))?;
+        .map_if_ok(|t| -> Result<_> {
+          let mut ffi_type = t.to_cpp_ffi_type(CppTypeRole::NotReturnType)?;
+          convert_enum_to_int(self.cpp_data, &mut ffi_type)?;
+          Ok(ffi_type)
+        })?;
       let args_captions = types
         .map_if_ok(|t| t.caption(TypeCaptionStrategy::Full))?;
       let args_caption = if args_captions.is_empty() {
@@ -773,8 +1143,11 @@ impl<'a> CppFfiGenerator<'a> {
             template_arguments_values: None,
             declaration_code: None,
             doc: None,
+            deprecation: None,
             inheritance_chain: Vec::new(),
             is_ffi_whitelisted: false,
+            // generated wrapper methods don't throw
+            is_noexcept: true,
           //is_fake_inherited_method: false,
           },
           kind: CppFfiMethodKind::Real,
@@ -863,6 +1236,479 @@ impl<'a> CppFfiGenerator<'a> {
                                  Some(CppTypeAllocationPlace::Heap),
                                  methods.iter().map(|i| i.as_ref()))?,
               qt_slot_wrappers: qt_slot_wrappers,
+              subclass_wrappers: Vec::new(),
+            }))
+  }
+
+  /// Returns all methods of class `class_name` declared in
+  /// the current library or its dependencies.
+  fn class_methods(&self, class_name: &str) -> Vec<&'a CppMethod> {
+    let cpp_data = self.cpp_data;
+    once(&cpp_data.current)
+      .chain(cpp_data.dependencies.iter().map(|d| *d))
+      .flat_map(|data| data.methods_and_implicit_destructors())
+      .filter(|m| m.class_name().map_or(false, |name| name == class_name))
+      .collect()
+  }
+
+  /// Generates subclass wrappers for all classes added with
+  /// `Config::add_subclassing_class`.
+  fn generate_subclass_wrappers(&'a self) -> Result<Option<CppFfiHeaderData>> {
+    let include_file_name = "subclasses";
+    if self.subclassing_classes.is_empty() {
+      return Ok(None);
+    }
+    let mut subclass_wrappers = Vec::new();
+    let mut methods = Vec::new();
+    for base_name in self.subclassing_classes {
+      match self.generate_subclass_wrapper(base_name, include_file_name) {
+        Ok((wrapper, mut wrapper_methods)) => {
+          subclass_wrappers.push(wrapper);
+          methods.append(&mut wrapper_methods);
+        }
+        Err(err) => {
+          log::error(format!("Failed to generate subclass wrapper for {}: {}",
+                             base_name,
+                             err));
+          err.discard_expected();
+        }
+      }
+    }
+    if subclass_wrappers.is_empty() {
+      return Ok(None);
+    }
+    Ok(Some(CppFfiHeaderData {
+              include_file_base_name: include_file_name.to_string(),
+              methods: self
+                .process_methods(include_file_name,
+                                 Some(CppTypeAllocationPlace::Heap),
+                                 methods.iter().map(|i| i.as_ref()))?,
+              qt_slot_wrappers: Vec::new(),
+              subclass_wrappers: subclass_wrappers,
             }))
   }
+
+  /// Generates a C++ subclass of `base_name` overriding all its
+  /// virtual methods. Returns information about the subclass and
+  /// the methods that should be exposed through FFI: constructors,
+  /// the destructor, callback setters, calls of base implementations
+  /// of virtual methods, protected methods of the base class and
+  /// the cast to the base class.
+  fn generate_subclass_wrapper(&self,
+                               base_name: &str,
+                               include_file_name: &str)
+                               -> Result<(CppSubclassWrapper, Vec<CppMethodWithKind>)> {
+    let type_info = self
+      .cpp_data
+      .find_type_info(|x| &x.name == base_name)
+      .chain_err(|| "class not found")?;
+    match type_info.kind {
+      CppTypeKind::Class { ref template_arguments, .. } => {
+        if template_arguments.is_some() {
+          return Err("template classes can't be subclassed".into());
+        }
+      }
+      CppTypeKind::Enum { .. } => return Err("enums can't be subclassed".into()),
+    }
+    let base_class = type_info.default_class_type()?;
+    let base_type = CppType {
+      base: CppTypeBase::Class(base_class.clone()),
+      indirection: CppTypeIndirection::None,
+      is_const: false,
+      is_const2: false,
+    };
+    let class_name = format!("{}_Subclass_{}",
+                             self.cpp_ffi_lib_name,
+                             base_type.caption(TypeCaptionStrategy::Full)?);
+    let class_type = CppTypeClassBase {
+      name: class_name.clone(),
+      template_arguments: None,
+    };
+
+    // collect virtual and protected methods of the class and its bases,
+    // skipping base methods hidden by methods with the same name
+    let mut virtual_methods: Vec<&CppMethod> = Vec::new();
+    let mut protected_methods: Vec<&CppMethod> = Vec::new();
+    let mut hidden_names = HashSet::new();
+    let mut pending_classes = vec![base_name.to_string()];
+    while !pending_classes.is_empty() {
+      let current_class = pending_classes.remove(0);
+      let class_methods = self.class_methods(&current_class);
+      for method in &class_methods {
+        if hidden_names.contains(&method.name) {
+          continue;
+        }
+        if let Some(ref info) = method.class_membership {
+          if info.kind.is_constructor() || info.kind.is_destructor() ||
+             method.operator.is_some() || info.is_signal ||
+             info.visibility == CppVisibility::Private {
+            continue;
+          }
+          if info.is_virtual &&
+             !virtual_methods
+                .iter()
+                .any(|m| {
+                       m.name == method.name && m.argument_types_equal(method) &&
+                       m.class_membership.as_ref().map(|i| i.is_const) == Some(info.is_const)
+                     }) {
+            virtual_methods.push(*method);
+          }
+          if info.visibility == CppVisibility::Protected {
+            protected_methods.push(*method);
+          }
+        }
+      }
+      hidden_names.extend(class_methods.iter().map(|m| m.name.clone()));
+      if let Some(info) = self.cpp_data.find_type_info(|x| &x.name == &current_class) {
+        if let CppTypeKind::Class { ref bases, .. } = info.kind {
+          for base in bases {
+            if base.visibility == CppVisibility::Private {
+              continue;
+            }
+            if let CppTypeBase::Class(CppTypeClassBase {
+                                        ref name,
+                                        template_arguments: None,
+                                      }) = base.base_type.base {
+              pending_classes.push(name.clone());
+            }
+          }
+        }
+      }
+    }
+
+    let mut subclass_virtual_methods = Vec::new();
+    let mut name_counters = HashMap::new();
+    for method in &virtual_methods {
+      let key = if virtual_methods
+           .iter()
+           .filter(|m| m.name == method.name)
+           .count() > 1 {
+        let counter = name_counters.entry(method.name.clone()).or_insert(0);
+        *counter += 1;
+        format!("{}_{}", method.name, counter)
+      } else {
+        method.name.clone()
+      };
+      match subclass_virtual_method(self.cpp_data, method, key, &class_type) {
+        Ok(r) => subclass_virtual_methods.push(r),
+        Err(msg) => {
+          if method
+               .class_membership
+               .as_ref()
+               .map_or(false, |info| info.is_pure_virtual) {
+            return Err(format!("pure virtual method can't be overridden: {}: {}",
+                               method.short_text(),
+                               msg)
+                           .into());
+          }
+          log::llog(log::DebugFfiSkips, || {
+            format!("Virtual method can't be overridden in subclass wrapper:\n{}\nError:{}\n",
+                    method.short_text(),
+                    msg)
+          });
+        }
+      }
+    }
+    if subclass_virtual_methods.is_empty() {
+      return Err("class has no virtual methods that can be overridden".into());
+    }
+
+    let base_methods = self.class_methods(base_name);
+    if base_methods
+         .iter()
+         .any(|m| {
+                m.is_destructor() &&
+                m.class_membership
+                  .as_ref()
+                  .map_or(false, |info| info.visibility == CppVisibility::Private)
+              }) {
+      return Err("class has a private destructor".into());
+    }
+    let mut constructors = Vec::new();
+    let mut has_constructors = false;
+    for method in &base_methods {
+      if let Some(ref info) = method.class_membership {
+        if info.kind.is_constructor() {
+          has_constructors = true;
+          // copy and move constructors are not useful because
+          // the subclass can't be copied or moved
+          let is_copy_or_move = method.arguments.len() == 1 && {
+            let arg_type = &method.arguments[0].argument_type;
+            arg_type.base == base_type.base &&
+            ((arg_type.indirection == CppTypeIndirection::Ref && arg_type.is_const) ||
+             arg_type.indirection == CppTypeIndirection::RValueRef)
+          };
+          if info.visibility != CppVisibility::Private && !is_copy_or_move &&
+             !method.allows_variadic_arguments {
+            constructors.push(method.arguments.clone());
+          }
+        }
+      }
+    }
+    if !has_constructors {
+      // implicit default constructor
+      constructors.push(Vec::new());
+    }
+    if constructors.is_empty() {
+      return Err("class has no accessible constructors".into());
+    }
+
+    let create_function = |kind: CppMethodKind,
+                           name: String,
+                           is_const: bool,
+                           arguments: Vec<CppMethodArgument>,
+                           return_type: CppType|
+     -> CppMethodWithKind {
+      CppMethodWithKind {
+        method: CppMethod {
+          name: name,
+          class_membership: Some(CppMethodClassMembership {
+                                   class_type: class_type.clone(),
+                                   is_virtual: false,
+                                   is_pure_virtual: false,
+                                   is_const: is_const,
+                                   is_static: false,
+                                   visibility: CppVisibility::Public,
+                                   is_signal: false,
+                                   is_slot: false,
+                                   kind: kind,
+                                 }),
+          operator: None,
+          return_type: return_type,
+          arguments: arguments,
+          allows_variadic_arguments: false,
+          include_file: include_file_name.to_string(),
+          origin_location: None,
+          template_arguments: None,
+          template_arguments_values: None,
+          declaration_code: None,
+          doc: None,
+          deprecation: None,
+          inheritance_chain: Vec::new(),
+          is_ffi_whitelisted: false,
+          // generated wrapper methods don't throw
+          is_noexcept: true,
+        },
+        kind: CppFfiMethodKind::Real,
+      }
+    };
+    let void_ptr = CppType {
+      base: CppTypeBase::Void,
+      indirection: CppTypeIndirection::Ptr,
+      is_const: false,
+      is_const2: false,
+    };
+    let mut methods = Vec::new();
+    for arguments in &constructors {
+      methods.push(create_function(CppMethodKind::Constructor,
+                                   class_name.clone(),
+                                   false,
+                                   arguments.clone(),
+                                   CppType::void()));
+    }
+    methods.push(create_function(CppMethodKind::Destructor,
+                                 format!("~{}", class_name),
+                                 false,
+                                 vec![],
+                                 CppType::void()));
+    let deleter_type = CppFunctionPointerType {
+      return_type: Box::new(CppType::void()),
+      arguments: vec![void_ptr.clone()],
+      allows_variadic_arguments: false,
+    };
+    methods.push(create_function(CppMethodKind::Regular,
+                                 "set_data".to_string(),
+                                 false,
+                                 vec![CppMethodArgument {
+                                        name: "data".to_string(),
+                                        argument_type: void_ptr.clone(),
+                                        has_default_value: false,
+                                      },
+                                      CppMethodArgument {
+                                        name: "deleter".to_string(),
+                                        argument_type: CppType {
+                                          base: CppTypeBase::FunctionPointer(deleter_type),
+                                          indirection: CppTypeIndirection::None,
+                                          is_const: false,
+                                          is_const2: false,
+                                        },
+                                        has_default_value: false,
+                                      }],
+                                 CppType::void()));
+    for virtual_method in &subclass_virtual_methods {
+      methods.push(create_function(CppMethodKind::Regular,
+                                   format!("set_{}_callback", virtual_method.key),
+                                   false,
+                                   vec![CppMethodArgument {
+                                          name: "func".to_string(),
+                                          argument_type: CppType {
+                                            base: CppTypeBase::FunctionPointer(virtual_method
+                                                                                 .function_type
+                                                                                 .clone()),
+                                            indirection: CppTypeIndirection::None,
+                                            is_const: false,
+                                            is_const2: false,
+                                          },
+                                          has_default_value: false,
+                                        }],
+                                   CppType::void()));
+      if let Some(ref info) = virtual_method.method.class_membership {
+        if !info.is_pure_virtual {
+          methods.push(create_function(CppMethodKind::Regular,
+                                       format!("{}_base", virtual_method.key),
+                                       info.is_const,
+                                       virtual_method
+                                         .method
+                                         .arguments
+                                         .iter()
+                                         .map(|arg| {
+                                                CppMethodArgument {
+                                                  has_default_value: false,
+                                                  ..arg.clone()
+                                                }
+                                              })
+                                         .collect(),
+                                       virtual_method.method.return_type.clone()));
+        }
+      }
+    }
+    let mut protected_method_names = Vec::new();
+    for method in &protected_methods {
+      if !protected_method_names.contains(&method.name) {
+        protected_method_names.push(method.name.clone());
+      }
+      let mut new_method = (*method).clone();
+      if let Some(ref mut info) = new_method.class_membership {
+        info.class_type = class_type.clone();
+        info.visibility = CppVisibility::Public;
+      }
+      new_method.include_file = include_file_name.to_string();
+      methods.push(CppMethodWithKind {
+                     method: new_method,
+                     kind: CppFfiMethodKind::Real,
+                   });
+    }
+    let class_ptr = CppType {
+      base: CppTypeBase::Class(class_type.clone()),
+      indirection: CppTypeIndirection::Ptr,
+      is_const: false,
+      is_const2: false,
+    };
+    let base_ptr = CppType {
+      base: CppTypeBase::Class(base_class.clone()),
+      indirection: CppTypeIndirection::Ptr,
+      is_const: false,
+      is_const2: false,
+    };
+    methods.push(create_cast_method(CppCast::Static {
+                                      is_unsafe: false,
+                                      is_direct: true,
+                                    },
+                                    &class_ptr,
+                                    &base_ptr,
+                                    include_file_name));
+    Ok((CppSubclassWrapper {
+          class_name: class_name,
+          base_class: base_class,
+          constructors: constructors,
+          virtual_methods: subclass_virtual_methods,
+          protected_method_names: protected_method_names,
+        },
+        methods))
+  }
+}
+
+/// Checks if virtual method `method` can be overridden in a subclass wrapper
+/// with class type `class_type` and generates information about the override.
+/// `key` is a unique identifier of the method within the subclass wrapper.
+fn subclass_virtual_method(cpp_data: &CppDataWithDeps,
+                           method: &CppMethod,
+                           key: String,
+                           class_type: &CppTypeClassBase)
+                           -> Result<CppSubclassVirtualMethod> {
+  if method.allows_variadic_arguments {
+    return Err("variadic methods are not supported".into());
+  }
+  if method.template_arguments.is_some() ||
+     method
+       .all_involved_types()
+       .iter()
+       .any(|x| x.base.is_or_contains_template_parameter()) {
+    return Err("template methods are not supported".into());
+  }
+  let is_const = method
+    .class_membership
+    .as_ref()
+    .map_or(false, |info| info.is_const);
+  let arguments = method
+    .arguments
+    .iter()
+    .map_if_ok(|arg| -> Result<_> {
+      let mut ffi_type = arg
+        .argument_type
+        .to_cpp_ffi_type(CppTypeRole::NotReturnType)?;
+      convert_enum_to_int(cpp_data, &mut ffi_type)?;
+      if ffi_type.conversion == CppIndirectionChange::RValueReferenceToPointer {
+        return Err("rvalue reference arguments are not supported".into());
+      }
+      Ok(ffi_type)
+    })?;
+  let mut return_type = method
+    .return_type
+    .to_cpp_ffi_type(CppTypeRole::ReturnType)?;
+  convert_enum_to_int(cpp_data, &mut return_type)?;
+  let (return_allocation_place, callback_return_type, output_argument) =
+    match return_type.conversion {
+      CppIndirectionChange::NoChange |
+      CppIndirectionChange::EnumToInt => {
+        (ReturnValueAllocationPlace::NotApplicable, return_type.ffi_type.clone(), None)
+      }
+      CppIndirectionChange::ValueToPointer => {
+        let class_name = if let CppTypeBase::Class(ref base) = method.return_type.base {
+          &base.name
+        } else {
+          return Err(unexpected("class type expected for ValueToPointer").into());
+        };
+        match cpp_data.type_allocation_place(class_name)? {
+          CppTypeAllocationPlace::Stack => {
+            (ReturnValueAllocationPlace::Stack,
+             CppType::void(),
+             Some(return_type.ffi_type.clone()))
+          }
+          CppTypeAllocationPlace::Heap => {
+            (ReturnValueAllocationPlace::Heap, return_type.ffi_type.clone(), None)
+          }
+        }
+      }
+      _ => return Err("the return type can't be returned from a callback".into()),
+    };
+  let void_ptr = CppType {
+    base: CppTypeBase::Void,
+    indirection: CppTypeIndirection::Ptr,
+    is_const: false,
+    is_const2: false,
+  };
+  let this_ptr = CppType {
+    base: CppTypeBase::Class(class_type.clone()),
+    indirection: CppTypeIndirection::Ptr,
+    is_const: is_const,
+    is_const2: false,
+  };
+  let function_type = CppFunctionPointerType {
+    return_type: Box::new(callback_return_type),
+    arguments: once(void_ptr)
+      .chain(once(this_ptr))
+      .chain(arguments.iter().map(|t| t.ffi_type.clone()))
+      .chain(output_argument)
+      .collect(),
+    allows_variadic_arguments: false,
+  };
+  Ok(CppSubclassVirtualMethod {
+       method: method.clone(),
+       key: key,
+       arguments: arguments,
+       return_type: return_type,
+       return_allocation_place: return_allocation_place,
+       function_type: function_type,
+     })
 }
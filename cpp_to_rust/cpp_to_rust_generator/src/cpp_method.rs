@@ -1,7 +1,8 @@
 //! Types for handling information about C++ methods.
 
 
-use cpp_data::{CppVisibility, CppOriginLocation, TemplateArgumentsDeclaration, CppBaseSpecifier};
+use cpp_data::{CppVisibility, CppOriginLocation, TemplateArgumentsDeclaration, CppBaseSpecifier,
+               CppDeprecation, CppDocContent};
 use cpp_ffi_data::{CppFfiType, CppFfiMethodSignature, CppFfiMethodArgument, CppFfiArgumentMeaning};
 use cpp_type::{CppType, CppTypeIndirection, CppTypeRole, CppTypeBase, CppTypeClassBase};
 use common::errors::{Result, unexpected};
@@ -95,8 +96,8 @@ pub struct CppMethodDoc {
   /// HTML anchor of this documentation entry
   /// (used to detect duplicates)
   pub anchor: String,
-  /// HTML content
-  pub html: String,
+  /// Content of the documentation
+  pub content: CppDocContent,
   /// If the documentation parser couldn't find documentation for the exact same
   /// method, it can still provide documentation entry for the closest match.
   /// In this case, this field should contain C++ declaration of the found method.
@@ -130,6 +131,9 @@ pub struct CppMethod {
   //  pub arguments_before_omitting: Option<Vec<CppMethodArgument>>,
   /// Whether the argument list is terminated with "..."
   pub allows_variadic_arguments: bool,
+  /// True if the method is declared with `noexcept` or `throw()`
+  /// exception specification, so it can't throw exceptions.
+  pub is_noexcept: bool,
   /// File name of the include file where the method is defined
   /// (without full path)
   pub include_file: String,
@@ -159,6 +163,8 @@ pub struct CppMethod {
   //pub is_fake_inherited_method: bool,
   /// C++ documentation data for this method
   pub doc: Option<CppMethodDoc>,
+  /// Deprecation attribute of the method, if any
+  pub deprecation: Option<CppDeprecation>,
   /// If true, FFI generator skips some checks
   pub is_ffi_whitelisted: bool,
 }
@@ -395,6 +401,31 @@ impl CppMethod {
     s.trim().to_string()
   }
 
+  /// Returns the method's signature consisting of its full name
+  /// and argument types, e.g. `QWidget::setLayout(QLayout*)`.
+  /// Signatures are used to refer to methods in `Config`.
+  pub fn signature(&self) -> String {
+    let mut s = self.full_name();
+    if let Some(ref args) = self.template_arguments_values {
+      s = format!("{}<{}>",
+                  s,
+                  args.iter().map(|x| x.to_cpp_pseudo_code()).join(", "));
+    }
+    s = format!("{}({})",
+                s,
+                self
+                  .arguments
+                  .iter()
+                  .map(|arg| arg.argument_type.to_cpp_pseudo_code())
+                  .join(", "));
+    if let Some(ref info) = self.class_membership {
+      if info.is_const {
+        s = format!("{} const", s);
+      }
+    }
+    s
+  }
+
   /// Returns debugging output for `inheritance_chain` content.
   pub fn inheritance_chain_text(&self) -> String {
     self
@@ -439,6 +470,56 @@ impl CppMethod {
     }
   }
 
+  /// Returns true if this method is a public constructor
+  /// that doesn't take any arguments.
+  pub fn is_default_constructor(&self) -> bool {
+    match self.class_info_if_constructor() {
+      Some(info) => info.visibility == CppVisibility::Public && self.arguments.is_empty(),
+      None => false,
+    }
+  }
+
+  /// Returns true if this method is a public constructor
+  /// that takes a single const reference to its own class.
+  pub fn is_copy_constructor(&self) -> bool {
+    match self.class_info_if_constructor() {
+      Some(info) => {
+        if info.visibility != CppVisibility::Public || self.arguments.len() != 1 {
+          return false;
+        }
+        let arg_type = &self.arguments[0].argument_type;
+        arg_type.indirection == CppTypeIndirection::Ref && arg_type.is_const &&
+        arg_type.base == CppTypeBase::Class(info.class_type.clone())
+      }
+      None => false,
+    }
+  }
+
+  /// Returns the class printed by this method if it's a free
+  /// `operator<<(std::ostream&, const T&)` function, and `None` otherwise.
+  pub fn stream_output_class(&self) -> Option<&CppTypeClassBase> {
+    if self.operator != Some(CppOperator::BitwiseLeftShift) || self.class_membership.is_some() ||
+       self.arguments.len() != 2 {
+      return None;
+    }
+    let stream_type = &self.arguments[0].argument_type;
+    if stream_type.indirection != CppTypeIndirection::Ref || stream_type.is_const {
+      return None;
+    }
+    match stream_type.base {
+      CppTypeBase::Class(ref base) if base.is_std_ostream() => {}
+      _ => return None,
+    }
+    let value_type = &self.arguments[1].argument_type;
+    if value_type.indirection != CppTypeIndirection::Ref || !value_type.is_const {
+      return None;
+    }
+    match value_type.base {
+      CppTypeBase::Class(ref base) => Some(base),
+      _ => None,
+    }
+  }
+
   /// A convenience method. Returns `class_membership` if
   /// the method is a constructor, and `None` otherwise.
   pub fn class_info_if_constructor(&self) -> Option<&CppMethodClassMembership> {
@@ -1,5 +1,6 @@
 //! Types for describing C++ operators
 
+use caption_strategy::TypeCaptionStrategy;
 use common::errors::Result;
 use cpp_type::CppType;
 
@@ -207,11 +208,11 @@ impl CppOperator {
 
   /// Returns alphanumeric identifier for this operator
   /// used to name FFI functions.
-  pub fn c_name(&self) -> Result<&'static str> {
+  pub fn c_name(&self) -> Result<String> {
     use self::CppOperator::*;
     Ok(match *self {
-         Conversion(..) => {
-           return Err("CppOperator::c_name: conversion operators are not supported".into())
+         Conversion(ref cpp_type) => {
+           return Ok(format!("convert_to_{}", cpp_type.caption(TypeCaptionStrategy::Full)?))
          }
          Assignment => "assign",
          Addition => "add",
@@ -261,7 +262,8 @@ impl CppOperator {
          NewArray => "new_array",
          Delete => "delete",
          DeleteArray => "delete_array",
-       })
+       }
+         .to_string())
   }
 
   /// Returns all existing operator kinds except for
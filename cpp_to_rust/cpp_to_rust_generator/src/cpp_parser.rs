@@ -1,11 +1,13 @@
 use cpp_data::{ParserCppData, CppData, CppTypeData, CppTypeKind, CppClassField, CppEnumValue,
                CppOriginLocation, CppVisibility, CppClassUsingDirective, CppBaseSpecifier,
-               TemplateArgumentsDeclaration};
-use cpp_method::{CppMethod, CppMethodArgument, CppMethodKind, CppMethodClassMembership};
+               TemplateArgumentsDeclaration, CppConstant, CppConstantValue, CppGlobalVariable,
+               CppTypeAlias, CppDeprecation, CppTypeDoc, CppDocContent};
+use cpp_method::{CppMethod, CppMethodArgument, CppMethodKind, CppMethodClassMembership,
+                 CppMethodDoc};
 use cpp_operator::CppOperator;
 use cpp_type::{CppType, CppTypeBase, CppBuiltInNumericType, CppTypeIndirection,
                CppSpecificNumericTypeKind, CppTypeClassBase, CppSpecificNumericType,
-               CppFunctionPointerType};
+               CppFunctionPointerType, CppTemplateArgumentValue, CppArrayType};
 use common::errors::{Result, ChainErr, unexpected};
 use common::file_utils::{remove_file, open_file, create_file, path_to_str, os_str_to_str};
 use common::string_utils::JoinWithSeparator;
@@ -13,9 +15,12 @@ use common::log;
 
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
+use std::iter::once;
+use std::collections::{HashMap, HashSet};
 
 use clang::*;
 use clang;
+use clang::documentation::{CommentChild, InlineCommandStyle};
 
 use regex::Regex;
 
@@ -97,7 +102,10 @@ fn get_template_arguments(entity: Entity) -> Option<TemplateArgumentsDeclaration
   let names: Vec<_> = entity
     .get_children()
     .into_iter()
-    .filter(|c| c.get_kind() == EntityKind::TemplateTypeParameter)
+    .filter(|c| {
+              c.get_kind() == EntityKind::TemplateTypeParameter ||
+              c.get_kind() == EntityKind::NonTypeTemplateParameter
+            })
     .enumerate()
     .map(|(i, c)| c.get_name().unwrap_or_else(|| format!("Type{}", i + 1)))
     .collect();
@@ -111,6 +119,29 @@ fn get_template_arguments(entity: Entity) -> Option<TemplateArgumentsDeclaration
   }
 }
 
+/// Splits comma-separated template arguments `text` (e.g. `"QPair<int, int>, 16"`)
+/// into separate items, taking nested template arguments into account.
+fn split_template_arguments(text: &str) -> Vec<String> {
+  let mut result = Vec::new();
+  let mut level = 0;
+  let mut current = String::new();
+  for c in text.chars() {
+    match c {
+      '<' | '(' => level += 1,
+      '>' | ')' => level -= 1,
+      ',' if level == 0 => {
+        result.push(current.trim().to_string());
+        current.clear();
+        continue;
+      }
+      _ => {}
+    }
+    current.push(c);
+  }
+  result.push(current.trim().to_string());
+  result
+}
+
 /// Returns fully qualified name of `entity`.
 fn get_full_name(entity: Entity) -> Result<String> {
   let mut current_entity = entity;
@@ -161,6 +192,15 @@ pub struct CppParserConfig {
   /// List of names that should be excluded from the processing.
   /// See `Config::add_cpp_parser_blocked_name` for more details.
   pub name_blacklist: Vec<String>,
+  /// C++ code of template class instantiations that should be parsed.
+  /// See `Config::add_template_instantiation` for more details.
+  pub template_instantiations: Vec<String>,
+  /// Names of integer typedefs representing OR-combinations of enum values.
+  /// See `Config::add_flags_typedef` for more details.
+  pub flags_typedefs: Vec<String>,
+  /// Skip entities marked as deprecated.
+  /// See `Config::set_skip_deprecated` for more details.
+  pub skip_deprecated: bool,
 }
 
 #[cfg(test)]
@@ -182,15 +222,37 @@ fn init_clang() -> Result<Clang> {
 }
 
 
-/// Runs `clang` parser with `config`.
-/// If `cpp_code` is specified, it's written to the C++ file before parsing it.
-/// If successful, calls `f` and passes the topmost entity (the translation unit)
+/// Returns arguments passed to `clang` for parsing with `config`.
+fn clang_arguments(config: &CppParserConfig) -> Result<Vec<String>> {
+  let mut args = vec!["-Xclang".to_string(),
+                      "-detailed-preprocessing-record".to_string()];
+  args.append(&mut config.clang_arguments.clone());
+  for dir in &config.include_paths {
+    let str = path_to_str(dir)?;
+    args.push("-I".to_string());
+    args.push(str.to_string());
+  }
+  if let Ok(path) = ::std::env::var("CLANG_SYSTEM_INCLUDE_PATH") {
+    args.push("-isystem".to_string());
+    args.push(path);
+  }
+  for dir in &config.framework_paths {
+    let str = path_to_str(dir)?;
+    args.push("-F".to_string());
+    args.push(str.to_string());
+  }
+  Ok(args)
+}
+
+/// Writes include directives of `config` followed by `cpp_code` to the
+/// temporary C++ file and parses it with `args`.
+/// If successful, calls `f` and passes the translation unit
 /// as its argument. Returns output value of `f` or an error.
-#[cfg_attr(feature="clippy", allow(block_in_if_condition_stmt))]
-fn run_clang<R, F: Fn(Entity) -> Result<R>>(config: &CppParserConfig,
-                                            cpp_code: Option<String>,
-                                            f: F)
-                                            -> Result<R> {
+fn parse_translation_unit<R, F: FnOnce(&TranslationUnit) -> Result<R>>(config: &CppParserConfig,
+                                                                      args: &[String],
+                                                                      cpp_code: Option<&str>,
+                                                                      f: F)
+                                                                      -> Result<R> {
   let clang = init_clang()?;
   let index = Index::new(&clang, false, false);
   {
@@ -203,14 +265,27 @@ fn run_clang<R, F: Fn(Entity) -> Result<R>>(config: &CppParserConfig,
       tmp_file.write(cpp_code)?;
     }
   }
-  let mut args = vec!["-Xclang".to_string(),
-                      "-detailed-preprocessing-record".to_string()];
-  args.append(&mut config.clang_arguments.clone());
-  for dir in &config.include_paths {
-    let str = path_to_str(dir)?;
-    args.push("-I".to_string());
-    args.push(str.to_string());
-  }
+  let tu = index
+    .parser(&config.tmp_cpp_path)
+    .arguments(args)
+    .detailed_preprocessing_record(true)
+    .parse()
+    .map_err(|err| format!("clang parse failed: {:?}", err))?;
+  let result = f(&tu);
+  remove_file(&config.tmp_cpp_path)?;
+  result
+}
+
+/// Runs `clang` parser with `config`.
+/// If `cpp_code` is specified, it's written to the C++ file before parsing it.
+/// If successful, calls `f` and passes the topmost entity (the translation unit)
+/// as its argument. Returns output value of `f` or an error.
+#[cfg_attr(feature="clippy", allow(block_in_if_condition_stmt))]
+fn run_clang<R, F: Fn(Entity) -> Result<R>>(config: &CppParserConfig,
+                                            cpp_code: Option<String>,
+                                            f: F)
+                                            -> Result<R> {
+  let args = clang_arguments(config)?;
   if let Ok(path) = ::std::env::var("CLANG_SYSTEM_INCLUDE_PATH") {
     if !Path::new(&path).exists() {
       log::error(format!("Warning: CLANG_SYSTEM_INCLUDE_PATH environment variable is set to \"{}\" \
@@ -218,76 +293,547 @@ fn run_clang<R, F: Fn(Entity) -> Result<R>>(config: &CppParserConfig,
                          path));
       log::error("This may result in parse errors related to system header includes.");
     }
-    args.push("-isystem".to_string());
-    args.push(path);
   } else {
     log::error("Warning: CLANG_SYSTEM_INCLUDE_PATH environment variable is not set.");
     log::error("This may result in parse errors related to system header includes.");
   }
-  for dir in &config.framework_paths {
-    let str = path_to_str(dir)?;
-    args.push("-F".to_string());
-    args.push(str.to_string());
-  }
   log::status(format!("clang arguments: {:?}", args));
 
-  let tu = index
-    .parser(&config.tmp_cpp_path)
-    .arguments(&args)
-    .parse()
-    .map_err(|err| format!("clang parse failed: {:?}", err))?;
-  let translation_unit = tu.get_entity();
-  assert!(translation_unit.get_kind() == EntityKind::TranslationUnit);
-  {
-    let diagnostics = tu.get_diagnostics();
-    if !diagnostics.is_empty() {
-      log::llog(log::DebugParser, || "Diagnostics:");
-      for diag in &diagnostics {
-        log::llog(log::DebugParser, || format!("{}", diag));
+  parse_translation_unit(config, &args, cpp_code.as_ref().map(|x| x.as_str()), |tu| {
+    let translation_unit = tu.get_entity();
+    assert!(translation_unit.get_kind() == EntityKind::TranslationUnit);
+    {
+      let diagnostics = tu.get_diagnostics();
+      if !diagnostics.is_empty() {
+        log::llog(log::DebugParser, || "Diagnostics:");
+        for diag in &diagnostics {
+          log::llog(log::DebugParser, || format!("{}", diag));
+        }
+      }
+      if diagnostics
+           .iter()
+           .any(|d| {
+                  d.get_severity() == clang::diagnostic::Severity::Error ||
+                  d.get_severity() == clang::diagnostic::Severity::Fatal
+                }) {
+        return Err(format!("fatal clang error:\n{}",
+                           diagnostics.iter().map(|d| d.to_string()).join("\n"))
+                       .into());
       }
     }
-    if diagnostics
-         .iter()
-         .any(|d| {
-                d.get_severity() == clang::diagnostic::Severity::Error ||
-                d.get_severity() == clang::diagnostic::Severity::Fatal
-              }) {
-      return Err(format!("fatal clang error:\n{}",
-                         diagnostics.iter().map(|d| d.to_string()).join("\n"))
-                     .into());
+    f(translation_unit)
+  })
+}
+
+/// An error reported by `clang` while checking C++ code.
+#[derive(Debug, Clone)]
+pub struct CppCodeError {
+  /// Line of the checked code (starting with 1) the error was caused by,
+  /// or `None` if the error couldn't be attributed to the checked code.
+  pub line: Option<u32>,
+  /// Text of the error.
+  pub message: String,
+}
+
+/// Parses `cpp_code` preceded by include directives of `config`
+/// and returns all errors reported by `clang`. Unlike the parser,
+/// this function instantiates all used templates, so an error
+/// in an instantiated template is attributed to the line of `cpp_code`
+/// that caused the instantiation.
+pub fn check_cpp_code(config: &CppParserConfig, cpp_code: &str) -> Result<Vec<CppCodeError>> {
+  let mut args = clang_arguments(config)?;
+  args.push("-ferror-limit=0".to_string());
+  parse_translation_unit(config, &args, Some(cpp_code), |tu| code_errors(config, tu))
+}
+
+/// Returns all errors reported by `clang` for translation unit `tu`
+/// created by `parse_translation_unit`. Lines are counted from the start
+/// of the code passed to `parse_translation_unit`.
+fn code_errors(config: &CppParserConfig, tu: &TranslationUnit) -> Result<Vec<CppCodeError>> {
+  let file_name = config
+    .tmp_cpp_path
+    .file_name()
+    .chain_err(|| "invalid tmp_cpp_path")?
+    .to_os_string();
+  let lines_offset = config.include_directives.len() as u32;
+  let mut errors = Vec::new();
+  for diag in tu.get_diagnostics() {
+    if diag.get_severity() != clang::diagnostic::Severity::Error &&
+       diag.get_severity() != clang::diagnostic::Severity::Fatal {
+      continue;
+    }
+    // notes attached to the error point to the code
+    // that requested the template instantiation
+    let line = once(&diag)
+      .chain(diag.get_children().iter())
+      .filter_map(|d| {
+        let (path, line, _) = d.get_location().get_presumed_location();
+        if Path::new(&path).file_name() == Some(&file_name) && line > lines_offset {
+          Some(line - lines_offset)
+        } else {
+          None
+        }
+      })
+      .next();
+    errors.push(CppCodeError {
+                  line: line,
+                  message: diag.to_string(),
+                });
+  }
+  Ok(errors)
+}
+
+/// A constant found by the parser before its value is evaluated.
+struct CppConstantCandidate {
+  /// Name of the macro or full name of the variable
+  name: String,
+  /// True if the constant is a macro
+  is_macro: bool,
+  /// Spellings of the tokens of the constant's value
+  value_tokens: Vec<String>,
+  /// File name of the include file
+  include_file: String,
+  /// Location of the declaration
+  origin_location: Option<CppOriginLocation>,
+}
+
+/// Returns spellings of the tokens within source range of `entity`.
+fn entity_tokens(entity: Entity) -> Result<Vec<String>> {
+  let range = entity
+    .get_range()
+    .chain_err(|| "failed to get range of the entity")?;
+  // clang sometimes includes the token following the range
+  let end_offset = range.get_end().get_file_location().offset;
+  Ok(range
+       .tokenize()
+       .into_iter()
+       .filter(|t| t.get_range().get_start().get_file_location().offset < end_offset)
+       .map(|t| t.get_spelling())
+       .collect())
+}
+
+/// Returns deprecation information if `entity` is marked as deprecated.
+/// The message is extracted from the first sequence of adjacent string literals
+/// of the attribute's tokens, so `[[deprecated("msg")]]`,
+/// `__attribute__((deprecated("msg")))` and `Q_DECL_DEPRECATED_X("msg")`
+/// are all supported.
+fn get_deprecation(entity: Entity) -> Option<CppDeprecation> {
+  if entity.get_availability() != Availability::Deprecated {
+    return None;
+  }
+  let mut message = None;
+  for child in entity.get_children() {
+    if child.get_kind() != EntityKind::UnexposedAttr {
+      continue;
+    }
+    let tokens = match entity_tokens(child) {
+      Ok(tokens) => tokens,
+      Err(err) => {
+        err.discard_expected();
+        continue;
+      }
+    };
+    let literals: Vec<_> = tokens
+      .iter()
+      .skip_while(|t| !t.starts_with('"'))
+      .take_while(|t| t.starts_with('"'))
+      .cloned()
+      .collect();
+    if !literals.is_empty() {
+      message = parse_string_literal(&literals);
+      if message.is_none() {
+        log::llog(log::DebugParserSkips, || {
+          format!("Failed to parse deprecation message: {:?}", literals)
+        });
+      }
+      break;
+    }
+  }
+  Some(CppDeprecation { message: message })
+}
+
+/// Converts the documentation comment of `entity` to Markdown.
+/// Returns `None` if the entity has no documentation comment.
+fn get_doc_comment(entity: Entity) -> Option<String> {
+  let comment = match entity.get_parsed_comment() {
+    Some(comment) => comment,
+    None => return None,
+  };
+  let markdown = doc_comment_to_markdown(&comment.get_children());
+  if markdown.is_empty() {
+    None
+  } else {
+    Some(markdown)
+  }
+}
+
+/// Returns documentation of a method extracted from its documentation comment.
+fn get_method_doc(entity: Entity) -> Option<CppMethodDoc> {
+  get_doc_comment(entity).map(|markdown| {
+                                CppMethodDoc {
+                                  anchor: String::new(),
+                                  content: CppDocContent::Markdown(markdown),
+                                  mismatched_declaration: None,
+                                  url: String::new(),
+                                  cross_references: Vec::new(),
+                                }
+                              })
+}
+
+/// Returns documentation of a type extracted from its documentation comment.
+fn get_type_doc(entity: Entity) -> Option<CppTypeDoc> {
+  get_doc_comment(entity).map(|markdown| {
+                                CppTypeDoc {
+                                  content: CppDocContent::Markdown(markdown),
+                                  url: String::new(),
+                                  cross_references: Vec::new(),
+                                }
+                              })
+}
+
+/// Converts block-level content of a parsed Doxygen comment to Markdown.
+/// `\brief` and regular paragraphs become Markdown paragraphs,
+/// `\param` and `\tparam` commands are collected into lists,
+/// and other block commands (`\return`, `\note`, etc.) become
+/// paragraphs starting with the command's title.
+fn doc_comment_to_markdown(children: &[CommentChild]) -> String {
+  let mut blocks = Vec::new();
+  let mut params = Vec::new();
+  let mut params_index = None;
+  let mut template_params = Vec::new();
+  let mut template_params_index = None;
+  for child in children {
+    match *child {
+      CommentChild::Paragraph(ref children) => {
+        let text = inline_doc_comment_to_markdown(children);
+        if !text.is_empty() {
+          blocks.push(text);
+        }
+      }
+      CommentChild::BlockCommand(ref command) => {
+        let mut text = inline_doc_comment_to_markdown(&command.children);
+        if !command.arguments.is_empty() {
+          text = format!("{} {}", command.arguments.join(" "), text);
+        }
+        let title = match command.command.as_ref() {
+          "brief" | "short" | "details" => None,
+          "return" | "returns" | "result" => Some("Returns".to_string()),
+          "see" | "sa" => Some("See also".to_string()),
+          "pre" => Some("Precondition".to_string()),
+          "post" => Some("Postcondition".to_string()),
+          "throw" | "throws" | "exception" => Some("Throws".to_string()),
+          name => {
+            let mut chars = name.chars();
+            chars
+              .next()
+              .map(|first| first.to_uppercase().chain(chars).collect())
+          }
+        };
+        if let Some(title) = title {
+          blocks.push(format!("**{}:** {}", title, text));
+        } else if !text.is_empty() {
+          blocks.push(text);
+        }
+      }
+      CommentChild::ParamCommand(ref param) => {
+        if params_index.is_none() {
+          params_index = Some(blocks.len());
+        }
+        params.push(format!("- `{}`: {}",
+                            param.parameter,
+                            inline_doc_comment_to_markdown(&param.children)));
+      }
+      CommentChild::TParamCommand(ref param) => {
+        if template_params_index.is_none() {
+          template_params_index = Some(blocks.len());
+        }
+        template_params.push(format!("- `{}`: {}",
+                                     param.parameter,
+                                     inline_doc_comment_to_markdown(&param.children)));
+      }
+      CommentChild::VerbatimCommand(ref lines) => {
+        blocks.push(format!("```text\n{}\n```",
+                            lines.iter().map(|line| line.trim_right()).join("\n")));
+      }
+      _ => {}
+    }
+  }
+  let mut lists = Vec::new();
+  if let Some(index) = template_params_index {
+    lists.push((index, format!("**Template parameters:**\n\n{}", template_params.join("\n"))));
+  }
+  if let Some(index) = params_index {
+    lists.push((index, format!("**Parameters:**\n\n{}", params.join("\n"))));
+  }
+  // insert lists starting from the last position so that indexes remain valid
+  lists.sort_by(|a, b| b.0.cmp(&a.0));
+  for (index, list) in lists {
+    blocks.insert(index, list);
+  }
+  blocks.join("\n\n")
+}
+
+/// Converts inline content of a parsed Doxygen comment
+/// (text, `\c`, `\b`, `\e` commands and HTML tags) to a line of Markdown.
+fn inline_doc_comment_to_markdown(children: &[CommentChild]) -> String {
+  let mut result = String::new();
+  for child in children {
+    match *child {
+      CommentChild::Text(ref text) => result.push_str(text),
+      CommentChild::InlineCommand(ref command) => {
+        let text = command.arguments.join(" ");
+        result.push_str(&match command.style {
+                          Some(InlineCommandStyle::Bold) => format!("**{}**", text),
+                          Some(InlineCommandStyle::Monospace) => format!("`{}`", text),
+                          Some(InlineCommandStyle::Emphasized) => format!("*{}*", text),
+                          None => text,
+                        });
+      }
+      CommentChild::HtmlStartTag(ref tag) => {
+        result.push_str(&format!("<{}{}{}>",
+                                 tag.name,
+                                 tag
+                                   .attributes
+                                   .iter()
+                                   .map(|&(ref name, ref value)| {
+                                          format!(" {}=\"{}\"", name, value)
+                                        })
+                                   .join(""),
+                                 if tag.closing { "/" } else { "" }));
+      }
+      CommentChild::HtmlEndTag(ref name) => result.push_str(&format!("</{}>", name)),
+      CommentChild::Paragraph(ref children) => {
+        result.push(' ');
+        result.push_str(&inline_doc_comment_to_markdown(children));
+      }
+      _ => {}
+    }
+  }
+  result.split_whitespace().join(" ")
+}
+
+/// Returns true if enum `entity` is declared as `enum class` or `enum struct`.
+fn enum_is_scoped(entity: Entity) -> bool {
+  match entity_tokens(entity) {
+    Ok(tokens) => {
+      tokens.len() > 1 && tokens[0] == "enum" && (tokens[1] == "class" || tokens[1] == "struct")
+    }
+    Err(err) => {
+      err.discard_expected();
+      false
     }
   }
-  let result = f(translation_unit);
-  remove_file(&config.tmp_cpp_path)?;
-  result
+}
+
+/// Returns true if `entity` is an anonymous struct or union declared
+/// inside a class without declaring a field (like `union { int a; float b; };`).
+/// `siblings` are all children of the enclosing class.
+fn is_anonymous_member(entity: Entity, siblings: &[Entity]) -> bool {
+  // newer libclang versions report names like "(anonymous union at file.h:3:5)"
+  let is_unnamed = match entity.get_name() {
+    None => true,
+    Some(name) => name.starts_with('('),
+  };
+  // `struct { int x; } pos;` declares a named field of an unnamed type
+  is_unnamed && entity.is_definition() &&
+  !siblings
+     .iter()
+     .any(|sibling| {
+            sibling.get_kind() == EntityKind::FieldDecl &&
+            sibling
+              .get_type()
+              .and_then(|t| t.get_declaration()) == Some(entity)
+          })
+}
+
+/// Returns true if all brackets in `tokens` are paired.
+fn brackets_are_balanced(tokens: &[String]) -> bool {
+  let mut stack = Vec::new();
+  for token in tokens {
+    match token.as_str() {
+      "(" | "[" | "{" => stack.push(token.as_str()),
+      ")" => if stack.pop() != Some("(") { return false; },
+      "]" => if stack.pop() != Some("[") { return false; },
+      "}" => if stack.pop() != Some("{") { return false; },
+      _ => {}
+    }
+  }
+  stack.is_empty()
+}
+
+/// Removes parentheses enclosing all `tokens`.
+fn remove_enclosing_parentheses(mut tokens: &[String]) -> &[String] {
+  while tokens.len() >= 2 && tokens[0] == "(" && tokens[tokens.len() - 1] == ")" &&
+        brackets_are_balanced(&tokens[1..tokens.len() - 1]) {
+    tokens = &tokens[1..tokens.len() - 1];
+  }
+  tokens
+}
+
+/// Parses a floating point literal optionally preceded by a sign
+/// and returns its value as a Rust literal.
+fn parse_float_literal(tokens: &[String]) -> Option<String> {
+  let tokens = remove_enclosing_parentheses(tokens);
+  let (is_negative, tokens) = match tokens.first().map(|x| x.as_str()) {
+    Some("-") => (true, &tokens[1..]),
+    Some("+") => (false, &tokens[1..]),
+    _ => (false, tokens),
+  };
+  if tokens.len() != 1 {
+    return None;
+  }
+  let text = tokens[0].trim_right_matches(|c| c == 'f' || c == 'F' || c == 'l' || c == 'L');
+  if text.starts_with("0x") || text.starts_with("0X") {
+    return None;
+  }
+  match text.parse::<f64>() {
+    Ok(value) => Some(format!("{:?}", if is_negative { -value } else { value })),
+    Err(_) => None,
+  }
+}
+
+/// Parses a sequence of adjacent narrow string literals
+/// and returns the resulting string.
+fn parse_string_literal(tokens: &[String]) -> Option<String> {
+  let tokens = remove_enclosing_parentheses(tokens);
+  if tokens.is_empty() {
+    return None;
+  }
+  let mut result = String::new();
+  for token in tokens {
+    if token.len() < 2 || !token.starts_with('"') || !token.ends_with('"') {
+      return None;
+    }
+    let mut chars = token[1..token.len() - 1].chars().peekable();
+    while let Some(c) = chars.next() {
+      if c != '\\' {
+        result.push(c);
+        continue;
+      }
+      let escaped = match chars.next() {
+        Some('n') => '\n',
+        Some('t') => '\t',
+        Some('r') => '\r',
+        Some('a') => '\x07',
+        Some('b') => '\x08',
+        Some('f') => '\x0c',
+        Some('v') => '\x0b',
+        Some('0') if !chars.peek().map_or(false, |c| c.is_digit(8)) => '\0',
+        Some(c @ '\\') | Some(c @ '"') | Some(c @ '\'') | Some(c @ '?') => c,
+        Some('x') => {
+          let mut code = String::new();
+          while let Some(&c) = chars.peek() {
+            if !c.is_digit(16) {
+              break;
+            }
+            code.push(c);
+            chars.next();
+          }
+          match u8::from_str_radix(&code, 16) {
+            Ok(code) if code < 0x80 => code as char,
+            _ => return None,
+          }
+        }
+        _ => return None,
+      };
+      result.push(escaped);
+    }
+  }
+  Some(result)
 }
 
 /// Runs the parser on specified data.
 pub fn run(config: CppParserConfig, dependencies_data: &[&CppData]) -> Result<ParserCppData> {
   log::status(get_version());
   log::status("Initializing clang...");
-  let (mut parser, methods) = run_clang(&config, None, |translation_unit| {
-    let mut parser = CppParser {
-      types: Vec::new(),
-      config: config.clone(),
-      dependencies_data: dependencies_data,
-    };
-    log::status("Parsing types");
-    parser.parse_types(translation_unit);
-    log::status("Parsing methods");
-    let methods = parser.parse_methods(translation_unit);
-    Ok((parser, methods))
-  })?;
+  let instantiations_code = config
+    .template_instantiations
+    .iter()
+    .enumerate()
+    .map(|(num, cpp_type)| {
+           format!("typedef {} {}{};\n",
+                   cpp_type,
+                   INSTANTIATION_TYPEDEF_PREFIX,
+                   num)
+         })
+    .join("");
+  let (mut parser, methods, instantiations, constant_candidates, variables, type_aliases) =
+    run_clang(&config, Some(instantiations_code), |translation_unit| {
+      let mut parser = CppParser {
+        types: Vec::new(),
+        config: config.clone(),
+        dependencies_data: dependencies_data,
+      };
+      log::status("Parsing types");
+      parser.parse_types(translation_unit);
+      log::status("Parsing methods");
+      let methods = parser.parse_methods(translation_unit);
+      let instantiations = parser.parse_requested_instantiations(translation_unit)?;
+      let mut constant_candidates = Vec::new();
+      parser.find_constants(translation_unit, &mut constant_candidates);
+      log::status("Parsing global variables");
+      let mut variables = Vec::new();
+      parser.parse_global_variables(translation_unit, &mut variables);
+      log::status("Parsing type aliases");
+      let mut type_aliases = Vec::new();
+      parser.parse_type_aliases(translation_unit, &mut type_aliases);
+      Ok((parser, methods, instantiations, constant_candidates, variables, type_aliases))
+    })?;
+  log::status("Evaluating constants");
+  let constants = parser.evaluate_constants(constant_candidates)?;
   log::status("Checking data integrity");
   let (good_methods, good_types) = parser.check_integrity(methods);
   parser.types = good_types;
+  let good_variables = variables
+    .into_iter()
+    .filter(|variable| match parser.check_type_integrity(&variable.variable_type) {
+              Ok(()) => true,
+              Err(msg) => {
+                log::llog(log::DebugParserSkips, || {
+                  format!("Variable removed because type is not available: {}: {}",
+                          variable.name,
+                          msg)
+                });
+                false
+              }
+            })
+    .collect();
+  let good_type_aliases = type_aliases
+    .into_iter()
+    .filter(|alias| match parser.check_type_integrity(&alias.target_type) {
+              Ok(()) => true,
+              Err(msg) => {
+                log::llog(log::DebugParserSkips, || {
+                  format!("Type alias removed because type is not available: {}: {}",
+                          alias.name,
+                          msg)
+                });
+                false
+              }
+            })
+    .collect();
+  for (cpp_type, code) in instantiations
+        .iter()
+        .zip(config.template_instantiations.iter()) {
+    parser
+      .check_type_integrity(cpp_type)
+      .chain_err(|| format!("requested template instantiation is invalid: {}", code))?;
+  }
   log::status("Searching for template instantiations");
   Ok(ParserCppData {
        types: parser.types,
        methods: good_methods,
+       requested_template_instantiations: instantiations,
+       constants: constants,
+       global_variables: good_variables,
+       type_aliases: good_type_aliases,
      })
 }
 
+/// Prefix of names of typedefs generated for
+/// template instantiations requested in the config.
+const INSTANTIATION_TYPEDEF_PREFIX: &'static str = "cpp_to_rust_template_instantiation_";
+
 impl<'a> CppParser<'a> {
   /// Search for a C++ type information in the types found by the parser
   /// and in types of the dependencies.
@@ -339,11 +885,8 @@ impl<'a> CppParser<'a> {
           if let Some(matches) = template_class_regex.captures(name.as_ref()) {
             let mut arg_types = Vec::new();
             if let Some(items) = matches.at(2) {
-              for arg in items.split(',') {
-                match self.parse_unexposed_type(None,
-                                                Some(arg.trim().to_string()),
-                                                context_class,
-                                                context_method) {
+              for arg in split_template_arguments(items) {
+                match self.parse_template_argument(&arg, context_class, context_method) {
                   Ok(arg_type) => arg_types.push(arg_type),
                   Err(msg) => {
                     return Err(format!("Template argument of unexposed type is not parsed: {}: {}",
@@ -512,11 +1055,8 @@ impl<'a> CppParser<'a> {
            .find_type(|x| &x.name == class_name && x.is_class())
            .is_some() {
         let mut arg_types = Vec::new();
-        for arg in matches[2].split(',') {
-          match self.parse_unexposed_type(None,
-                                          Some(arg.trim().to_string()),
-                                          context_class,
-                                          context_method) {
+        for arg in split_template_arguments(&matches[2]) {
+          match self.parse_template_argument(&arg, context_class, context_method) {
             Ok(arg_type) => arg_types.push(arg_type),
             Err(msg) => {
               return Err(format!("Template argument of unexposed type is not parsed: {}: {}",
@@ -541,6 +1081,86 @@ impl<'a> CppParser<'a> {
     Err(format!("Unrecognized unexposed type: {}", name).into())
   }
 
+  /// Attempts to parse `text` as a value of a non-type template argument,
+  /// like `16`, `true` or `Qt::Horizontal`.
+  fn parse_template_argument_value(&self, text: &str) -> Result<CppTemplateArgumentValue> {
+    match text {
+      "true" => return Ok(CppTemplateArgumentValue::Bool(true)),
+      "false" => return Ok(CppTemplateArgumentValue::Bool(false)),
+      _ => {}
+    }
+    let integer_regex = Regex::new(r"^(-?\d+)[uUlL]*$")?;
+    if let Some(matches) = integer_regex.captures(text) {
+      let value = matches[1]
+        .parse()
+        .chain_err(|| format!("integer template argument is out of range: {}", text))?;
+      return Ok(CppTemplateArgumentValue::Integer(value));
+    }
+    let enum_cast_regex = Regex::new(r"^\(([\w:]+)\)\s*(-?\d+)$")?;
+    if let Some(matches) = enum_cast_regex.captures(text) {
+      let enum_name = &matches[1];
+      if self
+           .find_type(|x| &x.name == enum_name && !x.is_class())
+           .is_some() {
+        return Ok(CppTemplateArgumentValue::Enum {
+                    enum_name: enum_name.to_string(),
+                    value: matches[2]
+                      .parse()
+                      .chain_err(|| format!("enum template argument is out of range: {}", text))?,
+                  });
+      }
+    }
+    let find_enum_value = |type_data: &CppTypeData| -> Option<i64> {
+      if let CppTypeKind::Enum {
+               ref values,
+               ref is_scoped,
+               ..
+             } = type_data.kind {
+        let scope = match type_data.name.rfind("::") {
+          Some(index) => &type_data.name[0..index + 2],
+          None => "",
+        };
+        // values of scoped enums are only accessible through the enum's name
+        values
+          .iter()
+          .find(|v| {
+                  (!*is_scoped && format!("{}{}", scope, v.name) == text) ||
+                  format!("{}::{}", type_data.name, v.name) == text
+                })
+          .map(|v| v.value)
+      } else {
+        None
+      }
+    };
+    if let Some(type_data) = self.find_type(|x| find_enum_value(x).is_some()) {
+      return Ok(CppTemplateArgumentValue::Enum {
+                  enum_name: type_data.name.clone(),
+                  value: find_enum_value(type_data).expect("checked in find_type"),
+                });
+    }
+    Err(format!("not a template argument value: {}", text).into())
+  }
+
+  /// Parses a template argument represented by `text`. The argument may be
+  /// a type or a value of a non-type template argument.
+  /// Surrounding class and/or
+  /// method may be specified in `context_class` and `context_method`.
+  fn parse_template_argument(&self,
+                             text: &str,
+                             context_class: Option<Entity>,
+                             context_method: Option<Entity>)
+                             -> Result<CppType> {
+    if let Ok(value) = self.parse_template_argument_value(text) {
+      return Ok(CppType {
+                  base: CppTypeBase::TemplateArgumentValue(value),
+                  is_const: false,
+                  is_const2: false,
+                  indirection: CppTypeIndirection::None,
+                });
+    }
+    self.parse_unexposed_type(None, Some(text.to_string()), context_class, context_method)
+  }
+
   /// Parses type `type1`.
   /// Surrounding class and/or
   /// method may be specified in `context_class` and `context_method`.
@@ -577,6 +1197,29 @@ impl<'a> CppParser<'a> {
                           is_const2: parsed.is_const2,
                         });
             }
+            if self.config.flags_typedefs.iter().any(|x| x == &name) {
+              // the typedef's name is preserved so that FFI generator can
+              // detect it and convert it to the flags type
+              if let CppTypeBase::BuiltInNumeric(ref numeric) = parsed.base {
+                if numeric.is_signed_integer() || numeric.is_unsigned_integer() {
+                  let size = type1
+                    .get_sizeof()
+                    .map_err(|err| format!("failed to get size of flags type: {:?}", err))?;
+                  return Ok(CppType {
+                              base: CppTypeBase::SpecificNumeric(CppSpecificNumericType {
+                                name: name,
+                                bits: size * 8,
+                                kind: CppSpecificNumericTypeKind::Integer {
+                                  is_signed: numeric.is_signed_integer(),
+                                },
+                              }),
+                              indirection: parsed.indirection,
+                              is_const: parsed.is_const,
+                              is_const2: parsed.is_const2,
+                            });
+                }
+              }
+            }
           }
         }
         Ok(parsed)
@@ -647,9 +1290,36 @@ impl<'a> CppParser<'a> {
               if arg_types.is_empty() {
                 return Err(unexpected("arg_types is empty").into());
               }
-              for arg_type in arg_types {
+              let display_name = type1.get_display_name();
+              for (index, arg_type) in arg_types.into_iter().enumerate() {
                 match arg_type {
-                  None => return Err("Template argument is None".into()),
+                  None => {
+                    // non-type template argument; its value is only
+                    // available in the type's display name
+                    let args_text = match (display_name.find('<'), display_name.rfind('>')) {
+                      (Some(start), Some(end)) if start < end => &display_name[start + 1..end],
+                      _ => {
+                        return Err(format!("Template argument is None and type name has no \
+                                            template arguments: {}",
+                                           display_name)
+                                       .into())
+                      }
+                    };
+                    let arg_texts = split_template_arguments(args_text);
+                    let arg_text = arg_texts
+                      .get(index)
+                      .chain_err(|| format!("not enough template arguments in type name: {}",
+                                            display_name))?;
+                    match self.parse_template_argument(arg_text, context_class, context_method) {
+                      Ok(parsed_type) => r.push(parsed_type),
+                      Err(msg) => {
+                        return Err(format!("Invalid non-type template argument: {}: {}",
+                                           arg_text,
+                                           msg)
+                                       .into())
+                      }
+                    }
+                  }
                   Some(arg_type) => {
                     match self.parse_type(arg_type, context_class, context_method) {
                       Ok(parsed_type) => r.push(parsed_type),
@@ -723,6 +1393,30 @@ impl<'a> CppParser<'a> {
              indirection: CppTypeIndirection::None,
            })
       }
+      TypeKind::ConstantArray => {
+        let element_type = type1
+          .get_element_type()
+          .chain_err(|| "can't get array element type")?;
+        let size = type1.get_size().chain_err(|| "can't get array size")?;
+        let element_type = self
+          .parse_type(element_type, context_class, context_method)
+          .chain_err(|| "failed to parse array element type")?;
+        Ok(CppType {
+             is_const: element_type.indirection == CppTypeIndirection::None &&
+                       element_type.is_const,
+             base: CppTypeBase::Array(CppArrayType {
+                                        element_type: Box::new(element_type),
+                                        size: size,
+                                      }),
+             is_const2: false,
+             indirection: CppTypeIndirection::None,
+           })
+      }
+      TypeKind::IncompleteArray |
+      TypeKind::VariableArray |
+      TypeKind::DependentSizedArray => {
+        Err("Arrays without a fixed size are not supported".into())
+      }
       TypeKind::Pointer |
       TypeKind::LValueReference |
       TypeKind::RValueReference => {
@@ -955,15 +1649,7 @@ impl<'a> CppParser<'a> {
       return Err(format!("failed to get function arguments: {:?}", entity).into());
     };
     let template_arguments = match entity.get_kind() {
-      EntityKind::FunctionTemplate => {
-        if entity
-             .get_children()
-             .into_iter()
-             .any(|c| c.get_kind() == EntityKind::NonTypeTemplateParameter) {
-          return Err("Non-type template parameter is not supported".into());
-        }
-        get_template_arguments(entity)
-      }
+      EntityKind::FunctionTemplate => get_template_arguments(entity),
       _ => None,
     };
     let mut is_signal = false;
@@ -1038,6 +1724,20 @@ impl<'a> CppParser<'a> {
       }
     }
     let allows_variadic_arguments = entity.is_variadic();
+    // libclang doesn't expose exception specifications directly,
+    // but they are included at the end of the function type's spelling,
+    // e.g. "void (int) noexcept" or "void () throw()". Conditional specifications
+    // other than `noexcept(true)` (e.g. `noexcept(false)` or `noexcept(sizeof(T) < 8)`)
+    // are considered potentially throwing.
+    let is_noexcept = match entity.get_type() {
+      Some(function_type) => {
+        let spelling = function_type.get_display_name();
+        [" noexcept", " noexcept(true)", " throw()"]
+          .iter()
+          .any(|x| spelling.ends_with(x))
+      }
+      None => false,
+    };
     let has_this_argument = class_name.is_some() && !entity.is_static_method();
     let real_arguments_count = arguments.len() + if has_this_argument { 1 } else { 0 };
     let mut method_operator = None;
@@ -1184,10 +1884,12 @@ impl<'a> CppParser<'a> {
          template_arguments: template_arguments,
          template_arguments_values: None,
          declaration_code: declaration_code,
-         doc: None,
+         doc: get_method_doc(entity),
+         deprecation: get_deprecation(entity),
          inheritance_chain: Vec::new(),
          //is_fake_inherited_method: false,
          is_ffi_whitelisted: false,
+         is_noexcept: is_noexcept,
        })
   }
 
@@ -1203,6 +1905,9 @@ impl<'a> CppParser<'a> {
     let mut values = Vec::new();
     for child in entity.get_children() {
       if child.get_kind() == EntityKind::EnumConstantDecl {
+        if self.config.skip_deprecated && child.get_availability() == Availability::Deprecated {
+          continue;
+        }
         let val = child
           .get_enum_constant_value()
           .chain_err(|| "failed to get value of enum variant")?;
@@ -1211,16 +1916,58 @@ impl<'a> CppParser<'a> {
                         .get_name()
                         .chain_err(|| "failed to get name of enum variant")?,
                       value: val.0,
-                      doc: None,
+                      doc: get_doc_comment(child).map(CppDocContent::Markdown),
+                      deprecation: get_deprecation(child),
                     });
       }
     }
+    let underlying_clang_type = entity
+      .get_enum_underlying_type()
+      .chain_err(|| "failed to get underlying type of enum")?;
+    let mut underlying_type = self
+      .parse_type(underlying_clang_type, None, None)
+      .chain_err(|| "failed to parse underlying type of enum")?;
+    // size or signedness of these types depends on the platform,
+    // so they are replaced with fixed-size types of the same size
+    let platform_type = if let CppTypeBase::BuiltInNumeric(ref numeric) = underlying_type.base {
+      let is_signed = match *numeric {
+        CppBuiltInNumericType::Long => Some(true),
+        CppBuiltInNumericType::ULong |
+        CppBuiltInNumericType::Bool => Some(false),
+        CppBuiltInNumericType::Char => {
+          Some(underlying_clang_type.get_canonical_type().get_kind() == TypeKind::CharS)
+        }
+        // clang doesn't report signedness of `wchar_t`, but only the size
+        // matters for passing the values
+        CppBuiltInNumericType::WChar => Some(values.iter().any(|x| x.value < 0)),
+        _ => None,
+      };
+      is_signed.map(|is_signed| (numeric.to_cpp_code(), is_signed))
+    } else {
+      None
+    };
+    if let Some((name, is_signed)) = platform_type {
+      let size = underlying_clang_type
+        .get_sizeof()
+        .map_err(|err| format!("failed to get size of enum's underlying type: {:?}", err))?;
+      let kind = CppSpecificNumericTypeKind::Integer { is_signed: is_signed };
+      underlying_type.base = CppTypeBase::SpecificNumeric(CppSpecificNumericType {
+                                                            name: name.to_string(),
+                                                            bits: size * 8,
+                                                            kind: kind,
+                                                          });
+    }
     Ok(CppTypeData {
          name: get_full_name(entity)?,
          include_file: include_file,
          origin_location: get_origin_location(entity)?,
-         kind: CppTypeKind::Enum { values: values },
-         doc: None,
+         kind: CppTypeKind::Enum {
+           values: values,
+           underlying_type: underlying_type,
+           is_scoped: enum_is_scoped(entity),
+         },
+         doc: get_type_doc(entity),
+         deprecation: get_deprecation(entity),
        })
   }
 
@@ -1244,6 +1991,11 @@ impl<'a> CppParser<'a> {
            Ok(size) => Some(size),
            Err(_) => None,
          },
+         bit_width: if entity.is_bit_field() {
+           entity.get_bit_field_width()
+         } else {
+           None
+         },
          name: field_name,
          field_type: field_type,
          visibility: match entity
@@ -1256,6 +2008,46 @@ impl<'a> CppParser<'a> {
        })
   }
 
+  /// Parses fields of a class, struct or union `entity` and adds them to `fields`.
+  /// Members of anonymous structs and unions nested in `entity` are added
+  /// as well because C++ code accesses them as members of the enclosing class.
+  fn parse_class_fields(&self, entity: Entity, fields: &mut Vec<CppClassField>) {
+    let children = entity.get_children();
+    for child in &children {
+      match child.get_kind() {
+        EntityKind::FieldDecl => {
+          match self.parse_class_field(*child) {
+            Ok(field) => fields.push(field),
+            Err(err) => {
+              log::llog(log::DebugParserSkips,
+                        || format!("failed to parse class field: {}", err));
+              err.discard_expected();
+            }
+          }
+        }
+        EntityKind::StructDecl | EntityKind::UnionDecl => {
+          if is_anonymous_member(*child, &children) {
+            let first_index = fields.len();
+            self.parse_class_fields(*child, fields);
+            let visibility = match child
+                    .get_accessibility()
+                    .unwrap_or(Accessibility::Public) {
+              Accessibility::Public => CppVisibility::Public,
+              Accessibility::Protected => CppVisibility::Protected,
+              Accessibility::Private => CppVisibility::Private,
+            };
+            if visibility != CppVisibility::Public {
+              for field in &mut fields[first_index..] {
+                field.visibility = visibility.clone();
+              }
+            }
+          }
+        }
+        _ => {}
+      }
+    }
+  }
+
   /// Parses a class or a struct `entity`.
   fn parse_class(&self, entity: Entity) -> Result<CppTypeData> {
     let include_file = self
@@ -1295,17 +2087,8 @@ impl<'a> CppParser<'a> {
              })
       })
       .collect();
+    self.parse_class_fields(entity, &mut fields);
     for child in entity.get_children() {
-      if child.get_kind() == EntityKind::FieldDecl {
-        match self.parse_class_field(child) {
-          Ok(field) => fields.push(field),
-          Err(err) => {
-            log::llog(log::DebugParserSkips,
-                      || format!("failed to parse class field: {}", err));
-            err.discard_expected();
-          }
-        }
-      }
       if child.get_kind() == EntityKind::BaseSpecifier {
         let base_type = match self.parse_type(child.get_type().unwrap(), Some(entity), None) {
           Ok(r) => r,
@@ -1323,9 +2106,6 @@ impl<'a> CppParser<'a> {
                      },
                    });
       }
-      if child.get_kind() == EntityKind::NonTypeTemplateParameter {
-        return Err("Non-type template parameter is not supported".into());
-      }
     }
     let template_arguments = get_template_arguments(entity);
     if entity.get_kind() == EntityKind::ClassTemplate {
@@ -1357,7 +2137,8 @@ impl<'a> CppParser<'a> {
            using_directives: using_directives,
            template_arguments: template_arguments,
          },
-         doc: None,
+         doc: get_type_doc(entity),
+         deprecation: get_deprecation(entity),
        })
   }
 
@@ -1414,9 +2195,427 @@ impl<'a> CppParser<'a> {
         return false;
       }
     }
+    // pure virtual methods are needed to detect abstract classes
+    if self.config.skip_deprecated && entity.get_availability() == Availability::Deprecated &&
+       !entity.is_pure_virtual_method() {
+      return false;
+    }
     true
   }
 
+  /// Parses types of template instantiations requested in the config
+  /// from typedefs generated for them in translation unit `entity`.
+  /// The output is in the same order as the requests.
+  fn parse_requested_instantiations(&self, entity: Entity) -> Result<Vec<CppType>> {
+    let mut result = Vec::new();
+    for (num, code) in self.config.template_instantiations.iter().enumerate() {
+      let name = format!("{}{}", INSTANTIATION_TYPEDEF_PREFIX, num);
+      let typedef = entity
+        .get_children()
+        .into_iter()
+        .find(|e| {
+                e.get_kind() == EntityKind::TypedefDecl && e.get_name().as_ref() == Some(&name)
+              })
+        .chain_err(|| {
+                     format!("typedef for requested template instantiation not found: {}",
+                             code)
+                   })?;
+      let clang_type = typedef
+        .get_typedef_underlying_type()
+        .chain_err(|| "failed to get typedef's underlying type")?;
+      let cpp_type = self
+        .parse_type(clang_type, None, None)
+        .chain_err(|| format!("failed to parse requested template instantiation: {}", code))?;
+      match cpp_type.base {
+        CppTypeBase::Class(CppTypeClassBase { template_arguments: Some(..), .. }) => {}
+        _ => return Err(format!("not a template class instantiation: {}", code).into()),
+      }
+      if cpp_type.indirection != CppTypeIndirection::None || cpp_type.is_const {
+        return Err(format!("requested template instantiation must be a class type: {}",
+                           code)
+                       .into());
+      }
+      result.push(cpp_type);
+    }
+    Ok(result)
+  }
+
+  /// Collects public global variables and static data members
+  /// within `entity` and its children to `result`.
+  fn parse_global_variables(&self, entity: Entity, result: &mut Vec<CppGlobalVariable>) {
+    if !self.should_process_entity(entity) {
+      return;
+    }
+    match entity.get_kind() {
+      EntityKind::VarDecl => {
+        match self.parse_global_variable(entity) {
+          Ok(Some(r)) => result.push(r),
+          Ok(None) => {}
+          Err(msg) => {
+            log::llog(log::DebugParserSkips, || {
+              format!("Failed to parse variable: {}\nentity: {:?}\nerror: {}\n",
+                      get_full_name(entity).unwrap_or("?".into()),
+                      entity,
+                      msg)
+            });
+          }
+        }
+      }
+      EntityKind::TranslationUnit |
+      EntityKind::Namespace |
+      EntityKind::StructDecl |
+      EntityKind::ClassDecl |
+      EntityKind::UnexposedDecl |
+      EntityKind::LinkageSpec => {
+        for c in entity.get_children() {
+          self.parse_global_variables(c, result);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  /// Parses a global variable or a static data member.
+  /// Returns `None` if the variable is not accessible from other
+  /// translation units or is a compile-time constant.
+  fn parse_global_variable(&self, entity: Entity) -> Result<Option<CppGlobalVariable>> {
+    if let Some(accessibility) = entity.get_accessibility() {
+      if accessibility != Accessibility::Public {
+        return Ok(None);
+      }
+    }
+    if entity.get_linkage() != Some(Linkage::External) {
+      return Ok(None);
+    }
+    if entity_tokens(entity)?.iter().any(|t| t == "constexpr") {
+      // handled as a constant
+      return Ok(None);
+    }
+    let parent = entity
+      .get_semantic_parent()
+      .chain_err(|| "variable has no semantic parent")?;
+    let class_entity = match parent.get_kind() {
+      EntityKind::ClassDecl | EntityKind::StructDecl => Some(parent),
+      _ => None,
+    };
+    let class_type = match class_entity {
+      Some(class_entity) => {
+        if get_template_arguments(class_entity).is_some() {
+          return Err("static members of template classes are not supported".into());
+        }
+        Some(CppTypeClassBase {
+               name: get_full_name(class_entity)?,
+               template_arguments: None,
+             })
+      }
+      None => None,
+    };
+    let type1 = entity
+      .get_type()
+      .chain_err(|| "failed to get type of the variable")?;
+    Ok(Some(CppGlobalVariable {
+              name: get_full_name(entity)?,
+              variable_type: self.parse_type(type1, class_entity, None)?,
+              class_type: class_type,
+              include_file: self.entity_include_file(entity)?,
+              origin_location: get_origin_location(entity).ok(),
+            }))
+  }
+
+  /// Collects public typedefs and `using` type aliases
+  /// within `entity` and its children to `result`.
+  fn parse_type_aliases(&self, entity: Entity, result: &mut Vec<CppTypeAlias>) {
+    if !self.should_process_entity(entity) {
+      return;
+    }
+    match entity.get_kind() {
+      EntityKind::TypedefDecl |
+      EntityKind::TypeAliasDecl => {
+        match self.parse_type_alias(entity) {
+          Ok(Some(r)) => result.push(r),
+          Ok(None) => {}
+          Err(msg) => {
+            log::llog(log::DebugParserSkips, || {
+              format!("Failed to parse type alias: {}\nentity: {:?}\nerror: {}\n",
+                      get_full_name(entity).unwrap_or("?".into()),
+                      entity,
+                      msg)
+            });
+          }
+        }
+      }
+      EntityKind::TranslationUnit |
+      EntityKind::Namespace |
+      EntityKind::StructDecl |
+      EntityKind::ClassDecl |
+      EntityKind::UnionDecl |
+      EntityKind::UnexposedDecl |
+      EntityKind::LinkageSpec => {
+        if get_template_arguments(entity).is_some() {
+          // aliases within class templates depend on template parameters
+          return;
+        }
+        for c in entity.get_children() {
+          self.parse_type_aliases(c, result);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  /// Parses a typedef or a `using` type alias.
+  /// Returns `None` if the alias is not accessible or
+  /// is already handled by the parser in a special way.
+  fn parse_type_alias(&self, entity: Entity) -> Result<Option<CppTypeAlias>> {
+    if let Some(accessibility) = entity.get_accessibility() {
+      if accessibility != Accessibility::Public {
+        return Ok(None);
+      }
+    }
+    let name = get_full_name(entity)?;
+    if name.starts_with(INSTANTIATION_TYPEDEF_PREFIX) ||
+       self.parse_special_typedef(&name).is_some() ||
+       self.config.flags_typedefs.iter().any(|x| x == &name) {
+      return Ok(None);
+    }
+    let underlying_type = entity
+      .get_typedef_underlying_type()
+      .chain_err(|| "failed to get underlying type of the alias")?;
+    let target_type = self.parse_type(underlying_type, None, None)?;
+    if let CppTypeBase::Class(CppTypeClassBase { name: ref class_name, .. }) = target_type.base {
+      if class_name == &name {
+        // `typedef struct Foo { ... } Foo;`
+        return Ok(None);
+      }
+    }
+    if let CppTypeBase::Enum { name: ref enum_name } = target_type.base {
+      if enum_name == &name {
+        return Ok(None);
+      }
+    }
+    Ok(Some(CppTypeAlias {
+              name: name,
+              target_type: target_type,
+              include_file: self.entity_include_file(entity)?,
+              origin_location: get_origin_location(entity).ok(),
+            }))
+  }
+
+  /// Collects object-like macros and `constexpr` variables
+  /// within `entity` and its children to `result`.
+  fn find_constants(&self, entity: Entity, result: &mut Vec<CppConstantCandidate>) {
+    if !self.should_process_entity(entity) {
+      return;
+    }
+    match entity.get_kind() {
+      EntityKind::MacroDefinition |
+      EntityKind::VarDecl => {
+        let candidate = if entity.get_kind() == EntityKind::MacroDefinition {
+          self.parse_macro_constant(entity)
+        } else {
+          self.parse_variable_constant(entity)
+        };
+        match candidate {
+          Ok(Some(r)) => result.push(r),
+          Ok(None) => {}
+          Err(msg) => {
+            log::llog(log::DebugParserSkips, || {
+              format!("Failed to parse constant: {}\nentity: {:?}\nerror: {}\n",
+                      entity.get_name().unwrap_or("?".into()),
+                      entity,
+                      msg)
+            });
+          }
+        }
+      }
+      EntityKind::TranslationUnit |
+      EntityKind::Namespace |
+      EntityKind::StructDecl |
+      EntityKind::ClassDecl => {
+        for c in entity.get_children() {
+          self.find_constants(c, result);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  /// Returns a constant candidate if `entity` is an object-like macro
+  /// with non-empty value.
+  fn parse_macro_constant(&self, entity: Entity) -> Result<Option<CppConstantCandidate>> {
+    let name = entity.get_name().chain_err(|| "macro has no name")?;
+    if name.starts_with("__") {
+      return Ok(None);
+    }
+    if !Path::new(&self.entity_include_path(entity)?).is_file() {
+      // built-in macro
+      return Ok(None);
+    }
+    let range = entity
+      .get_range()
+      .chain_err(|| "failed to get range of the macro")?;
+    let tokens = range.tokenize();
+    if tokens.len() >= 2 && tokens[1].get_spelling() == "(" &&
+       tokens[0].get_range().get_end().get_file_location().offset ==
+       tokens[1].get_range().get_start().get_file_location().offset {
+      // function-like macro
+      return Ok(None);
+    }
+    let value_tokens: Vec<_> = entity_tokens(entity)?.into_iter().skip(1).collect();
+    if value_tokens.is_empty() || !brackets_are_balanced(&value_tokens) {
+      return Ok(None);
+    }
+    Ok(Some(CppConstantCandidate {
+              name: name,
+              is_macro: true,
+              value_tokens: value_tokens,
+              include_file: self.entity_include_file(entity)?,
+              origin_location: get_origin_location(entity).ok(),
+            }))
+  }
+
+  /// Returns a constant candidate if `entity` is a public
+  /// `constexpr` variable.
+  fn parse_variable_constant(&self, entity: Entity) -> Result<Option<CppConstantCandidate>> {
+    if let Some(accessibility) = entity.get_accessibility() {
+      if accessibility != Accessibility::Public {
+        return Ok(None);
+      }
+    }
+    let tokens = entity_tokens(entity)?;
+    if !tokens.iter().any(|t| t == "constexpr") {
+      return Ok(None);
+    }
+    let value_tokens = tokens
+      .iter()
+      .position(|t| t == "=")
+      .map(|index| tokens[index + 1..].to_vec())
+      .unwrap_or_default();
+    Ok(Some(CppConstantCandidate {
+              name: get_full_name(entity)?,
+              is_macro: false,
+              value_tokens: value_tokens,
+              include_file: self.entity_include_file(entity)?,
+              origin_location: get_origin_location(entity).ok(),
+            }))
+  }
+
+  /// Evaluates values of constant `candidates` by parsing code that
+  /// uses them. Candidates that don't evaluate to an integer, a floating point
+  /// number or a string literal are skipped.
+  fn evaluate_constants(&self,
+                        candidates: Vec<CppConstantCandidate>)
+                        -> Result<Vec<CppConstant>> {
+    if candidates.is_empty() {
+      return Ok(Vec::new());
+    }
+    // each candidate takes two lines: a variable used to detect
+    // the value's type and an enum used to evaluate integer values
+    let cpp_code = candidates
+      .iter()
+      .enumerate()
+      .map(|(num, candidate)| {
+             format!("auto cpp_to_rust_constant_{num} = ({name});\n\
+                      enum cpp_to_rust_constant_enum_{num} : long long {{ \
+                      cpp_to_rust_constant_value_{num} = ({name}) }};\n",
+                     num = num,
+                     name = candidate.name)
+           })
+      .join("");
+    let mut args = clang_arguments(&self.config)?;
+    args.push("-ferror-limit=0".to_string());
+    parse_translation_unit(&self.config, &args, Some(&cpp_code), |tu| {
+      let error_lines: HashSet<u32> = code_errors(&self.config, tu)?
+        .into_iter()
+        .filter_map(|e| e.line)
+        .collect();
+      let mut entities = HashMap::new();
+      for child in tu.get_entity().get_children() {
+        let items = if child.get_kind() == EntityKind::EnumDecl {
+          child.get_children()
+        } else {
+          vec![child]
+        };
+        for item in items {
+          if let Some(name) = item.get_name() {
+            if name.starts_with("cpp_to_rust_constant_") {
+              entities.insert(name, item);
+            }
+          }
+        }
+      }
+      let mut constants = Vec::new();
+      for (num, candidate) in candidates.into_iter().enumerate() {
+        let line = 2 * num as u32 + 1;
+        if error_lines.contains(&line) {
+          log::llog(log::DebugParserSkips,
+                    || format!("Constant can't be evaluated: {}", candidate.name));
+          continue;
+        }
+        let value_type = match entities
+                .get(&format!("cpp_to_rust_constant_{}", num))
+                .and_then(|e| e.get_type())
+                .chain_err(|| "failed to get type of the constant")
+                .and_then(|t| self.parse_type(t.get_canonical_type(), None, None)) {
+          Ok(t) => t,
+          Err(msg) => {
+            log::llog(log::DebugParserSkips, || {
+              format!("Failed to get type of constant: {}: {}", candidate.name, msg)
+            });
+            continue;
+          }
+        };
+        let value = if !error_lines.contains(&(line + 1)) {
+          if value_type.indirection != CppTypeIndirection::None {
+            None
+          } else {
+            entities
+              .get(&format!("cpp_to_rust_constant_value_{}", num))
+              .and_then(|e| e.get_enum_constant_value())
+              .map(|(value, _)| CppConstantValue::Integer(value))
+          }
+        } else {
+          match (&value_type.base, &value_type.indirection) {
+            (&CppTypeBase::BuiltInNumeric(ref t), &CppTypeIndirection::None) if t.is_float() => {
+              parse_float_literal(&candidate.value_tokens).map(CppConstantValue::Float)
+            }
+            (&CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Char),
+             &CppTypeIndirection::Ptr) if value_type.is_const => {
+              parse_string_literal(&candidate.value_tokens).map(CppConstantValue::String)
+            }
+            _ => None,
+          }
+        };
+        let is_numeric_type = match value_type.base {
+          CppTypeBase::BuiltInNumeric(..) |
+          CppTypeBase::SpecificNumeric(..) |
+          CppTypeBase::PointerSizedInteger { .. } => true,
+          _ => false,
+        };
+        match value {
+          Some(value) if is_numeric_type => {
+            constants.push(CppConstant {
+                             name: candidate.name,
+                             is_macro: candidate.is_macro,
+                             value_type: value_type,
+                             value: value,
+                             include_file: candidate.include_file,
+                             origin_location: candidate.origin_location,
+                           });
+          }
+          _ => {
+            log::llog(log::DebugParserSkips, || {
+              format!("Unsupported value of constant: {}: {}",
+                      candidate.name,
+                      value_type.to_cpp_pseudo_code())
+            });
+          }
+        }
+      }
+      Ok(constants)
+    })
+  }
+
   /// Parses type declarations in translation unit `entity`
   /// and saves them to `self`.
   fn parse_types(&mut self, entity: Entity) {
@@ -1581,7 +2780,9 @@ impl<'a> CppParser<'a> {
       CppTypeBase::SpecificNumeric { .. } |
       CppTypeBase::PointerSizedInteger { .. } |
       CppTypeBase::TemplateParameter { .. } => {}
-      CppTypeBase::Enum { ref name } => {
+      CppTypeBase::Enum { ref name } |
+      CppTypeBase::TemplateArgumentValue(CppTemplateArgumentValue::Enum { enum_name: ref name,
+                                                                          .. }) => {
         if self.find_type(|x| &x.name == name).is_none() {
           return Err(format!("unknown type: {}", name).into());
         }
@@ -1601,6 +2802,12 @@ impl<'a> CppParser<'a> {
           }
         }
       }
+      CppTypeBase::TemplateArgumentValue(..) => {}
+      CppTypeBase::Array(CppArrayType { ref element_type, .. }) => {
+        if let Err(msg) = self.check_type_integrity(element_type) {
+          return Err(msg);
+        }
+      }
       CppTypeBase::FunctionPointer(CppFunctionPointerType {
                                      ref return_type,
                                      ref arguments,
@@ -1624,6 +2831,22 @@ impl<'a> CppParser<'a> {
     let good_methods = methods
       .into_iter()
       .filter(|method| {
+        // `std::ostream` is not parsed, but stream output operators
+        // are kept to implement `Debug` for the printed class
+        if let Some(class_base) = method.stream_output_class() {
+          let class_type = CppType {
+            base: CppTypeBase::Class(class_base.clone()),
+            indirection: CppTypeIndirection::None,
+            is_const: false,
+            is_const2: false,
+          };
+          if let Err(msg) = self.check_type_integrity(&class_type) {
+            log::llog(log::DebugParserSkips,
+                      || format!("Method is removed: {}: {}", method.short_text(), msg));
+            return false;
+          }
+          return true;
+        }
         if let Err(msg) = self.check_type_integrity(&method.return_type.clone()) {
           log::llog(log::DebugParserSkips,
                     || format!("Method is removed: {}: {}", method.short_text(), msg));
@@ -122,9 +122,11 @@ impl<'a> CppPostProcessor<'a> {
                          template_arguments_values: None,
                          declaration_code: None,
                          doc: None,
+                         deprecation: None,
                          inheritance_chain: Vec::new(),
                          //is_fake_inherited_method: false,
                          is_ffi_whitelisted: false,
+                         is_noexcept: false,
                        });
         }
       }
@@ -132,7 +134,8 @@ impl<'a> CppPostProcessor<'a> {
     Ok(methods)
   }
 
-  /// Searches for template instantiations in this library's API,
+  /// Searches for template instantiations in this library's API
+  /// and template instantiations requested in the config,
   /// excluding results that were already processed in dependencies.
   #[cfg_attr(feature="clippy", allow(block_in_if_condition_stmt))]
   fn find_template_instantiations(&self) -> Vec<CppTemplateInstantiations> {
@@ -224,6 +227,9 @@ impl<'a> CppPostProcessor<'a> {
         }
       }
     }
+    for t in &self.parser_data.requested_template_instantiations {
+      check_type(t, &self.dependencies, &mut result);
+    }
     result
   }
 
@@ -0,0 +1,280 @@
+//! Test compilation of instantiated C++ template methods.
+//!
+//! A template class or a template method may declare methods that
+//! can't be instantiated with some template arguments (e.g. `QList<T>::contains`
+//! requires `T` to have `operator==`). Such methods can't be detected by the parser,
+//! so the generator writes code using each instantiated method into a scratch
+//! translation unit and removes the methods `clang` reports errors for.
+
+use cpp_method::{CppMethod, CppMethodKind};
+use cpp_parser::{CppParserConfig, check_cpp_code};
+use clang::get_version;
+use cpp_type::CppTypeIndirection;
+use common::errors::{Result, ChainErr};
+use common::file_utils::{load_json, save_json};
+use common::string_utils::JoinWithSeparator;
+use common::log;
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// Maximal number of methods checked in a single translation unit.
+const BATCH_SIZE: usize = 500;
+
+/// Checks whether instantiated template methods compile.
+///
+/// Results are cached by the generated test code, so a method is only
+/// compiled again if its signature or the checker's code generation has changed.
+/// All cached results are discarded if the compilation environment has changed.
+pub struct CppTemplateChecker {
+  /// Parser configuration used to compile the test code
+  parser_config: CppParserConfig,
+  /// Path to the file with cached results
+  cache_path: PathBuf,
+  /// If true, the results are saved to `cache_path`
+  write_cache: bool,
+  /// Known results
+  cache: CppTemplateCheckerCache,
+}
+
+/// Settings affecting results of the checks.
+#[derive(Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+struct CppTemplateCheckerEnvironment {
+  /// Include paths passed to `clang`
+  include_paths: Vec<PathBuf>,
+  /// Framework paths passed to `clang`
+  framework_paths: Vec<PathBuf>,
+  /// Headers included in the test code
+  include_directives: Vec<PathBuf>,
+  /// Additional arguments passed to `clang`
+  clang_arguments: Vec<String>,
+  /// Version of `clang` used to compile the test code
+  clang_version: String,
+  /// Version of the C++ library
+  cpp_lib_version: Option<String>,
+}
+
+/// Content of the cache file
+#[derive(Debug)]
+#[derive(Serialize, Deserialize)]
+struct CppTemplateCheckerCache {
+  /// Compilation environment of `results`
+  environment: CppTemplateCheckerEnvironment,
+  /// Test code mapped to the error message,
+  /// or `None` if the code compiles
+  results: HashMap<String, Option<String>>,
+}
+
+/// Generates arguments and body of a test function using `method`.
+/// The output is appended to a function name to produce the function's code.
+fn test_function_code(method: &CppMethod) -> Result<String> {
+  let mut arguments = Vec::new();
+  let mut values = Vec::new();
+  for (num, arg) in method.arguments.iter().enumerate() {
+    let name = format!("arg{}", num);
+    arguments.push(arg.argument_type.to_cpp_declaration(&name)?);
+    values.push(if arg.argument_type.indirection == CppTypeIndirection::RValueRef {
+                  format!("std::move({})", name)
+                } else {
+                  name
+                });
+  }
+  let template_args = match method.template_arguments_values {
+    Some(ref args) => {
+      let mut texts = Vec::new();
+      for arg in args {
+        texts.push(arg.to_cpp_code(None)?);
+      }
+      format!("<{}>", texts.join(", "))
+    }
+    None => String::new(),
+  };
+  let statement = if let Some(ref info) = method.class_membership {
+    let class_code = info.class_type.to_cpp_code()?;
+    match info.kind {
+      CppMethodKind::Constructor => format!("new {}({})", class_code, values.join(", ")),
+      CppMethodKind::Destructor => {
+        arguments.insert(0, format!("{}* self", class_code));
+        "delete self".to_string()
+      }
+      CppMethodKind::Regular => {
+        if info.is_static {
+          format!("{}::{}{}({})",
+                  class_code,
+                  method.name,
+                  template_args,
+                  values.join(", "))
+        } else {
+          arguments.insert(0,
+                           format!("{}{}* self",
+                                   if info.is_const { "const " } else { "" },
+                                   class_code));
+          format!("self->{}{}({})",
+                  method.name,
+                  template_args,
+                  values.join(", "))
+        }
+      }
+    }
+  } else {
+    format!("{}{}({})", method.name, template_args, values.join(", "))
+  };
+  Ok(format!("({}) {{\n  {};\n}}\n", arguments.join(", "), statement))
+}
+
+impl CppTemplateChecker {
+  /// Creates a checker. If `use_cache` is true, results
+  /// are loaded from `cache_path` if it exists and was created
+  /// with the same parser settings, `clang` version and `cpp_lib_version`.
+  pub fn new(parser_config: CppParserConfig,
+             cache_path: PathBuf,
+             use_cache: bool,
+             write_cache: bool,
+             cpp_lib_version: Option<String>)
+             -> CppTemplateChecker {
+    let environment = CppTemplateCheckerEnvironment {
+      include_paths: parser_config.include_paths.clone(),
+      framework_paths: parser_config.framework_paths.clone(),
+      include_directives: parser_config.include_directives.clone(),
+      clang_arguments: parser_config.clang_arguments.clone(),
+      clang_version: get_version(),
+      cpp_lib_version: cpp_lib_version,
+    };
+    let results = if use_cache && cache_path.is_file() {
+      match load_json::<_, CppTemplateCheckerCache>(&cache_path) {
+        Ok(cache) => {
+          if cache.environment == environment {
+            cache.results
+          } else {
+            log::status("Template check results are outdated");
+            HashMap::new()
+          }
+        }
+        Err(err) => {
+          log::status(format!("Failed to load template check results: {}", err));
+          err.discard_expected();
+          HashMap::new()
+        }
+      }
+    } else {
+      HashMap::new()
+    };
+    CppTemplateChecker {
+      parser_config: parser_config,
+      cache_path: cache_path,
+      write_cache: write_cache,
+      cache: CppTemplateCheckerCache {
+        environment: environment,
+        results: results,
+      },
+    }
+  }
+
+  /// Checks whether each of `methods` compiles. Returns a list
+  /// containing an error message for each method that doesn't compile
+  /// and `None` for each method that compiles.
+  pub fn check(&mut self, methods: &[&CppMethod]) -> Result<Vec<Option<String>>> {
+    let codes: Vec<_> = methods
+      .iter()
+      .map(|method| test_function_code(method))
+      .collect();
+    let mut unknown_codes = Vec::new();
+    {
+      let mut unique_codes = HashSet::new();
+      for code in &codes {
+        if let Ok(ref code) = *code {
+          if !self.cache.results.contains_key(code) && unique_codes.insert(code.clone()) {
+            unknown_codes.push(code.clone());
+          }
+        }
+      }
+    }
+    if !unknown_codes.is_empty() {
+      log::status(format!("Test-compiling {} template instantiations", unknown_codes.len()));
+      for batch in unknown_codes.chunks(BATCH_SIZE) {
+        self.check_batch(batch.to_vec())?;
+      }
+      if self.write_cache {
+        save_json(&self.cache_path, &self.cache)?;
+      }
+    }
+    let mut output = Vec::new();
+    for code in codes {
+      output.push(match code {
+                    Ok(code) => {
+                      self
+                        .cache
+                        .results
+                        .get(&code)
+                        .chain_err(|| "missing template check result")?
+                        .clone()
+                    }
+                    Err(err) => {
+                      err.discard_expected();
+                      Some(format!("failed to generate test code: {}", err))
+                    }
+                  });
+    }
+    Ok(output)
+  }
+
+  /// Compiles all `codes` in a single translation unit and records results.
+  /// Codes with errors are excluded and the rest is compiled again
+  /// until no errors are left. If errors can't be attributed
+  /// to any code, the batch is split in halves.
+  fn check_batch(&mut self, mut codes: Vec<String>) -> Result<()> {
+    while !codes.is_empty() {
+      let mut cpp_code = "#include <utility>\n".to_string();
+      let mut line_ranges = Vec::new();
+      let mut line = 2;
+      for (num, code) in codes.iter().enumerate() {
+        let lines_count = code.lines().count() as u32;
+        line_ranges.push((line, line + lines_count - 1));
+        line += lines_count;
+        cpp_code.push_str(&format!("void cpp_to_rust_template_check_{}{}", num, code));
+      }
+      let errors = check_cpp_code(&self.parser_config, &cpp_code)
+        .chain_err(|| "failed to run template check")?;
+      if errors.is_empty() {
+        for code in codes {
+          self.cache.results.insert(code, None);
+        }
+        return Ok(());
+      }
+      let mut failed: HashMap<usize, Vec<String>> = HashMap::new();
+      for error in &errors {
+        if let Some(line) = error.line {
+          if let Some(index) = line_ranges
+               .iter()
+               .position(|&(first, last)| line >= first && line <= last) {
+            failed
+              .entry(index)
+              .or_insert_with(Vec::new)
+              .push(error.message.clone());
+          }
+        }
+      }
+      if failed.is_empty() {
+        if codes.len() == 1 {
+          let message = errors.iter().map(|e| e.message.clone()).join("\n");
+          self.cache.results.insert(codes.remove(0), Some(message));
+          return Ok(());
+        }
+        let second_half = codes.split_off(codes.len() / 2);
+        self.check_batch(codes)?;
+        return self.check_batch(second_half);
+      }
+      let mut remaining = Vec::new();
+      for (index, code) in codes.into_iter().enumerate() {
+        if let Some(messages) = failed.remove(&index) {
+          self.cache.results.insert(code, Some(messages.join("\n")));
+        } else {
+          remaining.push(code);
+        }
+      }
+      codes = remaining;
+    }
+    Ok(())
+  }
+}
@@ -71,10 +71,29 @@ pub struct CppTypeClassBase {
   pub name: String,
   /// For template classes, C++ types used as template
   /// arguments in this type,
-  /// like [QString, int] in QHash<QString, int>
+  /// like [QString, int] in QHash<QString, int>.
+  /// Non-type template arguments (like `16` in `Buffer<int, 16>`)
+  /// are represented by `CppTypeBase::TemplateArgumentValue`.
   pub template_arguments: Option<Vec<CppType>>,
 }
 
+/// Value of a non-type template argument
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[derive(Serialize, Deserialize)]
+pub enum CppTemplateArgumentValue {
+  /// Integer value, like `16` in `Buffer<int, 16>`
+  Integer(i64),
+  /// Boolean value
+  Bool(bool),
+  /// Value of an enum type
+  Enum {
+    /// Name of the enum type, including namespaces and nested classes
+    enum_name: String,
+    /// Numeric value
+    value: i64,
+  },
+}
+
 /// Information about a C++ function pointer type
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
 #[derive(Serialize, Deserialize)]
@@ -87,6 +106,17 @@ pub struct CppFunctionPointerType {
   pub allows_variadic_arguments: bool,
 }
 
+/// Information about a fixed-size C++ array type
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[derive(Serialize, Deserialize)]
+pub struct CppArrayType {
+  /// Type of array elements. Multidimensional arrays
+  /// are represented as arrays of arrays.
+  pub element_type: Box<CppType>,
+  /// Number of elements
+  pub size: usize,
+}
+
 /// Information about a numeric C++ type that is
 /// guaranteed to be the same on all platforms,
 /// e.g. `uint32_t`.
@@ -141,6 +171,14 @@ pub enum CppTypeBase {
   },
   /// Function pointer type
   FunctionPointer(CppFunctionPointerType),
+  /// Fixed-size array type, like `float[4]`.
+  /// Constness of the array is determined by its element type,
+  /// and `CppType::is_const` of the array type must be the same.
+  Array(CppArrayType),
+  /// Value of a non-type template argument, like `16` in `Buffer<int, 16>`.
+  /// This is not a real type and is only allowed in template arguments
+  /// of a class type.
+  TemplateArgumentValue(CppTemplateArgumentValue),
 }
 
 /// Information about a C++ type
@@ -273,6 +311,37 @@ impl CppBuiltInNumericType {
 }
 
 
+impl CppTemplateArgumentValue {
+  /// Returns C++ code representing this value.
+  pub fn to_cpp_code(&self) -> String {
+    match *self {
+      CppTemplateArgumentValue::Integer(ref value) => value.to_string(),
+      CppTemplateArgumentValue::Bool(ref value) => value.to_string(),
+      CppTemplateArgumentValue::Enum {
+        ref enum_name,
+        ref value,
+      } => format!("static_cast< {} >({})", enum_name, value),
+    }
+  }
+
+  /// Returns alphanumeric representation of this value.
+  pub fn caption(&self) -> String {
+    let value_caption = |value: i64| if value < 0 {
+      format!("minus_{}", -value)
+    } else {
+      value.to_string()
+    };
+    match *self {
+      CppTemplateArgumentValue::Integer(ref value) => value_caption(*value),
+      CppTemplateArgumentValue::Bool(ref value) => value.to_string(),
+      CppTemplateArgumentValue::Enum {
+        ref enum_name,
+        ref value,
+      } => format!("{}_{}", enum_name.replace("::", "_"), value_caption(*value)),
+    }
+  }
+}
+
 impl CppTypeClassBase {
   /// Returns C++ code representing this type.
   pub fn to_cpp_code(&self) -> Result<String> {
@@ -326,6 +395,26 @@ impl CppTypeClassBase {
        })
   }
 
+  /// Returns true if this type is `std::ostream`
+  /// (`std::basic_ostream<char>`, possibly in an inline namespace of `std`).
+  pub fn is_std_ostream(&self) -> bool {
+    if self.name != "std::basic_ostream" && self.name != "std::__1::basic_ostream" {
+      return false;
+    }
+    match self.template_arguments {
+      Some(ref args) => {
+        args.get(0) ==
+        Some(&CppType {
+                base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Char),
+                indirection: CppTypeIndirection::None,
+                is_const: false,
+                is_const2: false,
+              })
+      }
+      None => false,
+    }
+  }
+
   /// Returns string representation of this type for debugging output.
   pub fn to_cpp_pseudo_code(&self) -> String {
     if let Some(ref template_arguments) = self.template_arguments {
@@ -371,11 +460,21 @@ impl CppTypeBase {
       _ => false,
     }
   }
+  /// Returns true if this is a fixed-size array.
+  pub fn is_array(&self) -> bool {
+    match *self {
+      CppTypeBase::Array(..) => true,
+      _ => false,
+    }
+  }
   /// Returns true if this is a template parameter or a type that
   /// contains any template parameters.
   pub fn is_or_contains_template_parameter(&self) -> bool {
     match *self {
       CppTypeBase::TemplateParameter { .. } => true,
+      CppTypeBase::Array(CppArrayType { ref element_type, .. }) => {
+        element_type.base.is_or_contains_template_parameter()
+      }
       CppTypeBase::Class(CppTypeClassBase { ref template_arguments, .. }) => {
         if let Some(ref template_arguments) = *template_arguments {
           template_arguments
@@ -390,8 +489,11 @@ impl CppTypeBase {
   }
 
   /// Returns C++ code representing this type.
+  /// Function pointer and array types require `function_pointer_inner_text`
+  /// to be placed inside the type's declarator (e.g. `(*name)` or `name[4]`).
+  /// For arrays, the text may be omitted to produce an abstract type.
   pub fn to_cpp_code(&self, function_pointer_inner_text: Option<&str>) -> Result<String> {
-    if !self.is_function_pointer() && function_pointer_inner_text.is_some() {
+    if !self.is_function_pointer() && !self.is_array() && function_pointer_inner_text.is_some() {
       return Err("unexpected function_pointer_inner_text".into());
     }
     match *self {
@@ -406,6 +508,7 @@ impl CppTypeBase {
       CppTypeBase::TemplateParameter { .. } => {
         Err("template parameters are not allowed in C++ code generator".into())
       }
+      CppTypeBase::TemplateArgumentValue(ref value) => Ok(value.to_cpp_code()),
       CppTypeBase::FunctionPointer(CppFunctionPointerType {
                                      ref return_type,
                                      ref arguments,
@@ -427,6 +530,14 @@ impl CppTypeBase {
           return Err("function_pointer_inner_text argument is missing".into());
         }
       }
+      CppTypeBase::Array(CppArrayType {
+                           ref element_type,
+                           ref size,
+                         }) => {
+        element_type.to_cpp_declaration(&format!("{}[{}]",
+                                                 function_pointer_inner_text.unwrap_or(""),
+                                                 size))
+      }
     }
   }
 
@@ -443,6 +554,7 @@ impl CppTypeBase {
          CppTypeBase::TemplateParameter { .. } => {
       return Err("template parameters are not allowed to have captions".into());
     }
+         CppTypeBase::TemplateArgumentValue(ref value) => value.caption(),
          CppTypeBase::FunctionPointer(CppFunctionPointerType {
                                         ref return_type,
                                         ref arguments,
@@ -460,7 +572,10 @@ impl CppTypeBase {
              }
            }
          }
-
+         CppTypeBase::Array(CppArrayType {
+                              ref element_type,
+                              ref size,
+                            }) => format!("{}_array_{}", element_type.caption(strategy)?, size),
        })
   }
 
@@ -480,6 +595,10 @@ impl CppTypeBase {
                  .to_cpp_code(Some(&"FN_PTR".to_string()))
                  .unwrap_or_else(|_| "[?]".to_string())
       }
+      CppTypeBase::Array(CppArrayType {
+                           ref element_type,
+                           ref size,
+                         }) => return format!("{}[{}]", element_type.to_cpp_pseudo_code(), size),
       _ => {}
     };
     self
@@ -532,10 +651,65 @@ impl CppType {
 
   /// Returns C++ code representing this type.
   pub fn to_cpp_code(&self, function_pointer_inner_text: Option<&str>) -> Result<String> {
+    if self.base.is_array() {
+      // indirection is applied inside of the array's declarator,
+      // like `float (*name)[4]`
+      let indirection_text = match self.indirection {
+        CppTypeIndirection::None => "",
+        CppTypeIndirection::Ptr => "*",
+        CppTypeIndirection::Ref => "&",
+        CppTypeIndirection::RValueRef => "&&",
+        _ => {
+          return Err(format!("unsupported indirection for array type: {:?}", self.indirection)
+                       .into())
+        }
+      };
+      let inner_text = function_pointer_inner_text.unwrap_or("");
+      return if indirection_text.is_empty() {
+               self.base.to_cpp_code(function_pointer_inner_text)
+             } else {
+               self
+                 .base
+                 .to_cpp_code(Some(&format!("({}{})", indirection_text, inner_text)))
+             };
+    }
     let base_code = self.base.to_cpp_code(function_pointer_inner_text)?;
     Ok(self.to_cpp_code_intermediate(&base_code))
   }
 
+  /// Returns C++ code declaring a variable (or an argument) `name` of this type.
+  /// Unlike `"{type} {name}"`, this is also correct for
+  /// function pointers and arrays.
+  pub fn to_cpp_declaration(&self, name: &str) -> Result<String> {
+    if self.base.is_function_pointer() || self.base.is_array() {
+      self.to_cpp_code(Some(name))
+    } else if name.is_empty() {
+      self.to_cpp_code(None)
+    } else if name.starts_with('[') {
+      Ok(format!("{}{}", self.to_cpp_code(None)?, name))
+    } else {
+      Ok(format!("{} {}", self.to_cpp_code(None)?, name))
+    }
+  }
+
+  /// Returns a copy of this type with constness of the pointee
+  /// (or the value, if there is no indirection) set to `is_const`.
+  /// For array types, constness of the elements is changed as well.
+  pub fn with_const(&self, is_const: bool) -> CppType {
+    let mut r = self.clone();
+    r.is_const = is_const;
+    if let CppTypeBase::Array(ref mut array) = r.base {
+      if array.element_type.indirection == CppTypeIndirection::None {
+        *array.element_type = array.element_type.with_const(is_const);
+        r.is_const = array.element_type.is_const;
+      } else {
+        // constness of pointers stored in the array can't be represented
+        r.is_const = false;
+      }
+    }
+    r
+  }
+
   /// Returns string representation of this type for debugging output.
   pub fn to_cpp_pseudo_code(&self) -> String {
     let base_code = self.base.to_cpp_pseudo_code();
@@ -552,6 +726,10 @@ impl CppType {
       CppTypeBase::TemplateParameter { .. } => {
         return Err(Error::from("template parameters cannot be expressed in FFI")).chain_err(&err);
       }
+      CppTypeBase::TemplateArgumentValue(..) => {
+        return Err(Error::from("template argument values cannot be expressed in FFI"))
+                 .chain_err(&err);
+      }
       CppTypeBase::FunctionPointer(CppFunctionPointerType {
                                      ref return_type,
                                      ref arguments,
@@ -601,6 +779,38 @@ impl CppType {
                     original_type: self.clone(),
                   });
       }
+      CppTypeBase::Array(CppArrayType { ref element_type, .. }) => {
+        if self.base.is_or_contains_template_parameter() {
+          return Err(Error::from("template parameters cannot be expressed in FFI"))
+                   .chain_err(&err);
+        }
+        if element_type.base.is_function_pointer() {
+          return Err(Error::from("arrays of function pointers are not supported"))
+                   .chain_err(&err);
+        }
+        match self.indirection {
+          CppTypeIndirection::None => {
+            if role == CppTypeRole::ReturnType {
+              return Err(Error::from("arrays can't be returned by value")).chain_err(&err);
+            }
+            // array arguments decay to a pointer to the first element
+            let mut decayed_type = element_type.as_ref().clone();
+            decayed_type.indirection =
+              CppTypeIndirection::combine(&element_type.indirection, &CppTypeIndirection::Ptr)
+                .chain_err(&err)?;
+            return Ok(CppFfiType {
+                        ffi_type: decayed_type,
+                        conversion: CppIndirectionChange::NoChange,
+                        original_type: self.clone(),
+                      });
+          }
+          CppTypeIndirection::RValueRef => {
+            return Err(Error::from("rvalue references to arrays are not supported"))
+                     .chain_err(&err);
+          }
+          _ => {}
+        }
+      }
       _ => {}
     }
     let mut result = self.clone();
@@ -620,10 +830,18 @@ impl CppType {
         conversion = CppIndirectionChange::ReferenceToPointer;
       }
       CppTypeIndirection::RValueRef => {
-        return Err(Error::from("rvalue references are not supported")).chain_err(&err);
+        if role == CppTypeRole::ReturnType {
+          return Err(Error::from("rvalue reference return types are not supported"))
+                   .chain_err(&err);
+        }
+        result.indirection = CppTypeIndirection::Ptr;
+        conversion = CppIndirectionChange::RValueReferenceToPointer;
       }
     }
-    if let CppTypeBase::Class(CppTypeClassBase { ref name, .. }) = self.base {
+    if let CppTypeBase::Class(CppTypeClassBase {
+                                ref name,
+                                ref template_arguments,
+                              }) = self.base {
       if name == "QFlags" {
         if !(self.indirection == CppTypeIndirection::None ||
              (self.indirection == CppTypeIndirection::Ref && self.is_const)) {
@@ -632,7 +850,21 @@ impl CppType {
                                          self)))
                      .chain_err(&err);
         }
-        conversion = CppIndirectionChange::QFlagsToUInt;
+        let enum_name = match *template_arguments {
+          Some(ref args) if args.len() == 1 => {
+            if let CppTypeBase::Enum { ref name } = args[0].base {
+              name.clone()
+            } else {
+              return Err(Error::from("QFlags template argument must be an enum"))
+                       .chain_err(&err);
+            }
+          }
+          _ => {
+            return Err(Error::from("QFlags type must have exactly 1 template argument"))
+                     .chain_err(&err);
+          }
+        };
+        conversion = CppIndirectionChange::FlagsToInt { enum_name: enum_name };
         result.base = CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::UInt);
         result.is_const = false;
         result.indirection = CppTypeIndirection::None;
@@ -758,6 +990,17 @@ impl CppType {
              CppTypeBase::Class(data
                                   .instantiate_class(nested_level1, template_arguments1)?)
            }
+           CppTypeBase::Array(CppArrayType {
+                                ref element_type,
+                                ref size,
+                              }) => {
+             CppTypeBase::Array(CppArrayType {
+                                  element_type: Box::new(element_type
+                                                           .instantiate(nested_level1,
+                                                                        template_arguments1)?),
+                                  size: *size,
+                                })
+           }
            _ => self.base.clone(),
          },
        })
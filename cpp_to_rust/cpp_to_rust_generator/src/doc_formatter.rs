@@ -6,7 +6,9 @@ use rust_type::RustType;
 use rust_info::{RustMethodSelfArgKind, RustMethodArgumentsVariant, RustTypeDeclaration,
                 RustTypeDeclarationKind, RustMethodScope, RustEnumValue, RustMethod,
                 RustMethodArguments, RustMethodDocItem, RustTypeWrapperKind,
-                RustQtReceiverDeclaration, RustQtReceiverType};
+                RustQtReceiverDeclaration, RustQtReceiverType, RustConstant, RustTypeAlias,
+                RustMethodUnsafeReason};
+use cpp_data::CppDocContent;
 use cpp_type::{CppType, CppTypeBase, CppTypeClassBase, CppTypeIndirection};
 use common::string_utils::JoinWithSeparator;
 use common::log;
@@ -53,6 +55,65 @@ pub fn wrap_cpp_doc_block(html: &str) -> String {
           html)
 }
 
+/// Same as `wrap_cpp_doc_block`, but the content is rendered as Markdown.
+pub fn wrap_cpp_doc_markdown_block(markdown: &str) -> String {
+  format!("\n\n<div style='border: 1px solid #5CFF95; \
+                           background: #D6FFE4; padding: 16px;'>\n\n{}\n\n</div>",
+          markdown)
+}
+
+/// Wraps `content` using `wrap_cpp_doc_block` or `wrap_cpp_doc_markdown_block`
+/// depending on its format.
+pub fn wrap_cpp_doc_content(content: &CppDocContent) -> String {
+  match *content {
+    CppDocContent::Html(ref html) => wrap_cpp_doc_block(html),
+    CppDocContent::Markdown(ref markdown) => wrap_cpp_doc_markdown_block(markdown),
+  }
+}
+
+/// Returns caption for C++ documentation block,
+/// linking to `url` if it's not empty.
+fn cpp_doc_caption(url: &str) -> String {
+  if url.is_empty() {
+    "C++ documentation:".to_string()
+  } else {
+    format!("<a href=\"{}\">C++ documentation:</a>", url)
+  }
+}
+
+/// Generates text explaining why a method is marked as `unsafe`.
+fn unsafe_reason_doc(reason: &RustMethodUnsafeReason) -> String {
+  match *reason {
+    RustMethodUnsafeReason::RawPointerArgument(ref name) => {
+      format!("This function is unsafe because argument `{}` is a raw pointer. \
+               The caller must make sure it points to a valid object.",
+              name)
+    }
+    RustMethodUnsafeReason::AllUnsafePolicy => {
+      "This function is unsafe because all functions of this crate are marked as unsafe."
+        .to_string()
+    }
+    RustMethodUnsafeReason::NotInSafeMethods => {
+      "This function is unsafe because it's not in the list of functions \
+       known to be safe to call from Rust."
+        .to_string()
+    }
+    RustMethodUnsafeReason::UncheckedStaticCast => {
+      "This function is unsafe because the cast is not checked at runtime.".to_string()
+    }
+    RustMethodUnsafeReason::UncheckedIndex => {
+      "This function is unsafe because the index is not checked. \
+       The caller must make sure it's less than the container's length."
+        .to_string()
+    }
+    RustMethodUnsafeReason::UncheckedIterator => {
+      "This function is unsafe because the iterator's position is not checked. \
+       The caller must make sure it points to an element of the container."
+        .to_string()
+    }
+  }
+}
+
 pub fn type_doc(type1: &RustTypeDeclaration) -> String {
   let auto_doc = match type1.kind {
     RustTypeDeclarationKind::CppTypeWrapper {
@@ -73,9 +134,9 @@ pub fn type_doc(type1: &RustTypeDeclaration) -> String {
         .to_cpp_pseudo_code();
       let mut doc = format!("C++ type: {}", wrap_inline_cpp_code(&cpp_type_code));
       if let Some(ref cpp_doc) = *cpp_doc {
-        doc += &format!("\n\n<a href=\"{}\">C++ documentation:</a> {}",
-                        cpp_doc.url,
-                        wrap_cpp_doc_block(&cpp_doc.html));
+        doc += &format!("\n\n{} {}",
+                        cpp_doc_caption(&cpp_doc.url),
+                        wrap_cpp_doc_content(&cpp_doc.content));
       }
       doc
     }
@@ -145,8 +206,8 @@ pub fn enum_value_doc(value: &RustEnumValue) -> String {
                             wrap_inline_cpp_code(&format!("{} = {}",
                                                           cpp_doc.variant_name,
                                                           value.value)),
-                            if let Some(ref text) = cpp_doc.doc {
-                              format!(": {}", text)
+                            if let Some(ref content) = cpp_doc.doc {
+                              format!(": {}", content.text())
                             } else {
                               String::new()
                             }));
@@ -158,12 +219,24 @@ pub fn enum_value_doc(value: &RustEnumValue) -> String {
       format!("C++ enum variant: {}",
               wrap_inline_cpp_code(&format!("{} = {}", cpp_doc.variant_name, value.value)));
     match cpp_doc.doc {
-      Some(ref text) if !text.is_empty() => format!("{} ({})", text, doc_part),
+      Some(ref content) if !content.text().is_empty() => {
+        format!("{} ({})", content.text(), doc_part)
+      }
       _ => doc_part,
     }
   }
 }
 
+pub fn constant_doc(constant: &RustConstant) -> String {
+  format!("C++ {}: {}",
+          if constant.is_macro { "macro" } else { "constant" },
+          wrap_inline_cpp_code(&constant.cpp_name))
+}
+
+pub fn type_alias_doc(alias: &RustTypeAlias) -> String {
+  format!("C++ type alias: {}", wrap_inline_cpp_code(&alias.cpp_name))
+}
+
 pub fn method_doc(method: &RustMethod) -> String {
 
   let cpp_method_name = match method.arguments {
@@ -237,6 +310,9 @@ pub fn method_doc(method: &RustMethod) -> String {
     }
     doc.push(format!("C++ method: {}", wrap_inline_cpp_code(&doc_item.cpp_fn)));
     doc.push("\n\n".to_string());
+    if let Some(ref reason) = doc_item.unsafe_reason {
+      doc.push(format!("{}\n\n", unsafe_reason_doc(reason)));
+    }
     // TODO: use inheritance_chain to generate documentation
     //    if let Some(ref inherited_from) = doc_item.inherited_from {
     //      doc.push(format!("Inherited from {}. Original C++ method: {}\n\n",
@@ -252,9 +328,11 @@ pub fn method_doc(method: &RustMethod) -> String {
                 result.url,
                 declaration)
       } else {
-        format!("<a href=\"{}\">C++ documentation:</a>", result.url)
+        cpp_doc_caption(&result.url)
       };
-      doc.push(format!("{} {}", prefix, wrap_cpp_doc_block(&result.html)));
+      doc.push(format!("{} {}",
+                       prefix,
+                       wrap_cpp_doc_content(&result.content)));
     }
   }
   let variant_docs = doc.join("");
@@ -339,7 +417,22 @@ pub fn add_special_type_docs(data: &mut RustTypeDeclaration) -> Result<()> {
            ref mut methods,
            ..
          } = data.kind {
-    if let RustTypeWrapperKind::Struct { ref slot_wrapper, .. } = *kind {
+    if let RustTypeWrapperKind::Struct {
+             ref slot_wrapper,
+             ref subclass_wrapper,
+             ..
+           } = *kind {
+      if let Some(ref subclass_wrapper) = *subclass_wrapper {
+        type_doc = Some(format!("C++ subclass of `{cpp_base}` that forwards calls of its virtual \
+          methods to Rust.\n\n\
+          Create an object using one of its constructors and pass an object implementing `{trait_name}` \
+          to `set_overrides()`. Virtual methods that are not overridden use the base class \
+          implementation. Protected methods of `{cpp_base}` are available as methods of \
+          this type, and methods with `_base` suffix call base class implementations of \
+          virtual methods.",
+                                cpp_base = subclass_wrapper.cpp_base_name,
+                                trait_name = subclass_wrapper.trait_name));
+      }
       if let Some(ref slot_wrapper) = *slot_wrapper {
         type_doc = Some(format!("Allows to bind Qt signals with arguments `({cpp_args})` to a \
           Rust extern function.\n\n\
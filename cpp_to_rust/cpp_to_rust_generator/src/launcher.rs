@@ -4,9 +4,11 @@ use config::{Config, DebugLoggingConfig};
 use cpp_code_generator::{CppCodeGenerator, generate_cpp_type_size_requester, CppTypeSizeRequest};
 use cpp_type::CppTypeClassBase;
 use cpp_data::{CppData, CppDataWithDeps, ParserCppData};
+use cpp_ffi_data::CppFfiHeaderData;
 use cpp_ffi_generator;
 use cpp_parser;
 use cpp_post_processor::cpp_post_process;
+use cpp_template_checker::CppTemplateChecker;
 use common::errors::{Result, ChainErr};
 use common::string_utils::CaseOperations;
 use common::file_utils::{PathBufWithAdded, move_files, create_dir_all, save_json, load_bincode,
@@ -113,12 +115,30 @@ fn check_all_paths(config: &Config) -> Result<()> {
   Ok(())
 }
 
+/// Creates C++ parser configuration based on `config`.
+/// `tmp_file_name` is the name of the temporary C++ file
+/// in the cache directory used by the parser.
+fn cpp_parser_config(config: &Config, tmp_file_name: &str) -> cpp_parser::CppParserConfig {
+  cpp_parser::CppParserConfig {
+    include_paths: Vec::from(config.include_paths()),
+    framework_paths: Vec::from(config.framework_paths()),
+    include_directives: Vec::from(config.include_directives()),
+    target_include_paths: Vec::from(config.target_include_paths()),
+    tmp_cpp_path: config.cache_dir_path().with_added(tmp_file_name),
+    name_blacklist: Vec::from(config.cpp_parser_blocked_names()),
+    clang_arguments: Vec::from(config.cpp_parser_arguments()),
+    template_instantiations: Vec::from(config.template_instantiations()),
+    flags_typedefs: config.flags_typedefs().keys().cloned().collect(),
+    skip_deprecated: config.skip_deprecated(),
+  }
+}
+
 /// Loads C++ data saved during a previous run of the generator
 /// from the cache directory if it's available and permitted by `config.cache_usage()`.
 /// Otherwise, performs necessary steps to parse and process C++ data.
-fn load_or_create_cpp_data<'a>(config: &Config,
-                               dependencies_cpp_data: Vec<&'a CppData>)
-                               -> Result<CppDataWithDeps<'a>> {
+pub fn load_or_create_cpp_data<'a>(config: &Config,
+                                   dependencies_cpp_data: Vec<&'a CppData>)
+                                   -> Result<CppDataWithDeps<'a>> {
   let parser_cpp_data_file_path = config.cache_dir_path().with_added("parser_cpp_data.bin");
 
   let loaded_parser_cpp_data = if config.cache_usage().can_use_raw_cpp_data() &&
@@ -142,15 +162,7 @@ fn load_or_create_cpp_data<'a>(config: &Config,
     x
   } else {
     log::status("Running C++ parser");
-    let parser_config = cpp_parser::CppParserConfig {
-      include_paths: Vec::from(config.include_paths()),
-      framework_paths: Vec::from(config.framework_paths()),
-      include_directives: Vec::from(config.include_directives()),
-      target_include_paths: Vec::from(config.target_include_paths()),
-      tmp_cpp_path: config.cache_dir_path().with_added("1.cpp"),
-      name_blacklist: Vec::from(config.cpp_parser_blocked_names()),
-      clang_arguments: Vec::from(config.cpp_parser_arguments()),
-    };
+    let parser_config = cpp_parser_config(config, "1.cpp");
     let mut parser_cpp_data: ParserCppData = cpp_parser::run(parser_config, &dependencies_cpp_data)
       .chain_err(|| "C++ parser failed")?;
     parser_cpp_data
@@ -217,6 +229,59 @@ fn load_or_create_cpp_data<'a>(config: &Config,
   Ok(full_cpp_data)
 }
 
+/// Runs the C++ wrapper generator on `cpp_data` using settings from `config`.
+/// `cpp_ffi_lib_name` is the name of the generated C++ wrapper library.
+pub fn run_cpp_ffi_generator(config: &Config,
+                             cpp_data: &CppDataWithDeps,
+                             cpp_ffi_lib_name: String)
+                             -> Result<Vec<CppFfiHeaderData>> {
+  let mut template_checker =
+    CppTemplateChecker::new(cpp_parser_config(config, "template_check.cpp"),
+                            config.cache_dir_path().with_added("template_checks.json"),
+                            config.cache_usage().can_use_raw_cpp_data(),
+                            config.write_cache(),
+                            config.cpp_lib_version().map(|s| s.to_string()));
+  cpp_ffi_generator::run(cpp_data,
+                         cpp_ffi_lib_name,
+                         config.cpp_ffi_generator_filters(),
+                         config.catch_exceptions(),
+                         config.catch_exceptions_filters(),
+                         config.subclassing_classes(),
+                         config.flags_typedefs(),
+                         &mut template_checker)
+}
+
+/// Creates input data of the Rust generator based on `config`.
+pub fn rust_generator_input_data<'a>(config: &'a Config,
+                                     cpp_data: &'a CppDataWithDeps<'a>,
+                                     cpp_ffi_headers: Vec<CppFfiHeaderData>,
+                                     dependencies: &'a [DependencyInfo])
+                                     -> rust_generator::RustGeneratorInputData<'a> {
+  rust_generator::RustGeneratorInputData {
+    cpp_data: cpp_data,
+    cpp_ffi_headers: cpp_ffi_headers,
+    dependency_types: dependencies
+      .iter()
+      .map(|dep| &dep.rust_export_info.rust_types as &[_])
+      .collect(),
+    crate_name: config.crate_properties().name().clone(),
+    // TODO: allow to remove any prefix through `Config` (#25)
+    remove_qt_prefix: config.crate_properties().name().starts_with("qt_"),
+    enums_as_newtypes: config.enums_as_newtypes(),
+    filtered_namespaces: config.cpp_filtered_namespaces().clone(),
+    constant_filters: config.constant_filters(),
+    flags_typedefs: config.flags_typedefs(),
+    preserved_type_aliases: config.preserved_type_aliases(),
+    pointer_annotations: config.pointer_annotations(),
+    return_value_lifetimes: config.return_value_lifetimes(),
+    unsafe_policy: config.unsafe_policy(),
+    safe_methods: config.safe_methods(),
+    debug_methods: config.debug_methods(),
+    eq_classes: config.eq_classes(),
+    container_traits: config.container_traits(),
+  }
+}
+
 /// Executes the generator for a single config.
 pub fn exec_one(config: Config) -> Result<()> {
   exec(::std::iter::once(config))
@@ -297,9 +362,6 @@ pub fn exec<T: Iterator<Item = Config>>(configs: T) -> Result<()> {
       logger.set_all_category_settings(category_settings);
     }
 
-    // TODO: allow to remove any prefix through `Config` (#25)
-    let remove_qt_prefix = config.crate_properties().name().starts_with("qt_");
-
     if !config.dependency_cache_paths().is_empty() {
       log::status("Loading dependencies");
     }
@@ -337,10 +399,8 @@ pub fn exec<T: Iterator<Item = Config>>(configs: T) -> Result<()> {
       create_dir_all(&c_lib_tmp_path)?;
       log::status(format!("Generating C++ wrapper library ({})", cpp_ffi_lib_name));
 
-      let cpp_ffi_headers = cpp_ffi_generator::run(&cpp_data,
-                                                   cpp_ffi_lib_name.clone(),
-                                                   config.cpp_ffi_generator_filters())
-          .chain_err(|| "FFI generator failed")?;
+      let cpp_ffi_headers = run_cpp_ffi_generator(&config, &cpp_data, cpp_ffi_lib_name.clone())
+        .chain_err(|| "FFI generator failed")?;
 
       log::status(format!("Generating C++ wrapper code"));
       let code_gen = CppCodeGenerator::new(cpp_ffi_lib_name.clone(), c_lib_tmp_path.clone());
@@ -370,18 +430,7 @@ pub fn exec<T: Iterator<Item = Config>>(configs: T) -> Result<()> {
         cpp_lib_version: config.cpp_lib_version().map(|s| s.into()),
       };
       log::status("Preparing Rust functions");
-      let rust_data = rust_generator::RustGeneratorInputData {
-          cpp_data: &cpp_data,
-          cpp_ffi_headers: cpp_ffi_headers,
-          dependency_types: dependencies
-            .iter()
-            .map(|dep| &dep.rust_export_info.rust_types as &[_])
-            .collect(),
-          crate_name: config.crate_properties().name().clone(),
-          // TODO: more universal prefix removal (#25)
-          remove_qt_prefix: remove_qt_prefix,
-          filtered_namespaces: config.cpp_filtered_namespaces().clone(),
-        }
+      let rust_data = rust_generator_input_data(&config, &cpp_data, cpp_ffi_headers, &dependencies)
         .run()
         .chain_err(|| "Rust data generator failed")?;
       log::status(format!("Generating Rust crate code ({})",
@@ -401,6 +450,8 @@ pub fn exec<T: Iterator<Item = Config>>(configs: T) -> Result<()> {
                                             }
                                             .to_cpp_code()?,
                                           size_const_name: size_const_name.clone(),
+                                          align_type_name:
+                                            rust_generator::align_type_name(size_const_name),
                                         });
           }
         }
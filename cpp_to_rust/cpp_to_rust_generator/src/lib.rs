@@ -44,6 +44,7 @@ mod rust_code_generator;
 mod rust_info;
 mod rust_type;
 mod cpp_parser;
+mod cpp_template_checker;
 mod versions;
 
 #[cfg(test)]
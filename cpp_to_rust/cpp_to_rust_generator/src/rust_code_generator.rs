@@ -5,22 +5,25 @@ use common::file_utils::{PathBufWithAdded, copy_recursively, file_to_string, cop
                          create_file, create_dir_all, read_dir, os_str_to_str, save_toml,
                          path_to_str, repo_crate_local_path};
 use common::log;
-use rust_generator::RustGeneratorOutput;
+use rust_generator::{RustGeneratorOutput, align_type_name};
 use rust_info::{RustTypeDeclarationKind, RustTypeWrapperKind, RustModule, RustMethod,
                 RustMethodArguments, RustMethodArgumentsVariant, RustMethodScope,
                 RustMethodArgument, TraitImpl, TraitImplExtra, RustQtReceiverType, DependencyInfo,
-                RustFFIFunction};
+                RustFFIFunction, RustSubclassWrapper, RustEnumValue};
 use rust_type::{RustName, RustType, RustTypeIndirection, RustToCTypeConversion, CompleteType};
 use common::string_utils::{JoinWithSeparator, CaseOperations};
 use common::utils::MapIfOk;
 use doc_formatter;
 use std::path::{Path, PathBuf};
+use std::iter::once;
+use std::collections::HashSet;
 
 use common::toml;
 use rustfmt;
 use versions;
 
 use config::CrateProperties;
+use cpp_data::CppDeprecation;
 
 /// Data required for Rust code generation.
 pub struct RustCodeGeneratorConfig<'a> {
@@ -59,6 +62,29 @@ fn format_doc(doc: &str) -> String {
   }
 }
 
+/// Returns Rust literal for `value` of an enum variant.
+/// Values of enums with an unsigned 64-bit underlying type
+/// are stored as their bit pattern in `i64`.
+fn enum_value_literal(value: i64, int_type: &str) -> String {
+  if int_type.starts_with('u') {
+    (value as u64).to_string()
+  } else {
+    value.to_string()
+  }
+}
+
+/// Generates `#[deprecated]` attribute corresponding to `deprecation`
+/// or an empty string if the item is not deprecated.
+fn format_deprecation(deprecation: &Option<CppDeprecation>) -> String {
+  match *deprecation {
+    None => String::new(),
+    Some(CppDeprecation { message: Some(ref message) }) => {
+      format!("#[deprecated(note = {:?})]\n", message)
+    }
+    Some(CppDeprecation { message: None }) => "#[deprecated]\n".to_string(),
+  }
+}
+
 /// Generates Rust code representing type `rust_type` inside crate `crate_name`.
 /// Same as `RustCodeGenerator::rust_type_to_code`, but accessible by other modules.
 pub fn rust_type_to_code(rust_type: &RustType, crate_name: &str) -> String {
@@ -81,45 +107,16 @@ pub fn rust_type_to_code(rust_type: &RustType, crate_name: &str) -> String {
                            .map(|x| rust_type_to_code(x, crate_name))
                            .join(", "));
       }
-      match *indirection {
-        RustTypeIndirection::None => base_s,
-        RustTypeIndirection::Ref { ref lifetime } => {
-          let lifetime_text = match *lifetime {
-            Some(ref lifetime) => format!("'{} ", lifetime),
-            None => String::new(),
-          };
-          if *is_const {
-            format!("&{}{}", lifetime_text, base_s)
-          } else {
-            format!("&{}mut {}", lifetime_text, base_s)
-          }
-        }
-        RustTypeIndirection::Ptr => {
-          if *is_const {
-            format!("*const {}", base_s)
-          } else {
-            format!("*mut {}", base_s)
-          }
-        }
-        RustTypeIndirection::PtrPtr => {
-          let const_text1 = if *is_const { "*const " } else { "*mut " };
-          let const_text2 = if *is_const2 { "*const " } else { "*mut " };
-          format!("{}{}{}", const_text2, const_text1, base_s)
-        }
-        RustTypeIndirection::PtrRef { ref lifetime } => {
-          let const_text1 = if *is_const { "*const " } else { "*mut " };
-          let lifetime_text = match *lifetime {
-            Some(ref lifetime) => format!("'{} ", lifetime),
-            None => String::new(),
-          };
-          let const_text2 = if *is_const2 {
-            format!("&{}", lifetime_text)
-          } else {
-            format!("&{}mut ", lifetime_text)
-          };
-          format!("{}{}{}", const_text2, const_text1, base_s)
-        }
-      }
+      apply_indirection_to_code(base_s, indirection, *is_const, *is_const2)
+    }
+    RustType::Array {
+      ref element_type,
+      ref size,
+      ref is_const,
+      ref indirection,
+    } => {
+      let base_s = format!("[{}; {}]", rust_type_to_code(element_type, crate_name), size);
+      apply_indirection_to_code(base_s, indirection, *is_const, false)
     }
     RustType::FunctionPointer {
       ref return_type,
@@ -138,6 +135,53 @@ pub fn rust_type_to_code(rust_type: &RustType, crate_name: &str) -> String {
   }
 }
 
+/// Adds `indirection` to the code of a type with no indirection.
+fn apply_indirection_to_code(base_s: String,
+                             indirection: &RustTypeIndirection,
+                             is_const: bool,
+                             is_const2: bool)
+                             -> String {
+  match *indirection {
+    RustTypeIndirection::None => base_s,
+    RustTypeIndirection::Ref { ref lifetime } => {
+      let lifetime_text = match *lifetime {
+        Some(ref lifetime) => format!("'{} ", lifetime),
+        None => String::new(),
+      };
+      if is_const {
+        format!("&{}{}", lifetime_text, base_s)
+      } else {
+        format!("&{}mut {}", lifetime_text, base_s)
+      }
+    }
+    RustTypeIndirection::Ptr => {
+      if is_const {
+        format!("*const {}", base_s)
+      } else {
+        format!("*mut {}", base_s)
+      }
+    }
+    RustTypeIndirection::PtrPtr => {
+      let const_text1 = if is_const { "*const " } else { "*mut " };
+      let const_text2 = if is_const2 { "*const " } else { "*mut " };
+      format!("{}{}{}", const_text2, const_text1, base_s)
+    }
+    RustTypeIndirection::PtrRef { ref lifetime } => {
+      let const_text1 = if is_const { "*const " } else { "*mut " };
+      let lifetime_text = match *lifetime {
+        Some(ref lifetime) => format!("'{} ", lifetime),
+        None => String::new(),
+      };
+      let const_text2 = if is_const2 {
+        format!("&{}", lifetime_text)
+      } else {
+        format!("&{}mut ", lifetime_text)
+      };
+      format!("{}{}{}", const_text2, const_text1, base_s)
+    }
+  }
+}
+
 /// Executes the code generator with `config` on `data`.
 pub fn run(config: RustCodeGeneratorConfig, data: &RustGeneratorOutput) -> Result<()> {
   let template_rustfmt_config_path = config
@@ -467,21 +511,43 @@ impl<'a> RustCodeGenerator<'a> {
                 unsafe_start = unsafe_start,
                 unsafe_end = unsafe_end)
       }
-      RustToCTypeConversion::QFlagsToUInt => {
-        let mut qflags_type = type1.rust_api_type.clone();
-        if let RustType::Common { ref mut generic_arguments, .. } = qflags_type {
+      RustToCTypeConversion::CppBoxIntoPtr => {
+        return Err(unexpected("CppBoxIntoPtr is not supported for return values").into());
+      }
+      RustToCTypeConversion::FlagsToInt => {
+        let mut flags_type = type1.rust_api_type.clone();
+        if let RustType::Common { ref mut generic_arguments, .. } = flags_type {
           *generic_arguments = None;
         } else {
           unreachable!();
         }
-        format!("{}::from_int({} as i32)",
-                self.rust_type_to_code(&qflags_type),
+        format!("{}::from_int({} as ::libc::c_int)",
+                self.rust_type_to_code(&flags_type),
                 source_expr)
       }
+      RustToCTypeConversion::EnumToInt => {
+        format!("{unsafe_start}::std::mem::transmute::<{}, {}>({}){unsafe_end}",
+                self.rust_type_to_code(&type1.rust_ffi_type),
+                self.rust_type_to_code(&type1.rust_api_type),
+                source_expr,
+                unsafe_start = unsafe_start,
+                unsafe_end = unsafe_end)
+      }
+      RustToCTypeConversion::UsizeToInt => format!("{} as usize", source_expr),
     };
     Ok(code1 + &code2)
   }
 
+  /// Generates Rust code converting `expression` of an enum type to
+  /// the enum's underlying integer type. The code must be placed
+  /// inside an `unsafe` block.
+  fn enum_to_int(&self, type1: &CompleteType, expression: &str) -> String {
+    format!("::std::mem::transmute::<{}, {}>({})",
+            self.rust_type_to_code(&type1.rust_api_type),
+            self.rust_type_to_code(&type1.rust_ffi_type),
+            expression)
+  }
+
   /// Generates Rust code for calling an FFI function from a wrapper function.
   /// If `in_unsafe_context` is `true`, the output code will be placed inside
   /// an `unsafe` block.
@@ -495,8 +561,27 @@ impl<'a> RustCodeGenerator<'a> {
     } else {
       ("unsafe { ", " }")
     };
+    // if the FFI function catches C++ exceptions, the API return type
+    // is a `Result`, and the conversion applies to its `Ok` type
+    let mut return_type = variant.return_type.clone();
+    if variant.exception_info_ffi_index.is_some() {
+      let ok_type = if let RustType::Common { ref generic_arguments, .. } =
+        variant.return_type.rust_api_type {
+        generic_arguments
+          .as_ref()
+          .and_then(|args| args.get(0))
+          .chain_err(|| "Result must have non-empty generic_arguments")?
+          .clone()
+      } else {
+        return Err(unexpected("Result type expected").into());
+      };
+      return_type.rust_api_type = ok_type;
+    }
     let mut final_args = Vec::new();
     final_args.resize(variant.cpp_method.c_signature.arguments.len(), None);
+    if let Some(ref i) = variant.exception_info_ffi_index {
+      final_args[*i] = Some("&mut exception_info".to_string());
+    }
     let mut all_args: Vec<RustMethodArgument> = Vec::from(shared_arguments);
     for arg in &variant.arguments {
       all_args.push(arg.clone());
@@ -506,25 +591,47 @@ impl<'a> RustCodeGenerator<'a> {
       let mut code = arg.name.clone();
       match arg.argument_type.rust_api_to_c_conversion {
         RustToCTypeConversion::None => {}
+        RustToCTypeConversion::RefToPtr |
         RustToCTypeConversion::OptionRefToPtr => {
-          return Err("OptionRefToPtr is not supported here yet".into());
-        }
-        RustToCTypeConversion::RefToPtr => {
-          if arg.argument_type.rust_api_type.is_const()? &&
-             !arg.argument_type.rust_ffi_type.is_const()? {
+          let (api_ref_type, value_code) = if arg.argument_type.rust_api_to_c_conversion ==
+                                              RustToCTypeConversion::OptionRefToPtr {
+            if let RustType::Common { generic_arguments: Some(ref args), .. } =
+              arg.argument_type.rust_api_type {
+              (args.get(0).chain_err(|| "Option with no generic_arguments")?, "x".to_string())
+            } else {
+              return Err(unexpected("Option type expected").into());
+            }
+          } else {
+            (&arg.argument_type.rust_api_type, code.clone())
+          };
+          let value_code = if api_ref_type.is_const()? &&
+                              !arg.argument_type.rust_ffi_type.is_const()? {
             let mut intermediate_type = arg.argument_type.rust_ffi_type.clone();
             intermediate_type.set_const(true)?;
-            code = format!("{} as {} as {}",
-                           code,
-                           self.rust_type_to_code(&intermediate_type),
-                           self.rust_type_to_code(&arg.argument_type.rust_ffi_type));
-
+            format!("{} as {} as {}",
+                    value_code,
+                    self.rust_type_to_code(&intermediate_type),
+                    self.rust_type_to_code(&arg.argument_type.rust_ffi_type))
           } else {
-            code = format!("{} as {}",
-                           code,
-                           self.rust_type_to_code(&arg.argument_type.rust_ffi_type));
+            format!("{} as {}",
+                    value_code,
+                    self.rust_type_to_code(&arg.argument_type.rust_ffi_type))
+          };
+          if arg.argument_type.rust_api_to_c_conversion == RustToCTypeConversion::OptionRefToPtr {
+            let null_fn = if arg.argument_type.rust_ffi_type.is_const()? {
+              "null"
+            } else {
+              "null_mut"
+            };
+            code = format!("{}.map_or(::std::ptr::{}(), |x| {})", code, null_fn, value_code);
+          } else {
+            code = value_code;
           }
         }
+        RustToCTypeConversion::CppBoxIntoPtr => {
+          // the object is now owned by the C++ side
+          code = format!("{}.into_raw()", code);
+        }
         RustToCTypeConversion::ValueToPtr |
         RustToCTypeConversion::CppBoxToPtr => {
           let is_const = if let RustType::Common {
@@ -551,8 +658,18 @@ impl<'a> RustCodeGenerator<'a> {
                            self.rust_type_to_code(&arg.argument_type.rust_ffi_type));
           }
         }
-        RustToCTypeConversion::QFlagsToUInt => {
-          code = format!("{}.to_int() as ::libc::c_uint", code);
+        RustToCTypeConversion::FlagsToInt => {
+          code = format!("{}.to_int() as {}",
+                         code,
+                         self.rust_type_to_code(&arg.argument_type.rust_ffi_type));
+        }
+        RustToCTypeConversion::EnumToInt => {
+          code = self.enum_to_int(&arg.argument_type, &code);
+        }
+        RustToCTypeConversion::UsizeToInt => {
+          code = format!("{} as {}",
+                         code,
+                         self.rust_type_to_code(&arg.argument_type.rust_ffi_type));
         }
       }
       final_args[arg.ffi_index] = Some(code);
@@ -570,9 +687,9 @@ impl<'a> RustCodeGenerator<'a> {
         ii += 1;
         return_var_name = format!("object{}", ii);
       }
-      let struct_name = if variant.return_type.rust_api_to_c_conversion ==
+      let struct_name = if return_type.rust_api_to_c_conversion ==
                            RustToCTypeConversion::CppBoxToPtr {
-        if let RustType::Common { ref generic_arguments, .. } = variant.return_type.rust_api_type {
+        if let RustType::Common { ref generic_arguments, .. } = return_type.rust_api_type {
           let generic_arguments = generic_arguments
             .as_ref()
             .chain_err(|| "CppBox must have generic_arguments")?;
@@ -584,7 +701,7 @@ impl<'a> RustCodeGenerator<'a> {
           return Err(unexpected("CppBox type expected").into());
         }
       } else {
-        self.rust_type_to_code(&variant.return_type.rust_api_type)
+        self.rust_type_to_code(&return_type.rust_api_type)
       };
       result.push(format!("{{\nlet mut {var}: {t} = {unsafe_start}\
                            ::cpp_utils::new_uninitialized::NewUninitialized::new_uninitialized()\
@@ -600,25 +717,177 @@ impl<'a> RustCodeGenerator<'a> {
       .into_iter()
       .map_if_ok(|x| x.chain_err(|| "ffi argument is missing"))?;
 
-    result.push(format!("{unsafe_start}::ffi::{}({}){maybe_semicolon}{unsafe_end}",
-                        variant.cpp_method.c_name,
-                        final_args.join(", "),
-                        maybe_semicolon = if maybe_result_var_name.is_some() {
-                          ";"
-                        } else {
-                          ""
-                        },
-                        unsafe_start = unsafe_start,
-                        unsafe_end = unsafe_end));
-    if let Some(ref name) = maybe_result_var_name {
-      result.push(format!("{}\n}}", name));
+    // the FFI function's return value is discarded if
+    // the Rust method doesn't return anything
+    let discards_return_value = return_type.rust_api_type == RustType::EmptyTuple &&
+                                (return_type.rust_ffi_type != RustType::EmptyTuple ||
+                                 variant.exception_info_ffi_index.is_some());
+    let call = format!("{unsafe_start}::ffi::{}({}){maybe_semicolon}{unsafe_end}",
+                       variant.cpp_method.c_name,
+                       final_args.join(", "),
+                       maybe_semicolon = if maybe_result_var_name.is_some() ||
+                                            discards_return_value {
+                         ";"
+                       } else {
+                         ""
+                       },
+                       unsafe_start = unsafe_start,
+                       unsafe_end = unsafe_end);
+    if variant.exception_info_ffi_index.is_none() {
+      result.push(call);
+      if let Some(ref name) = maybe_result_var_name {
+        result.push(format!("{}\n}}", name));
+      }
+      let code = result.join("");
+      return if maybe_result_var_name.is_none() {
+               self.convert_type_from_ffi(&return_type, code, in_unsafe_context, true)
+             } else {
+               Ok(code)
+             };
     }
-    let code = result.join("");
-    if maybe_result_var_name.is_none() {
-      self.convert_type_from_ffi(&variant.return_type, code, in_unsafe_context, true)
+    // the exception must be checked before the returned value is used
+    let mut code = "let mut exception_info = ::cpp_utils::CppExceptionInfo::new();\n".to_string();
+    code.push_str(&result.join(""));
+    if let Some(ref name) = maybe_result_var_name {
+      // the object was not initialized if an exception was thrown,
+      // so its destructor must not be called
+      code.push_str(&format!("{}\nif let Err(err) = exception_info.to_result() {{\n\
+                              ::std::mem::forget({name});\nreturn Err(err);\n}}\n\
+                              Ok({name})\n}}",
+                             call,
+                             name = name));
+    } else if return_type.rust_api_type == RustType::EmptyTuple {
+      code.push_str(&format!("{}\nexception_info.to_result()?;\nOk(())", call));
     } else {
-      Ok(code)
+      let value = self
+        .convert_type_from_ffi(&return_type, "ffi_result".to_string(), in_unsafe_context, false)?;
+      code.push_str(&format!("let ffi_result = {};\nexception_info.to_result()?;\nOk({})",
+                             call,
+                             value));
     }
+    Ok(code)
+  }
+
+  /// Generates the overrides trait of a subclass wrapper, its setter
+  /// and callback functions forwarding calls of virtual methods to the trait.
+  fn generate_subclass_wrapper(&self,
+                               type_name: &str,
+                               wrapper: &RustSubclassWrapper)
+                               -> Result<String> {
+    let prefix = type_name.to_snake_case();
+    let drop_callback_name = format!("{}_drop_overrides", prefix);
+    let mut trait_methods = String::new();
+    let mut set_callbacks = String::new();
+    let mut callbacks = String::new();
+    for method in &wrapper.virtual_methods {
+      // `this` is passed as a raw pointer because the C++ object may
+      // already be borrowed by the Rust code that called the virtual method
+      let this_ptr = if method.is_const { "*const" } else { "*mut" };
+      let args = once(format!("&self, this: {} {}", this_ptr, type_name))
+        .chain(method
+                 .argument_names
+                 .iter()
+                 .zip(method.argument_types.iter())
+                 .map(|(name, t)| format!("{}: {}", name, self.rust_type_to_code(&t.rust_api_type))))
+        .join(", ");
+      let return_type = if method.return_type.rust_api_type == RustType::EmptyTuple {
+        String::new()
+      } else {
+        format!(" -> {}",
+                self.rust_type_to_code(&method.return_type.rust_api_type))
+      };
+      let body = if let Some(ref base_method_name) = method.base_method_name {
+        format!(" {{\n    unsafe {{ (*this).{}({}) }}\n  }}\n",
+                base_method_name,
+                method.argument_names.join(", "))
+      } else {
+        ";\n".to_string()
+      };
+      trait_methods.push_str(&format!("  /// Overrides C++ method `{}`.\n  fn {}({}){}{}\n",
+                                      method.cpp_method,
+                                      method.name,
+                                      args,
+                                      return_type,
+                                      body));
+
+      let callback_name = format!("{}_{}_callback", prefix, method.name);
+      set_callbacks.push_str(&format!("      ::ffi::{}(self as *mut {}, {}::<T>);\n",
+                                      method.set_callback_ffi_name,
+                                      type_name,
+                                      callback_name));
+      let return_conversion = &method.return_type.rust_api_to_c_conversion;
+      let output_arg = if *return_conversion == RustToCTypeConversion::ValueToPtr {
+        Some(format!("output: {}",
+                     self.rust_type_to_code(&method.return_type.rust_ffi_type)))
+      } else {
+        None
+      };
+      let callback_args = once(format!("data: *mut ::libc::c_void, this: {} {}",
+                                       this_ptr,
+                                       type_name))
+        .chain(method
+                 .argument_types
+                 .iter()
+                 .enumerate()
+                 .map(|(num, t)| format!("arg{}: {}", num, self.rust_type_to_code(&t.rust_ffi_type))))
+        .chain(output_arg)
+        .join(", ");
+      let func_args = once("this".to_string())
+        .chain(method
+                 .argument_types
+                 .iter()
+                 .enumerate()
+                 .map_if_ok(|(num, t)| {
+                              self.convert_type_from_ffi(t, format!("arg{}", num), false, false)
+                            })?)
+        .join(", ");
+      let call = format!("data.{}({})", method.name, func_args);
+      let (callback_return_type, call) = match *return_conversion {
+        RustToCTypeConversion::ValueToPtr => {
+          // the C++ side passes a buffer for the value and destroys it afterwards
+          (String::new(), format!("unsafe {{ ::std::ptr::write(output, {}) }}", call))
+        }
+        RustToCTypeConversion::CppBoxToPtr => {
+          // the C++ side takes ownership of the returned object
+          (format!(" -> {}",
+                   self.rust_type_to_code(&method.return_type.rust_ffi_type)),
+           format!("{}.into_raw()", call))
+        }
+        RustToCTypeConversion::EnumToInt => {
+          (format!(" -> {}",
+                   self.rust_type_to_code(&method.return_type.rust_ffi_type)),
+           format!("unsafe {{ {} }}", self.enum_to_int(&method.return_type, &call)))
+        }
+        _ => {
+          if method.return_type.rust_ffi_type == RustType::EmptyTuple {
+            (String::new(), call)
+          } else {
+            (format!(" -> {}",
+                     self.rust_type_to_code(&method.return_type.rust_ffi_type)),
+             call)
+          }
+        }
+      };
+      callbacks.push_str(&format!("extern \"C\" fn {name}<T: {trait_name}>({args}){ret} {{\n  \
+                                   ::cpp_utils::abort_on_panic(|| {{\n    \
+                                   let data = unsafe {{ &*(data as *const T) }};\n    \
+                                   {call}\n  \
+                                   }})\n}}\n\n",
+                                  name = callback_name,
+                                  trait_name = wrapper.trait_name,
+                                  args = callback_args,
+                                  ret = callback_return_type,
+                                  call = call));
+    }
+    Ok(format!(include_str!("../templates/crate/subclass_wrapper.rs.in"),
+               cpp_base_name = wrapper.cpp_base_name,
+               type_name = type_name,
+               trait_name = wrapper.trait_name,
+               trait_methods = trait_methods,
+               set_data_ffi_name = wrapper.set_data_ffi_name,
+               drop_callback_name = drop_callback_name,
+               set_callbacks = set_callbacks,
+               callbacks = callbacks))
   }
 
   /// Generates Rust code for declaring a function's arguments.
@@ -691,6 +960,12 @@ impl<'a> RustCodeGenerator<'a> {
       _ => "pub ",
     };
     let maybe_unsafe = if func.is_unsafe { "unsafe " } else { "" };
+    let doc = format_doc(&doc_formatter::method_doc(&func)) +
+              &match func.scope {
+                 // `#[deprecated]` has no effect on trait impl items
+                 RustMethodScope::TraitImpl => String::new(),
+                 _ => format_deprecation(&func.deprecation),
+               };
     Ok(match func.arguments {
          RustMethodArguments::SingleVariant(ref variant) => {
       let body = self
@@ -702,11 +977,19 @@ impl<'a> RustCodeGenerator<'a> {
         format!(" -> {}",
                 self.rust_type_to_code(&variant.return_type.rust_api_type))
       };
-      let all_lifetimes: Vec<_> = variant
-        .arguments
-        .iter()
-        .filter_map(|x| x.argument_type.rust_api_type.lifetime())
-        .collect();
+      let mut all_lifetimes = Vec::new();
+      if func.scope != RustMethodScope::TraitImpl {
+        // lifetimes of trait methods are declared in the `impl` header
+        for lifetime in variant
+              .arguments
+              .iter()
+              .filter_map(|x| x.argument_type.rust_api_type.lifetime()) {
+          // the same lifetime may be shared by multiple arguments
+          if !all_lifetimes.contains(&lifetime) {
+            all_lifetimes.push(lifetime);
+          }
+        }
+      }
       let lifetimes_text = if all_lifetimes.is_empty() {
         String::new()
       } else {
@@ -719,7 +1002,7 @@ impl<'a> RustCodeGenerator<'a> {
 
       format!("{doc}{maybe_pub}{maybe_unsafe}fn {name}{lifetimes_text}({args}){return_type} \
                  {{\n{body}}}\n\n",
-              doc = format_doc(&doc_formatter::method_doc(&func)),
+              doc = doc,
               maybe_pub = maybe_pub,
               maybe_unsafe = maybe_unsafe,
               lifetimes_text = lifetimes_text,
@@ -770,7 +1053,7 @@ impl<'a> RustCodeGenerator<'a> {
         format!("{}::ReturnType", tpl_type)
       };
       format!(include_str!("../templates/crate/overloaded_function.rs.in"),
-              doc = format_doc(&doc_formatter::method_doc(&func)),
+              doc = doc,
               maybe_pub = maybe_pub,
               maybe_unsafe = maybe_unsafe,
               tpl_decl = tpl_decl,
@@ -790,7 +1073,9 @@ impl<'a> RustCodeGenerator<'a> {
   pub fn generate_lib_file(&self, modules: &[RustModule]) -> Result<()> {
     let mut code = String::new();
 
-
+    // deprecated C++ entities are marked as deprecated in Rust,
+    // but they may still be used within the crate
+    code.push_str("#![allow(deprecated)]\n\n");
     code.push_str("pub extern crate libc;\n");
     code.push_str("pub extern crate cpp_utils;\n\n");
     for dep in self.config.generator_dependencies {
@@ -849,10 +1134,96 @@ impl<'a> RustCodeGenerator<'a> {
         .map(|t| format!("type {} = {};", t.name, self.rust_type_to_code(&t.value)))
         .join("\n");
 
-      let trait_content = if let Some(TraitImplExtra::CppDeletable { ref deleter_name }) =
+      let mut trait_content = if let Some(TraitImplExtra::CppDeletable { ref deleter_name }) =
         trait1.extra {
         format!("fn deleter() -> ::cpp_utils::Deleter<Self> {{\n  ::ffi::{}\n}}\n",
                 deleter_name)
+      } else if let Some(TraitImplExtra::DebugFromMethod) = trait1.extra {
+        let variant = match trait1.methods.get(0).map(|x| &x.arguments) {
+          Some(&RustMethodArguments::SingleVariant(ref variant)) => variant,
+          _ => return Err(unexpected("Debug implementation must have a single method").into()),
+        };
+        format!("fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {{\n\
+                 let value = {{\n{}}};\n\
+                 ::std::fmt::Debug::fmt(&value, f)\n\
+                 }}\n",
+                self.generate_ffi_call(variant, &Vec::new(), false)?)
+      } else if let Some(TraitImplExtra::DebugFromStream) = trait1.extra {
+        let variant = match trait1.methods.get(0).map(|x| &x.arguments) {
+          Some(&RustMethodArguments::SingleVariant(ref variant)) => variant,
+          _ => return Err(unexpected("Debug implementation must have a single method").into()),
+        };
+        // the first call only returns the text's length
+        let call = self.generate_ffi_call(variant, &Vec::new(), false)?;
+        format!("fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {{\n\
+                 let buffer = ::std::ptr::null_mut();\n\
+                 let buffer_size = 0;\n\
+                 let size = {{\n{call}}};\n\
+                 let mut text = vec![0u8; size];\n\
+                 let buffer = text.as_mut_ptr() as *mut ::libc::c_char;\n\
+                 let buffer_size = size;\n\
+                 {{\n{call}}};\n\
+                 f.write_str(&String::from_utf8_lossy(&text))\n\
+                 }}\n",
+                call = call)
+      } else if trait1.extra == Some(TraitImplExtra::ContainerIntoIterator) ||
+                trait1.extra == Some(TraitImplExtra::IterableIntoIterator) {
+        let mut container_type = trait1.target_type.clone();
+        let (lifetime, is_const) = if let RustType::Common {
+                 ref mut indirection,
+                 ref mut is_const,
+                 ..
+               } = container_type {
+          let lifetime = if let RustTypeIndirection::Ref { lifetime: Some(ref lifetime) } =
+            *indirection {
+            lifetime.clone()
+          } else {
+            return Err(unexpected("IntoIterator must be implemented for a reference").into());
+          };
+          let r = (lifetime, *is_const);
+          *indirection = RustTypeIndirection::None;
+          *is_const = false;
+          r
+        } else {
+          return Err(unexpected("RustType::Common expected").into());
+        };
+        let iter_type = format!("::cpp_utils::container::{}",
+                                if trait1.extra == Some(TraitImplExtra::IterableIntoIterator) {
+                                  "BeginEndIter"
+                                } else if is_const {
+                                  "Iter"
+                                } else {
+                                  "IterMut"
+                                });
+        format!("\ntype IntoIter = {iter_type}<'{lifetime}, {container}>;\n\
+                 fn into_iter(self) -> Self::IntoIter {{\n\
+                 {iter_type}::new(self)\n\
+                 }}\n",
+                iter_type = iter_type,
+                lifetime = lifetime,
+                container = self.rust_type_to_code(&container_type))
+      } else if trait1.extra == Some(TraitImplExtra::ContainerExtend) ||
+                trait1.extra == Some(TraitImplExtra::ContainerFromIterator) {
+        let item_type = if let RustType::Common { ref generic_arguments, .. } =
+          trait1.trait_type {
+          generic_arguments
+            .as_ref()
+            .and_then(|args| args.get(0))
+            .chain_err(|| "Extend and FromIterator must have generic arguments")?
+        } else {
+          return Err(unexpected("RustType::Common expected").into());
+        };
+        if trait1.extra == Some(TraitImplExtra::ContainerExtend) {
+          format!("fn extend<I: ::std::iter::IntoIterator<Item = {}>>(&mut self, iter: I) {{\n\
+                   ::cpp_utils::container::extend(self, iter)\n\
+                   }}\n",
+                  self.rust_type_to_code(item_type))
+        } else {
+          format!("fn from_iter<I: ::std::iter::IntoIterator<Item = {}>>(iter: I) -> Self {{\n\
+                   ::cpp_utils::container::from_iter(iter)\n\
+                   }}\n",
+                  self.rust_type_to_code(item_type))
+        }
       } else {
         trait1
           .methods
@@ -860,7 +1231,33 @@ impl<'a> RustCodeGenerator<'a> {
           .map_if_ok(|method| self.generate_rust_final_function(method))?
           .join("")
       };
-      results.push(format!("impl {} for {} {{\n{}{}}}\n\n",
+      if let Some(TraitImplExtra::PartialOrdFromLessThan) = trait1.extra {
+        trait_content.push_str("fn partial_cmp(&self, other: &Self) -> \
+                                Option<::std::cmp::Ordering> {\n\
+                                if ::std::cmp::PartialOrd::lt(self, other) {\n\
+                                Some(::std::cmp::Ordering::Less)\n\
+                                } else if ::std::cmp::PartialOrd::lt(other, self) {\n\
+                                Some(::std::cmp::Ordering::Greater)\n\
+                                } else if self == other {\n\
+                                Some(::std::cmp::Ordering::Equal)\n\
+                                } else {\n\
+                                None\n\
+                                }\n\
+                                }\n");
+      }
+      let mut lifetimes = trait1.trait_type.all_lifetimes();
+      for lifetime in trait1.target_type.all_lifetimes() {
+        if !lifetimes.contains(&lifetime) {
+          lifetimes.push(lifetime);
+        }
+      }
+      let lifetimes_text = if lifetimes.is_empty() {
+        String::new()
+      } else {
+        format!("<{}>", lifetimes.iter().map(|x| format!("'{}", x)).join(", "))
+      };
+      results.push(format!("impl{} {} for {} {{\n{}{}}}\n\n",
+                           lifetimes_text,
                            self.rust_type_to_code(&trait1.trait_type),
                            self.rust_type_to_code(&trait1.target_type),
                            associated_types_text,
@@ -869,6 +1266,43 @@ impl<'a> RustCodeGenerator<'a> {
     Ok(results.join(""))
   }
 
+  /// Generates declaration of an enum represented by a newtype struct
+  /// with associated constants for all C++ enum variants.
+  fn generate_enum_newtype(&self,
+                           name: &str,
+                           maybe_pub: &str,
+                           values: &[RustEnumValue],
+                           int_type: &str)
+                           -> String {
+    let constants = values
+      .iter()
+      .map(|item| {
+             format!("{}{}  pub const {}: {name} = {name}({});\n",
+                     format_doc(&doc_formatter::enum_value_doc(item)),
+                     format_deprecation(&item.deprecation),
+                     item.name,
+                     enum_value_literal(item.value, int_type),
+                     name = name)
+           })
+      .join("");
+    let mut known_values = HashSet::new();
+    let variant_names = values
+      .iter()
+      .filter(|item| known_values.insert(item.value))
+      .map(|item| {
+             format!("      {} => Some(\"{}\"),",
+                     enum_value_literal(item.value, int_type),
+                     item.name)
+           })
+      .join("\n");
+    format!(include_str!("../templates/crate/enum_newtype_declaration.rs.in"),
+            maybe_pub = maybe_pub,
+            name = name,
+            int_type = int_type,
+            constants = constants,
+            variant_names = variant_names)
+  }
+
   /// Generates code for a module of the output crate.
   /// This may be a top level or nested module.
   #[cfg_attr(feature="clippy", allow(single_match_else))]
@@ -876,6 +1310,7 @@ impl<'a> RustCodeGenerator<'a> {
     let mut results = Vec::new();
     for type1 in &data.types {
       results.push(format_doc(&doc_formatter::type_doc(type1)));
+      results.push(format_deprecation(&type1.deprecation));
       let maybe_pub = if type1.is_public { "pub " } else { "" };
       match type1.kind {
         RustTypeDeclarationKind::CppTypeWrapper {
@@ -890,27 +1325,44 @@ impl<'a> RustCodeGenerator<'a> {
             RustTypeWrapperKind::Enum {
               ref values,
               ref is_flaggable,
+              ref is_newtype,
+              ref int_type,
             } => {
-              let mut r = format!(include_str!("../templates/crate/enum_declaration.rs.in"),
-                                  maybe_pub = maybe_pub,
-                                  name = type1.name.last_name()?,
-                                  variants = values
-                                    .iter()
-                                    .map(|item| {
-                                           format!("{}  {} = {}",
-                                              format_doc(&doc_formatter::enum_value_doc(&item)),
-                                              item.name,
-                                              item.value)
-                                         })
-                                    .join(", \n"));
+              let mut r = if *is_newtype {
+                self.generate_enum_newtype(type1.name.last_name()?, maybe_pub, values, int_type)
+              } else {
+                format!(include_str!("../templates/crate/enum_declaration.rs.in"),
+                        maybe_pub = maybe_pub,
+                        name = type1.name.last_name()?,
+                        int_type = int_type,
+                        variants = values
+                          .iter()
+                          .map(|item| {
+                                 format!("{}{}  {} = {}",
+                                         format_doc(&doc_formatter::enum_value_doc(&item)),
+                                         format_deprecation(&item.deprecation),
+                                         item.name,
+                                         enum_value_literal(item.value, int_type))
+                               })
+                          .join(", \n"))
+              };
               if *is_flaggable {
                 r = r +
                     &format!(include_str!("../templates/crate/impl_flaggable.rs.in"),
                              name = type1.name.last_name()?,
-                             trait_type =
-                               RustName::new(vec!["qt_core".to_string(),
-                                                  "flags".to_string(),
-                                                  "FlaggableEnum".to_string()])?
+                             value = if *is_newtype { "self.0" } else { "self" },
+                             values_count = values.len(),
+                             flag_values = values
+                               .iter()
+                               .map(|item| {
+                                      format!("(\"{}\", {}i64 as ::libc::c_int)",
+                                              item.name,
+                                              item.value)
+                                    })
+                               .join(", "),
+                             flags_module =
+                               RustName::new(vec!["cpp_utils".to_string(),
+                                                  "flags".to_string()])?
                                    .full_name(Some(&self.config.crate_properties.name())));
               }
               r
@@ -918,13 +1370,15 @@ impl<'a> RustCodeGenerator<'a> {
             RustTypeWrapperKind::Struct {
               ref size_const_name,
               ref slot_wrapper,
+              ref subclass_wrapper,
               ..
             } => {
               let mut r = if let Some(ref size_const_name) = *size_const_name {
                 format!(include_str!("../templates/crate/struct_declaration.rs.in"),
                         maybe_pub = maybe_pub,
                         name = type1.name.last_name()?,
-                        size_const_name = size_const_name)
+                        size_const_name = size_const_name,
+                        align_type_name = align_type_name(size_const_name))
               } else {
                 format!("#[repr(C)]\n{maybe_pub}struct {}(u8);\n\n",
                         type1.name.last_name()?,
@@ -956,6 +1410,10 @@ impl<'a> RustCodeGenerator<'a> {
                                     connections_mod = connections_mod,
                                     object_type_name = object_type_name));
               }
+              if let Some(ref subclass_wrapper) = *subclass_wrapper {
+                r.push_str(&self.generate_subclass_wrapper(type1.name.last_name()?,
+                                                           subclass_wrapper)?);
+              }
               r
             }
           };
@@ -1173,6 +1631,19 @@ impl<'a> {connections_mod}::Receiver for {type_name}<'a> {{
         }
       };
     }
+    for constant in &data.constants {
+      results.push(format!("{}pub const {}: {} = {};\n\n",
+                           format_doc(&doc_formatter::constant_doc(constant)),
+                           constant.name.last_name()?,
+                           self.rust_type_to_code(&constant.rust_type),
+                           constant.value));
+    }
+    for alias in &data.type_aliases {
+      results.push(format!("{}pub type {} = {};\n\n",
+                           format_doc(&doc_formatter::type_alias_doc(alias)),
+                           alias.name.last_name()?,
+                           self.rust_type_to_code(&alias.rust_type)));
+    }
     for method in &data.functions {
       results.push(self.generate_rust_final_function(method)?);
     }
@@ -1,26 +1,31 @@
 //! Generates Rust public API and FFI functions
 
 use caption_strategy::TypeCaptionStrategy;
-use cpp_data::{CppTypeKind, CppEnumValue, CppTypeAllocationPlace, CppDataWithDeps};
+use cpp_data::{CppTypeKind, CppEnumValue, CppTypeAllocationPlace, CppDataWithDeps, CppConstant,
+               CppConstantValue, CppTypeAlias, CppDeprecation};
 use cpp_ffi_data::{CppAndFfiMethod, CppFfiArgumentMeaning, CppFfiType, CppIndirectionChange,
-                   CppFfiMethodKind, CppCast, CppFfiHeaderData};
+                   CppFfiMethodKind, CppCast, CppFfiHeaderData, CppSubclassWrapper};
 use cpp_method::{CppMethod, ReturnValueAllocationPlace};
 use cpp_operator::CppOperator;
 use cpp_type::{CppType, CppTypeBase, CppBuiltInNumericType, CppTypeIndirection,
                CppSpecificNumericTypeKind, CppSpecificNumericType, CppTypeClassBase, CppTypeRole,
-               CppFunctionPointerType};
+               CppFunctionPointerType, CppArrayType};
 use common::errors::{Result, ChainErr, unexpected};
 use common::log;
 use rust_info::{RustTypeDeclaration, RustTypeDeclarationKind, RustTypeWrapperKind, RustModule,
                 RustMethod, RustMethodScope, RustMethodArgument, RustMethodArgumentsVariant,
                 RustMethodArguments, TraitImpl, TraitImplExtra, RustEnumValue,
                 RustProcessedTypeInfo, RustMethodDocItem, RustQtReceiverDeclaration,
-                RustQtReceiverType, RustQtSlotWrapper, RustMethodSelfArgKind,
-                RustMethodCaptionStrategy, RustFFIFunction, RustFFIArgument, TraitAssociatedType};
+                RustQtReceiverType, RustQtSlotWrapper, RustSubclassWrapper,
+                RustSubclassVirtualMethod, RustMethodSelfArgKind,
+                RustMethodCaptionStrategy, RustFFIFunction, RustFFIArgument, TraitAssociatedType,
+                RustConstant, RustTypeAlias, RustMethodUnsafeReason};
 use rust_type::{RustName, RustType, CompleteType, RustTypeIndirection, RustToCTypeConversion};
 use common::string_utils::{CaseOperations, WordIterator};
 use common::utils::{add_to_multihash, MapIfOk};
 use common::string_utils::JoinWithSeparator;
+use config::{CppConstantFilterFn, MethodPointerAnnotations, PointerAnnotation,
+             PointerAnnotationTarget, ReturnValueLifetime, UnsafePolicy, ContainerTraits};
 use doc_formatter;
 use std::collections::{HashMap, HashSet, hash_map};
 
@@ -31,8 +36,8 @@ use std::collections::{HashMap, HashSet, hash_map};
 struct RustSingleMethod {
   /// Location of the method
   scope: RustMethodScope,
-  /// True if the method is `unsafe`.
-  is_unsafe: bool,
+  /// Reason for marking the method as `unsafe`, or `None` if it's safe.
+  unsafe_reason: Option<RustMethodUnsafeReason>,
   /// Name of the method. For free functions, this is the full name.
   /// for `impl` methods, this is only the method's own name.
   name: RustName,
@@ -52,6 +57,14 @@ fn size_const_name(type_name: &RustName) -> String {
     .join("_")
 }
 
+/// Returns name of the zero-sized type that will have the same alignment
+/// as the C++ type with size constant `size_const_name` in the generated `type_sizes.rs`.
+/// The name may coincide with a size constant of another type, so the build script
+/// declares the type as a braced struct that doesn't define a value.
+pub fn align_type_name(size_const_name: &str) -> String {
+  format!("{}_ALIGN", size_const_name)
+}
+
 
 /// Mode of case conversion
 enum Case {
@@ -62,8 +75,8 @@ enum Case {
 }
 
 /// Returns name of the Rust function that will provide access
-/// to a C++ operator. Most of these functions should be replaced
-/// with trait implementations in the future.
+/// to a C++ operator. These functions are generated for operators
+/// that can't be represented by a trait implementation.
 fn operator_rust_name(operator: &CppOperator) -> Result<String> {
   Ok(match *operator {
        CppOperator::Conversion(ref type1) => {
@@ -76,6 +89,161 @@ fn operator_rust_name(operator: &CppOperator) -> Result<String> {
      })
 }
 
+/// Returns module, name and method name of the Rust trait
+/// corresponding to a C++ operator, or `None` if there is no such trait.
+/// `operator[]` is reported as `Index` and may be converted to `IndexMut`
+/// depending on constness of the method.
+fn operator_rust_trait(operator: &CppOperator)
+                       -> Option<(&'static str, &'static str, &'static str)> {
+  use cpp_operator::CppOperator::*;
+  Some(match *operator {
+         Addition => ("ops", "Add", "add"),
+         Subtraction => ("ops", "Sub", "sub"),
+         Multiplication => ("ops", "Mul", "mul"),
+         Division => ("ops", "Div", "div"),
+         Modulo => ("ops", "Rem", "rem"),
+         UnaryMinus => ("ops", "Neg", "neg"),
+         LogicalNot | BitwiseNot => ("ops", "Not", "not"),
+         AdditionAssignment => ("ops", "AddAssign", "add_assign"),
+         SubtractionAssignment => ("ops", "SubAssign", "sub_assign"),
+         MultiplicationAssignment => ("ops", "MulAssign", "mul_assign"),
+         DivisionAssignment => ("ops", "DivAssign", "div_assign"),
+         ModuloAssignment => ("ops", "RemAssign", "rem_assign"),
+         Subscript => ("ops", "Index", "index"),
+         EqualTo => ("cmp", "PartialEq", "eq"),
+         LessThan => ("cmp", "PartialOrd", "lt"),
+         _ => return None,
+       })
+}
+
+/// Returns a copy of `rust_type` with indirection and constness removed.
+fn rust_type_to_value(rust_type: &RustType) -> Result<RustType> {
+  let mut r = rust_type.clone();
+  if let RustType::Common {
+           ref mut indirection,
+           ref mut is_const,
+           ..
+         } = r {
+    *indirection = RustTypeIndirection::None;
+    *is_const = false;
+  } else {
+    return Err("RustType::Common expected".into());
+  }
+  Ok(r)
+}
+
+/// Returns true if `cpp_type` is an integer type without indirection.
+fn is_integer_type(cpp_type: &CppType) -> bool {
+  if cpp_type.indirection != CppTypeIndirection::None {
+    return false;
+  }
+  match cpp_type.base {
+    CppTypeBase::BuiltInNumeric(ref t) => t.is_signed_integer() || t.is_unsigned_integer(),
+    CppTypeBase::SpecificNumeric(CppSpecificNumericType { ref kind, .. }) => {
+      kind != &CppSpecificNumericTypeKind::FloatingPoint
+    }
+    CppTypeBase::PointerSizedInteger { .. } => true,
+    _ => false,
+  }
+}
+
+/// Returns true if `method` is one of the class operators used to implement
+/// `cpp_utils::container::CppIterator`.
+fn is_iterator_operator(method: &CppMethod) -> bool {
+  match method.operator {
+    Some(CppOperator::Indirection) |
+    Some(CppOperator::PrefixIncrement) |
+    Some(CppOperator::EqualTo) => method.class_membership.is_some(),
+    _ => false,
+  }
+}
+
+/// Returns type of a trait with specified full name.
+fn trait_type(path: &[&str], generic_arguments: Option<Vec<RustType>>) -> Result<RustType> {
+  Ok(RustType::Common {
+       base: RustName::new(path.iter().map(|x| x.to_string()).collect())?,
+       indirection: RustTypeIndirection::None,
+       is_const: false,
+       is_const2: false,
+       generic_arguments: generic_arguments,
+     })
+}
+
+/// Returns a reference to `rust_type` with lifetime `'a`.
+fn rust_type_to_ref(rust_type: &RustType, is_const: bool) -> Result<RustType> {
+  let mut r = rust_type.clone();
+  if let RustType::Common {
+           ref mut indirection,
+           is_const: ref mut r_is_const,
+           ..
+         } = r {
+    *indirection = RustTypeIndirection::Ref { lifetime: Some("a".to_string()) };
+    *r_is_const = is_const;
+  } else {
+    return Err("RustType::Common expected".into());
+  }
+  Ok(r)
+}
+
+/// Returns true if the supertrait required by an operator trait implementation
+/// (`Index` for `IndexMut`, `PartialEq` for `PartialOrd`) is implemented
+/// for the same type in `trait_impls`, or if no supertrait is required.
+fn has_supertrait_impl(trait_impl: &TraitImpl, trait_impls: &[TraitImpl]) -> Result<bool> {
+  let (trait_name, generic_arguments) = if let RustType::Common {
+           ref base,
+           ref generic_arguments,
+           ..
+         } = trait_impl.trait_type {
+    (base.last_name()?, generic_arguments)
+  } else {
+    return Err(unexpected("RustType::Common expected").into());
+  };
+  let (supertrait_name, supertrait_generic_arguments, output) = if trait_name == "IndexMut" {
+    let output = match trait_impl.methods.get(0).map(|x| &x.arguments) {
+      Some(&RustMethodArguments::SingleVariant(ref variant)) => {
+        rust_type_to_value(&variant.return_type.rust_api_type)?
+      }
+      _ => return Err(unexpected("IndexMut must have a single method").into()),
+    };
+    ("Index", generic_arguments.clone(), Some(output))
+  } else if trait_impl.extra == Some(TraitImplExtra::PartialOrdFromLessThan) {
+    ("PartialEq", Some(vec![trait_impl.target_type.clone()]), None)
+  } else {
+    return Ok(true);
+  };
+  Ok(trait_impls.iter().any(|x| {
+    if let RustType::Common {
+             ref base,
+             ref generic_arguments,
+             ..
+           } = x.trait_type {
+      base.last_name().ok().map_or(false, |name| name == supertrait_name) &&
+      generic_arguments == &supertrait_generic_arguments &&
+      x.target_type == trait_impl.target_type &&
+      output.as_ref().map_or(true, |output| {
+        x.associated_types.iter().any(|t| &t.name == "Output" && &t.value == output)
+      })
+    } else {
+      false
+    }
+  }))
+}
+
+/// Returns the source type of a `From` trait implementation
+/// generated from a conversion operator, or `None` for other traits.
+fn conversion_source_type(trait_impl: &TraitImpl) -> Option<&RustType> {
+  if let RustType::Common {
+           ref base,
+           generic_arguments: Some(ref args),
+           ..
+         } = trait_impl.trait_type {
+    if base.last_name().ok().map_or(false, |name| name == "From") {
+      return args.get(0);
+    }
+  }
+  None
+}
+
 /// If `remove_qt_prefix` is true, removes "Q" or "Qt"
 /// if it is first word of the string and not the only one word.
 /// Also converts case of the words.
@@ -126,9 +294,36 @@ fn sanitize_rust_identifier(name: &str) -> String {
 /// Rust does not allow such duplicates.
 /// - If there is only one variant, adds another variant.
 /// Rust does not allow repr(C) enums having only one variant.
-fn prepare_enum_values(values: &[CppEnumValue]) -> Vec<RustEnumValue> {
+///
+/// If `is_newtype` is true, the variants are prepared for being represented
+/// as associated constants, so only names are converted.
+fn prepare_enum_values(values: &[CppEnumValue], is_newtype: bool) -> Vec<RustEnumValue> {
   use rust_info::CppEnumValueDocItem as DocItem;
 
+  if is_newtype {
+    let mut result: Vec<_> = values
+      .iter()
+      .map(|variant| {
+             RustEnumValue {
+               name: sanitize_rust_identifier(&variant.name.to_class_case()),
+               value: variant.value,
+               cpp_docs: vec![DocItem {
+                                variant_name: variant.name.clone(),
+                                doc: variant.doc.clone(),
+                              }],
+               is_dummy: false,
+               deprecation: variant.deprecation.clone(),
+             }
+           })
+      .collect();
+    remove_enum_names_affixes(&mut result);
+    // names may still collide after case conversion
+    let mut names = HashSet::new();
+    result.retain(|item| names.insert(item.name.clone()));
+    result.sort_by(|a, b| a.value.cmp(&b.value));
+    return result;
+  }
+
   let mut value_to_variant: HashMap<i64, RustEnumValue> = HashMap::new();
   for variant in values {
     let value = variant.value;
@@ -139,6 +334,9 @@ fn prepare_enum_values(values: &[CppEnumValue]) -> Vec<RustEnumValue> {
     match value_to_variant.entry(value) {
       hash_map::Entry::Occupied(mut entry) => {
         entry.get_mut().cpp_docs.push(doc_item);
+        if variant.deprecation.is_none() {
+          entry.get_mut().deprecation = None;
+        }
       }
       hash_map::Entry::Vacant(entry) => {
         entry.insert(RustEnumValue {
@@ -146,12 +344,12 @@ fn prepare_enum_values(values: &[CppEnumValue]) -> Vec<RustEnumValue> {
                        value: variant.value,
                        cpp_docs: vec![doc_item],
                        is_dummy: false,
+                       deprecation: variant.deprecation.clone(),
                      });
 
       }
     }
   }
-  let more_than_one = value_to_variant.len() > 1;
   let dummy_value: i64 = if value_to_variant.contains_key(&0) {
     1
   } else {
@@ -164,10 +362,19 @@ fn prepare_enum_values(values: &[CppEnumValue]) -> Vec<RustEnumValue> {
                   value: dummy_value,
                   cpp_docs: Vec::new(),
                   is_dummy: true,
+                  deprecation: None,
                 });
+  } else {
+    remove_enum_names_affixes(&mut result);
   }
+  result.sort_by(|a, b| a.value.cmp(&b.value));
+  result
+}
 
-  if more_than_one {
+/// Removes common prefix and suffix from names of enum variants
+/// if there is more than one variant and names remain valid after that.
+fn remove_enum_names_affixes(result: &mut [RustEnumValue]) {
+  if result.len() > 1 {
     let new_names = {
       let all_words: Vec<Vec<&str>> = result
         .iter()
@@ -209,10 +416,7 @@ fn prepare_enum_values(values: &[CppEnumValue]) -> Vec<RustEnumValue> {
         result[i].name = sanitize_rust_identifier(&new_names[i].clone());
       }
     }
-
   }
-  result.sort_by(|a, b| a.value.cmp(&b.value));
-  result
 }
 
 /// Generator of the Rust public API of the crate.
@@ -223,6 +427,10 @@ pub struct RustGenerator<'a> {
   top_module_names: HashMap<String, RustName>,
   /// Type wrappers created for this crate
   processed_types: Vec<RustProcessedTypeInfo>,
+  /// Constants created for this crate
+  constants: Vec<RustConstant>,
+  /// Type aliases created for this crate
+  type_aliases: Vec<RustTypeAlias>,
 }
 
 /// Results of adapting API for Rust wrapper.
@@ -253,6 +461,30 @@ pub struct RustGeneratorInputData<'a> {
   pub remove_qt_prefix: bool,
   /// List of namespaces to filter out during code generation
   pub filtered_namespaces: Vec<String>,
+  /// Generate enums as newtype structs instead of Rust enums
+  pub enums_as_newtypes: bool,
+  /// Functions that decide whether a C++ constant should be generated
+  pub constant_filters: Vec<&'a Box<CppConstantFilterFn>>,
+  /// Integer typedefs representing flags mapped to names of their enums
+  pub flags_typedefs: &'a HashMap<String, String>,
+  /// C++ type aliases that should be used in method signatures
+  /// instead of their target types
+  pub preserved_type_aliases: &'a [String],
+  /// Ownership and nullability of raw pointers in methods,
+  /// keyed by C++ method signature
+  pub pointer_annotations: &'a HashMap<String, MethodPointerAnnotations>,
+  /// Lifetimes of references returned by methods, keyed by C++ method signature
+  pub return_value_lifetimes: &'a HashMap<String, ReturnValueLifetime>,
+  /// Policy of marking methods as `unsafe`
+  pub unsafe_policy: UnsafePolicy,
+  /// Full names or signatures of C++ methods considered safe
+  pub safe_methods: &'a [String],
+  /// Signatures of C++ methods used to implement `Debug`
+  pub debug_methods: &'a [String],
+  /// Names of C++ classes for which `Eq` should be implemented
+  pub eq_classes: &'a [String],
+  /// Traits generated for container-like classes, keyed by C++ class name
+  pub container_traits: &'a HashMap<String, ContainerTraits>,
 }
 
 impl<'a> RustGeneratorInputData<'a> {
@@ -263,11 +495,15 @@ impl<'a> RustGeneratorInputData<'a> {
     let mut generator = RustGenerator {
       top_module_names: HashMap::new(),
       processed_types: Vec::new(),
+      constants: Vec::new(),
+      type_aliases: Vec::new(),
       input_data: self,
     };
     generator.top_module_names = generator.calc_top_module_names()?;
 
     generator.processed_types = generator.calc_processed_types()?;
+    generator.constants = generator.calc_constants()?;
+    generator.type_aliases = generator.calc_type_aliases()?;
     let mut modules = Vec::new();
     {
       let mut cpp_methods: Vec<&CppAndFfiMethod> = Vec::new();
@@ -308,6 +544,16 @@ impl<'a> RustGeneratorInputData<'a> {
           }
         }
       }
+      for constant in &generator.constants {
+        if !module_names_set.contains(&constant.name.parts[1]) {
+          module_names_set.insert(constant.name.parts[1].clone());
+        }
+      }
+      for alias in &generator.type_aliases {
+        if !module_names_set.contains(&alias.name.parts[1]) {
+          module_names_set.insert(alias.name.parts[1].clone());
+        }
+      }
 
       let mut module_names: Vec<_> = module_names_set.into_iter().collect();
       module_names.sort();
@@ -480,33 +726,85 @@ fn complete_type(processed_types: &[RustProcessedTypeInfo],
         }
         rust_api_to_c_conversion = RustToCTypeConversion::RefToPtr;
       }
-      CppIndirectionChange::QFlagsToUInt => {}
+      CppIndirectionChange::RValueReferenceToPointer => {
+        assert!(indirection == &RustTypeIndirection::Ptr);
+        if argument_meaning == &CppFfiArgumentMeaning::ReturnValue {
+          return Err(unexpected("rvalue reference return types are not supported").into());
+        }
+        // The value is consumed by the Rust function. C++ moves contents
+        // out of the object, and the moved-from object is destroyed
+        // when the Rust value is dropped.
+        *indirection = RustTypeIndirection::None;
+        let is_stack_allocated =
+          if let CppTypeBase::Class(..) = cpp_ffi_type.original_type.base {
+            match find_type_info(processed_types, dependency_types, |x| &x.rust_name == base) {
+              Some(&RustProcessedTypeInfo {
+                     kind: RustTypeWrapperKind::Struct {
+                       ref size_const_name,
+                       ref is_deletable,
+                       ..
+                     },
+                     ..
+                   }) => {
+                if !*is_deletable {
+                  return Err(format!("{} is not deletable", base.full_name(None)).into());
+                }
+                size_const_name.is_some()
+              }
+              _ => return Err(unexpected("class type expected here").into()),
+            }
+          } else {
+            true
+          };
+        if is_stack_allocated {
+          rust_api_to_c_conversion = RustToCTypeConversion::ValueToPtr;
+        } else {
+          rust_api_to_c_conversion = RustToCTypeConversion::CppBoxToPtr;
+          assert!(generic_arguments.is_none());
+          let new_generic_argument = RustType::Common {
+            base: base.clone(),
+            generic_arguments: None,
+            is_const: false,
+            is_const2: false,
+            indirection: RustTypeIndirection::None,
+          };
+          *base = RustName::new(vec!["cpp_utils".to_string(), "CppBox".to_string()])?;
+          *generic_arguments = Some(vec![new_generic_argument]);
+        }
+        *is_const = false;
+      }
+      CppIndirectionChange::FlagsToInt { .. } |
+      CppIndirectionChange::EnumToInt => {}
     }
   }
-  if cpp_ffi_type.conversion == CppIndirectionChange::QFlagsToUInt {
-    rust_api_to_c_conversion = RustToCTypeConversion::QFlagsToUInt;
-    let enum_type = if let CppTypeBase::Class(CppTypeClassBase {
-                                                ref template_arguments, ..
-                                              }) = cpp_ffi_type.original_type.base {
-      let args = template_arguments
-        .as_ref()
-        .chain_err(|| "QFlags type must have template arguments")?;
-      if args.len() != 1 {
-        return Err("QFlags type must have exactly 1 template argument".into());
+  if let RustType::Array { ref mut indirection, .. } = rust_api_type {
+    if cpp_ffi_type.conversion == CppIndirectionChange::ReferenceToPointer {
+      if *indirection != RustTypeIndirection::Ptr {
+        return Err(unexpected("invalid indirection for ReferenceToPointer").into());
       }
-      if let CppTypeBase::Enum { ref name } = args[0].base {
-        match find_type_info(processed_types, dependency_types, |x| &x.cpp_name == name) {
-          None => return Err(format!("type has no Rust equivalent: {}", name).into()),
-          Some(info) => info.rust_name.clone(),
+      *indirection = RustTypeIndirection::Ref { lifetime: None };
+      rust_api_to_c_conversion = RustToCTypeConversion::RefToPtr;
+    }
+  }
+  if cpp_ffi_type.conversion == CppIndirectionChange::EnumToInt {
+    rust_api_to_c_conversion = RustToCTypeConversion::EnumToInt;
+    rust_api_type = ffi_type(processed_types, dependency_types, &cpp_ffi_type.original_type)?;
+  }
+  if let CppIndirectionChange::FlagsToInt { ref enum_name } = cpp_ffi_type.conversion {
+    rust_api_to_c_conversion = RustToCTypeConversion::FlagsToInt;
+    let enum_type =
+      match find_type_info(processed_types, dependency_types, |x| &x.cpp_name == enum_name) {
+        None => return Err(format!("type has no Rust equivalent: {}", enum_name).into()),
+        Some(info) => {
+          if let RustTypeWrapperKind::Enum { is_flaggable: true, .. } = info.kind {
+            info.rust_name.clone()
+          } else {
+            return Err(format!("enum can't be used as flags: {}", enum_name).into());
+          }
         }
-      } else {
-        return Err(unexpected("invalid original type for QFlags").into());
-      }
-    } else {
-      return Err(unexpected("invalid original type for QFlags").into());
-    };
+      };
     rust_api_type = RustType::Common {
-      base: RustName::new(vec!["qt_core".to_string(),
+      base: RustName::new(vec!["cpp_utils".to_string(),
                                "flags".to_string(),
                                "Flags".to_string()])?,
       generic_arguments: Some(vec![RustType::Common {
@@ -551,6 +849,47 @@ fn find_type_info<'a, F>(processed_types: &'a [RustProcessedTypeInfo],
   }
 }
 
+/// Returns name of the Rust primitive integer type
+/// matching `underlying_type` of a C++ enum.
+/// Types with platform-dependent size or signedness (`long`, `char`, `wchar_t`
+/// and `bool`) are replaced with fixed-size types by the parser.
+fn enum_int_type(underlying_type: &CppType) -> Result<String> {
+  if underlying_type.indirection != CppTypeIndirection::None {
+    return Err(unexpected("enum's underlying type can't be a pointer").into());
+  }
+  Ok(match underlying_type.base {
+       CppTypeBase::BuiltInNumeric(ref numeric) => {
+         let name = match *numeric {
+           CppBuiltInNumericType::SChar => "i8",
+           CppBuiltInNumericType::UChar => "u8",
+           CppBuiltInNumericType::Short => "i16",
+           CppBuiltInNumericType::UShort |
+           CppBuiltInNumericType::Char16 => "u16",
+           CppBuiltInNumericType::Int => "i32",
+           CppBuiltInNumericType::UInt |
+           CppBuiltInNumericType::Char32 => "u32",
+           CppBuiltInNumericType::LongLong => "i64",
+           CppBuiltInNumericType::ULongLong => "u64",
+           _ => {
+             return Err(format!("unsupported underlying type of enum: {:?}", numeric).into());
+           }
+         };
+         name.to_string()
+       }
+       CppTypeBase::SpecificNumeric(CppSpecificNumericType {
+                                      ref bits,
+                                      kind: CppSpecificNumericTypeKind::Integer { ref is_signed },
+                                      ..
+                                    }) => format!("{}{}", if *is_signed { "i" } else { "u" }, bits),
+       CppTypeBase::PointerSizedInteger { ref is_signed, .. } => {
+         if *is_signed { "isize" } else { "usize" }.to_string()
+       }
+       _ => {
+         return Err(format!("unsupported underlying type of enum: {:?}", underlying_type).into())
+       }
+     })
+}
+
 /// Converts `CppType` to its exact Rust equivalent (FFI-compatible)
 fn ffi_type(processed_types: &[RustProcessedTypeInfo],
             dependency_types: &[&[RustProcessedTypeInfo]],
@@ -599,6 +938,8 @@ fn ffi_type(processed_types: &[RustProcessedTypeInfo],
       RustName::new(vec![if *is_signed { "isize" } else { "usize" }.to_string()])?
     }
     CppTypeBase::Enum { ref name } => {
+      // enum values are passed to FFI functions as integers
+      // (`CppIndirectionChange::EnumToInt`), but pointers to enums are not converted
       match find_type_info(processed_types, dependency_types, |x| &x.cpp_name == name) {
         None => return Err(format!("type has no Rust equivalent: {}", name).into()),
         Some(info) => info.rust_name.clone(),
@@ -631,7 +972,48 @@ fn ffi_type(processed_types: &[RustProcessedTypeInfo],
                   return_type: Box::new(rust_return_type),
                 });
     }
-    CppTypeBase::TemplateParameter { .. } => return Err(unexpected("invalid cpp type").into()),
+    CppTypeBase::Array(CppArrayType {
+                         ref element_type,
+                         ref size,
+                       }) => {
+      if let CppTypeBase::Class(ref name_and_args) = element_type.base {
+        if element_type.indirection == CppTypeIndirection::None {
+          // Rust arrays can only contain structs that have the same size as C++ classes
+          match find_type_info(processed_types, dependency_types, |x| {
+            &x.cpp_name == &name_and_args.name &&
+            &x.cpp_template_arguments == &name_and_args.template_arguments
+          }) {
+            Some(&RustProcessedTypeInfo {
+                   kind: RustTypeWrapperKind::Struct { size_const_name: Some(..), .. }, ..
+                 }) => {}
+            _ => {
+              return Err(format!("arrays of non-stack-allocated classes are not supported: \
+                                  {:?}",
+                                 name_and_args)
+                             .into())
+            }
+          }
+        }
+      }
+      return Ok(RustType::Array {
+                  element_type: Box::new(ffi_type(processed_types,
+                                                  dependency_types,
+                                                  element_type)?),
+                  size: *size,
+                  is_const: cpp_ffi_type.is_const,
+                  indirection: match cpp_ffi_type.indirection {
+                    CppTypeIndirection::None => RustTypeIndirection::None,
+                    CppTypeIndirection::Ptr => RustTypeIndirection::Ptr,
+                    _ => {
+                      return Err(format!("invalid FFI type indirection for array: {:?}",
+                                         cpp_ffi_type.indirection)
+                                     .into())
+                    }
+                  },
+                });
+    }
+    CppTypeBase::TemplateParameter { .. } |
+    CppTypeBase::TemplateArgumentValue(..) => return Err(unexpected("invalid cpp type").into()),
   };
   Ok(RustType::Common {
        base: rust_name,
@@ -702,6 +1084,7 @@ impl<'aa> RustGenerator<'aa> {
                 },
                 is_public: info.is_public,
                 rust_doc: None,
+                deprecation: self.cpp_type_deprecation(&info.cpp_name),
               },
               overloading_types: Vec::new(),
             },
@@ -831,6 +1214,7 @@ impl<'aa> RustGenerator<'aa> {
            },
            is_public: info.is_public,
            rust_doc: None,
+           deprecation: self.cpp_type_deprecation(&info.cpp_name),
          },
          overloading_types: functions_result.overloading_types,
        },
@@ -839,6 +1223,324 @@ impl<'aa> RustGenerator<'aa> {
        })
   }
 
+  /// Converts C++ constants of the current library to Rust constants.
+  /// Constants rejected by constant filters or having unsupported values are skipped.
+  fn calc_constants(&self) -> Result<Vec<RustConstant>> {
+    let mut result: Vec<RustConstant> = Vec::new();
+    'constants: for constant in &self.input_data.cpp_data.current.parser.constants {
+      for filter in &self.input_data.constant_filters {
+        let allowed = filter(constant)
+          .chain_err(|| format!("constant filter failed for {}", constant.name))?;
+        if !allowed {
+          log::llog(log::DebugRustSkips,
+                    || format!("Constant is blocked by filter: {}", constant.name));
+          continue 'constants;
+        }
+      }
+      match self.generate_constant(constant) {
+        Ok(rust_constant) => {
+          if result.iter().any(|c| c.name == rust_constant.name) {
+            log::llog(log::DebugRustSkips, || {
+              format!("Constant is skipped because of name conflict: {}",
+                      constant.name)
+            });
+            continue;
+          }
+          result.push(rust_constant);
+        }
+        Err(err) => {
+          log::llog(log::DebugRustSkips,
+                    || format!("Can't generate constant {}: {}", constant.name, err));
+          err.discard_expected();
+        }
+      }
+    }
+    Ok(result)
+  }
+
+  /// Returns deprecation attribute of the C++ type `cpp_name`
+  /// of the current library.
+  fn cpp_type_deprecation(&self, cpp_name: &str) -> Option<CppDeprecation> {
+    self
+      .input_data
+      .cpp_data
+      .current
+      .parser
+      .types
+      .iter()
+      .find(|t| t.name == cpp_name)
+      .and_then(|t| t.deprecation.clone())
+  }
+
+  /// Converts C++ type aliases of the current library to Rust type aliases.
+  /// Aliases referring to types unavailable in Rust are skipped.
+  fn calc_type_aliases(&self) -> Result<Vec<RustTypeAlias>> {
+    let mut result: Vec<RustTypeAlias> = Vec::new();
+    for alias in &self.input_data.cpp_data.current.parser.type_aliases {
+      match self.generate_type_alias(alias) {
+        Ok(rust_alias) => {
+          if result.iter().any(|a| a.name == rust_alias.name) ||
+             self
+               .processed_types
+               .iter()
+               .any(|t| t.rust_name == rust_alias.name) {
+            log::llog(log::DebugRustSkips, || {
+              format!("Type alias is skipped because of name conflict: {}",
+                      alias.name)
+            });
+            continue;
+          }
+          result.push(rust_alias);
+        }
+        Err(err) => {
+          log::llog(log::DebugRustSkips,
+                    || format!("Can't generate type alias {}: {}", alias.name, err));
+          err.discard_expected();
+        }
+      }
+    }
+    for name in self.input_data.preserved_type_aliases {
+      if !result.iter().any(|a| &a.cpp_name == name) {
+        log::llog(log::DebugRustSkips,
+                  || format!("Preserved type alias is not available: {}", name));
+      }
+    }
+    Ok(result)
+  }
+
+  /// Converts a C++ type alias to a Rust type alias.
+  fn generate_type_alias(&self, alias: &CppTypeAlias) -> Result<RustTypeAlias> {
+    match alias.target_type.indirection {
+      CppTypeIndirection::Ref |
+      CppTypeIndirection::PtrRef |
+      CppTypeIndirection::RValueRef => {
+        return Err("aliases of reference types are not supported".into());
+      }
+      _ => {}
+    }
+    Ok(RustTypeAlias {
+         name: self
+           .calculate_rust_name(&alias.name, &alias.include_file, false, None)?,
+         rust_type: ffi_type(&self.processed_types,
+                             &self.input_data.dependency_types,
+                             &alias.target_type)?,
+         cpp_name: alias.name.clone(),
+       })
+  }
+
+  /// Replaces target types of type aliases requested by
+  /// `Config::add_preserved_type_alias` with the aliases' names
+  /// in `rust_type` and its generic arguments.
+  fn apply_preserved_type_aliases(&self, rust_type: &RustType) -> RustType {
+    for alias in &self.type_aliases {
+      if !self
+            .input_data
+            .preserved_type_aliases
+            .iter()
+            .any(|x| x == &alias.cpp_name) {
+        continue;
+      }
+      let alias_type = RustType::Common {
+        base: alias.name.clone(),
+        generic_arguments: None,
+        is_const: false,
+        is_const2: false,
+        indirection: RustTypeIndirection::None,
+      };
+      if rust_type == &alias.rust_type {
+        return alias_type;
+      }
+      if let RustType::Common {
+               ref base,
+               ref generic_arguments,
+               ref indirection,
+               ref is_const,
+               ref is_const2,
+             } = *rust_type {
+        if let RustType::Common {
+                 base: ref alias_base,
+                 generic_arguments: ref alias_generic_arguments,
+                 indirection: RustTypeIndirection::None,
+                 is_const: false,
+                 ..
+               } = alias.rust_type {
+          if base == alias_base && generic_arguments == alias_generic_arguments {
+            // keep indirection of the original type, e.g. `&QStringList`
+            return RustType::Common {
+                     base: alias.name.clone(),
+                     generic_arguments: None,
+                     indirection: indirection.clone(),
+                     is_const: *is_const,
+                     is_const2: *is_const2,
+                   };
+          }
+        }
+      }
+    }
+    if let RustType::Common {
+             ref base,
+             generic_arguments: Some(ref generic_arguments),
+             ref indirection,
+             ref is_const,
+             ref is_const2,
+           } = *rust_type {
+      return RustType::Common {
+               base: base.clone(),
+               generic_arguments: Some(generic_arguments
+                                         .iter()
+                                         .map(|t| self.apply_preserved_type_aliases(t))
+                                         .collect()),
+               indirection: indirection.clone(),
+               is_const: *is_const,
+               is_const2: *is_const2,
+             };
+    }
+    rust_type.clone()
+  }
+
+  /// Changes Rust API type of a raw pointer argument or return value
+  /// according to `annotation` added by `Config::add_pointer_annotation`.
+  fn apply_pointer_annotation(&self,
+                              complete_type: &CompleteType,
+                              target: PointerAnnotationTarget,
+                              annotation: PointerAnnotation)
+                              -> Result<CompleteType> {
+    let (base, generic_arguments, is_const) = match complete_type.rust_api_type {
+      RustType::Common {
+        ref base,
+        ref generic_arguments,
+        ref indirection,
+        ref is_const,
+        ..
+      } if indirection == &RustTypeIndirection::Ptr &&
+           complete_type.rust_api_to_c_conversion == RustToCTypeConversion::None => {
+        (base, generic_arguments, *is_const)
+      }
+      _ => return Err("pointer annotations are only supported for raw pointers".into()),
+    };
+    match annotation {
+      PointerAnnotation::Borrowed => complete_type.ptr_to_ref(is_const),
+      PointerAnnotation::Nullable => {
+        let mut r = complete_type.ptr_to_ref(is_const)?;
+        r.rust_api_to_c_conversion = RustToCTypeConversion::OptionRefToPtr;
+        r.rust_api_type = RustType::Common {
+          base: RustName::new(vec!["std".to_string(),
+                                   "option".to_string(),
+                                   "Option".to_string()])?,
+          indirection: RustTypeIndirection::None,
+          is_const: false,
+          is_const2: false,
+          generic_arguments: Some(vec![r.rust_api_type]),
+        };
+        Ok(r)
+      }
+      PointerAnnotation::Owned => {
+        if is_const {
+          return Err("ownership of a const object can't be transferred".into());
+        }
+        match find_type_info(&self.processed_types,
+                             &self.input_data.dependency_types,
+                             |x| &x.rust_name == base) {
+          Some(&RustProcessedTypeInfo {
+                 kind: RustTypeWrapperKind::Struct { ref is_deletable, .. }, ..
+               }) => {
+            if !*is_deletable {
+              return Err(format!("{} is not deletable", base.full_name(None)).into());
+            }
+          }
+          _ => return Err("owned pointers are only supported for class types".into()),
+        }
+        let mut r = complete_type.clone();
+        r.rust_api_type = RustType::Common {
+          base: RustName::new(vec!["cpp_utils".to_string(), "CppBox".to_string()])?,
+          indirection: RustTypeIndirection::None,
+          is_const: false,
+          is_const2: false,
+          generic_arguments: Some(vec![RustType::Common {
+                                         base: base.clone(),
+                                         generic_arguments: generic_arguments.clone(),
+                                         indirection: RustTypeIndirection::None,
+                                         is_const: false,
+                                         is_const2: false,
+                                       }]),
+        };
+        r.rust_api_to_c_conversion = if target == PointerAnnotationTarget::ReturnValue {
+          RustToCTypeConversion::CppBoxToPtr
+        } else {
+          RustToCTypeConversion::CppBoxIntoPtr
+        };
+        Ok(r)
+      }
+    }
+  }
+
+  /// Converts a C++ constant to a Rust constant.
+  fn generate_constant(&self, constant: &CppConstant) -> Result<RustConstant> {
+    let mut name = self
+      .calculate_rust_name(&constant.name, &constant.include_file, true, None)?;
+    {
+      let last_part = name.parts.last_mut().chain_err(|| "name can't be empty")?;
+      *last_part = constant
+        .name
+        .split("::")
+        .last()
+        .chain_err(|| "name can't be empty")?
+        .to_upper_case_words();
+    }
+    let (rust_type, value) = match constant.value {
+      CppConstantValue::String(ref string) => {
+        (RustType::Common {
+           base: RustName::new(vec!["str".to_string()])?,
+           generic_arguments: None,
+           indirection: RustTypeIndirection::Ref { lifetime: Some("static".to_string()) },
+           is_const: true,
+           is_const2: false,
+         },
+         format!("{:?}", string))
+      }
+      _ => {
+        let is_unsigned = match constant.value_type.base {
+          CppTypeBase::BuiltInNumeric(ref t) => t.is_unsigned_integer(),
+          CppTypeBase::SpecificNumeric(CppSpecificNumericType { ref kind, .. }) => {
+            kind == &CppSpecificNumericTypeKind::Integer { is_signed: false }
+          }
+          CppTypeBase::PointerSizedInteger { is_signed, .. } => !is_signed,
+          _ => false,
+        };
+        let value = match constant.value {
+          CppConstantValue::Integer(value) => {
+            if constant.value_type.base == CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Bool) {
+              (value != 0).to_string()
+            } else if is_unsigned && value < 0 {
+              return Err("negative value of unsigned type".into());
+            } else {
+              value.to_string()
+            }
+          }
+          CppConstantValue::Float(ref value) => value.clone(),
+          CppConstantValue::String(..) => unreachable!(),
+        };
+        let rust_type = complete_type(&self.processed_types,
+                                      &self.input_data.dependency_types,
+                                      &constant
+                                         .value_type
+                                         .to_cpp_ffi_type(CppTypeRole::NotReturnType)?,
+                                      &CppFfiArgumentMeaning::Argument(0),
+                                      false,
+                                      &ReturnValueAllocationPlace::NotApplicable)?
+          .rust_api_type;
+        (rust_type, value)
+      }
+    };
+    Ok(RustConstant {
+         name: name,
+         rust_type: rust_type,
+         value: value,
+         cpp_name: constant.name.clone(),
+         is_macro: constant.is_macro,
+       })
+  }
+
   /// Returns full name of the Rust method corresponding to `cpp_method`.
   fn free_function_rust_name(&self, cpp_method: &CppMethod) -> Result<RustName> {
     self.calculate_rust_name(&cpp_method.name,
@@ -873,15 +1575,31 @@ impl<'aa> RustGenerator<'aa> {
                                  scope: &RustMethodScope,
                                  generate_doc: bool)
                                  -> Result<RustSingleMethod> {
+    let signature = method.cpp_method.signature();
+    let pointer_annotations = self.input_data.pointer_annotations.get(&signature);
     let mut arguments = Vec::new();
+    let mut exception_info_arg_index = None;
     for (arg_index, arg) in method.c_signature.arguments.iter().enumerate() {
-      if arg.meaning != CppFfiArgumentMeaning::ReturnValue {
-        let arg_type = complete_type(&self.processed_types,
-                                     &self.input_data.dependency_types,
-                                     &arg.argument_type,
-                                     &arg.meaning,
-                                     false,
-                                     &method.allocation_place)?;
+      if arg.meaning == CppFfiArgumentMeaning::ExceptionInfo {
+        exception_info_arg_index = Some(arg_index);
+      } else if arg.meaning != CppFfiArgumentMeaning::ReturnValue {
+        let mut arg_type = complete_type(&self.processed_types,
+                                         &self.input_data.dependency_types,
+                                         &arg.argument_type,
+                                         &arg.meaning,
+                                         false,
+                                         &method.allocation_place)?;
+        if let CppFfiArgumentMeaning::Argument(cpp_index) = arg.meaning {
+          let target = PointerAnnotationTarget::Argument(cpp_index as usize);
+          if let Some(annotation) = pointer_annotations.and_then(|x| x.get(&target)) {
+            arg_type = self
+              .apply_pointer_annotation(&arg_type, target, *annotation)
+              .chain_err(|| format!("invalid pointer annotation for argument {}", cpp_index))?;
+          }
+        }
+        if arg.meaning != CppFfiArgumentMeaning::This {
+          arg_type.rust_api_type = self.apply_preserved_type_aliases(&arg_type.rust_api_type);
+        }
         arguments.push(RustMethodArgument {
                          ffi_index: arg_index,
                          argument_type: arg_type,
@@ -921,54 +1639,126 @@ impl<'aa> RustGenerator<'aa> {
                                       &method.allocation_place)?;
       (return_type, None)
     };
-    if return_type.rust_api_type.is_ref() && return_type.rust_api_type.lifetime().is_none() {
-      let mut found = false;
-      for arg in &arguments {
-        if let Some(lifetime) = arg.argument_type.rust_api_type.lifetime() {
-          return_type.rust_api_type = return_type.rust_api_type.with_lifetime(lifetime.clone());
-          found = true;
-          break;
+    let return_value_target = PointerAnnotationTarget::ReturnValue;
+    if let Some(annotation) = pointer_annotations.and_then(|x| x.get(&return_value_target)) {
+      return_type = self
+        .apply_pointer_annotation(&return_type, return_value_target, *annotation)
+        .chain_err(|| "invalid pointer annotation for return value")?;
+    }
+    return_type.rust_api_type = self.apply_preserved_type_aliases(&return_type.rust_api_type);
+    let return_value_lifetime = self.input_data.return_value_lifetimes.get(&signature).cloned();
+    let mut return_as_raw_pointer = false;
+    {
+      // lifetime of `Option<&T>` is set on its generic argument
+      let return_ref_type = if return_type.rust_api_to_c_conversion ==
+                               RustToCTypeConversion::OptionRefToPtr {
+        if let RustType::Common { generic_arguments: Some(ref mut args), .. } =
+          return_type.rust_api_type {
+          args.get_mut(0).chain_err(|| "Option with no generic_arguments")?
+        } else {
+          return Err(unexpected("Option type expected").into());
         }
-      }
-      if !found {
-        let mut next_lifetime_num = 0;
-        for arg in &mut arguments {
-          if arg.argument_type.rust_api_type.is_ref() &&
-             arg.argument_type.rust_api_type.lifetime().is_none() {
-            arg.argument_type.rust_api_type = arg
-              .argument_type
-              .rust_api_type
-              .with_lifetime(format!("l{}", next_lifetime_num));
-            next_lifetime_num += 1;
+      } else {
+        &mut return_type.rust_api_type
+      };
+      if return_ref_type.is_ref() && return_ref_type.lifetime().is_none() {
+        let self_arg_index = arguments
+          .iter()
+          .position(|arg| {
+                      method.c_signature.arguments[arg.ffi_index].meaning ==
+                      CppFfiArgumentMeaning::This
+                    });
+        // indexes of arguments the return value borrows from
+        let source_arg_indexes = match return_value_lifetime {
+          Some(ReturnValueLifetime::Static) => Vec::new(),
+          Some(ReturnValueLifetime::OfSelf) => {
+            vec![self_arg_index.chain_err(|| "method has no self argument")?]
           }
-        }
-        let return_lifetime = if next_lifetime_num == 0 {
+          Some(ReturnValueLifetime::OfArgument(cpp_index)) => {
+            let index = arguments
+              .iter()
+              .position(|arg| {
+                          method.c_signature.arguments[arg.ffi_index].meaning ==
+                          CppFfiArgumentMeaning::Argument(cpp_index as i8)
+                        })
+              .chain_err(|| format!("method has no argument {}", cpp_index))?;
+            vec![index]
+          }
+          None => {
+            if let Some(index) = self_arg_index {
+              vec![index]
+            } else {
+              (0..arguments.len())
+                .filter(|&i| arguments[i].argument_type.rust_api_type.is_ref())
+                .collect()
+            }
+          }
+        };
+        if return_value_lifetime == Some(ReturnValueLifetime::Static) {
+          *return_ref_type = return_ref_type.with_lifetime("static".to_string());
+        } else if source_arg_indexes.is_empty() {
           log::llog(log::DebugGeneral, || {
             format!("Method returns a reference but doesn't receive a reference: {}",
                     method.short_text())
           });
           log::llog(log::DebugGeneral,
-                    || "Assuming static lifetime of return value.");
-          "static".to_string()
+                    || "Return type is kept as a raw pointer.");
+          return_as_raw_pointer = true;
         } else {
-          "l0".to_string()
-        };
-        return_type.rust_api_type = return_type.rust_api_type.with_lifetime(return_lifetime);
+          for &index in &source_arg_indexes {
+            if !arguments[index].argument_type.rust_api_type.is_ref() {
+              return Err(format!("return value can't borrow from non-reference argument {}",
+                                 arguments[index].name)
+                             .into());
+            }
+          }
+          let lifetime = source_arg_indexes
+            .iter()
+            .filter_map(|&index| arguments[index].argument_type.rust_api_type.lifetime())
+            .next()
+            .cloned()
+            .unwrap_or_else(|| "l0".to_string());
+          for &index in &source_arg_indexes {
+            let arg_type = &mut arguments[index].argument_type.rust_api_type;
+            *arg_type = arg_type.with_lifetime(lifetime.clone());
+          }
+          *return_ref_type = return_ref_type.with_lifetime(lifetime);
+        }
       }
     }
+    if return_as_raw_pointer {
+      return_type.rust_api_type = self.apply_preserved_type_aliases(&return_type.rust_ffi_type);
+      return_type.rust_api_to_c_conversion = RustToCTypeConversion::None;
+    }
+    if exception_info_arg_index.is_some() {
+      return_type.rust_api_type = RustType::Common {
+        base: RustName::new(vec!["std".to_string(), "result".to_string(), "Result".to_string()])?,
+        generic_arguments: Some(vec![return_type.rust_api_type.clone(),
+                                     RustType::Common {
+                                       base: RustName::new(vec!["cpp_utils".to_string(),
+                                                                "CppException".to_string()])?,
+                                       generic_arguments: None,
+                                       is_const: false,
+                                       is_const2: false,
+                                       indirection: RustTypeIndirection::None,
+                                     }]),
+        is_const: false,
+        is_const2: false,
+        indirection: RustTypeIndirection::None,
+      };
+    }
 
+    let unsafe_reason = self.method_unsafe_reason(method, &arguments);
     let doc = if generate_doc {
       Some(RustMethodDocItem {
              cpp_fn: method.short_text(),
              rust_fns: Vec::new(),
              doc: method.cpp_method.doc.clone(),
+             unsafe_reason: unsafe_reason.clone(),
            })
     } else {
       None
     };
-    let is_unsafe = arguments
-      .iter()
-      .any(|arg| arg.argument_type.rust_api_type.is_unsafe_argument());
     Ok(RustSingleMethod {
          name: self.method_rust_name(method)?,
          scope: scope.clone(),
@@ -977,12 +1767,42 @@ impl<'aa> RustGenerator<'aa> {
            cpp_method: method.clone(),
            return_type: return_type,
            return_type_ffi_index: return_arg_index,
+           exception_info_ffi_index: exception_info_arg_index,
          },
          doc: doc,
-         is_unsafe: is_unsafe,
+         unsafe_reason: unsafe_reason,
        })
   }
 
+  /// Returns the reason for marking the Rust method generated from `method`
+  /// with `arguments` as `unsafe` according to `Config::set_unsafe_policy`,
+  /// or `None` if the method should be safe.
+  fn method_unsafe_reason(&self,
+                          method: &CppAndFfiMethod,
+                          arguments: &[RustMethodArgument])
+                          -> Option<RustMethodUnsafeReason> {
+    if self.input_data.unsafe_policy == UnsafePolicy::AllUnsafe {
+      return Some(RustMethodUnsafeReason::AllUnsafePolicy);
+    }
+    if let Some(arg) = arguments
+         .iter()
+         .find(|arg| arg.argument_type.rust_api_type.is_unsafe_argument()) {
+      return Some(RustMethodUnsafeReason::RawPointerArgument(arg.name.clone()));
+    }
+    if self.input_data.unsafe_policy == UnsafePolicy::HeuristicWithAllowlist {
+      let full_name = method.cpp_method.full_name();
+      let signature = method.cpp_method.signature();
+      if !self
+            .input_data
+            .safe_methods
+            .iter()
+            .any(|x| x == &full_name || x == &signature) {
+        return Some(RustMethodUnsafeReason::NotInSafeMethods);
+      }
+    }
+    None
+  }
+
   /// Generates `Drop` or `CppDeletable` trait implementation
   /// from a C++ destructor.
   fn process_destructor(&self,
@@ -995,6 +1815,11 @@ impl<'aa> RustGenerator<'aa> {
           let mut method = self.generate_rust_single_method(method, scope, true)?;
           method.name = RustName::new(vec!["drop".to_string()])?;
           method.scope = RustMethodScope::TraitImpl;
+          // `Drop::drop` can't be unsafe
+          method.unsafe_reason = None;
+          if let Some(ref mut doc) = method.doc {
+            doc.unsafe_reason = None;
+          }
           Ok(TraitImpl {
                target_type: target_type.clone(),
                associated_types: Vec::new(),
@@ -1074,7 +1899,14 @@ impl<'aa> RustGenerator<'aa> {
       };
       final_method.scope = RustMethodScope::TraitImpl;
       final_method.name = RustName::new(vec![method_name])?;
-      final_method.is_unsafe = cpp_cast.is_unsafe_static_cast();
+      final_method.unsafe_reason = if cpp_cast.is_unsafe_static_cast() {
+        Some(RustMethodUnsafeReason::UncheckedStaticCast)
+      } else {
+        None
+      };
+      if let Some(ref mut doc) = final_method.doc {
+        doc.unsafe_reason = final_method.unsafe_reason.clone();
+      }
       let return_ref_type = args.return_type.ptr_to_ref(*final_is_const)?;
       if &final_method.arguments.cpp_method.cpp_method.name == "static_cast" {
         final_method.arguments.return_type = return_ref_type;
@@ -1134,24 +1966,1019 @@ impl<'aa> RustGenerator<'aa> {
 
       }
     }
-    let trait_type = RustType::Common {
-      base: RustName::new(trait_name)?,
-      indirection: RustTypeIndirection::None,
-      is_const: false,
-      is_const2: false,
-      generic_arguments: Some(vec![to_type.ptr_to_value()?.rust_api_type]),
+    let trait_type = RustType::Common {
+      base: RustName::new(trait_name)?,
+      indirection: RustTypeIndirection::None,
+      is_const: false,
+      is_const2: false,
+      generic_arguments: Some(vec![to_type.ptr_to_value()?.rust_api_type]),
+    };
+    results.push(TraitImpl {
+                   target_type: from_type.ptr_to_value()?.rust_api_type,
+                   associated_types: Vec::new(),
+                   trait_type: trait_type,
+                   extra: None,
+                   methods: final_methods
+                     .into_iter()
+                     .map(|x| x.0.to_rust_method())
+                     .collect(),
+                 });
+    Ok(results)
+  }
+
+  /// Generates `From` trait implementation from a C++ conversion operator
+  /// wrapper. The implementation converts a reference to the class
+  /// to the operator's target type, so `Into` is also available.
+  fn process_conversion_operator(&self, method: &RustSingleMethod) -> Result<TraitImpl> {
+    if method.has_unsafe_arguments() {
+      return Err("conversions taking raw pointers are not supported".into());
+    }
+    if method.arguments.exception_info_ffi_index.is_some() {
+      return Err("conversions catching C++ exceptions are not supported".into());
+    }
+    let args = &method.arguments;
+    if args.arguments.len() != 1 || &args.arguments[0].name != "self" {
+      return Err(unexpected("conversion operator must only have self argument").into());
+    }
+    if let RustType::Common { ref indirection, .. } = args.return_type.rust_api_type {
+      if *indirection != RustTypeIndirection::None {
+        return Err("conversion to a reference or a pointer is not supported".into());
+      }
+    } else {
+      return Err("conversion to a non-common type is not supported".into());
+    }
+    let mut final_method = method.to_trait_method();
+    final_method.name = RustName::new(vec!["from".to_string()])?;
+    {
+      let arg = &mut final_method.arguments.arguments[0];
+      arg.name = "value".to_string();
+      arg.argument_type.rust_api_type = arg
+        .argument_type
+        .rust_api_type
+        .with_lifetime("a".to_string());
+    }
+    let trait_type = RustType::Common {
+      base: RustName::new(vec!["std".to_string(), "convert".to_string(), "From".to_string()])?,
+      indirection: RustTypeIndirection::None,
+      is_const: false,
+      is_const2: false,
+      generic_arguments: Some(vec![final_method.arguments.arguments[0]
+                                     .argument_type
+                                     .rust_api_type
+                                     .clone()]),
+    };
+    Ok(TraitImpl {
+         target_type: args.return_type.rust_api_type.clone(),
+         associated_types: Vec::new(),
+         trait_type: trait_type,
+         extra: None,
+         methods: vec![final_method.to_rust_method()],
+       })
+  }
+
+  /// Generates `Clone` or `Default` trait implementation from a wrapper
+  /// of a C++ copy constructor or default constructor. For types that
+  /// are always placed on the heap, `cpp_utils::CppBoxClone` is implemented
+  /// instead of `Clone`, and `Default` is not supported because
+  /// `CppBox` already implements it as a null pointer.
+  fn process_special_constructor(&self, method: &RustSingleMethod) -> Result<TraitImpl> {
+    if method.has_unsafe_arguments() {
+      return Err("constructors taking raw pointers are not supported".into());
+    }
+    if method.arguments.exception_info_ffi_index.is_some() {
+      return Err("constructors catching C++ exceptions are not supported".into());
+    }
+    let is_copy = method
+      .arguments
+      .cpp_method
+      .cpp_method
+      .is_copy_constructor();
+    let return_type = &method.arguments.return_type;
+    let (trait_name, method_name, target_type) = match return_type.rust_api_to_c_conversion {
+      RustToCTypeConversion::ValueToPtr => {
+        if is_copy {
+          (vec!["std", "clone", "Clone"], "clone", return_type.rust_api_type.clone())
+        } else {
+          (vec!["std", "default", "Default"], "default", return_type.rust_api_type.clone())
+        }
+      }
+      RustToCTypeConversion::CppBoxToPtr => {
+        if !is_copy {
+          return Err("Default is not supported for types wrapped in CppBox".into());
+        }
+        let target_type = if let RustType::Common { ref generic_arguments, .. } =
+          return_type.rust_api_type {
+          generic_arguments.as_ref().and_then(|args| args.get(0)).cloned()
+        } else {
+          None
+        };
+        (vec!["cpp_utils", "CppBoxClone"],
+         "clone_box",
+         target_type.chain_err(|| "CppBox must have generic arguments")?)
+      }
+      _ => return Err(unexpected("constructor must return a value or a CppBox").into()),
+    };
+    let mut final_method = method.to_trait_method();
+    final_method.name = RustName::new(vec![method_name.to_string()])?;
+    if is_copy {
+      if final_method.arguments.arguments.len() != 1 {
+        return Err(unexpected("copy constructor must have one argument").into());
+      }
+      let arg = &mut final_method.arguments.arguments[0];
+      if arg.argument_type.rust_api_to_c_conversion != RustToCTypeConversion::RefToPtr {
+        return Err("copy constructor must take the copied value by reference".into());
+      }
+      arg.name = "self".to_string();
+      arg.argument_type.rust_api_type = arg.argument_type.rust_api_type.without_lifetime();
+    }
+    Ok(TraitImpl {
+         target_type: target_type,
+         associated_types: Vec::new(),
+         trait_type: RustType::Common {
+           base: RustName::new(trait_name.into_iter().map(|x| x.to_string()).collect())?,
+           indirection: RustTypeIndirection::None,
+           is_const: false,
+           is_const2: false,
+           generic_arguments: None,
+         },
+         extra: None,
+         methods: vec![final_method.to_rust_method()],
+       })
+  }
+
+  /// Generates `Debug` trait implementation that formats the return value
+  /// of a C++ method configured with `Config::add_debug_method`.
+  fn process_debug_method(&self, method: &RustSingleMethod) -> Result<TraitImpl> {
+    if method.has_unsafe_arguments() {
+      return Err("methods taking raw pointers can't be used to implement Debug".into());
+    }
+    if method.arguments.exception_info_ffi_index.is_some() {
+      return Err("methods catching C++ exceptions can't be used to implement Debug".into());
+    }
+    let args = &method.arguments;
+    if args.arguments.len() != 1 || &args.arguments[0].name != "self" {
+      return Err("Debug method must only have self argument".into());
+    }
+    let self_type = &args.arguments[0].argument_type.rust_api_type;
+    if !self_type.is_ref() || !self_type.is_const()? {
+      return Err("Debug method must take self by const reference".into());
+    }
+    if args.return_type.rust_api_type == RustType::EmptyTuple {
+      return Err("Debug method must return a value".into());
+    }
+    let mut final_method = method.to_trait_method();
+    final_method.name = RustName::new(vec!["fmt".to_string()])?;
+    final_method.arguments.arguments[0].argument_type.rust_api_type = self_type
+      .without_lifetime();
+    final_method.arguments.return_type.rust_api_type = final_method
+      .arguments
+      .return_type
+      .rust_api_type
+      .without_lifetime();
+    Ok(TraitImpl {
+         target_type: rust_type_to_value(self_type)?,
+         associated_types: Vec::new(),
+         trait_type: RustType::Common {
+           base: RustName::new(vec!["std".to_string(), "fmt".to_string(), "Debug".to_string()])?,
+           indirection: RustTypeIndirection::None,
+           is_const: false,
+           is_const2: false,
+           generic_arguments: None,
+         },
+         extra: Some(TraitImplExtra::DebugFromMethod),
+         methods: vec![final_method.to_rust_method()],
+       })
+  }
+
+  /// Generates `Debug` trait implementation from a fictional
+  /// `write_to_buffer` method that writes the text printed by
+  /// `operator<<` of the C++ class to a buffer.
+  fn process_stream_output_method(&self, method: &RustSingleMethod) -> Result<TraitImpl> {
+    if method.arguments.exception_info_ffi_index.is_some() {
+      return Err("methods catching C++ exceptions can't be used to implement Debug".into());
+    }
+    let args = &method.arguments;
+    if args.arguments.len() != 3 || &args.arguments[0].name != "self" {
+      return Err(unexpected("stream output method must have self, buffer and buffer_size \
+                             arguments")
+                     .into());
+    }
+    let self_type = &args.arguments[0].argument_type.rust_api_type;
+    let mut final_method = method.to_trait_method();
+    final_method.name = RustName::new(vec!["fmt".to_string()])?;
+    final_method.arguments.arguments[0].argument_type.rust_api_type = self_type
+      .without_lifetime();
+    Ok(TraitImpl {
+         target_type: rust_type_to_value(self_type)?,
+         associated_types: Vec::new(),
+         trait_type: RustType::Common {
+           base: RustName::new(vec!["std".to_string(), "fmt".to_string(), "Debug".to_string()])?,
+           indirection: RustTypeIndirection::None,
+           is_const: false,
+           is_const2: false,
+           generic_arguments: None,
+         },
+         extra: Some(TraitImplExtra::DebugFromStream),
+         methods: vec![final_method.to_rust_method()],
+       })
+  }
+
+  /// Returns true if `operator==` of `cpp_type` can be expected to be
+  /// an equivalence relation, i.e. the type doesn't contain floating point
+  /// values in its fields, fields of its base classes or its template arguments.
+  fn cpp_type_may_implement_eq(&self, cpp_type: &CppType) -> bool {
+    if cpp_type.indirection != CppTypeIndirection::None {
+      return true;
+    }
+    match cpp_type.base {
+      CppTypeBase::BuiltInNumeric(ref t) => !t.is_float(),
+      CppTypeBase::SpecificNumeric(CppSpecificNumericType { ref kind, .. }) => {
+        kind != &CppSpecificNumericTypeKind::FloatingPoint
+      }
+      CppTypeBase::Array(CppArrayType { ref element_type, .. }) => {
+        self.cpp_type_may_implement_eq(element_type)
+      }
+      CppTypeBase::Class(CppTypeClassBase {
+                           ref name,
+                           ref template_arguments,
+                         }) => {
+        if let Some(ref args) = *template_arguments {
+          if !args.iter().all(|arg| self.cpp_type_may_implement_eq(arg)) {
+            return false;
+          }
+        }
+        match self.input_data.cpp_data.find_type_info(|t| &t.name == name) {
+          Some(type_data) => {
+            if let CppTypeKind::Class {
+                     ref bases,
+                     ref fields,
+                     ..
+                   } = type_data.kind {
+              bases
+                .iter()
+                .all(|base| self.cpp_type_may_implement_eq(&base.base_type)) &&
+              fields
+                .iter()
+                .all(|field| self.cpp_type_may_implement_eq(&field.field_type))
+            } else {
+              true
+            }
+          }
+          None => false,
+        }
+      }
+      _ => true,
+    }
+  }
+
+  /// Generates `Eq` trait implementation for the target type of
+  /// `partial_eq_impl` if its C++ class was added with `Config::add_eq_class`,
+  /// it's compared with the same type and doesn't contain floating point values.
+  fn process_eq(&self, partial_eq_impl: &TraitImpl) -> Result<Option<TraitImpl>> {
+    if let RustType::Common { ref generic_arguments, .. } = partial_eq_impl.trait_type {
+      if generic_arguments.as_ref() != Some(&vec![partial_eq_impl.target_type.clone()]) {
+        return Ok(None);
+      }
+    }
+    let cpp_type = if let RustType::Common {
+             ref base,
+             ref generic_arguments,
+             ..
+           } = partial_eq_impl.target_type {
+      if generic_arguments.is_some() {
+        return Ok(None);
+      }
+      match find_type_info(&self.processed_types,
+                           &self.input_data.dependency_types,
+                           |x| &x.rust_name == base) {
+        Some(info) => {
+          if !self.input_data.eq_classes.contains(&info.cpp_name) {
+            return Ok(None);
+          }
+          CppType {
+            base: CppTypeBase::Class(CppTypeClassBase {
+                                       name: info.cpp_name.clone(),
+                                       template_arguments: info.cpp_template_arguments.clone(),
+                                     }),
+            indirection: CppTypeIndirection::None,
+            is_const: false,
+            is_const2: false,
+          }
+        }
+        None => return Ok(None),
+      }
+    } else {
+      return Err(unexpected("RustType::Common expected").into());
+    };
+    if !self.cpp_type_may_implement_eq(&cpp_type) {
+      return Ok(None);
+    }
+    Ok(Some(TraitImpl {
+              target_type: partial_eq_impl.target_type.clone(),
+              associated_types: Vec::new(),
+              trait_type: RustType::Common {
+                base: RustName::new(vec!["std".to_string(), "cmp".to_string(), "Eq".to_string()])?,
+                indirection: RustTypeIndirection::None,
+                is_const: false,
+                is_const2: false,
+                generic_arguments: None,
+              },
+              extra: None,
+              methods: Vec::new(),
+            }))
+  }
+
+  /// Converts a method of a C++ container to a method of a
+  /// `cpp_utils::container` trait implementation. If `has_index` is true,
+  /// the method's only argument (except `self`) is converted to `usize`.
+  fn container_trait_method(&self,
+                            method: &RustSingleMethod,
+                            name: &str,
+                            has_index: bool)
+                            -> Result<RustSingleMethod> {
+    if method.has_unsafe_arguments() {
+      return Err("methods taking raw pointers are not supported".into());
+    }
+    if method.arguments.exception_info_ffi_index.is_some() {
+      return Err("methods catching C++ exceptions are not supported".into());
+    }
+    let mut r = method.to_trait_method();
+    r.name = RustName::new(vec![name.to_string()])?;
+    // access by index is unsafe regardless of the unsafe policy
+    r.unsafe_reason = if has_index {
+      Some(RustMethodUnsafeReason::UncheckedIndex)
+    } else {
+      None
+    };
+    if let Some(ref mut doc) = r.doc {
+      doc.unsafe_reason = r.unsafe_reason.clone();
+    }
+    for arg in &mut r.arguments.arguments {
+      arg.argument_type.rust_api_type = arg.argument_type.rust_api_type.without_lifetime();
+      if has_index && &arg.name != "self" {
+        if !is_integer_type(&arg.argument_type.cpp_type) {
+          return Err("index must have an integer type".into());
+        }
+        arg.name = "index".to_string();
+        arg.argument_type.rust_api_type = RustType::Common {
+          base: RustName::new(vec!["usize".to_string()])?,
+          indirection: RustTypeIndirection::None,
+          is_const: false,
+          is_const2: false,
+          generic_arguments: None,
+        };
+        arg.argument_type.rust_api_to_c_conversion = RustToCTypeConversion::UsizeToInt;
+      }
+    }
+    r.arguments.return_type.rust_api_type = r.arguments
+      .return_type
+      .rust_api_type
+      .without_lifetime();
+    Ok(r)
+  }
+
+  /// Generates `cpp_utils::container::CppIterator` implementation for a class
+  /// that has `operator*` const method returning a reference, prefix `operator++`
+  /// and `operator==` const method comparing with the same class.
+  /// `methods` must contain the class's operators (see `is_iterator_operator`).
+  /// Returns `None` if any of the operators is missing.
+  fn process_cpp_iterator(&self, methods: &[RustSingleMethod]) -> Result<Option<TraitImpl>> {
+    let find_operator = |operator: CppOperator, is_const: bool| {
+      methods
+        .iter()
+        .find(|m| {
+          let cpp_method = &m.arguments.cpp_method.cpp_method;
+          cpp_method.operator.as_ref() == Some(&operator) &&
+          cpp_method
+            .class_membership
+            .as_ref()
+            .map_or(false, |info| info.is_const == is_const && !info.is_static)
+        })
+    };
+    let (deref_method, increment_method, equal_method) =
+      match (find_operator(CppOperator::Indirection, true),
+             find_operator(CppOperator::PrefixIncrement, false),
+             find_operator(CppOperator::EqualTo, true)) {
+        (Some(a), Some(b), Some(c)) => (a, b, c),
+        _ => return Ok(None),
+      };
+    let item_type = match deref_method.arguments.return_type.rust_api_type {
+      RustType::Common { indirection: RustTypeIndirection::Ref { .. }, .. } => {
+        rust_type_to_value(&deref_method.arguments.return_type.rust_api_type)?
+      }
+      _ => return Err("operator* must return a reference".into()),
+    };
+    let iterator_type = match deref_method
+            .arguments
+            .arguments
+            .iter()
+            .find(|arg| &arg.name == "self") {
+      Some(arg) => rust_type_to_value(&arg.argument_type.rust_api_type)?,
+      None => return Err(unexpected("operator* must have self argument").into()),
+    };
+    let mut current_method = self.container_trait_method(deref_method, "current", false)?;
+    let mut increment_method =
+      self.container_trait_method(increment_method, "increment", false)?;
+    // the returned reference to the iterator is discarded
+    increment_method.arguments.return_type.rust_api_type = RustType::EmptyTuple;
+    increment_method.arguments.return_type.rust_api_to_c_conversion =
+      RustToCTypeConversion::None;
+    // using the iterator is unsafe regardless of the unsafe policy
+    let unsafe_reason = Some(RustMethodUnsafeReason::UncheckedIterator);
+    for method in &mut [&mut current_method, &mut increment_method] {
+      method.unsafe_reason = unsafe_reason.clone();
+      if let Some(ref mut doc) = method.doc {
+        doc.unsafe_reason = unsafe_reason.clone();
+      }
+    }
+    let mut is_equal_method = self.container_trait_method(equal_method, "is_equal", false)?;
+    let iterator_ref_type = rust_type_to_ref(&iterator_type, true)?.without_lifetime();
+    for arg in &mut is_equal_method.arguments.arguments {
+      if &arg.name != "self" {
+        if arg.argument_type.rust_api_type != iterator_ref_type {
+          return Err("operator== must compare with the same iterator type".into());
+        }
+        arg.name = "other".to_string();
+      }
+    }
+    Ok(Some(TraitImpl {
+              target_type: iterator_type,
+              associated_types: vec![TraitAssociatedType {
+                                       name: "Item".to_string(),
+                                       value: item_type,
+                                     }],
+              trait_type: trait_type(&["cpp_utils", "container", "CppIterator"], None)?,
+              extra: None,
+              methods: vec![current_method.to_rust_method(),
+                            increment_method.to_rust_method(),
+                            is_equal_method.to_rust_method()],
+            }))
+  }
+
+  /// Generates `cpp_utils::container::CppIterator` implementation for
+  /// the C++ class `class_type` in the same way it's generated when
+  /// the class's methods are processed. Returns `None` if the class
+  /// is not an iterator.
+  fn cpp_iterator_impl(&self, class_type: &CppTypeClassBase) -> Result<Option<TraitImpl>> {
+    let info = find_type_info(&self.processed_types,
+                              &self.input_data.dependency_types,
+                              |x| {
+                                x.cpp_name == class_type.name &&
+                                x.cpp_template_arguments == class_type.template_arguments
+                              })
+        .chain_err(|| format!("type info not found for {}", class_type.name))?;
+    let scope = RustMethodScope::Impl {
+      target_type: RustType::Common {
+        base: info.rust_name.clone(),
+        generic_arguments: None,
+        indirection: RustTypeIndirection::None,
+        is_const: false,
+        is_const2: false,
+      },
+    };
+    let mut methods = Vec::new();
+    for method in self
+          .input_data
+          .cpp_ffi_headers
+          .iter()
+          .flat_map(|header| header.methods.iter()) {
+      let is_class_method = method
+        .cpp_method
+        .class_membership
+        .as_ref()
+        .map_or(false, |info| &info.class_type == class_type);
+      if is_class_method && is_iterator_operator(&method.cpp_method) {
+        methods.push(self.generate_rust_single_method(method, &scope, false)?);
+      }
+    }
+    self.process_cpp_iterator(&methods)
+  }
+
+  /// Generates `cpp_utils::container::CppIterable` implementation and `IntoIterator`
+  /// implementation for a reference to a class that has `begin()` and `end()`
+  /// const methods returning a stack-allocated iterator class. Returns the type
+  /// of the elements, the type of the container and the implementations,
+  /// or `None` if the class doesn't have these methods.
+  fn process_iterable(&self,
+                      methods: &[RustSingleMethod])
+                      -> Result<Option<(RustType, RustType, Vec<TraitImpl>)>> {
+    let find_method = |name: &str| {
+      methods
+        .iter()
+        .find(|m| {
+          let cpp_method = &m.arguments.cpp_method.cpp_method;
+          &cpp_method.name == name && cpp_method.arguments.is_empty() &&
+          cpp_method
+            .class_membership
+            .as_ref()
+            .map_or(false, |info| info.is_const && !info.is_static)
+        })
+    };
+    let (begin_method, end_method) = match (find_method("begin"), find_method("end")) {
+      (Some(a), Some(b)) => (a, b),
+      _ => return Ok(None),
+    };
+    let return_type = &begin_method.arguments.return_type;
+    if return_type.rust_api_to_c_conversion != RustToCTypeConversion::ValueToPtr {
+      return Err("begin() must return a stack-allocated iterator".into());
+    }
+    if end_method.arguments.return_type != *return_type {
+      return Err("begin() and end() must return the same type".into());
+    }
+    let iterator_impl = match return_type.cpp_type.base {
+      CppTypeBase::Class(ref class_type) => {
+        self
+          .cpp_iterator_impl(class_type)?
+          .chain_err(|| "iterator must have operator*, prefix operator++ and operator==")?
+      }
+      _ => return Err("begin() must return a class".into()),
+    };
+    let item_type = iterator_impl
+      .associated_types
+      .get(0)
+      .chain_err(|| "CppIterator must have Item type")?
+      .value
+      .clone();
+    let container_type = match begin_method
+            .arguments
+            .arguments
+            .iter()
+            .find(|arg| &arg.name == "self") {
+      Some(arg) => rust_type_to_value(&arg.argument_type.rust_api_type)?,
+      None => return Err(unexpected("begin() must have self argument").into()),
+    };
+    let results =
+      vec![TraitImpl {
+             target_type: container_type.clone(),
+             associated_types: vec![TraitAssociatedType {
+                                      name: "Iterator".to_string(),
+                                      value: return_type.rust_api_type.clone(),
+                                    }],
+             trait_type: trait_type(&["cpp_utils", "container", "CppIterable"], None)?,
+             extra: None,
+             methods: vec![self
+                             .container_trait_method(begin_method, "begin", false)?
+                             .to_rust_method(),
+                           self
+                             .container_trait_method(end_method, "end", false)?
+                             .to_rust_method()],
+           },
+           TraitImpl {
+             target_type: rust_type_to_ref(&container_type, true)?,
+             associated_types: vec![TraitAssociatedType {
+                                      name: "Item".to_string(),
+                                      value: rust_type_to_ref(&item_type, true)?,
+                                    }],
+             trait_type: trait_type(&["std", "iter", "IntoIterator"], None)?,
+             extra: Some(TraitImplExtra::IterableIntoIterator),
+             methods: Vec::new(),
+           }];
+    Ok(Some((item_type, container_type, results)))
+  }
+
+  /// Generates `cpp_utils::container` trait implementations and `IntoIterator`
+  /// implementations for references to a class that has `size()` and `at(index)`
+  /// const methods (or `begin()` and `end()` const methods if it doesn't),
+  /// as well as `Extend` and `FromIterator` implementations if
+  /// the class has `append` or `push_back` method. `methods` must contain
+  /// the class's methods with these names (and `operator[]`), and `trait_impls` must
+  /// contain other trait implementations of the class. Returns an empty vector
+  /// if the class is not a container.
+  fn process_container(&self,
+                       methods: &[RustSingleMethod],
+                       trait_impls: &[TraitImpl])
+                       -> Result<Vec<TraitImpl>> {
+    let class_name = match methods
+            .get(0)
+            .and_then(|m| m.arguments.cpp_method.cpp_method.class_name()) {
+      Some(name) => name,
+      None => return Ok(Vec::new()),
+    };
+    let container_traits = self
+      .input_data
+      .container_traits
+      .get(class_name)
+      .cloned()
+      .unwrap_or_else(ContainerTraits::default);
+    if container_traits == ContainerTraits::Disabled {
+      return Ok(Vec::new());
+    }
+    // finds non-static methods with one argument
+    let find_methods = |names: &[&str], is_const: bool| {
+      methods
+        .iter()
+        .filter(|m| {
+          let cpp_method = &m.arguments.cpp_method.cpp_method;
+          names.contains(&cpp_method.name.as_str()) && cpp_method.arguments.len() == 1 &&
+          cpp_method
+            .class_membership
+            .as_ref()
+            .map_or(false, |info| info.is_const == is_const && !info.is_static)
+        })
+        .collect::<Vec<_>>()
+    };
+    let size_method = methods
+      .iter()
+      .find(|m| {
+              let cpp_method = &m.arguments.cpp_method.cpp_method;
+              &cpp_method.name == "size" && cpp_method.arguments.is_empty() &&
+              cpp_method
+                .class_membership
+                .as_ref()
+                .map_or(false, |info| info.is_const && !info.is_static)
+            });
+    let at_method = find_methods(&["at"], true).into_iter().next();
+    let mut results = Vec::new();
+    let (item_type, container_type) = match (size_method, at_method) {
+      (Some(size_method), Some(at_method)) => {
+        if !is_integer_type(&size_method.arguments.return_type.cpp_type) {
+          return Err("size() must return an integer".into());
+        }
+        let item_type = match at_method.arguments.return_type.rust_api_type {
+          RustType::Common { indirection: RustTypeIndirection::Ref { .. }, .. } => {
+            rust_type_to_value(&at_method.arguments.return_type.rust_api_type)?
+          }
+          _ => return Err("at() must return a reference".into()),
+        };
+        let container_type = match at_method
+                .arguments
+                .arguments
+                .iter()
+                .find(|arg| &arg.name == "self") {
+          Some(arg) => rust_type_to_value(&arg.argument_type.rust_api_type)?,
+          None => return Err(unexpected("at() must have self argument").into()),
+        };
+
+        let mut len_method = self.container_trait_method(size_method, "len", false)?;
+        len_method.arguments.return_type.rust_api_type = RustType::Common {
+          base: RustName::new(vec!["usize".to_string()])?,
+          indirection: RustTypeIndirection::None,
+          is_const: false,
+          is_const2: false,
+          generic_arguments: None,
+        };
+        len_method.arguments.return_type.rust_api_to_c_conversion =
+          RustToCTypeConversion::UsizeToInt;
+        let get_method = self.container_trait_method(at_method, "get", true)?;
+        results.push(TraitImpl {
+                       target_type: container_type.clone(),
+                       associated_types: vec![TraitAssociatedType {
+                                                name: "Item".to_string(),
+                                                value: item_type.clone(),
+                                              }],
+                       trait_type: trait_type(&["cpp_utils", "container", "CppContainer"],
+                                              None)?,
+                       extra: None,
+                       methods: vec![len_method.to_rust_method(), get_method.to_rust_method()],
+                     });
+        results.push(TraitImpl {
+                       target_type: rust_type_to_ref(&container_type, true)?,
+                       associated_types: vec![TraitAssociatedType {
+                                                name: "Item".to_string(),
+                                                value: rust_type_to_ref(&item_type, true)?,
+                                              }],
+                       trait_type: trait_type(&["std", "iter", "IntoIterator"], None)?,
+                       extra: Some(TraitImplExtra::ContainerIntoIterator),
+                       methods: Vec::new(),
+                     });
+
+        // non-const `operator[]` is preferred because it usually
+        // doesn't perform additional checks
+        let mut_ref_item_type = rust_type_to_ref(&item_type, false)?.without_lifetime();
+        let get_mut_method = find_methods(&["operator[]"], false)
+          .into_iter()
+          .chain(find_methods(&["at"], false).into_iter())
+          .find(|m| {
+                  m.arguments.return_type.rust_api_type.without_lifetime() == mut_ref_item_type
+                });
+        if let Some(get_mut_method) = get_mut_method {
+          let get_mut_method = self.container_trait_method(get_mut_method, "get_mut", true)?;
+          results.push(TraitImpl {
+                         target_type: container_type.clone(),
+                         associated_types: Vec::new(),
+                         trait_type: trait_type(&["cpp_utils", "container", "CppContainerMut"],
+                                                None)?,
+                         extra: None,
+                         methods: vec![get_mut_method.to_rust_method()],
+                       });
+          results.push(TraitImpl {
+                         target_type: rust_type_to_ref(&container_type, false)?,
+                         associated_types: vec![TraitAssociatedType {
+                                                  name: "Item".to_string(),
+                                                  value: rust_type_to_ref(&item_type, false)?,
+                                                }],
+                         trait_type: trait_type(&["std", "iter", "IntoIterator"], None)?,
+                         extra: Some(TraitImplExtra::ContainerIntoIterator),
+                         methods: Vec::new(),
+                       });
+        }
+        (item_type, container_type)
+      }
+      _ => {
+        match self.process_iterable(methods)? {
+          Some((item_type, container_type, mut r)) => {
+            results.append(&mut r);
+            (item_type, container_type)
+          }
+          None => return Ok(Vec::new()),
+        }
+      }
+    };
+
+    let const_ref_item_type = rust_type_to_ref(&item_type, true)?.without_lifetime();
+    let push_method = find_methods(&["append", "push_back"], false)
+      .into_iter()
+      .find(|m| {
+        m.arguments
+          .arguments
+          .iter()
+          .find(|arg| &arg.name != "self")
+          .map_or(false, |arg| {
+            let arg_type = &arg.argument_type;
+            match arg_type.rust_api_to_c_conversion {
+              RustToCTypeConversion::RefToPtr => {
+                arg_type.rust_api_type.without_lifetime() == const_ref_item_type
+              }
+              RustToCTypeConversion::ValueToPtr => arg_type.rust_api_type == item_type,
+              _ => false,
+            }
+          })
+      });
+    if let Some(push_method) = push_method {
+      let mut push_method = self.container_trait_method(push_method, "push", false)?;
+      for arg in &mut push_method.arguments.arguments {
+        if &arg.name != "self" {
+          // values passed by value are also passed by reference
+          // to the FFI function, so they can be borrowed
+          arg.name = "value".to_string();
+          arg.argument_type.rust_api_type = const_ref_item_type.clone();
+          arg.argument_type.rust_api_to_c_conversion = RustToCTypeConversion::RefToPtr;
+        }
+      }
+      results.push(TraitImpl {
+                     target_type: container_type.clone(),
+                     associated_types: vec![TraitAssociatedType {
+                                              name: "Item".to_string(),
+                                              value: item_type.clone(),
+                                            }],
+                     trait_type: trait_type(&["cpp_utils", "container", "CppContainerPush"], None)?,
+                     extra: None,
+                     methods: vec![push_method.to_rust_method()],
+                   });
+      if container_traits == ContainerTraits::All {
+        // `FromIterator` creates an empty container using `Default`
+        let default_trait = trait_type(&["std", "default", "Default"], None)?;
+        let has_default = trait_impls
+          .iter()
+          .any(|x| x.target_type == container_type && x.trait_type == default_trait);
+        // both values and references can be appended
+        for item in vec![item_type.clone(), rust_type_to_ref(&item_type, true)?] {
+          results.push(TraitImpl {
+                         target_type: container_type.clone(),
+                         associated_types: Vec::new(),
+                         trait_type: trait_type(&["std", "iter", "Extend"],
+                                                Some(vec![item.clone()]))?,
+                         extra: Some(TraitImplExtra::ContainerExtend),
+                         methods: Vec::new(),
+                       });
+          if has_default {
+            results.push(TraitImpl {
+                           target_type: container_type.clone(),
+                           associated_types: Vec::new(),
+                           trait_type: trait_type(&["std", "iter", "FromIterator"],
+                                                  Some(vec![item]))?,
+                           extra: Some(TraitImplExtra::ContainerFromIterator),
+                           methods: Vec::new(),
+                         });
+          }
+        }
+      }
+    }
+    Ok(results)
+  }
+
+  /// Generates a `std::ops` or `std::cmp` trait implementation from
+  /// a C++ operator wrapper. Returns an error if the operator's
+  /// signature is not compatible with the corresponding trait.
+  fn process_operator(&self, method: &RustSingleMethod) -> Result<TraitImpl> {
+    let operator = method
+      .arguments
+      .cpp_method
+      .cpp_method
+      .operator
+      .as_ref()
+      .chain_err(|| "not an operator")?;
+    let (trait_module, mut trait_name, mut method_name) =
+      operator_rust_trait(operator).chain_err(|| "operator has no matching trait")?;
+    if method.has_unsafe_arguments() {
+      return Err("operators taking raw pointers are not supported".into());
+    }
+    if method.arguments.exception_info_ffi_index.is_some() {
+      return Err("operators catching C++ exceptions are not supported".into());
+    }
+    let crate_name = &self.input_data.crate_name;
+    let is_local = |t: &RustType| if let RustType::Common { ref base, .. } = *t {
+      base.crate_name() == Some(crate_name)
+    } else {
+      false
+    };
+    if !method
+          .arguments
+          .arguments
+          .iter()
+          .any(|arg| is_local(&arg.argument_type.rust_api_type)) {
+      return Err("operator doesn't involve any type from the current crate".into());
+    }
+    let mut final_method = method.to_trait_method();
+    let expected_args_count = match trait_name {
+      "Neg" | "Not" => 1,
+      _ => 2,
+    };
+    if final_method.arguments.arguments.len() != expected_args_count {
+      return Err("unexpected number of operator arguments".into());
+    }
+    let self_type = final_method.arguments.arguments[0]
+      .argument_type
+      .rust_api_type
+      .clone();
+    if let RustType::Common { ref base, .. } = self_type {
+      let is_flaggable_enum = find_type_info(&self.processed_types,
+                                             &self.input_data.dependency_types,
+                                             |x| &x.rust_name == base)
+        .map_or(false, |info| match info.kind {
+          RustTypeWrapperKind::Enum { is_flaggable, .. } => is_flaggable,
+          _ => false,
+        });
+      if is_flaggable_enum {
+        // operators of flaggable enums are implemented generically through `EnumOrFlags`
+        // (e.g. `impl<T: EnumOrFlags<E>> BitOr<T> for E`), so they would conflict
+        return Err("operators of flaggable enums are not supported".into());
+      }
+    }
+    let is_bool_return = if let RustType::Common {
+             ref base,
+             ref indirection,
+             ..
+           } = final_method.arguments.return_type.rust_api_type {
+      base.parts == vec!["bool".to_string()] && *indirection == RustTypeIndirection::None
+    } else {
+      false
     };
-    results.push(TraitImpl {
-                   target_type: from_type.ptr_to_value()?.rust_api_type,
-                   associated_types: Vec::new(),
-                   trait_type: trait_type,
-                   extra: None,
-                   methods: final_methods
-                     .into_iter()
-                     .map(|x| x.0.to_rust_method())
-                     .collect(),
-                 });
-    Ok(results)
+    let mut associated_types = Vec::new();
+    let mut extra = None;
+    let target_type;
+    let mut trait_generic_arguments = None;
+    match trait_name {
+      "Index" => {
+        if !self_type.is_ref() || !final_method.arguments.return_type.rust_api_type.is_ref() {
+          return Err("Index trait requires self and return value to be references".into());
+        }
+        if !self_type.is_const()? {
+          if final_method
+               .arguments
+               .return_type
+               .rust_api_type
+               .is_const()? {
+            return Err("IndexMut trait requires a mutable return value".into());
+          }
+          trait_name = "IndexMut";
+          method_name = "index_mut";
+        } else {
+          final_method
+            .arguments
+            .return_type
+            .rust_api_type
+            .set_const(true)?;
+        }
+        // lifetimes are elided in Index and IndexMut methods
+        final_method.arguments.arguments[0].argument_type.rust_api_type = self_type
+          .without_lifetime();
+        final_method.arguments.return_type.rust_api_type = final_method
+          .arguments
+          .return_type
+          .rust_api_type
+          .without_lifetime();
+        target_type = rust_type_to_value(&self_type)?;
+        if trait_name == "Index" {
+          associated_types.push(TraitAssociatedType {
+                                  name: "Output".to_string(),
+                                  value: rust_type_to_value(&final_method
+                                                               .arguments
+                                                               .return_type
+                                                               .rust_api_type)?,
+                                });
+        }
+      }
+      "PartialEq" | "PartialOrd" => {
+        if !is_bool_return {
+          return Err("comparison operator must return bool".into());
+        }
+        if !self_type.is_ref() || !self_type.is_const()? {
+          return Err("comparison operator must take self by const reference".into());
+        }
+        target_type = rust_type_to_value(&self_type)?;
+        final_method.arguments.arguments[0].argument_type.rust_api_type = self_type
+          .without_lifetime();
+        let other_arg = &mut final_method.arguments.arguments[1];
+        let other_type = other_arg.argument_type.rust_api_type.clone();
+        match other_arg.argument_type.rust_api_to_c_conversion {
+          RustToCTypeConversion::RefToPtr => {
+            if !other_type.is_const()? {
+              return Err("comparison operator must take other value by const reference"
+                           .into());
+            }
+          }
+          RustToCTypeConversion::ValueToPtr => {
+            other_arg.argument_type.rust_api_to_c_conversion = RustToCTypeConversion::RefToPtr;
+          }
+          _ => return Err("unsupported type of the compared value".into()),
+        }
+        let other_value_type = rust_type_to_value(&other_type)?;
+        if let RustType::Common {
+                 ref mut indirection,
+                 ref mut is_const,
+                 ..
+               } = other_arg.argument_type.rust_api_type {
+          *indirection = RustTypeIndirection::Ref { lifetime: None };
+          *is_const = true;
+        }
+        other_arg.name = "other".to_string();
+        if trait_name == "PartialOrd" {
+          if other_value_type != target_type {
+            return Err("PartialOrd is only supported for comparison with the same type".into());
+          }
+          extra = Some(TraitImplExtra::PartialOrdFromLessThan);
+        }
+        trait_generic_arguments = Some(vec![other_value_type]);
+      }
+      _ => {
+        if trait_name.ends_with("Assign") {
+          if !self_type.is_ref() || self_type.is_const()? {
+            return Err("assignment operator must take self by mutable reference".into());
+          }
+          if final_method.arguments.return_type_ffi_index.is_some() {
+            return Err("assignment operator must not return a value".into());
+          }
+          // the return value (usually a reference to self) is discarded
+          final_method.arguments.return_type.rust_api_type = RustType::EmptyTuple;
+          final_method.arguments.return_type.rust_api_to_c_conversion =
+            RustToCTypeConversion::None;
+          target_type = rust_type_to_value(&self_type)?;
+          final_method.arguments.arguments[0].argument_type.rust_api_type = self_type
+            .without_lifetime();
+        } else {
+          let return_type = final_method.arguments.return_type.rust_api_type.clone();
+          if return_type.is_ref() || return_type == RustType::EmptyTuple {
+            return Err("operator must return a value".into());
+          }
+          if self_type.is_ref() {
+            // the trait is implemented for a reference, so
+            // `self` is passed by value, and it's already a reference
+            target_type = self_type.with_lifetime("a".to_string());
+            final_method.arguments.arguments[0].argument_type.rust_api_type =
+              rust_type_to_value(&self_type)?;
+            final_method.arguments.arguments[0]
+              .argument_type
+              .rust_api_type
+              .set_const(self_type.is_const()?)?;
+          } else {
+            target_type = self_type.clone();
+          }
+          associated_types.push(TraitAssociatedType {
+                                  name: "Output".to_string(),
+                                  value: return_type,
+                                });
+        }
+        if expected_args_count == 2 {
+          let other_arg = &mut final_method.arguments.arguments[1];
+          if other_arg.argument_type.rust_api_type.is_ref() {
+            other_arg.argument_type.rust_api_type = other_arg
+              .argument_type
+              .rust_api_type
+              .with_lifetime("a".to_string());
+          }
+          other_arg.name = "other".to_string();
+          trait_generic_arguments = Some(vec![other_arg.argument_type.rust_api_type.clone()]);
+        }
+      }
+    }
+    final_method.arguments.arguments[0].name = "self".to_string();
+    final_method.name = RustName::new(vec![method_name.to_string()])?;
+    Ok(TraitImpl {
+         target_type: target_type,
+         associated_types: associated_types,
+         trait_type: RustType::Common {
+           base: RustName::new(vec!["std".to_string(),
+                                    trait_module.to_string(),
+                                    trait_name.to_string()])?,
+           indirection: RustTypeIndirection::None,
+           is_const: false,
+           is_const2: false,
+           generic_arguments: trait_generic_arguments,
+         },
+         extra: extra,
+         methods: vec![final_method.to_rust_method()],
+       })
   }
 
   /// Generates a single overloaded method from all specified methods or
@@ -1212,6 +3039,7 @@ impl<'aa> RustGenerator<'aa> {
         trait_name = format!("{}{}", target_type_name, trait_name);
       }
       let mut grouped_by_cpp_method: HashMap<_, Vec<_>> = HashMap::new();
+      let mut unsafe_reasons = HashMap::new();
       for mut method in filtered_methods {
         assert!(method.name == first_method.name);
         assert!(method.scope == first_method.scope);
@@ -1226,6 +3054,7 @@ impl<'aa> RustGenerator<'aa> {
         //          cpp_method_key.arguments = v;
         //          cpp_method_key.arguments_before_omitting = None;
         //        }
+        unsafe_reasons.insert(cpp_method_key.clone(), method.unsafe_reason.clone());
         add_to_multihash(&mut grouped_by_cpp_method,
                          cpp_method_key,
                          method.arguments.clone());
@@ -1237,9 +3066,31 @@ impl<'aa> RustGenerator<'aa> {
       grouped_by_cpp_method_vec.sort_by(|&(ref a, _), &(ref b, _)| {
                                           a.short_text().cmp(&b.short_text())
                                         });
+      let deprecation = if grouped_by_cpp_method_vec
+           .iter()
+           .all(|&(ref m, _)| m.deprecation.is_some()) {
+        let mut messages = Vec::new();
+        for &(ref cpp_method, _) in &grouped_by_cpp_method_vec {
+          if let Some(CppDeprecation { message: Some(ref message) }) = cpp_method.deprecation {
+            if !messages.contains(message) {
+              messages.push(message.clone());
+            }
+          }
+        }
+        Some(CppDeprecation {
+               message: if messages.is_empty() {
+                 None
+               } else {
+                 Some(messages.join("; "))
+               },
+             })
+      } else {
+        None
+      };
       for (cpp_method, variants) in grouped_by_cpp_method_vec {
         doc_items.push(RustMethodDocItem {
                          doc: cpp_method.doc.clone(),
+                         unsafe_reason: unsafe_reasons[&cpp_method].clone(),
                          cpp_fn: cpp_method.short_text(),
                          rust_fns: variants
                            .iter()
@@ -1273,7 +3124,7 @@ impl<'aa> RustGenerator<'aa> {
       let common_return_type = if args_variants
            .iter()
            .all(|x| &x.return_type.rust_api_type == &first_return_type) {
-        if first_return_type.is_ref() {
+        if first_return_type.is_ref() || first_return_type.lifetime().is_some() {
           has_trait_lifetime = true;
           Some(first_return_type.with_lifetime(trait_lifetime_name.to_string()))
         } else {
@@ -1312,10 +3163,11 @@ impl<'aa> RustGenerator<'aa> {
                                   common_return_type: common_return_type.clone(),
                                   method_name: method_name.clone(),
                                   method_scope: first_method.scope.clone(),
-                                  is_unsafe: first_method.is_unsafe,
+                                  is_unsafe: first_method.is_unsafe(),
                                 },
                                 is_public: true,
                                 rust_doc: None,
+                                deprecation: None,
                               });
 
       RustMethod {
@@ -1331,7 +3183,8 @@ impl<'aa> RustGenerator<'aa> {
         },
         variant_docs: doc_items,
         common_doc: None,
-        is_unsafe: first_method.is_unsafe,
+        is_unsafe: first_method.is_unsafe(),
+        deprecation: deprecation,
       }
     } else {
       let mut method = filtered_methods
@@ -1354,6 +3207,7 @@ impl<'aa> RustGenerator<'aa> {
                           cpp_fn: method.arguments.cpp_method.cpp_method.short_text(),
                           rust_fns: Vec::new(),
                           doc: method.arguments.cpp_method.cpp_method.doc.clone(),
+                          unsafe_reason: method.unsafe_reason.clone(),
                         });
       method.to_rust_method()
     };
@@ -1439,6 +3293,10 @@ impl<'aa> RustGenerator<'aa> {
     // Step 1: convert all methods to SingleVariant Rust methods and
     // split them by last name.
     let mut single_rust_methods: HashMap<String, Vec<RustSingleMethod>> = HashMap::new();
+    let mut operator_trait_impls: Vec<(TraitImpl, RustSingleMethod)> = Vec::new();
+    let mut container_methods: Vec<RustSingleMethod> = Vec::new();
+    let mut iterator_methods: Vec<RustSingleMethod> = Vec::new();
+    let mut stream_output_trait_impls: Vec<TraitImpl> = Vec::new();
     let mut result = ProcessFunctionsResult::default();
     for method in methods {
       if method.cpp_method.is_destructor() {
@@ -1453,6 +3311,24 @@ impl<'aa> RustGenerator<'aa> {
       }
       match self.generate_rust_single_method(method, scope, false) {
         Ok(rust_method) => {
+          if method.kind == CppFfiMethodKind::StreamOutput {
+            match self.process_stream_output_method(&rust_method) {
+              Ok(r) => stream_output_trait_impls.push(r),
+              Err(msg) => {
+                log::llog(log::DebugRustSkips, || {
+                  format!("Failed to generate Debug from operator<<: {}\n{:?}\n", msg, method)
+                })
+              }
+            }
+            continue;
+          }
+          if ["size", "at", "operator[]", "append", "push_back", "begin", "end"]
+               .contains(&method.cpp_method.name.as_str()) {
+            container_methods.push(rust_method.clone());
+          }
+          if is_iterator_operator(&method.cpp_method) {
+            iterator_methods.push(rust_method.clone());
+          }
           if (&method.cpp_method.name == "static_cast" ||
               &method.cpp_method.name == "dynamic_cast" ||
               &method.cpp_method.name == "qobject_cast") &&
@@ -1465,6 +3341,77 @@ impl<'aa> RustGenerator<'aa> {
               }
             }
           } else {
+            if method.cpp_method.is_copy_constructor() ||
+               method.cpp_method.is_default_constructor() {
+              match self.process_special_constructor(&rust_method) {
+                Ok(r) => {
+                  if !result
+                        .trait_impls
+                        .iter()
+                        .any(|x| x.trait_type == r.trait_type && x.target_type == r.target_type) {
+                    result.trait_impls.push(r);
+                  }
+                }
+                Err(msg) => {
+                  log::llog(log::DebugRustSkips, || {
+                    format!("Failed to generate Clone or Default: {}\n{:?}\n", msg, method)
+                  })
+                }
+              }
+            }
+            if self
+                 .input_data
+                 .debug_methods
+                 .contains(&method.cpp_method.signature()) {
+              match self.process_debug_method(&rust_method) {
+                Ok(r) => result.trait_impls.push(r),
+                Err(msg) => {
+                  log::llog(log::DebugRustSkips, || {
+                    format!("Failed to generate Debug: {}\n{:?}\n", msg, method)
+                  })
+                }
+              }
+            }
+            if let Some(CppOperator::Conversion(..)) = method.cpp_method.operator {
+              match self.process_conversion_operator(&rust_method) {
+                Ok(r) => {
+                  // const and non-const overloads of the same operator convert
+                  // to the same type, and only the conversion from a const reference is kept
+                  let existing = result
+                    .trait_impls
+                    .iter()
+                    .position(|x| {
+                                x.target_type == r.target_type &&
+                                conversion_source_type(x).is_some()
+                              });
+                  if let Some(index) = existing {
+                    if conversion_source_type(&r).map_or(false, |t| t.is_const().unwrap_or(false)) {
+                      result.trait_impls[index] = r;
+                    }
+                  } else {
+                    result.trait_impls.push(r);
+                  }
+                }
+                Err(msg) => {
+                  log::llog(log::DebugRustSkips, || {
+                    format!("Failed to generate conversion trait: {}\n{:?}\n", msg, method)
+                  })
+                }
+              }
+            }
+            if method.cpp_method.operator.is_some() {
+              match self.process_operator(&rust_method) {
+                Ok(r) => {
+                  operator_trait_impls.push((r, rust_method));
+                  continue;
+                }
+                Err(msg) => {
+                  log::llog(log::DebugRustSkips, || {
+                    format!("Failed to generate operator trait: {}\n{:?}\n", msg, method)
+                  })
+                }
+              }
+            }
             let name = rust_method.name.last_name()?.clone();
             add_to_multihash(&mut single_rust_methods, name, rust_method);
           }
@@ -1475,6 +3422,81 @@ impl<'aa> RustGenerator<'aa> {
         }
       }
     }
+    // `Debug` generated from `operator<<` is only used if
+    // there is no method configured with `Config::add_debug_method`.
+    for trait_impl in stream_output_trait_impls {
+      if result
+           .trait_impls
+           .iter()
+           .any(|x| x.trait_type == trait_impl.trait_type && x.target_type == trait_impl.target_type) {
+        log::llog(log::DebugRustSkips, || {
+          format!("Debug from operator<< is replaced by a configured method: {:?}",
+                  trait_impl.target_type)
+        });
+      } else {
+        result.trait_impls.push(trait_impl);
+      }
+    }
+    // Step 2: accept operator trait implementations that don't conflict
+    // with each other and have their supertraits implemented.
+    // Other operators are added as regular methods.
+    let mut accepted_trait_impls: Vec<TraitImpl> = Vec::new();
+    for &(ref trait_impl, _) in &operator_trait_impls {
+      if !accepted_trait_impls
+            .iter()
+            .any(|x| x.trait_type == trait_impl.trait_type && x.target_type == trait_impl.target_type) {
+        accepted_trait_impls.push(trait_impl.clone());
+      }
+    }
+    for (trait_impl, rust_method) in operator_trait_impls {
+      let is_accepted = accepted_trait_impls.contains(&trait_impl) &&
+                        has_supertrait_impl(&trait_impl, &accepted_trait_impls)?;
+      if is_accepted {
+        result.trait_impls.push(trait_impl);
+      } else {
+        log::llog(log::DebugRustSkips, || {
+          format!("Operator trait implementation is conflicting or incomplete: {:?}",
+                  trait_impl.trait_type)
+        });
+        let name = rust_method.name.last_name()?.clone();
+        add_to_multihash(&mut single_rust_methods, name, rust_method);
+      }
+    }
+    // `Eq` is added to `PartialEq` implementations of the classes
+    // enabled in the config if the compared type doesn't contain
+    // floating point values.
+    let mut eq_trait_impls = Vec::new();
+    for trait_impl in &result.trait_impls {
+      if let RustType::Common { ref base, .. } = trait_impl.trait_type {
+        if base.last_name()? == "PartialEq" {
+          if let Some(r) = self.process_eq(trait_impl)? {
+            eq_trait_impls.push(r);
+          }
+        }
+      }
+    }
+    result.trait_impls.append(&mut eq_trait_impls);
+    if !container_methods.is_empty() {
+      match self.process_container(&container_methods, &result.trait_impls) {
+        Ok(mut r) => result.trait_impls.append(&mut r),
+        Err(msg) => {
+          log::llog(log::DebugRustSkips,
+                    || format!("Failed to generate container traits: {}", msg))
+        }
+      }
+    }
+    if !iterator_methods.is_empty() {
+      match self.process_cpp_iterator(&iterator_methods) {
+        Ok(Some(r)) => result.trait_impls.push(r),
+        Ok(None) => {}
+        Err(msg) => {
+          log::llog(log::DebugRustSkips,
+                    || format!("Failed to generate iterator trait: {}", msg))
+        }
+      }
+    }
+
+    // Step 3: generate final methods.
     for (_, current_methods) in single_rust_methods {
       assert!(!current_methods.is_empty());
 
@@ -1527,6 +3549,8 @@ impl<'aa> RustGenerator<'aa> {
       name: module_name.last_name()?.clone(),
       types: Vec::new(),
       functions: Vec::new(),
+      constants: Vec::new(),
+      type_aliases: Vec::new(),
       submodules: Vec::new(),
       trait_impls: Vec::new(),
       doc: if module_name.parts.len() >= 2 && module_name.parts[1] == "slots" {
@@ -1573,7 +3597,7 @@ impl<'aa> RustGenerator<'aa> {
               if let RustTypeDeclarationKind::CppTypeWrapper { ref cpp_doc, .. } =
                 result.main_type.kind {
                 if let Some(ref cpp_doc) = *cpp_doc {
-                  let mut doc = cpp_doc.html.as_str();
+                  let mut doc = cpp_doc.content.text();
                   if let Some(index) = doc.find("\n") {
                     doc = &doc[0..index];
                   }
@@ -1601,6 +3625,18 @@ impl<'aa> RustGenerator<'aa> {
         tmp_cpp_methods.push(method);
       }
       cpp_methods = tmp_cpp_methods;
+
+      for constant in &self.constants {
+        if check_name(&constant.name) {
+          module.constants.push(constant.clone());
+        }
+      }
+
+      for alias in &self.type_aliases {
+        if check_name(&alias.name) {
+          module.type_aliases.push(alias.clone());
+        }
+      }
     }
     for name in direct_submodules {
       let mut new_name = module_name.clone();
@@ -1625,6 +3661,8 @@ impl<'aa> RustGenerator<'aa> {
                 name: "overloading".to_string(),
                 types: rust_overloading_types,
                 functions: Vec::new(),
+                constants: Vec::new(),
+                type_aliases: Vec::new(),
                 submodules: Vec::new(),
                 trait_impls: Vec::new(),
                 doc: Some(doc_formatter::overloading_module_doc()),
@@ -1632,7 +3670,10 @@ impl<'aa> RustGenerator<'aa> {
     }
     module.types.sort_by(|a, b| a.name.cmp(&b.name));
     module.submodules.sort_by(|a, b| a.name.cmp(&b.name));
-    if module.types.is_empty() && module.functions.is_empty() && module.submodules.is_empty() {
+    module.constants.sort_by(|a, b| a.name.cmp(&b.name));
+    module.type_aliases.sort_by(|a, b| a.name.cmp(&b.name));
+    if module.types.is_empty() && module.functions.is_empty() && module.constants.is_empty() &&
+       module.type_aliases.is_empty() && module.submodules.is_empty() {
       log::llog(log::DebugRustSkips,
                 || format!("Skipping empty module: {}", module.name));
       return Ok((None, cpp_methods));
@@ -1645,9 +3686,19 @@ impl<'aa> RustGenerator<'aa> {
   fn generate_ffi_function(&self, data: &CppAndFfiMethod) -> Result<RustFFIFunction> {
     let mut args = Vec::new();
     for arg in &data.c_signature.arguments {
-      let rust_type = ffi_type(&self.processed_types,
-                               &self.input_data.dependency_types,
-                               &arg.argument_type.ffi_type)?;
+      let rust_type = if arg.meaning == CppFfiArgumentMeaning::ExceptionInfo {
+        RustType::Common {
+          base: RustName::new(vec!["cpp_utils".to_string(), "CppExceptionInfo".to_string()])?,
+          generic_arguments: None,
+          is_const: false,
+          is_const2: false,
+          indirection: RustTypeIndirection::Ptr,
+        }
+      } else {
+        ffi_type(&self.processed_types,
+                 &self.input_data.dependency_types,
+                 &arg.argument_type.ffi_type)?
+      };
       args.push(RustFFIArgument {
                   name: sanitize_rust_identifier(&arg.name),
                   argument_type: rust_type,
@@ -1731,13 +3782,30 @@ impl<'aa> RustGenerator<'aa> {
                                                                  template_arguments: None,
                                                                }),
                   slot_wrapper: None,
+                  subclass_wrapper: None,
                 }
               }
             }
           }
-          CppTypeKind::Enum { ref values } => {
+          CppTypeKind::Enum {
+            ref values,
+            ref underlying_type,
+            ..
+          } => {
+            let int_type = match enum_int_type(underlying_type) {
+              Ok(r) => r,
+              Err(err) => {
+                log::llog(log::DebugRustSkips,
+                          || format!("Can't process type: {}: {}", type_info.name, err));
+                continue;
+              }
+            };
 
-            let mut is_flaggable = false;
+            let mut is_flaggable = self
+              .input_data
+              .flags_typedefs
+              .values()
+              .any(|x| x == &type_info.name);
             let template_arg_sample = CppType {
               is_const: false,
               is_const2: false,
@@ -1769,9 +3837,19 @@ impl<'aa> RustGenerator<'aa> {
                 }
               }
             }
+            if is_flaggable && (int_type.ends_with("64") || int_type.ends_with("size")) {
+              // `Flags` stores values as `c_int`
+              log::llog(log::DebugRustSkips, || {
+                format!("Enum can't be used as flags because its underlying type is too wide: {}",
+                        type_info.name)
+              });
+              is_flaggable = false;
+            }
             RustTypeWrapperKind::Enum {
-              values: prepare_enum_values(values),
+              values: prepare_enum_values(values, self.input_data.enums_as_newtypes),
               is_flaggable: is_flaggable,
+              is_newtype: self.input_data.enums_as_newtypes,
+              int_type: int_type,
             }
           }
         },
@@ -1790,6 +3868,10 @@ impl<'aa> RustGenerator<'aa> {
         let mut arg_captions = Vec::new();
         if let Some(ref args) = item.cpp_template_arguments {
           for x in args {
+            if let CppTypeBase::TemplateArgumentValue(ref value) = x.base {
+              arg_captions.push(value.caption().to_class_case());
+              continue;
+            }
             let rust_type = complete_type(result,
                                           &self.input_data.dependency_types,
                                           &x.to_cpp_ffi_type(CppTypeRole::NotReturnType)?,
@@ -1852,6 +3934,7 @@ impl<'aa> RustGenerator<'aa> {
                                                                            .clone()),
                                                                 }),
                                slot_wrapper: None,
+                               subclass_wrapper: None,
                              },
                              rust_name: rust_name,
                              is_public: true,
@@ -1965,14 +4048,165 @@ impl<'aa> RustGenerator<'aa> {
                                  callback_name: format!("slot_{}_callback", args_text)
                                    .to_snake_case(),
                                }),
+            subclass_wrapper: None,
           },
         };
         result.push(rust_type_info);
       }
     }
+    for header in &self.input_data.cpp_ffi_headers {
+      for wrapper in &header.subclass_wrappers {
+        // methods of a skipped wrapper are skipped later
+        // because its type is not available in Rust
+        match self.subclass_wrapper_type_info(header, wrapper, &result) {
+          Ok(r) => result.push(r),
+          Err(err) => {
+            log::error(format!("Failed to process subclass wrapper for {}: {}",
+                               wrapper.base_class.name,
+                               err));
+            err.discard_expected();
+          }
+        }
+      }
+    }
     Ok(result)
   }
 
+  /// Generates Rust type information for a C++ subclass wrapper,
+  /// including Rust signatures of its overridable virtual methods.
+  fn subclass_wrapper_type_info(&self,
+                                header: &CppFfiHeaderData,
+                                wrapper: &CppSubclassWrapper,
+                                processed_types: &[RustProcessedTypeInfo])
+                                -> Result<RustProcessedTypeInfo> {
+    let find_ffi_method = |name: &str| {
+      header
+        .methods
+        .iter()
+        .find(|m| {
+                m.cpp_method.class_name() == Some(&wrapper.class_name) &&
+                &m.cpp_method.name == name
+              })
+    };
+    let set_data_ffi_name = find_ffi_method("set_data")
+      .chain_err(|| "set_data FFI function is missing")?
+      .c_name
+      .clone();
+    let mut virtual_methods = Vec::new();
+    for virtual_method in &wrapper.virtual_methods {
+      let method = &virtual_method.method;
+      let rust_method = (|| -> Result<_> {
+        let argument_types = virtual_method
+          .arguments
+          .iter()
+          .enumerate()
+          .map_if_ok(|(index, t)| {
+                       complete_type(processed_types,
+                                     &self.input_data.dependency_types,
+                                     t,
+                                     &CppFfiArgumentMeaning::Argument(index as i8),
+                                     false,
+                                     &ReturnValueAllocationPlace::NotApplicable)
+                     })?;
+        let return_type = complete_type(processed_types,
+                                        &self.input_data.dependency_types,
+                                        &virtual_method.return_type,
+                                        &CppFfiArgumentMeaning::ReturnValue,
+                                        false,
+                                        &virtual_method.return_allocation_place)?;
+        match return_type.rust_api_to_c_conversion {
+          RustToCTypeConversion::None |
+          RustToCTypeConversion::ValueToPtr |
+          RustToCTypeConversion::CppBoxToPtr |
+          RustToCTypeConversion::EnumToInt => {}
+          _ => return Err("return type requires an unsupported conversion".into()),
+        }
+        let set_callback_ffi_name = find_ffi_method(&format!("set_{}_callback",
+                                                             virtual_method.key))
+          .chain_err(|| "callback setter FFI function is missing")?
+          .c_name
+          .clone();
+        let base_method_name = match find_ffi_method(&format!("{}_base", virtual_method.key)) {
+          Some(ffi_method) => Some(self.method_rust_name(ffi_method)?.last_name()?.clone()),
+          None => None,
+        };
+        Ok(RustSubclassVirtualMethod {
+             name: sanitize_rust_identifier(&virtual_method.key.to_snake_case()),
+             cpp_method: method.short_text(),
+             is_const: method
+               .class_membership
+               .as_ref()
+               .map_or(false, |info| info.is_const),
+             argument_names: method
+               .arguments
+               .iter()
+               .enumerate()
+               .map(|(index, arg)| if arg.name.is_empty() {
+                      format!("arg{}", index)
+                    } else {
+                      sanitize_rust_identifier(&arg.name.to_snake_case())
+                    })
+               .collect(),
+             argument_types: argument_types,
+             return_type: return_type,
+             set_callback_ffi_name: set_callback_ffi_name,
+             base_method_name: base_method_name,
+           })
+      })();
+      match rust_method {
+        Ok(rust_method) => virtual_methods.push(rust_method),
+        Err(msg) => {
+          let is_pure_virtual = method
+            .class_membership
+            .as_ref()
+            .map_or(false, |info| info.is_pure_virtual);
+          if is_pure_virtual {
+            return Err(msg).chain_err(|| {
+                                        format!("pure virtual method can't be overridden: {}",
+                                                method.short_text())
+                                      });
+          }
+          // the callback is not set, so the C++ subclass
+          // will use the base class implementation
+          log::llog(log::DebugRustSkips, || {
+            format!("Virtual method can't be overridden in Rust:\n{}\nError: {}\n",
+                    method.short_text(),
+                    msg)
+          });
+        }
+      }
+    }
+    Ok(RustProcessedTypeInfo {
+         cpp_name: wrapper.class_name.clone(),
+         cpp_template_arguments: None,
+         cpp_doc: None,
+         rust_name: self
+           .calculate_rust_name(&format!("{}Subclass", wrapper.base_class.name),
+                                &header.include_file_base_name,
+                                false,
+                                None)?,
+         is_public: true,
+         kind: RustTypeWrapperKind::Struct {
+           size_const_name: None,
+           is_deletable: true,
+           slot_wrapper: None,
+           subclass_wrapper: Some(RustSubclassWrapper {
+                                    trait_name: self
+                                      .calculate_rust_name(&format!("{}Overrides",
+                                                                    wrapper.base_class.name),
+                                                           &header.include_file_base_name,
+                                                           false,
+                                                           None)?
+                                      .last_name()?
+                                      .clone(),
+                                    cpp_base_name: wrapper.base_class.name.clone(),
+                                    set_data_ffi_name: set_data_ffi_name,
+                                    virtual_methods: virtual_methods,
+                                  }),
+         },
+       })
+  }
+
   /// Generates `RustName` for specified function or type name,
   /// including crate name and modules list.
   fn calculate_rust_name(&self,
@@ -2068,10 +4302,13 @@ fn calculate_rust_name_test_part(name: &'static str,
     include_file_base_name: include_file.to_string(),
     methods: Vec::new(),
     qt_slot_wrappers: Vec::new(),
+    subclass_wrappers: Vec::new(),
   };
   let mut generator = RustGenerator {
     top_module_names: HashMap::new(),
     processed_types: Vec::new(),
+    constants: Vec::new(),
+    type_aliases: Vec::new(),
     input_data: RustGeneratorInputData {
       cpp_ffi_headers: vec![header],
       cpp_data: &Default::default(),
@@ -2079,6 +4316,17 @@ fn calculate_rust_name_test_part(name: &'static str,
       crate_name: "qt_core".to_string(),
       remove_qt_prefix: true,
       filtered_namespaces: Vec::new(),
+      enums_as_newtypes: false,
+      constant_filters: Vec::new(),
+      flags_typedefs: &Default::default(),
+      preserved_type_aliases: &[],
+      pointer_annotations: &Default::default(),
+      return_value_lifetimes: &Default::default(),
+      unsafe_policy: UnsafePolicy::Heuristic,
+      safe_methods: &[],
+      debug_methods: &[],
+      eq_classes: &[],
+      container_traits: &Default::default(),
     },
   };
   generator.top_module_names = generator.calc_top_module_names().unwrap();
@@ -2122,12 +4370,15 @@ fn prepare_enum_values_test_simple() {
                                   name: "var1".to_string(),
                                   value: 1,
                                   doc: None,
+                                  deprecation: None,
                                 },
                                 CppEnumValue {
                                   name: "other_var2".to_string(),
                                   value: 2,
                                   doc: None,
-                                }]);
+                                  deprecation: None,
+                                }],
+                              false);
   assert_eq!(r.len(), 2);
   assert_eq!(r[0].name, "Var1");
   assert_eq!(r[0].value, 1);
@@ -2141,17 +4392,21 @@ fn prepare_enum_values_test_duplicates() {
                                   name: "var1".to_string(),
                                   value: 1,
                                   doc: None,
+                                  deprecation: None,
                                 },
                                 CppEnumValue {
                                   name: "other_var2".to_string(),
                                   value: 2,
                                   doc: None,
+                                  deprecation: None,
                                 },
                                 CppEnumValue {
                                   name: "other_var_dup".to_string(),
                                   value: 2,
                                   doc: None,
-                                }]);
+                                  deprecation: None,
+                                }],
+                              false);
   assert_eq!(r.len(), 2);
   assert_eq!(r[0].name, "Var1");
   assert_eq!(r[0].value, 1);
@@ -2165,17 +4420,21 @@ fn prepare_enum_values_test_prefix() {
                                   name: "OptionGood".to_string(),
                                   value: 1,
                                   doc: None,
+                                  deprecation: None,
                                 },
                                 CppEnumValue {
                                   name: "OptionBad".to_string(),
                                   value: 2,
                                   doc: None,
+                                  deprecation: None,
                                 },
                                 CppEnumValue {
                                   name: "OptionNecessaryEvil".to_string(),
                                   value: 3,
                                   doc: None,
-                                }]);
+                                  deprecation: None,
+                                }],
+                              false);
   assert_eq!(r.len(), 3);
   assert_eq!(r[0].name, "Good");
   assert_eq!(r[1].name, "Bad");
@@ -2188,17 +4447,21 @@ fn prepare_enum_values_test_suffix() {
                                   name: "BestFriend".to_string(),
                                   value: 1,
                                   doc: None,
+                                  deprecation: None,
                                 },
                                 CppEnumValue {
                                   name: "GoodFriend".to_string(),
                                   value: 2,
                                   doc: None,
+                                  deprecation: None,
                                 },
                                 CppEnumValue {
                                   name: "NoFriend".to_string(),
                                   value: 3,
                                   doc: None,
-                                }]);
+                                  deprecation: None,
+                                }],
+                              false);
   assert_eq!(r.len(), 3);
   assert_eq!(r[0].name, "Best");
   assert_eq!(r[1].name, "Good");
@@ -2211,12 +4474,15 @@ fn prepare_enum_values_test_prefix_digits() {
                                   name: "Base32".to_string(),
                                   value: 1,
                                   doc: None,
+                                  deprecation: None,
                                 },
                                 CppEnumValue {
                                   name: "Base64".to_string(),
                                   value: 2,
                                   doc: None,
-                                }]);
+                                  deprecation: None,
+                                }],
+                              false);
   assert_eq!(r.len(), 2);
   assert_eq!(r[0].name, "Base32");
   assert_eq!(r[1].name, "Base64");
@@ -2228,12 +4494,15 @@ fn prepare_enum_values_test_suffix_empty() {
                                   name: "NonRecursive".to_string(),
                                   value: 1,
                                   doc: None,
+                                  deprecation: None,
                                 },
                                 CppEnumValue {
                                   name: "Recursive".to_string(),
                                   value: 2,
                                   doc: None,
-                                }]);
+                                  deprecation: None,
+                                }],
+                              false);
   assert_eq!(r.len(), 2);
   assert_eq!(r[0].name, "NonRecursive");
   assert_eq!(r[1].name, "Recursive");
@@ -2245,19 +4514,234 @@ fn prepare_enum_values_test_suffix_partial() {
                                   name: "PreciseTimer".to_string(),
                                   value: 1,
                                   doc: None,
+                                  deprecation: None,
                                 },
                                 CppEnumValue {
                                   name: "CoarseTimer".to_string(),
                                   value: 2,
                                   doc: None,
-                                }]);
+                                  deprecation: None,
+                                }],
+                              false);
   assert_eq!(r.len(), 2);
   assert_eq!(r[0].name, "Precise");
   assert_eq!(r[1].name, "Coarse");
 }
 
+#[test]
+fn prepare_enum_values_test_newtype() {
+  let r = prepare_enum_values(&[CppEnumValue {
+                                  name: "OptionSingle".to_string(),
+                                  value: 1,
+                                  doc: None,
+                                  deprecation: None,
+                                }],
+                              true);
+  assert_eq!(r.len(), 1);
+  assert_eq!(r[0].name, "OptionSingle");
+  assert!(!r[0].is_dummy);
+
+  let r = prepare_enum_values(&[CppEnumValue {
+                                  name: "OptionGood".to_string(),
+                                  value: 2,
+                                  doc: None,
+                                  deprecation: None,
+                                },
+                                CppEnumValue {
+                                  name: "OptionBad".to_string(),
+                                  value: 1,
+                                  doc: None,
+                                  deprecation: None,
+                                },
+                                CppEnumValue {
+                                  name: "OptionFine".to_string(),
+                                  value: 2,
+                                  doc: None,
+                                  deprecation: None,
+                                }],
+                              true);
+  assert_eq!(r.len(), 3);
+  assert_eq!(r[0].name, "Bad");
+  assert_eq!(r[0].value, 1);
+  assert_eq!(r[1].name, "Good");
+  assert_eq!(r[1].value, 2);
+  assert_eq!(r[2].name, "Fine");
+  assert_eq!(r[2].value, 2);
+}
+
+#[test]
+fn prepare_enum_values_test_newtype_deprecation_and_docs() {
+  use cpp_data::CppDocContent;
+
+  // variants with equal values are not merged, so each of them
+  // keeps its own documentation and deprecation
+  let r = prepare_enum_values(&[CppEnumValue {
+                                  name: "ValueNew".to_string(),
+                                  value: 1,
+                                  doc: Some(CppDocContent::Html("new".to_string())),
+                                  deprecation: None,
+                                },
+                                CppEnumValue {
+                                  name: "ValueOld".to_string(),
+                                  value: 1,
+                                  doc: Some(CppDocContent::Html("old".to_string())),
+                                  deprecation: Some(CppDeprecation {
+                                                      message: Some("use ValueNew".to_string()),
+                                                    }),
+                                }],
+                              true);
+  assert_eq!(r.len(), 2);
+  assert_eq!(r[0].name, "New");
+  assert_eq!(r[0].deprecation, None);
+  assert_eq!(r[0].cpp_docs.len(), 1);
+  assert_eq!(r[0].cpp_docs[0].variant_name, "ValueNew");
+  assert_eq!(r[0].cpp_docs[0].doc,
+             Some(CppDocContent::Html("new".to_string())));
+  assert_eq!(r[1].name, "Old");
+  assert_eq!(r[1].deprecation,
+             Some(CppDeprecation { message: Some("use ValueNew".to_string()) }));
+  assert_eq!(r[1].cpp_docs.len(), 1);
+  assert_eq!(r[1].cpp_docs[0].variant_name, "ValueOld");
+
+  let r = prepare_enum_values(&[CppEnumValue {
+                                  name: "ValueNew".to_string(),
+                                  value: 1,
+                                  doc: None,
+                                  deprecation: None,
+                                },
+                                CppEnumValue {
+                                  name: "ValueOld".to_string(),
+                                  value: 1,
+                                  doc: None,
+                                  deprecation: Some(CppDeprecation { message: None }),
+                                }],
+                              false);
+  assert_eq!(r.len(), 2);
+  assert!(r[0].is_dummy);
+  assert_eq!(r[1].cpp_docs.len(), 2);
+  assert_eq!(r[1].deprecation, None);
+}
+
+#[test]
+fn prepare_enum_values_test_newtype_name_collisions() {
+  // names that become equal after case conversion are kept only once
+  let r = prepare_enum_values(&[CppEnumValue {
+                                  name: "big_value".to_string(),
+                                  value: 3,
+                                  doc: None,
+                                  deprecation: None,
+                                },
+                                CppEnumValue {
+                                  name: "BigValue".to_string(),
+                                  value: 2,
+                                  doc: None,
+                                  deprecation: None,
+                                },
+                                CppEnumValue {
+                                  name: "small_value".to_string(),
+                                  value: 1,
+                                  doc: None,
+                                  deprecation: None,
+                                }],
+                              true);
+  assert_eq!(r.len(), 2);
+  assert_eq!(r[0].name, "Small");
+  assert_eq!(r[0].value, 1);
+  assert_eq!(r[1].name, "Big");
+  assert_eq!(r[1].value, 3);
+}
+
+#[test]
+fn prepare_enum_values_test_newtype_affixes() {
+  let r = prepare_enum_values(&[CppEnumValue {
+                                  name: "AlignLeft".to_string(),
+                                  value: 0,
+                                  doc: None,
+                                  deprecation: None,
+                                },
+                                CppEnumValue {
+                                  name: "AlignRight".to_string(),
+                                  value: -1,
+                                  doc: None,
+                                  deprecation: None,
+                                }],
+                              true);
+  assert_eq!(r.len(), 2);
+  assert_eq!(r[0].name, "Right");
+  assert_eq!(r[0].value, -1);
+  assert_eq!(r[1].name, "Left");
+  assert_eq!(r[1].value, 0);
+  assert!(r.iter().all(|x| !x.is_dummy));
+}
+
+#[test]
+fn operator_rust_trait_test() {
+  assert_eq!(operator_rust_trait(&CppOperator::Addition),
+             Some(("ops", "Add", "add")));
+  assert_eq!(operator_rust_trait(&CppOperator::MultiplicationAssignment),
+             Some(("ops", "MulAssign", "mul_assign")));
+  assert_eq!(operator_rust_trait(&CppOperator::Subscript),
+             Some(("ops", "Index", "index")));
+  assert_eq!(operator_rust_trait(&CppOperator::EqualTo),
+             Some(("cmp", "PartialEq", "eq")));
+  assert_eq!(operator_rust_trait(&CppOperator::LessThan),
+             Some(("cmp", "PartialOrd", "lt")));
+  assert_eq!(operator_rust_trait(&CppOperator::GreaterThan), None);
+  assert_eq!(operator_rust_trait(&CppOperator::Assignment), None);
+}
+
+#[test]
+fn is_integer_type_test() {
+  let mut t = CppType {
+    base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Int),
+    indirection: CppTypeIndirection::None,
+    is_const: false,
+    is_const2: false,
+  };
+  assert!(is_integer_type(&t));
+  t.indirection = CppTypeIndirection::Ref;
+  assert!(!is_integer_type(&t));
+  t.indirection = CppTypeIndirection::None;
+  t.base = CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Double);
+  assert!(!is_integer_type(&t));
+  t.base = CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Bool);
+  assert!(!is_integer_type(&t));
+  t.base = CppTypeBase::PointerSizedInteger {
+    name: "size_t".to_string(),
+    is_signed: false,
+  };
+  assert!(is_integer_type(&t));
+}
 
 impl RustSingleMethod {
+  /// Returns true if the method is `unsafe`.
+  fn is_unsafe(&self) -> bool {
+    self.unsafe_reason.is_some()
+  }
+
+  /// Returns true if the method is `unsafe` because it takes a raw pointer.
+  /// Unlike `is_unsafe`, the result doesn't depend on `Config::set_unsafe_policy`.
+  fn has_unsafe_arguments(&self) -> bool {
+    if let Some(RustMethodUnsafeReason::RawPointerArgument(..)) = self.unsafe_reason {
+      true
+    } else {
+      false
+    }
+  }
+
+  /// Returns a copy of this method placed in a trait implementation.
+  /// Trait implementations are not affected by `Config::set_unsafe_policy`,
+  /// so the copy is safe.
+  fn to_trait_method(&self) -> RustSingleMethod {
+    let mut r = self.clone();
+    r.scope = RustMethodScope::TraitImpl;
+    r.unsafe_reason = None;
+    if let Some(ref mut doc) = r.doc {
+      doc.unsafe_reason = None;
+    }
+    r
+  }
+
   /// Converts this method to a final Rust method
   /// without overloading.
   fn to_rust_method(&self) -> RustMethod {
@@ -2270,8 +4754,9 @@ impl RustSingleMethod {
         Vec::new()
       },
       common_doc: None,
-      is_unsafe: self.is_unsafe,
+      is_unsafe: self.is_unsafe(),
       scope: self.scope.clone(),
+      deprecation: self.arguments.cpp_method.cpp_method.deprecation.clone(),
     }
   }
 
@@ -2309,7 +4794,7 @@ impl RustSingleMethod {
   /// Returns true if all  requirements for overloading
   /// `self` with `other_method` are satisfied.
   fn can_be_overloaded_with(&self, other_method: &RustSingleMethod) -> Result<bool> {
-    if self.is_unsafe != other_method.is_unsafe {
+    if self.is_unsafe() != other_method.is_unsafe() {
       return Ok(false);
     }
     if self.self_arg_kind()? != other_method.self_arg_kind()? {
@@ -2345,7 +4830,7 @@ impl RustSingleMethod {
                  index: usize)
                  -> Result<Option<String>> {
     if caption_strategy == &RustMethodCaptionStrategy::UnsafeOnly {
-      return Ok(if self.is_unsafe {
+      return Ok(if self.is_unsafe() {
                   Some("unsafe".to_string())
                 } else {
                   None
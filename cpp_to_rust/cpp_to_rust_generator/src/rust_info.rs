@@ -5,7 +5,7 @@ use cpp_type::CppType;
 use cpp_data::CppData;
 use rust_type::{RustName, CompleteType, RustType};
 use cpp_method::CppMethodDoc;
-use cpp_data::CppTypeDoc;
+use cpp_data::{CppTypeDoc, CppDeprecation, CppDocContent};
 use std::path::PathBuf;
 
 /// One variant of a Rust enum
@@ -21,6 +21,9 @@ pub struct RustEnumValue {
   /// True if this variant was added because enums with
   /// one variant are not supported
   pub is_dummy: bool,
+  /// Deprecation attribute of corresponding C++ variants.
+  /// `None` if at least one of them is not deprecated.
+  pub deprecation: Option<CppDeprecation>,
 }
 
 
@@ -30,8 +33,8 @@ pub struct RustEnumValue {
 pub struct CppEnumValueDocItem {
   /// C++ name of the variant
   pub variant_name: String,
-  /// HTML content
-  pub doc: Option<String>,
+  /// C++ documentation of the variant
+  pub doc: Option<CppDocContent>,
 }
 
 /// Information about a Qt slot wrapper on Rust side
@@ -48,6 +51,44 @@ pub struct RustQtSlotWrapper {
   pub callback_name: String,
 }
 
+/// Information about an overridable virtual method
+/// of a subclass wrapper on Rust side
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct RustSubclassVirtualMethod {
+  /// Name of the method in the overrides trait
+  pub name: String,
+  /// Short text of the original C++ method
+  pub cpp_method: String,
+  /// True if the C++ method is const
+  pub is_const: bool,
+  /// Names of the method's arguments
+  pub argument_names: Vec<String>,
+  /// Types of the method's arguments
+  pub argument_types: Vec<CompleteType>,
+  /// Return type of the method
+  pub return_type: CompleteType,
+  /// Name of the FFI function that sets the callback for this method
+  pub set_callback_ffi_name: String,
+  /// Name of the Rust method calling the base class implementation
+  /// of this method. `None` if the method is pure virtual.
+  pub base_method_name: Option<String>,
+}
+
+/// Information about a subclass wrapper on Rust side
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct RustSubclassWrapper {
+  /// Name of the trait containing overridable methods
+  pub trait_name: String,
+  /// Name of the C++ base class
+  pub cpp_base_name: String,
+  /// Name of the FFI function that sets the data object
+  pub set_data_ffi_name: String,
+  /// Overridable virtual methods
+  pub virtual_methods: Vec<RustSubclassVirtualMethod>,
+}
+
 /// Information about a Rust type wrapper
 #[derive(Debug, PartialEq, Eq, Clone)]
 #[derive(Serialize, Deserialize)]
@@ -58,8 +99,14 @@ pub enum RustTypeWrapperKind {
     values: Vec<RustEnumValue>,
     /// True if `FlaggableEnum` trait is implemented
     /// for this type, i.e. if `QFlags<T>` with this C++ type
-    /// is used in API.
+    /// or a flags typedef of this type is used in API.
     is_flaggable: bool,
+    /// True if this enum is represented by a newtype struct
+    /// with associated constants instead of a Rust enum.
+    is_newtype: bool,
+    /// Rust primitive integer type matching the C++ enum's
+    /// underlying type (e.g. `u8` for `enum class Foo : uint8_t`)
+    int_type: String,
   },
   /// Struct wrapper
   Struct {
@@ -75,6 +122,8 @@ pub enum RustTypeWrapperKind {
     is_deletable: bool,
     /// Additional information for a Qt slot wrapper struct
     slot_wrapper: Option<RustQtSlotWrapper>,
+    /// Additional information for a subclass wrapper struct
+    subclass_wrapper: Option<RustSubclassWrapper>,
   },
 }
 
@@ -130,6 +179,26 @@ pub struct RustMethodDocItem {
   pub rust_fns: Vec<String>,
   /// C++ code containing declaration of the corresponding C++ method.
   pub cpp_fn: String,
+  /// Reason for marking the Rust method as `unsafe`, or `None` if it's safe.
+  pub unsafe_reason: Option<RustMethodUnsafeReason>,
+}
+
+/// Reason for marking a Rust method as `unsafe`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RustMethodUnsafeReason {
+  /// The argument with the specified name is a raw pointer.
+  RawPointerArgument(String),
+  /// `UnsafePolicy::AllUnsafe` is used.
+  AllUnsafePolicy,
+  /// `UnsafePolicy::HeuristicWithAllowlist` is used,
+  /// and the method wasn't added with `Config::add_safe_method`.
+  NotInSafeMethods,
+  /// The method performs a static cast that can't be checked.
+  UncheckedStaticCast,
+  /// The method accesses an element of a container without checking the index.
+  UncheckedIndex,
+  /// The method uses a C++ iterator without checking its position.
+  UncheckedIterator,
 }
 
 
@@ -170,6 +239,11 @@ pub struct RustMethodArgumentsVariant {
   /// if any. `None` if the return value is passed normally (as the return value
   /// of the FFI function).
   pub return_type_ffi_index: Option<usize>,
+  /// Index of the FFI function argument receiving information about
+  /// a caught C++ exception, if the FFI function catches exceptions.
+  /// If set, `return_type.rust_api_type` is a `Result` wrapping
+  /// the converted return value.
+  pub exception_info_ffi_index: Option<usize>,
   /// C++ and Rust return types at all levels.
   pub return_type: CompleteType,
 }
@@ -215,6 +289,9 @@ pub struct RustMethod {
   pub variant_docs: Vec<RustMethodDocItem>,
   /// Rustdoc content that will appear before documentation for variants.
   pub common_doc: Option<String>,
+  /// Deprecation attribute of the method. `Some` only if
+  /// all corresponding C++ methods are deprecated.
+  pub deprecation: Option<CppDeprecation>,
 }
 
 /// Information about type of `self` argument of the method.
@@ -236,6 +313,33 @@ pub enum TraitImplExtra {
   /// For `CppDeletable` trait implementation,
   /// `deleter_name` contains name of the FFI function used as deleter.
   CppDeletable { deleter_name: String },
+  /// For `PartialOrd` trait implementation generated from
+  /// C++ `operator<`. The implementation only contains `lt` method,
+  /// and `partial_cmp` is generated using `lt` and `PartialEq`.
+  PartialOrdFromLessThan,
+  /// For `Debug` trait implementation generated from a C++ method
+  /// configured with `Config::add_debug_method`. The implementation's
+  /// only method wraps the C++ method, and `fmt` formats its return value.
+  DebugFromMethod,
+  /// For `Debug` trait implementation generated from `operator<<`
+  /// of a C++ class. The implementation's only method wraps a fictional
+  /// method writing the printed text to a buffer, and `fmt` calls it
+  /// twice to get the text's length and the text itself.
+  DebugFromStream,
+  /// For `IntoIterator` trait implementation for a reference to
+  /// a C++ container. `into_iter` returns `cpp_utils::container::Iter`
+  /// or `cpp_utils::container::IterMut` depending on the reference's mutability.
+  ContainerIntoIterator,
+  /// For `IntoIterator` trait implementation for a reference to
+  /// a C++ container with `begin()` and `end()` methods.
+  /// `into_iter` returns `cpp_utils::container::BeginEndIter`.
+  IterableIntoIterator,
+  /// For `Extend` trait implementation for a C++ container.
+  /// `extend` is implemented using `cpp_utils::container::extend`.
+  ContainerExtend,
+  /// For `FromIterator` trait implementation for a C++ container.
+  /// `from_iter` is implemented using `cpp_utils::container::from_iter`.
+  ContainerFromIterator,
 }
 
 /// Information about an associated type value
@@ -346,6 +450,36 @@ pub struct RustTypeDeclaration {
   /// Additional documentation content that will appear before C++ documentation or any other
   /// automatically generated content.
   pub rust_doc: Option<String>,
+  /// Deprecation attribute of the corresponding C++ type, if any.
+  pub deprecation: Option<CppDeprecation>,
+}
+
+/// Information about a Rust constant generated from a C++ macro
+/// or a `constexpr` variable.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RustConstant {
+  /// Full name of the constant.
+  pub name: RustName,
+  /// Type of the constant.
+  pub rust_type: RustType,
+  /// Rust code of the constant's value.
+  pub value: String,
+  /// Name of the C++ macro or full name of the C++ variable.
+  pub cpp_name: String,
+  /// True if the constant was generated from a macro.
+  pub is_macro: bool,
+}
+
+/// Information about a Rust type alias generated from
+/// a C++ typedef or a `using` declaration.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RustTypeAlias {
+  /// Full name of the alias.
+  pub name: RustName,
+  /// Type referred to by the alias.
+  pub rust_type: RustType,
+  /// Full name of the C++ alias.
+  pub cpp_name: String,
 }
 
 /// Information about a Rust module.
@@ -359,6 +493,10 @@ pub struct RustModule {
   pub types: Vec<RustTypeDeclaration>,
   /// Free functions within the module.
   pub functions: Vec<RustMethod>,
+  /// Constants within the module.
+  pub constants: Vec<RustConstant>,
+  /// Type aliases within the module.
+  pub type_aliases: Vec<RustTypeAlias>,
   /// Trait implementations associated with free functions.
   pub trait_impls: Vec<TraitImpl>,
   /// Markdown content of Rust documentation for this module.
@@ -34,8 +34,16 @@ pub enum RustToCTypeConversion {
   ValueToPtr,
   /// `CppBox<T>` to `*const T` (or similar mutable type)
   CppBoxToPtr,
-  /// `qt_core::flags::Flags<T>` to `libc::c_uint`
-  QFlagsToUInt,
+  /// `CppBox<T>` to `*mut T`, transferring ownership of the object
+  /// to the C++ side (for arguments only)
+  CppBoxIntoPtr,
+  /// `cpp_utils::flags::Flags<T>` to the integer type of the FFI function
+  FlagsToInt,
+  /// Rust enum type to the enum's underlying integer type
+  EnumToInt,
+  /// `usize` to the integer type of the FFI function (used for sizes
+  /// and indexes of containers)
+  UsizeToInt,
 }
 
 /// Information about a completely processed type
@@ -107,6 +115,19 @@ pub enum RustType {
     /// Argument types of the function.
     arguments: Vec<RustType>,
   },
+  /// A fixed-size array type, like `[f32; 4]`, with some indirection.
+  Array {
+    /// Type of array elements
+    element_type: Box<RustType>,
+    /// Number of elements
+    size: usize,
+    /// Constness of the array or the indirection,
+    /// the same as in `RustType::Common`
+    is_const: bool,
+    /// Indirection of this type. Only `None`, `Ptr` and `Ref`
+    /// are allowed for arrays.
+    indirection: RustTypeIndirection,
+  },
 }
 
 
@@ -240,6 +261,24 @@ impl RustType {
       name
     }
          RustType::FunctionPointer { .. } => "fn".to_string(),
+         RustType::Array {
+           ref element_type,
+           ref size,
+           ref is_const,
+           ref indirection,
+         } => {
+           let mut_text = if *is_const { "" } else { "_mut" };
+           let indirection_text = match *indirection {
+             RustTypeIndirection::None => String::new(),
+             RustTypeIndirection::Ref { .. } => format!("{}_ref", mut_text),
+             RustTypeIndirection::Ptr => format!("{}_ptr", mut_text),
+             _ => return Err(unexpected("invalid indirection for array type").into()),
+           };
+           format!("{}_array_{}{}",
+                   element_type.caption(context)?,
+                   size,
+                   indirection_text)
+         }
        })
   }
 
@@ -247,7 +286,8 @@ impl RustType {
   #[allow(dead_code)]
   pub fn is_ref(&self) -> bool {
     match *self {
-      RustType::Common { ref indirection, .. } => {
+      RustType::Common { ref indirection, .. } |
+      RustType::Array { ref indirection, .. } => {
         match *indirection {
           RustTypeIndirection::Ref { .. } |
           RustTypeIndirection::PtrRef { .. } => true,
@@ -259,15 +299,64 @@ impl RustType {
     }
   }
 
+  /// Returns the reference wrapped in this type if this type is
+  /// `Option<&T>`. Lifetime of such type is the lifetime of the reference.
+  fn option_ref_argument(&self) -> Option<&RustType> {
+    if let RustType::Common {
+             ref base,
+             generic_arguments: Some(ref args),
+             indirection: RustTypeIndirection::None,
+             ..
+           } = *self {
+      if base.full_name(None) == "std::option::Option" && args.len() == 1 && args[0].is_ref() {
+        return Some(&args[0]);
+      }
+    }
+    None
+  }
+
   /// Returns a copy of this type with `new_lifetime` added, if possible.
   pub fn with_lifetime(&self, new_lifetime: String) -> RustType {
     let mut r = self.clone();
-    if let RustType::Common { ref mut indirection, .. } = r {
-      match *indirection {
-        RustTypeIndirection::Ref { ref mut lifetime } |
-        RustTypeIndirection::PtrRef { ref mut lifetime } => *lifetime = Some(new_lifetime),
-        _ => {}
+    if self.option_ref_argument().is_some() {
+      if let RustType::Common { generic_arguments: Some(ref mut args), .. } = r {
+        args[0] = args[0].with_lifetime(new_lifetime);
+      }
+      return r;
+    }
+    match r {
+      RustType::Common { ref mut indirection, .. } |
+      RustType::Array { ref mut indirection, .. } => {
+        match *indirection {
+          RustTypeIndirection::Ref { ref mut lifetime } |
+          RustTypeIndirection::PtrRef { ref mut lifetime } => *lifetime = Some(new_lifetime),
+          _ => {}
+        }
       }
+      _ => {}
+    }
+    r
+  }
+
+  /// Returns a copy of this type with its lifetime removed, if any.
+  pub fn without_lifetime(&self) -> RustType {
+    let mut r = self.clone();
+    if self.option_ref_argument().is_some() {
+      if let RustType::Common { generic_arguments: Some(ref mut args), .. } = r {
+        args[0] = args[0].without_lifetime();
+      }
+      return r;
+    }
+    match r {
+      RustType::Common { ref mut indirection, .. } |
+      RustType::Array { ref mut indirection, .. } => {
+        match *indirection {
+          RustTypeIndirection::Ref { ref mut lifetime } |
+          RustTypeIndirection::PtrRef { ref mut lifetime } => *lifetime = None,
+          _ => {}
+        }
+      }
+      _ => {}
     }
     r
   }
@@ -275,8 +364,12 @@ impl RustType {
   /// Returns name of the lifetime of this type,
   /// or `None` if there isn't any lifetime in this type.
   pub fn lifetime(&self) -> Option<&String> {
+    if let Some(arg) = self.option_ref_argument() {
+      return arg.lifetime();
+    }
     match *self {
-      RustType::Common { ref indirection, .. } => {
+      RustType::Common { ref indirection, .. } |
+      RustType::Array { ref indirection, .. } => {
         match *indirection {
           RustTypeIndirection::Ref { ref lifetime } |
           RustTypeIndirection::PtrRef { ref lifetime } => lifetime.as_ref(),
@@ -286,6 +379,33 @@ impl RustType {
       _ => None,
     }
   }
+
+  /// Returns names of all lifetimes used in this type,
+  /// including lifetimes of generic arguments.
+  pub fn all_lifetimes(&self) -> Vec<&String> {
+    let mut result = Vec::new();
+    if let Some(lifetime) = self.lifetime() {
+      result.push(lifetime);
+    }
+    if let RustType::Common { generic_arguments: Some(ref args), .. } = *self {
+      for arg in args {
+        for lifetime in arg.all_lifetimes() {
+          if !result.contains(&lifetime) {
+            result.push(lifetime);
+          }
+        }
+      }
+    }
+    if let RustType::Array { ref element_type, .. } = *self {
+      for lifetime in element_type.all_lifetimes() {
+        if !result.contains(&lifetime) {
+          result.push(lifetime);
+        }
+      }
+    }
+    result
+  }
+
   /// Returns true if indirection that is applied last has const qualifier.
   pub fn last_is_const(&self) -> Result<bool> {
     if let RustType::Common {
@@ -299,6 +419,8 @@ impl RustType {
         RustTypeIndirection::PtrRef { .. } => Ok(*is_const2),
         _ => Ok(*is_const),
       }
+    } else if let RustType::Array { ref is_const, .. } = *self {
+      Ok(*is_const)
     } else {
       Err("not a Common type".into())
     }
@@ -307,7 +429,8 @@ impl RustType {
   /// Returns true if this type (or first indirection of the type) is const.
   pub fn is_const(&self) -> Result<bool> {
     match *self {
-      RustType::Common { ref is_const, .. } => Ok(*is_const),
+      RustType::Common { ref is_const, .. } |
+      RustType::Array { ref is_const, .. } => Ok(*is_const),
       _ => Err("not a Common type".into()),
     }
   }
@@ -315,7 +438,8 @@ impl RustType {
   /// Sets value of `is_const` for a common type.
   pub fn set_const(&mut self, value: bool) -> Result<()> {
     match *self {
-      RustType::Common { ref mut is_const, .. } => {
+      RustType::Common { ref mut is_const, .. } |
+      RustType::Array { ref mut is_const, .. } => {
         *is_const = value;
         Ok(())
       }
@@ -354,6 +478,17 @@ impl RustType {
         }
         false
       }
+      RustType::Array {
+        ref indirection,
+        ref element_type,
+        ..
+      } => {
+        match *indirection {
+          RustTypeIndirection::None |
+          RustTypeIndirection::Ref { .. } => element_type.is_unsafe_argument(),
+          _ => true,
+        }
+      }
       RustType::EmptyTuple => false,
       RustType::FunctionPointer { .. } => true,
     }
@@ -0,0 +1,67 @@
+use common::cpp_build_config::TYPE_SIZES_MARKER;
+use common::file_utils::{create_file, PathBufWithAdded};
+use common::utils::get_command_output;
+use cpp_code_generator::{generate_cpp_type_size_requester, CppTypeSizeRequest};
+use rust_generator::align_type_name;
+use tests::TempTestDir;
+
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn align_type_names() {
+  assert_eq!(align_type_name("NS_POINT"), "NS_POINT_ALIGN");
+  assert_eq!(align_type_name("NS_POINT_ALIGN"), "NS_POINT_ALIGN_ALIGN");
+}
+
+#[test]
+fn type_size_requester() {
+  let dir = TempTestDir::new("test_cpp_code_generator_type_size_requester");
+  {
+    let mut header = create_file(dir.path().with_added("point.h")).unwrap();
+    header
+      .write("namespace ns {
+  struct Point { double x; char c; };
+}
+struct Byte { char c; };
+")
+      .unwrap();
+  }
+  let requests = vec![CppTypeSizeRequest {
+                        cpp_code: "ns::Point".to_string(),
+                        size_const_name: "NS_POINT".to_string(),
+                        align_type_name: align_type_name("NS_POINT"),
+                      },
+                      CppTypeSizeRequest {
+                        cpp_code: "Byte".to_string(),
+                        size_const_name: "BYTE".to_string(),
+                        align_type_name: align_type_name("BYTE"),
+                      }];
+  let code = generate_cpp_type_size_requester(&requests, &[PathBuf::from("point.h")]).unwrap();
+  assert!(code.starts_with("#include <point.h>\n"));
+  assert!(code.contains("CPP_TO_RUST_HEX((sizeof(ns::Point)))"));
+  assert!(code.contains("CPP_TO_RUST_HEX((alignof(ns::Point)))"));
+
+  let cpp_path = dir.path().with_added("type_sizes.cpp");
+  create_file(&cpp_path)
+    .unwrap()
+    .write(code)
+    .unwrap();
+  let exe_path = dir.path().with_added("type_sizes");
+  get_command_output(Command::new("c++")
+                       .arg("-std=c++11")
+                       .arg("-I")
+                       .arg(dir.path())
+                       .arg(&cpp_path)
+                       .arg("-o")
+                       .arg(&exe_path))
+      .unwrap();
+  let output = get_command_output(&mut Command::new(&exe_path)).unwrap();
+  let double_align = ::std::mem::align_of::<f64>();
+  assert_eq!(output,
+             format!("{}NS_POINT NS_POINT_ALIGN {:08x} {:08x}\n\
+                      BYTE BYTE_ALIGN 00000001 00000001\n",
+                     TYPE_SIZES_MARKER,
+                     2 * double_align,
+                     double_align));
+}
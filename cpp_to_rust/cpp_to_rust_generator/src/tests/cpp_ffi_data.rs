@@ -15,6 +15,9 @@ fn argument_meaning() {
 
   let a3 = CppFfiArgumentMeaning::ReturnValue;
   assert!(!a3.is_argument());
+
+  let a4 = CppFfiArgumentMeaning::ExceptionInfo;
+  assert!(!a4.is_argument());
 }
 
 #[test]
@@ -0,0 +1,275 @@
+use cpp_data::CppTypeAllocationPlace;
+use cpp_ffi_data::{CppFfiHeaderData, CppSubclassWrapper, CppFfiMethodKind, CppFieldAccessorType,
+                   CppIndirectionChange};
+use cpp_method::ReturnValueAllocationPlace;
+use cpp_type::{CppBuiltInNumericType, CppTypeBase, CppTypeIndirection};
+use common::file_utils::PathBufWithAdded;
+use tests::TempTestDir;
+use tests::pipeline::{create_config, run_ffi_generator};
+
+fn find_subclass_wrapper(headers: &[CppFfiHeaderData]) -> &CppSubclassWrapper {
+  let wrappers: Vec<_> = headers
+    .iter()
+    .flat_map(|header| header.subclass_wrappers.iter())
+    .collect();
+  assert_eq!(wrappers.len(), 1);
+  wrappers[0]
+}
+
+#[test]
+fn subclass_wrapper_abstract_class() {
+  let dir = TempTestDir::new("test_cpp_ffi_generator_subclass_wrapper");
+  let mut config = create_config(&dir,
+                                 "class Point {
+public:
+  Point(int x, int y);
+};
+
+class Model {
+public:
+  Model(Model* parent = 0);
+  Model(const Model& other);
+  virtual ~Model();
+  virtual Point position(int index) const = 0;
+  virtual int count() const = 0;
+  virtual void reset();
+protected:
+  void changed();
+};");
+  config.set_type_allocation_place(CppTypeAllocationPlace::Stack, "Point");
+  config.add_subclassing_class("Model");
+  let (_cpp_data, headers) = run_ffi_generator(&config);
+  let wrapper = find_subclass_wrapper(&headers);
+  assert_eq!(wrapper.base_class.name, "Model");
+  assert_eq!(wrapper.protected_method_names, vec!["changed".to_string()]);
+
+  // the copy constructor is skipped, but the constructor taking
+  // a pointer to the same class is not, and its default value is kept
+  assert_eq!(wrapper.constructors.len(), 1);
+  assert_eq!(wrapper.constructors[0].len(), 1);
+  assert_eq!(wrapper.constructors[0][0].argument_type.indirection,
+             CppTypeIndirection::Ptr);
+  assert!(wrapper.constructors[0][0].has_default_value);
+  let mut constructor_arg_counts: Vec<_> = headers
+    .iter()
+    .flat_map(|header| header.methods.iter())
+    .filter(|m| {
+              m.cpp_method.class_name() == Some(&wrapper.class_name) &&
+              m.cpp_method.is_constructor()
+            })
+    .map(|m| m.cpp_method.arguments.len())
+    .collect();
+  constructor_arg_counts.sort();
+  constructor_arg_counts.dedup();
+  assert_eq!(constructor_arg_counts, vec![0, 1]);
+
+  let keys: Vec<_> = wrapper
+    .virtual_methods
+    .iter()
+    .map(|m| m.key.as_str())
+    .collect();
+  assert_eq!(keys, vec!["position", "count", "reset"]);
+
+  // the value returned by the pure virtual method is written
+  // to an output argument appended after the original arguments
+  let position = &wrapper.virtual_methods[0];
+  assert_eq!(position.return_allocation_place, ReturnValueAllocationPlace::Stack);
+  assert_eq!(position.function_type.return_type.base, CppTypeBase::Void);
+  assert_eq!(position.function_type.arguments.len(), 3);
+  let output_type = &position.function_type.arguments[2];
+  assert_eq!(output_type.indirection, CppTypeIndirection::Ptr);
+  assert!(if let CppTypeBase::Class(ref base) = output_type.base {
+            base.name == "Point"
+          } else {
+            false
+          });
+
+  let count = &wrapper.virtual_methods[1];
+  assert_eq!(count.return_allocation_place,
+             ReturnValueAllocationPlace::NotApplicable);
+  assert_eq!(count.function_type.arguments.len(), 2);
+
+  let ffi_names: Vec<_> = headers
+    .iter()
+    .flat_map(|header| header.methods.iter())
+    .filter(|m| m.cpp_method.class_name() == Some(&wrapper.class_name))
+    .map(|m| m.cpp_method.name.as_str())
+    .collect();
+  assert!(ffi_names.contains(&"set_position_callback"));
+  assert!(ffi_names.contains(&"reset_base"));
+  // pure virtual methods have no base implementation
+  assert!(!ffi_names.contains(&"position_base"));
+  assert!(!ffi_names.contains(&"count_base"));
+}
+
+#[test]
+fn subclass_wrapper_unsupported_class_is_skipped() {
+  let dir = TempTestDir::new("test_cpp_ffi_generator_subclass_wrapper_skipped");
+  let mut config = create_config(&dir,
+                                 "class Model {
+public:
+  virtual ~Model();
+  virtual int count(...) const = 0;
+};
+
+class View {
+public:
+  virtual ~View();
+  virtual void update();
+};");
+  config.add_subclassing_class("Model");
+  config.add_subclassing_class("View");
+  // the failure of `Model` doesn't abort the generation
+  let (_cpp_data, headers) = run_ffi_generator(&config);
+  assert_eq!(find_subclass_wrapper(&headers).base_class.name, "View");
+}
+
+#[test]
+fn variable_accessors() {
+  let dir = TempTestDir::new("test_cpp_ffi_generator_variable_accessors");
+  let config = create_config(&dir,
+                             "class Point {
+public:
+  Point(int x, int y);
+};
+namespace ns {
+  extern int counter;
+  extern const double ratio;
+  extern Point origin;
+  class Item {
+  public:
+    static int count;
+  };
+}
+extern \"C\" {
+  extern int c_value;
+}");
+  let (_cpp_data, headers) = run_ffi_generator(&config);
+  let mut accessors: Vec<_> = headers
+    .iter()
+    .flat_map(|header| header.methods.iter())
+    .filter_map(|m| if let CppFfiMethodKind::FieldAccessor {
+                             ref accessor_type,
+                             ref field_name,
+                           } = m.kind {
+                  Some((m.cpp_method.full_name(),
+                        accessor_type.clone(),
+                        field_name.clone(),
+                        m.cpp_method
+                          .class_membership
+                          .as_ref()
+                          .map(|info| info.is_static)))
+                } else {
+                  None
+                })
+    .collect();
+  accessors.sort_by(|a, b| a.0.cmp(&b.0));
+  accessors.dedup();
+  assert_eq!(accessors,
+             vec![("c_value".to_string(),
+                   CppFieldAccessorType::CopyGetter,
+                   "c_value".to_string(),
+                   None),
+                  ("ns::Item::count".to_string(),
+                   CppFieldAccessorType::CopyGetter,
+                   "count".to_string(),
+                   Some(true)),
+                  ("ns::Item::set_count".to_string(),
+                   CppFieldAccessorType::Setter,
+                   "count".to_string(),
+                   Some(true)),
+                  ("ns::counter".to_string(),
+                   CppFieldAccessorType::CopyGetter,
+                   "ns::counter".to_string(),
+                   None),
+                  ("ns::origin".to_string(),
+                   CppFieldAccessorType::ConstRefGetter,
+                   "ns::origin".to_string(),
+                   None),
+                  ("ns::origin_mut".to_string(),
+                   CppFieldAccessorType::MutRefGetter,
+                   "ns::origin".to_string(),
+                   None),
+                  // const variables have no setters
+                  ("ns::ratio".to_string(),
+                   CppFieldAccessorType::CopyGetter,
+                   "ns::ratio".to_string(),
+                   None),
+                  ("ns::set_counter".to_string(),
+                   CppFieldAccessorType::Setter,
+                   "ns::counter".to_string(),
+                   None),
+                  ("ns::set_origin".to_string(),
+                   CppFieldAccessorType::Setter,
+                   "ns::origin".to_string(),
+                   None),
+                  ("set_c_value".to_string(),
+                   CppFieldAccessorType::Setter,
+                   "c_value".to_string(),
+                   None)]);
+}
+
+#[test]
+fn stream_output_methods() {
+  let dir = TempTestDir::new("test_cpp_ffi_generator_stream_output_methods");
+  let mut config = create_config(&dir,
+                                 "#include <ostream>
+class Color {
+public:
+  int red() const;
+};
+std::ostream& operator<<(std::ostream& stream, const Color& color);
+std::ostream& operator<<(std::ostream& stream, Color& color);");
+  config.add_target_include_path(dir.path().with_added("include"));
+  let (_cpp_data, headers) = run_ffi_generator(&config);
+  let methods: Vec<_> = headers
+    .iter()
+    .flat_map(|header| header.methods.iter())
+    .collect();
+  // operators taking `std::ostream` are not wrapped directly
+  assert!(!methods.iter().any(|m| m.cpp_method.operator.is_some()));
+  let stream_methods: Vec<_> = methods
+    .iter()
+    .filter(|m| m.kind == CppFfiMethodKind::StreamOutput)
+    .collect();
+  // only `const Color&` argument is supported
+  assert_eq!(stream_methods.len(), 1);
+  let method = &stream_methods[0].cpp_method;
+  assert_eq!(method.full_name(), "Color::write_to_buffer");
+  assert!(method.class_membership.as_ref().unwrap().is_const);
+  let argument_names: Vec<_> = method.arguments.iter().map(|a| a.name.as_str()).collect();
+  assert_eq!(argument_names, vec!["buffer", "buffer_size"]);
+  assert_eq!(method.arguments[0].argument_type.indirection,
+             CppTypeIndirection::Ptr);
+  assert_eq!(method.return_type, method.arguments[1].argument_type);
+}
+
+#[test]
+fn enum_arguments() {
+  let dir = TempTestDir::new("test_cpp_ffi_generator_enum_arguments");
+  let config = create_config(&dir,
+                             "enum class Mode : unsigned char { Read = 1, Write = 2 };
+Mode next_mode(Mode mode, const Mode& other);");
+  let (_cpp_data, headers) = run_ffi_generator(&config);
+  let method = headers
+    .iter()
+    .flat_map(|header| header.methods.iter())
+    .find(|m| m.cpp_method.name == "next_mode")
+    .unwrap();
+  let args = &method.c_signature.arguments;
+  assert_eq!(args.len(), 2);
+  // the enum value is passed as the underlying integer type
+  assert_eq!(args[0].argument_type.ffi_type.base,
+             CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::UChar));
+  assert_eq!(args[0].argument_type.conversion, CppIndirectionChange::EnumToInt);
+  // a reference to the enum is converted to a pointer to the enum
+  assert_eq!(args[1].argument_type.ffi_type.base,
+             CppTypeBase::Enum { name: "Mode".to_string() });
+  assert_eq!(args[1].argument_type.ffi_type.indirection, CppTypeIndirection::Ptr);
+  assert_eq!(args[1].argument_type.conversion,
+             CppIndirectionChange::ReferenceToPointer);
+  let return_type = &method.c_signature.return_type;
+  assert_eq!(return_type.ffi_type.base,
+             CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::UChar));
+  assert_eq!(return_type.conversion, CppIndirectionChange::EnumToInt);
+}
@@ -43,6 +43,7 @@ pub fn empty_regular_method() -> CppMethod {
     return_type: CppType::void(),
     arguments: vec![],
     doc: None,
+    deprecation: None,
     inheritance_chain: Vec::new(),
     allows_variadic_arguments: false,
     include_file: String::new(),
@@ -52,6 +53,7 @@ pub fn empty_regular_method() -> CppMethod {
     operator: None,
     declaration_code: None,
     is_ffi_whitelisted: false,
+    is_noexcept: false,
   }
 }
 
@@ -741,6 +743,7 @@ fn short_text1() {
                       has_default_value: true,
                     }],
     doc: None,
+    deprecation: None,
     inheritance_chain: Vec::new(),
     allows_variadic_arguments: false,
     include_file: String::new(),
@@ -749,7 +752,79 @@ fn short_text1() {
     template_arguments_values: None,
     declaration_code: None,
     is_ffi_whitelisted: false,
+    is_noexcept: false,
   };
   assert_eq!(method.short_text(),
              "protected int Class1::method1(int arg1, double arg2 = ?) const");
 }
+
+#[test]
+fn signature() {
+  let mut method = empty_regular_method();
+  method.name = "setLayout".to_string();
+  method.class_membership = Some(empty_membership("QWidget"));
+  method.arguments = vec![CppMethodArgument {
+                            argument_type: CppType {
+                              indirection: CppTypeIndirection::Ptr,
+                              is_const: false,
+                              is_const2: false,
+                              base: CppTypeBase::Class(CppTypeClassBase {
+                                                         name: "QLayout".to_string(),
+                                                         template_arguments: None,
+                                                       }),
+                            },
+                            name: "layout".to_string(),
+                            has_default_value: false,
+                          },
+                          CppMethodArgument {
+                            argument_type: CppType {
+                              indirection: CppTypeIndirection::None,
+                              is_const: false,
+                              is_const2: false,
+                              base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Int),
+                            },
+                            name: "arg2".to_string(),
+                            has_default_value: true,
+                          }];
+  assert_eq!(method.signature(), "QWidget::setLayout(QLayout*, int)");
+  if let Some(ref mut info) = method.class_membership {
+    info.is_const = true;
+  }
+  assert_eq!(method.signature(), "QWidget::setLayout(QLayout*, int) const");
+}
+
+#[test]
+fn special_constructors() {
+  let mut method = empty_regular_method();
+  method.name = "QPoint".to_string();
+  let mut info = empty_membership("QPoint");
+  info.kind = CppMethodKind::Constructor;
+  method.class_membership = Some(info);
+  assert!(method.is_default_constructor());
+  assert!(!method.is_copy_constructor());
+
+  method.arguments = vec![CppMethodArgument {
+                            argument_type: CppType {
+                              indirection: CppTypeIndirection::Ref,
+                              is_const: true,
+                              is_const2: false,
+                              base: CppTypeBase::Class(CppTypeClassBase {
+                                                         name: "QPoint".to_string(),
+                                                         template_arguments: None,
+                                                       }),
+                            },
+                            name: "other".to_string(),
+                            has_default_value: false,
+                          }];
+  assert!(!method.is_default_constructor());
+  assert!(method.is_copy_constructor());
+
+  method.arguments[0].argument_type.is_const = false;
+  assert!(!method.is_copy_constructor());
+
+  if let Some(ref mut info) = method.class_membership {
+    info.kind = CppMethodKind::Regular;
+  }
+  method.arguments.clear();
+  assert!(!method.is_default_constructor());
+}
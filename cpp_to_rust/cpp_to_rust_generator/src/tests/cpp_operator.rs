@@ -1,4 +1,5 @@
 use cpp_operator::*;
+use cpp_type::{CppType, CppTypeBase, CppBuiltInNumericType, CppTypeIndirection};
 
 #[test]
 fn info1() {
@@ -7,3 +8,15 @@ fn info1() {
   assert_eq!(info.arguments_count, 2);
   assert_eq!(info.allows_variadic_arguments, false);
 }
+
+#[test]
+fn c_name_conversion() {
+  let operator = CppOperator::Conversion(CppType {
+                                           is_const: false,
+                                           is_const2: false,
+                                           base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Int),
+                                           indirection: CppTypeIndirection::None,
+                                         });
+  assert_eq!(operator.c_name().unwrap(), "convert_to_int");
+  assert_eq!(CppOperator::Modulo.c_name().unwrap(), "rem");
+}
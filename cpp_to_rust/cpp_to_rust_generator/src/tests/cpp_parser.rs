@@ -10,6 +10,12 @@ use common::file_utils::{create_dir, create_file, PathBufWithAdded};
 use std::path::PathBuf;
 
 fn run_parser(code: &'static str) -> ParserCppData {
+  run_parser_with_instantiations(code, Vec::new())
+}
+
+fn run_parser_with_instantiations(code: &'static str,
+                                  template_instantiations: Vec<String>)
+                                  -> ParserCppData {
   let dir = tempdir::TempDir::new("test_cpp_parser_run").unwrap();
   let include_dir = dir.path().with_added("include");
   create_dir(&include_dir).unwrap();
@@ -28,6 +34,9 @@ fn run_parser(code: &'static str) -> ParserCppData {
                                      name_blacklist: Vec::new(),
                                      framework_paths: Vec::new(),
                                      clang_arguments: Vec::new(),
+                                     template_instantiations: template_instantiations,
+                                     flags_typedefs: Vec::new(),
+                                     skip_deprecated: false,
                                    },
                                    &[])
       .unwrap();
@@ -71,6 +80,7 @@ fn simple_func() {
                                  has_default_value: false,
                                }],
                doc: None,
+               deprecation: None,
                inheritance_chain: Vec::new(),
                allows_variadic_arguments: false,
                include_file: "myfakelib.h".to_string(),
@@ -79,6 +89,7 @@ fn simple_func() {
                template_arguments_values: None,
                declaration_code: Some("int func1 ( int x )".to_string()),
                is_ffi_whitelisted: false,
+               is_noexcept: false,
              });
 }
 
@@ -109,6 +120,7 @@ fn simple_func_with_default_value() {
                                  has_default_value: true,
                                }],
                doc: None,
+               deprecation: None,
                inheritance_chain: Vec::new(),
                allows_variadic_arguments: false,
                include_file: "myfakelib.h".to_string(),
@@ -117,6 +129,7 @@ fn simple_func_with_default_value() {
                template_arguments_values: None,
                declaration_code: Some("bool func1 ( int x = 42 )".to_string()),
                is_ffi_whitelisted: false,
+               is_noexcept: false,
              });
 }
 
@@ -186,6 +199,7 @@ fn functions_with_class_arg() {
                                  has_default_value: false,
                                }],
                doc: None,
+               deprecation: None,
                inheritance_chain: Vec::new(),
                allows_variadic_arguments: false,
                include_file: "myfakelib.h".to_string(),
@@ -194,6 +208,7 @@ fn functions_with_class_arg() {
                template_arguments_values: None,
                declaration_code: Some("bool func1 ( Magic x )".to_string()),
                is_ffi_whitelisted: false,
+               is_noexcept: false,
              });
   assert_eq!(data.methods[1],
              CppMethod {
@@ -220,6 +235,7 @@ fn functions_with_class_arg() {
                                  has_default_value: false,
                                }],
                doc: None,
+               deprecation: None,
                inheritance_chain: Vec::new(),
                allows_variadic_arguments: false,
                include_file: "myfakelib.h".to_string(),
@@ -228,6 +244,7 @@ fn functions_with_class_arg() {
                template_arguments_values: None,
                declaration_code: Some("bool func1 ( Magic * x )".to_string()),
                is_ffi_whitelisted: false,
+               is_noexcept: false,
              });
   assert_eq!(data.methods[2],
              CppMethod {
@@ -254,6 +271,7 @@ fn functions_with_class_arg() {
                                  has_default_value: false,
                                }],
                doc: None,
+               deprecation: None,
                inheritance_chain: Vec::new(),
                allows_variadic_arguments: false,
                include_file: "myfakelib.h".to_string(),
@@ -262,6 +280,7 @@ fn functions_with_class_arg() {
                template_arguments_values: None,
                declaration_code: Some("bool func2 ( const Magic & )".to_string()),
                is_ffi_whitelisted: false,
+               is_noexcept: false,
              });
 }
 
@@ -299,6 +318,7 @@ fn variadic_func() {
                                  has_default_value: false,
                                }],
                doc: None,
+               deprecation: None,
                inheritance_chain: Vec::new(),
                allows_variadic_arguments: true,
                include_file: "myfakelib.h".to_string(),
@@ -307,6 +327,7 @@ fn variadic_func() {
                template_arguments_values: None,
                declaration_code: Some("int my_printf ( const char * format , ... )".to_string()),
                is_ffi_whitelisted: false,
+               is_noexcept: false,
              });
 }
 
@@ -343,6 +364,7 @@ fn free_template_func() {
                                  has_default_value: false,
                                }],
                doc: None,
+               deprecation: None,
                inheritance_chain: Vec::new(),
                allows_variadic_arguments: false,
                include_file: "myfakelib.h".to_string(),
@@ -354,6 +376,7 @@ fn free_template_func() {
                template_arguments_values: None,
                declaration_code: Some("template < typename T > T abs ( T value )".to_string()),
                is_ffi_whitelisted: false,
+               is_noexcept: false,
              });
 }
 
@@ -405,6 +428,7 @@ fn free_func_operator_sub() {
                                    has_default_value: false,
                                  }],
                  doc: None,
+                 deprecation: None,
                  inheritance_chain: Vec::new(),
                  allows_variadic_arguments: false,
                  include_file: "myfakelib.h".to_string(),
@@ -413,6 +437,7 @@ fn free_func_operator_sub() {
                  template_arguments_values: None,
                  declaration_code: Some("C1 operator - ( C1 a , C1 b )".to_string()),
                  is_ffi_whitelisted: false,
+                 is_noexcept: false,
                });
   }
 }
@@ -475,6 +500,7 @@ fn simple_class_method() {
                                  has_default_value: false,
                                }],
                doc: None,
+               deprecation: None,
                inheritance_chain: Vec::new(),
                allows_variadic_arguments: false,
                include_file: "myfakelib.h".to_string(),
@@ -483,6 +509,7 @@ fn simple_class_method() {
                template_arguments_values: None,
                declaration_code: Some("int func1 ( int x )".to_string()),
                is_ffi_whitelisted: false,
+               is_noexcept: false,
              });
 }
 
@@ -689,6 +716,7 @@ fn template_class_method() {
                                  has_default_value: false,
                                }],
                doc: None,
+               deprecation: None,
                inheritance_chain: Vec::new(),
                allows_variadic_arguments: false,
                include_file: "myfakelib.h".to_string(),
@@ -697,6 +725,7 @@ fn template_class_method() {
                template_arguments_values: None,
                declaration_code: Some("T get ( int index )".to_string()),
                is_ffi_whitelisted: false,
+               is_noexcept: false,
              });
 }
 
@@ -743,6 +772,21 @@ fn template_class_template_method() {
 
 }
 
+/// Returns underlying type selected by the compiler
+/// for an enum without negative values.
+fn default_enum_underlying_type() -> CppType {
+  CppType {
+    base: CppTypeBase::BuiltInNumeric(if cfg!(target_env = "msvc") {
+                                        CppBuiltInNumericType::Int
+                                      } else {
+                                        CppBuiltInNumericType::UInt
+                                      }),
+    indirection: CppTypeIndirection::None,
+    is_const: false,
+    is_const2: false,
+  }
+}
+
 #[test]
 fn simple_enum() {
   let data = run_parser("
@@ -758,12 +802,16 @@ fn simple_enum() {
                               name: "Good".to_string(),
                               value: 0,
                               doc: None,
+                              deprecation: None,
                             },
                             CppEnumValue {
                               name: "Bad".to_string(),
                               value: 1,
                               doc: None,
+                              deprecation: None,
                             }],
+               underlying_type: default_enum_underlying_type(),
+               is_scoped: false,
              });
 }
 
@@ -785,17 +833,102 @@ fn simple_enum2() {
                               name: "Good".to_string(),
                               value: 1,
                               doc: None,
+                              deprecation: None,
                             },
                             CppEnumValue {
                               name: "Bad".to_string(),
                               value: 2,
                               doc: None,
+                              deprecation: None,
                             },
                             CppEnumValue {
                               name: "Questionable".to_string(),
                               value: 3,
                               doc: None,
+                              deprecation: None,
+                            }],
+               underlying_type: default_enum_underlying_type(),
+               is_scoped: false,
+             });
+}
+
+#[test]
+fn enum_underlying_type() {
+  let data = run_parser("
+  typedef unsigned char uint8_t;
+  enum class Enum1 : uint8_t {
+    Good,
+    Bad
+  };
+  enum Enum2 : short {
+    Minus = -1
+  };
+  enum struct Enum3 : unsigned long long {
+    Big = 0xFFFFFFFFFFFFFFFFULL
+  };");
+  assert_eq!(data.types.len(), 3);
+  assert_eq!(data.types[0].name, "Enum1");
+  assert_eq!(data.types[0].kind,
+             CppTypeKind::Enum {
+               values: vec![CppEnumValue {
+                              name: "Good".to_string(),
+                              value: 0,
+                              doc: None,
+                              deprecation: None,
+                            },
+                            CppEnumValue {
+                              name: "Bad".to_string(),
+                              value: 1,
+                              doc: None,
+                              deprecation: None,
+                            }],
+               underlying_type: CppType {
+                 base: CppTypeBase::SpecificNumeric(CppSpecificNumericType {
+                                                      name: "uint8_t".to_string(),
+                                                      bits: 8,
+                                                      kind: CppSpecificNumericTypeKind::Integer {
+                                                        is_signed: false,
+                                                      },
+                                                    }),
+                 indirection: CppTypeIndirection::None,
+                 is_const: false,
+                 is_const2: false,
+               },
+               is_scoped: true,
+             });
+  assert_eq!(data.types[1].name, "Enum2");
+  assert_eq!(data.types[1].kind,
+             CppTypeKind::Enum {
+               values: vec![CppEnumValue {
+                              name: "Minus".to_string(),
+                              value: -1,
+                              doc: None,
+                              deprecation: None,
                             }],
+               underlying_type: CppType {
+                 base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Short),
+                 indirection: CppTypeIndirection::None,
+                 is_const: false,
+                 is_const2: false,
+               },
+               is_scoped: false,
+             });
+  assert_eq!(data.types[2].name, "Enum3");
+  assert_eq!(data.types[2].kind,
+             CppTypeKind::Enum {
+               values: vec![CppEnumValue {
+                              name: "Big".to_string(),
+                              value: -1,
+                              doc: None,
+                              deprecation: None,
+                            }],
+               underlying_type: CppType {
+                 base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::ULongLong),
+                 indirection: CppTypeIndirection::None,
+                 is_const: false,
+                 is_const2: false,
+               },
+               is_scoped: true,
              });
 }
 
@@ -1201,7 +1334,108 @@ fn non_type_template_parameter() {
   let data = run_parser("\
   template<int> struct QAtomicOpsSupport { enum { IsSupported = 0 }; };
   template<> struct QAtomicOpsSupport<4> { enum { IsSupported = 1 }; };");
-  assert!(data.types.is_empty());
+  assert_eq!(data.types.len(), 1);
+  assert_eq!(data.types[0].name, "QAtomicOpsSupport");
+  if let CppTypeKind::Class { ref template_arguments, .. } = data.types[0].kind {
+    assert_eq!(template_arguments,
+               &Some(TemplateArgumentsDeclaration {
+                       nested_level: 0,
+                       names: vec!["Type1".to_string()],
+                     }));
+  } else {
+    panic!("invalid type kind");
+  }
+}
+
+#[test]
+fn non_type_template_argument() {
+  let data = run_parser("
+  enum Mode { ModeA, ModeB };
+  template<class T, int N>
+  class Buffer {
+  public:
+    T at(int index);
+  };
+  template<bool B, Mode M> class Tag {};
+  Buffer<int, 16> f1();
+  Tag<true, ModeB> f2();
+  ");
+  assert_eq!(data.types.len(), 3);
+  assert_eq!(data.types[1].name, "Buffer");
+  if let CppTypeKind::Class { ref template_arguments, .. } = data.types[1].kind {
+    assert_eq!(template_arguments,
+               &Some(TemplateArgumentsDeclaration {
+                       nested_level: 0,
+                       names: vec!["T".to_string(), "N".to_string()],
+                     }));
+  } else {
+    panic!("invalid type kind");
+  }
+  assert_eq!(data.methods.len(), 3);
+  assert_eq!(&data.methods[0].name, "at");
+  assert_eq!(data.methods[0].class_membership.as_ref().unwrap().class_type,
+             CppTypeClassBase {
+               name: "Buffer".to_string(),
+               template_arguments: Some(vec![CppType {
+                                               indirection: CppTypeIndirection::None,
+                                               is_const: false,
+                                               is_const2: false,
+                                               base: CppTypeBase::TemplateParameter {
+                                                 nested_level: 0,
+                                                 index: 0,
+                                               },
+                                             },
+                                             CppType {
+                                               indirection: CppTypeIndirection::None,
+                                               is_const: false,
+                                               is_const2: false,
+                                               base: CppTypeBase::TemplateParameter {
+                                                 nested_level: 0,
+                                                 index: 1,
+                                               },
+                                             }]),
+             });
+  let value_type = |value| {
+    CppType {
+      indirection: CppTypeIndirection::None,
+      is_const: false,
+      is_const2: false,
+      base: CppTypeBase::TemplateArgumentValue(value),
+    }
+  };
+  assert_eq!(&data.methods[1].name, "f1");
+  assert_eq!(&data.methods[1].return_type,
+             &CppType {
+                indirection: CppTypeIndirection::None,
+                is_const: false,
+                is_const2: false,
+                base: CppTypeBase::Class(CppTypeClassBase {
+                                           name: "Buffer".to_string(),
+                                           template_arguments: Some(vec![CppType {
+                  indirection: CppTypeIndirection::None,
+                  is_const: false,
+                  is_const2: false,
+                  base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Int),
+                },
+                                                                         value_type(CppTemplateArgumentValue::Integer(16))]),
+                                         }),
+              });
+  assert_eq!(&data.methods[2].name, "f2");
+  assert_eq!(&data.methods[2].return_type,
+             &CppType {
+                indirection: CppTypeIndirection::None,
+                is_const: false,
+                is_const2: false,
+                base: CppTypeBase::Class(CppTypeClassBase {
+                                           name: "Tag".to_string(),
+                                           template_arguments:
+                                             Some(vec![value_type(CppTemplateArgumentValue::Bool(true)),
+                                                       value_type(CppTemplateArgumentValue::Enum {
+                                                                    enum_name: "Mode".to_string(),
+                                                                    value: 1,
+                                                                  })]),
+                                         }),
+              });
 }
 
 #[test]
@@ -1289,3 +1523,599 @@ fn template_class_with_base() {
     panic!("invalid type kind");
   }
 }
+
+#[test]
+fn noexcept_functions() {
+  let data = run_parser("int func1(int x) noexcept;
+  int func2(int x) throw();
+  int func3(int x) noexcept(false);
+  int func4(int x);
+  int func5(int x) noexcept(true);
+  int func6(int x) noexcept(sizeof(int) > 1);");
+  assert_eq!(data.methods.len(), 6);
+  assert_eq!(data.methods[0].name, "func1");
+  assert!(data.methods[0].is_noexcept);
+  assert_eq!(data.methods[1].name, "func2");
+  assert!(data.methods[1].is_noexcept);
+  assert_eq!(data.methods[2].name, "func3");
+  assert!(!data.methods[2].is_noexcept);
+  assert_eq!(data.methods[3].name, "func4");
+  assert!(!data.methods[3].is_noexcept);
+  assert_eq!(data.methods[4].name, "func5");
+  assert!(data.methods[4].is_noexcept);
+  // conditional specifications are only recognized if the condition is `true`
+  assert_eq!(data.methods[5].name, "func6");
+  assert!(!data.methods[5].is_noexcept);
+}
+
+#[test]
+fn requested_template_instantiations() {
+  let data = run_parser_with_instantiations("template<typename T> class Vector { public: T value; };
+class Item {};",
+                                            vec!["Vector<Item>".to_string(),
+                                                 "Vector<int>".to_string()]);
+  assert_eq!(data.requested_template_instantiations,
+             vec![CppType {
+                    indirection: CppTypeIndirection::None,
+                    is_const: false,
+                    is_const2: false,
+                    base: CppTypeBase::Class(CppTypeClassBase {
+                                               name: "Vector".to_string(),
+                                               template_arguments: Some(vec![CppType {
+                      indirection: CppTypeIndirection::None,
+                      is_const: false,
+                      is_const2: false,
+                      base: CppTypeBase::Class(CppTypeClassBase {
+                                                 name: "Item".to_string(),
+                                                 template_arguments: None,
+                                               }),
+                    }]),
+                                             }),
+                  },
+                  CppType {
+                    indirection: CppTypeIndirection::None,
+                    is_const: false,
+                    is_const2: false,
+                    base: CppTypeBase::Class(CppTypeClassBase {
+                                               name: "Vector".to_string(),
+                                               template_arguments: Some(vec![CppType {
+                      indirection: CppTypeIndirection::None,
+                      is_const: false,
+                      is_const2: false,
+                      base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Int),
+                    }]),
+                                             }),
+                  }]);
+}
+
+#[test]
+fn constants() {
+  let data = run_parser("#define MY_INT 42
+#define MY_NEGATIVE (-MY_INT - 1)
+#define MY_FLOAT 2.5f
+#define MY_STRING \"text\\n\" \"more\"
+#define MY_FUNCTION_LIKE(x) (x + 1)
+#define MY_EMPTY
+#define MY_BAD this is not an expression
+namespace ns {
+  constexpr unsigned int max_count = 1u << 4;
+  const int not_constexpr = 5;
+  class Item {
+  public:
+    static constexpr double scale = -0.125;
+  private:
+    static constexpr int hidden = 3;
+  };
+}");
+  let int_type = CppType {
+    indirection: CppTypeIndirection::None,
+    is_const: false,
+    is_const2: false,
+    base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Int),
+  };
+  let mut constants = data.constants.clone();
+  for constant in &mut constants {
+    assert!(constant.origin_location.is_some());
+    constant.origin_location = None;
+  }
+  constants.sort_by(|a, b| a.name.cmp(&b.name));
+  assert_eq!(constants,
+             vec![CppConstant {
+                    name: "MY_FLOAT".to_string(),
+                    is_macro: true,
+                    value_type: CppType {
+                      indirection: CppTypeIndirection::None,
+                      is_const: false,
+                      is_const2: false,
+                      base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Float),
+                    },
+                    value: CppConstantValue::Float("2.5".to_string()),
+                    include_file: "myfakelib.h".to_string(),
+                    origin_location: None,
+                  },
+                  CppConstant {
+                    name: "MY_INT".to_string(),
+                    is_macro: true,
+                    value_type: int_type.clone(),
+                    value: CppConstantValue::Integer(42),
+                    include_file: "myfakelib.h".to_string(),
+                    origin_location: None,
+                  },
+                  CppConstant {
+                    name: "MY_NEGATIVE".to_string(),
+                    is_macro: true,
+                    value_type: int_type.clone(),
+                    value: CppConstantValue::Integer(-43),
+                    include_file: "myfakelib.h".to_string(),
+                    origin_location: None,
+                  },
+                  CppConstant {
+                    name: "MY_STRING".to_string(),
+                    is_macro: true,
+                    value_type: CppType {
+                      indirection: CppTypeIndirection::Ptr,
+                      is_const: true,
+                      is_const2: false,
+                      base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Char),
+                    },
+                    value: CppConstantValue::String("text\nmore".to_string()),
+                    include_file: "myfakelib.h".to_string(),
+                    origin_location: None,
+                  },
+                  CppConstant {
+                    name: "ns::Item::scale".to_string(),
+                    is_macro: false,
+                    value_type: CppType {
+                      indirection: CppTypeIndirection::None,
+                      is_const: false,
+                      is_const2: false,
+                      base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Double),
+                    },
+                    value: CppConstantValue::Float("-0.125".to_string()),
+                    include_file: "myfakelib.h".to_string(),
+                    origin_location: None,
+                  },
+                  CppConstant {
+                    name: "ns::max_count".to_string(),
+                    is_macro: false,
+                    value_type: CppType {
+                      indirection: CppTypeIndirection::None,
+                      is_const: false,
+                      is_const2: false,
+                      base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::UInt),
+                    },
+                    value: CppConstantValue::Integer(16),
+                    include_file: "myfakelib.h".to_string(),
+                    origin_location: None,
+                  }]);
+}
+
+#[test]
+fn global_variables() {
+  let data = run_parser("namespace ns {
+  extern int counter;
+  extern const double ratio;
+  static int internal_value = 1;
+  class Item {
+  public:
+    static Item* default_item;
+  private:
+    static int hidden;
+  };
+}
+int top_level;
+extern \"C\" {
+  extern int c_value;
+}");
+  let mut variables = data.global_variables.clone();
+  for variable in &mut variables {
+    assert!(variable.origin_location.is_some());
+    variable.origin_location = None;
+  }
+  variables.sort_by(|a, b| a.name.cmp(&b.name));
+  assert_eq!(variables,
+             vec![CppGlobalVariable {
+                    name: "c_value".to_string(),
+                    variable_type: CppType {
+                      indirection: CppTypeIndirection::None,
+                      is_const: false,
+                      is_const2: false,
+                      base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Int),
+                    },
+                    class_type: None,
+                    include_file: "myfakelib.h".to_string(),
+                    origin_location: None,
+                  },
+                  CppGlobalVariable {
+                    name: "ns::Item::default_item".to_string(),
+                    variable_type: CppType {
+                      indirection: CppTypeIndirection::Ptr,
+                      is_const: false,
+                      is_const2: false,
+                      base: CppTypeBase::Class(CppTypeClassBase {
+                                                 name: "ns::Item".to_string(),
+                                                 template_arguments: None,
+                                               }),
+                    },
+                    class_type: Some(CppTypeClassBase {
+                                       name: "ns::Item".to_string(),
+                                       template_arguments: None,
+                                     }),
+                    include_file: "myfakelib.h".to_string(),
+                    origin_location: None,
+                  },
+                  CppGlobalVariable {
+                    name: "ns::counter".to_string(),
+                    variable_type: CppType {
+                      indirection: CppTypeIndirection::None,
+                      is_const: false,
+                      is_const2: false,
+                      base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Int),
+                    },
+                    class_type: None,
+                    include_file: "myfakelib.h".to_string(),
+                    origin_location: None,
+                  },
+                  CppGlobalVariable {
+                    name: "ns::ratio".to_string(),
+                    variable_type: CppType {
+                      indirection: CppTypeIndirection::None,
+                      is_const: true,
+                      is_const2: false,
+                      base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Double),
+                    },
+                    class_type: None,
+                    include_file: "myfakelib.h".to_string(),
+                    origin_location: None,
+                  },
+                  CppGlobalVariable {
+                    name: "top_level".to_string(),
+                    variable_type: CppType {
+                      indirection: CppTypeIndirection::None,
+                      is_const: false,
+                      is_const2: false,
+                      base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Int),
+                    },
+                    class_type: None,
+                    include_file: "myfakelib.h".to_string(),
+                    origin_location: None,
+                  }]);
+}
+
+#[test]
+fn array_types() {
+  let data = run_parser("
+  class Matrix {
+  public:
+    float m[4][4];
+    const int ids[2];
+  };
+  void set_name(const char name[32]);
+  ");
+  let float_type = CppType {
+    indirection: CppTypeIndirection::None,
+    is_const: false,
+    is_const2: false,
+    base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Float),
+  };
+  let int_type = CppType {
+    indirection: CppTypeIndirection::None,
+    is_const: true,
+    is_const2: false,
+    base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Int),
+  };
+  let row_type = CppType {
+    indirection: CppTypeIndirection::None,
+    is_const: false,
+    is_const2: false,
+    base: CppTypeBase::Array(CppArrayType {
+                               element_type: Box::new(float_type),
+                               size: 4,
+                             }),
+  };
+  assert_eq!(data.types.len(), 1);
+  if let CppTypeKind::Class { ref fields, .. } = data.types[0].kind {
+    assert_eq!(fields.len(), 2);
+    assert_eq!(fields[0].name, "m");
+    assert_eq!(fields[0].field_type,
+               CppType {
+                 indirection: CppTypeIndirection::None,
+                 is_const: false,
+                 is_const2: false,
+                 base: CppTypeBase::Array(CppArrayType {
+                                            element_type: Box::new(row_type),
+                                            size: 4,
+                                          }),
+               });
+    assert_eq!(fields[0].field_type.to_cpp_code(None).unwrap(),
+               "float[4][4]");
+    assert_eq!(fields[1].name, "ids");
+    assert_eq!(fields[1].field_type,
+               CppType {
+                 indirection: CppTypeIndirection::None,
+                 is_const: true,
+                 is_const2: false,
+                 base: CppTypeBase::Array(CppArrayType {
+                                            element_type: Box::new(int_type),
+                                            size: 2,
+                                          }),
+               });
+  } else {
+    panic!("invalid type kind");
+  }
+  assert_eq!(data.methods.len(), 1);
+  assert_eq!(data.methods[0].name, "set_name");
+  // clang may report the argument type as an array or as a decayed pointer,
+  // but FFI type is the same in both cases
+  let ffi_type = data.methods[0].arguments[0]
+    .argument_type
+    .to_cpp_ffi_type(CppTypeRole::NotReturnType)
+    .unwrap();
+  assert_eq!(ffi_type.ffi_type,
+             CppType {
+               indirection: CppTypeIndirection::Ptr,
+               is_const: true,
+               is_const2: false,
+               base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Char),
+             });
+}
+
+#[test]
+fn bitfields_and_anonymous_members() {
+  let data = run_parser("
+  class Item {
+  public:
+    unsigned int is_visible : 1;
+    unsigned int level : 7;
+    union {
+      int i;
+      float f;
+    };
+  private:
+    union {
+      int hidden;
+    };
+  };
+  ");
+  assert_eq!(data.types.len(), 1);
+  if let CppTypeKind::Class { ref fields, .. } = data.types[0].kind {
+    let uint_type = CppType {
+      indirection: CppTypeIndirection::None,
+      is_const: false,
+      is_const2: false,
+      base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::UInt),
+    };
+    let int_type = CppType {
+      indirection: CppTypeIndirection::None,
+      is_const: false,
+      is_const2: false,
+      base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Int),
+    };
+    assert_eq!(fields.len(), 5);
+    assert_eq!(fields[0].name, "is_visible");
+    assert_eq!(fields[0].field_type, uint_type);
+    assert_eq!(fields[0].bit_width, Some(1));
+    assert_eq!(fields[0].visibility, CppVisibility::Public);
+    assert_eq!(fields[1].name, "level");
+    assert_eq!(fields[1].field_type, uint_type);
+    assert_eq!(fields[1].bit_width, Some(7));
+
+    assert_eq!(fields[2].name, "i");
+    assert_eq!(fields[2].field_type, int_type);
+    assert_eq!(fields[2].bit_width, None);
+    assert_eq!(fields[2].visibility, CppVisibility::Public);
+    assert_eq!(fields[3].name, "f");
+    assert_eq!(fields[3].field_type,
+               CppType {
+                 indirection: CppTypeIndirection::None,
+                 is_const: false,
+                 is_const2: false,
+                 base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Float),
+               });
+    assert_eq!(fields[3].visibility, CppVisibility::Public);
+
+    assert_eq!(fields[4].name, "hidden");
+    assert_eq!(fields[4].field_type, int_type);
+    assert_eq!(fields[4].visibility, CppVisibility::Private);
+  } else {
+    panic!("invalid type kind");
+  }
+}
+
+#[test]
+fn type_aliases() {
+  let data = run_parser("namespace ns {
+  class Item {};
+  typedef Item* ItemPtr;
+  using Handle = const Item*;
+  typedef struct Point { int x; } Point;
+  class Widget {
+    typedef int Private;
+  public:
+    typedef unsigned int Id;
+  };
+  template<typename T>
+  class Box {
+  public:
+    typedef T value_type;
+  };
+  union Value {
+    typedef long Raw;
+    int i;
+  };
+}
+extern \"C\" {
+  typedef unsigned char c_byte;
+}
+typedef int qint32;");
+  let mut aliases = data.type_aliases.clone();
+  for alias in &mut aliases {
+    assert!(alias.origin_location.is_some());
+    alias.origin_location = None;
+  }
+  aliases.sort_by(|a, b| a.name.cmp(&b.name));
+  let item = CppTypeBase::Class(CppTypeClassBase {
+                                  name: "ns::Item".to_string(),
+                                  template_arguments: None,
+                                });
+  assert_eq!(aliases,
+             vec![CppTypeAlias {
+                    name: "c_byte".to_string(),
+                    target_type: CppType {
+                      indirection: CppTypeIndirection::None,
+                      is_const: false,
+                      is_const2: false,
+                      base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::UChar),
+                    },
+                    include_file: "myfakelib.h".to_string(),
+                    origin_location: None,
+                  },
+                  CppTypeAlias {
+                    name: "ns::Handle".to_string(),
+                    target_type: CppType {
+                      indirection: CppTypeIndirection::Ptr,
+                      is_const: true,
+                      is_const2: false,
+                      base: item.clone(),
+                    },
+                    include_file: "myfakelib.h".to_string(),
+                    origin_location: None,
+                  },
+                  CppTypeAlias {
+                    name: "ns::ItemPtr".to_string(),
+                    target_type: CppType {
+                      indirection: CppTypeIndirection::Ptr,
+                      is_const: false,
+                      is_const2: false,
+                      base: item.clone(),
+                    },
+                    include_file: "myfakelib.h".to_string(),
+                    origin_location: None,
+                  },
+                  CppTypeAlias {
+                    name: "ns::Value::Raw".to_string(),
+                    target_type: CppType {
+                      indirection: CppTypeIndirection::None,
+                      is_const: false,
+                      is_const2: false,
+                      base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Long),
+                    },
+                    include_file: "myfakelib.h".to_string(),
+                    origin_location: None,
+                  },
+                  CppTypeAlias {
+                    name: "ns::Widget::Id".to_string(),
+                    target_type: CppType {
+                      indirection: CppTypeIndirection::None,
+                      is_const: false,
+                      is_const2: false,
+                      base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::UInt),
+                    },
+                    include_file: "myfakelib.h".to_string(),
+                    origin_location: None,
+                  }]);
+}
+
+#[test]
+fn deprecated_entities() {
+  let data = run_parser("class [[deprecated(\"use Bar\")]] Foo {};
+class Bar {
+public:
+  [[deprecated]] void f();
+  __attribute__((deprecated(\"use \" \"h\"))) void g();
+  void h();
+  [[deprecated(\"use \\\"k2\\\"\\tor \\\\n\")]] void k();
+};
+enum Mode { Mode1, Mode2 __attribute__((deprecated(\"old\"))) };");
+  let type_deprecation = |name: &str| {
+    data
+      .types
+      .iter()
+      .find(|t| t.name == name)
+      .unwrap()
+      .deprecation
+      .clone()
+  };
+  assert_eq!(type_deprecation("Foo"),
+             Some(CppDeprecation { message: Some("use Bar".to_string()) }));
+  assert_eq!(type_deprecation("Bar"), None);
+  assert_eq!(type_deprecation("Mode"), None);
+  let method_deprecation = |name: &str| {
+    data
+      .methods
+      .iter()
+      .find(|m| m.name == name)
+      .unwrap()
+      .deprecation
+      .clone()
+  };
+  assert_eq!(method_deprecation("f"),
+             Some(CppDeprecation { message: None }));
+  assert_eq!(method_deprecation("g"),
+             Some(CppDeprecation { message: Some("use h".to_string()) }));
+  assert_eq!(method_deprecation("h"), None);
+  assert_eq!(method_deprecation("k"),
+             Some(CppDeprecation { message: Some("use \"k2\"\tor \\n".to_string()) }));
+  let mode = data.types.iter().find(|t| t.name == "Mode").unwrap();
+  if let CppTypeKind::Enum { ref values, .. } = mode.kind {
+    assert_eq!(values.len(), 2);
+    assert_eq!(values[0].deprecation, None);
+    assert_eq!(values[1].deprecation,
+               Some(CppDeprecation { message: Some("old".to_string()) }));
+  } else {
+    panic!("invalid type kind");
+  }
+}
+
+#[test]
+fn doc_comments() {
+  let data = run_parser("/// A simple class.
+///
+/// Longer description with \\c code and \\b bold text.
+class Foo {
+public:
+  /**
+   * \\brief Sets the value.
+   * \\param value New value
+   * \\param flags Additional flags
+   * \\return True if the value was changed
+   */
+  bool set(int value, int flags);
+  void undocumented();
+};
+enum Mode {
+  /// The first mode.
+  Mode1,
+  Mode2 ///< The second mode.
+};");
+  let foo = data.types.iter().find(|t| t.name == "Foo").unwrap();
+  let foo_doc = foo.doc.clone().unwrap();
+  assert_eq!(foo_doc.content,
+             CppDocContent::Markdown("A simple class.\n\nLonger description with `code` and \
+                                     **bold** text."
+                                       .to_string()));
+  let set = data.methods.iter().find(|m| m.name == "set").unwrap();
+  let set_doc = set.doc.clone().unwrap();
+  assert_eq!(set_doc.mismatched_declaration, None);
+  assert_eq!(set_doc.content,
+             CppDocContent::Markdown("Sets the value.\n\n**Parameters:**\n\n\
+                                     - `value`: New value\n\
+                                     - `flags`: Additional flags\n\n\
+                                     **Returns:** True if the value was changed"
+                                       .to_string()));
+  let undocumented = data
+    .methods
+    .iter()
+    .find(|m| m.name == "undocumented")
+    .unwrap();
+  assert_eq!(undocumented.doc, None);
+  let mode = data.types.iter().find(|t| t.name == "Mode").unwrap();
+  if let CppTypeKind::Enum { ref values, .. } = mode.kind {
+    assert_eq!(values[0].doc,
+               Some(CppDocContent::Markdown("The first mode.".to_string())));
+    assert_eq!(values[1].doc,
+               Some(CppDocContent::Markdown("The second mode.".to_string())));
+  } else {
+    panic!("invalid type kind");
+  }
+}
@@ -0,0 +1,122 @@
+extern crate tempdir;
+
+use cpp_parser::CppParserConfig;
+use cpp_template_checker::CppTemplateChecker;
+use cpp_method::CppMethodArgument;
+use cpp_type::*;
+use common::file_utils::{create_dir, create_file, PathBufWithAdded};
+use tests::cpp_method::{empty_regular_method, empty_membership};
+
+use std::path::PathBuf;
+
+#[test]
+fn check_template_methods() {
+  let dir = tempdir::TempDir::new("test_cpp_template_checker").unwrap();
+  let include_dir = dir.path().with_added("include");
+  create_dir(&include_dir).unwrap();
+  let include_name = "myfakelib.h";
+  {
+    let mut include_file = create_file(include_dir.with_added(&include_name)).unwrap();
+    include_file
+      .write("template<typename T> class Container {
+public:
+  void add(const T& value) { m_value = value; }
+  bool contains(const T& value) const { return m_value == value; }
+private:
+  T m_value;
+};
+class Comparable { public: bool operator==(const Comparable&) const; };
+class NotComparable {};
+")
+      .unwrap();
+  }
+  let parser_config = CppParserConfig {
+    include_paths: vec![include_dir],
+    include_directives: vec![PathBuf::from(include_name)],
+    target_include_paths: Vec::new(),
+    tmp_cpp_path: dir.path().with_added("1.cpp"),
+    name_blacklist: Vec::new(),
+    framework_paths: Vec::new(),
+    clang_arguments: Vec::new(),
+    template_instantiations: Vec::new(),
+    flags_typedefs: Vec::new(),
+    skip_deprecated: false,
+  };
+  let cache_path = dir.path().with_added("template_checks.json");
+  let mut checker =
+    CppTemplateChecker::new(parser_config.clone(), cache_path.clone(), false, true, None);
+
+  let method = |arg_name: &str, method_name: &str, is_const: bool| {
+    let arg_type = CppType {
+      indirection: CppTypeIndirection::Ref,
+      is_const: true,
+      is_const2: false,
+      base: CppTypeBase::Class(CppTypeClassBase {
+                                 name: arg_name.to_string(),
+                                 template_arguments: None,
+                               }),
+    };
+    let mut method = empty_regular_method();
+    method.name = method_name.to_string();
+    let mut membership = empty_membership("Container");
+    membership.class_type.template_arguments = Some(vec![CppType {
+                                                           is_const: false,
+                                                           indirection: CppTypeIndirection::None,
+                                                           ..arg_type.clone()
+                                                         }]);
+    membership.is_const = is_const;
+    method.class_membership = Some(membership);
+    method.arguments = vec![CppMethodArgument {
+                              name: "value".to_string(),
+                              argument_type: arg_type,
+                              has_default_value: false,
+                            }];
+    method
+  };
+  let methods = vec![method("Comparable", "add", false),
+                     method("Comparable", "contains", true),
+                     method("NotComparable", "add", false),
+                     method("NotComparable", "contains", true)];
+  let results = checker
+    .check(&methods.iter().collect::<Vec<_>>())
+    .unwrap();
+  assert_eq!(results.len(), 4);
+  assert!(results[0].is_none());
+  assert!(results[1].is_none());
+  assert!(results[2].is_none());
+  assert!(results[3].is_some());
+  assert!(cache_path.exists());
+
+  // make `NotComparable` comparable to see whether the results are taken from the cache
+  {
+    let mut include_file = create_file(include_dir.with_added(&include_name)).unwrap();
+    include_file
+      .write("template<typename T> class Container {
+public:
+  void add(const T& value) { m_value = value; }
+  bool contains(const T& value) const { return m_value == value; }
+private:
+  T m_value;
+};
+class Comparable { public: bool operator==(const Comparable&) const; };
+class NotComparable { public: bool operator==(const NotComparable&) const; };
+")
+      .unwrap();
+  }
+  let check_not_comparable = |parser_config: &CppParserConfig, cpp_lib_version: Option<&str>| {
+    let mut checker = CppTemplateChecker::new(parser_config.clone(),
+                                              cache_path.clone(),
+                                              true,
+                                              false,
+                                              cpp_lib_version.map(|s| s.to_string()));
+    checker.check(&[&methods[3]]).unwrap().remove(0)
+  };
+  assert!(check_not_comparable(&parser_config, None).is_some());
+  assert!(check_not_comparable(&parser_config, Some("1.0")).is_none());
+  let mut parser_config2 = parser_config.clone();
+  parser_config2.clang_arguments.push("-DCPP_TO_RUST_TEST".to_string());
+  assert!(check_not_comparable(&parser_config2, None).is_none());
+  let mut parser_config3 = parser_config.clone();
+  parser_config3.include_paths.push(dir.path().to_path_buf());
+  assert!(check_not_comparable(&parser_config3, None).is_none());
+}
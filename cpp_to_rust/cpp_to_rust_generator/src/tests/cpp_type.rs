@@ -1,6 +1,6 @@
 use cpp_type::{CppType, CppTypeRole, CppTypeIndirection, CppTypeBase, CppBuiltInNumericType,
                CppSpecificNumericTypeKind, CppTypeClassBase, CppSpecificNumericType,
-               CppFunctionPointerType};
+               CppFunctionPointerType, CppTemplateArgumentValue, CppArrayType};
 use caption_strategy::TypeCaptionStrategy;
 use cpp_ffi_data::CppIndirectionChange;
 
@@ -366,6 +366,39 @@ fn class_mut_ref() {
   assert!(!type1.needs_allocation_place_variants());
 }
 
+#[test]
+fn class_rvalue_ref() {
+  let type1 = CppType {
+    indirection: CppTypeIndirection::RValueRef,
+    is_const: false,
+    is_const2: false,
+    base: CppTypeBase::Class(CppTypeClassBase {
+                               name: "QString".to_string(),
+                               template_arguments: None,
+                             }),
+  };
+  assert_eq!(type1.to_cpp_code(None).unwrap(), "QString&&");
+  assert_eq!(type1.caption(TypeCaptionStrategy::Full).unwrap(),
+             "QString_rvalue_ref");
+
+  let ffi1 = type1.to_cpp_ffi_type(CppTypeRole::NotReturnType).unwrap();
+  assert_eq!(&ffi1.original_type, &type1);
+  assert_eq!(&ffi1.ffi_type,
+             &CppType {
+                indirection: CppTypeIndirection::Ptr,
+                is_const: false,
+                is_const2: false,
+                base: CppTypeBase::Class(CppTypeClassBase {
+                                           name: "QString".to_string(),
+                                           template_arguments: None,
+                                         }),
+              });
+  assert_eq!(ffi1.conversion,
+             CppIndirectionChange::RValueReferenceToPointer);
+  assert!(type1.to_cpp_ffi_type(CppTypeRole::ReturnType).is_err());
+  assert!(!type1.needs_allocation_place_variants());
+}
+
 #[test]
 fn class_mut_ptr() {
   let type1 = CppType {
@@ -509,6 +542,41 @@ fn nested_template_cpp_code() {
   assert!(!code.contains("<<"));
 }
 
+#[test]
+fn template_argument_values() {
+  let value_type = |value| {
+    CppType {
+      indirection: CppTypeIndirection::None,
+      is_const: false,
+      is_const2: false,
+      base: CppTypeBase::TemplateArgumentValue(value),
+    }
+  };
+  let type1 = CppType {
+    indirection: CppTypeIndirection::None,
+    is_const: false,
+    is_const2: false,
+    base: CppTypeBase::Class(CppTypeClassBase {
+                               name: "Buffer".to_string(),
+                               template_arguments:
+                                 Some(vec![value_type(CppTemplateArgumentValue::Integer(-16)),
+                                           value_type(CppTemplateArgumentValue::Bool(false)),
+                                           value_type(CppTemplateArgumentValue::Enum {
+                                                        enum_name: "Qt::Orientation".to_string(),
+                                                        value: 2,
+                                                      })]),
+                             }),
+  };
+  assert_eq!(type1.to_cpp_code(None).unwrap(),
+             "Buffer< -16, false, static_cast< Qt::Orientation >(2) >");
+  assert_eq!(type1.caption(TypeCaptionStrategy::Full).unwrap(),
+             "Buffer_minus_16_false_Qt_Orientation_2");
+  assert!(!type1.base.is_or_contains_template_parameter());
+  assert!(value_type(CppTemplateArgumentValue::Integer(1))
+            .to_cpp_ffi_type(CppTypeRole::NotReturnType)
+            .is_err());
+}
+
 #[test]
 fn qflags() {
   let args = Some(vec![CppType {
@@ -558,7 +626,8 @@ fn qflags() {
                 });
     assert_eq!(&ffi_type.ffi_type.to_cpp_code(None).unwrap(),
                "unsigned int");
-    assert_eq!(ffi_type.conversion, CppIndirectionChange::QFlagsToUInt);
+    assert_eq!(ffi_type.conversion,
+               CppIndirectionChange::FlagsToInt { enum_name: "Qt::AlignmentFlag".to_string() });
   }
   assert!(!type1.needs_allocation_place_variants());
 }
@@ -647,6 +716,65 @@ fn function1() {
   assert!(!type1.needs_allocation_place_variants());
 }
 
+#[test]
+fn array1() {
+  let float_type = CppType {
+    indirection: CppTypeIndirection::None,
+    is_const: false,
+    is_const2: false,
+    base: CppTypeBase::BuiltInNumeric(CppBuiltInNumericType::Float),
+  };
+  let row_type = CppType {
+    indirection: CppTypeIndirection::None,
+    is_const: false,
+    is_const2: false,
+    base: CppTypeBase::Array(CppArrayType {
+                               element_type: Box::new(float_type.clone()),
+                               size: 4,
+                             }),
+  };
+  let type1 = CppType {
+    indirection: CppTypeIndirection::None,
+    is_const: false,
+    is_const2: false,
+    base: CppTypeBase::Array(CppArrayType {
+                               element_type: Box::new(row_type.clone()),
+                               size: 3,
+                             }),
+  };
+  assert_eq!(type1.is_void(), false);
+  assert_eq!(type1.base.is_class(), false);
+  assert_eq!(type1.base.is_array(), true);
+  assert_eq!(type1.to_cpp_code(None).unwrap(), "float[3][4]");
+  assert_eq!(type1.to_cpp_declaration("m").unwrap(), "float m[3][4]");
+  assert_eq!(type1.base.caption(TypeCaptionStrategy::Full).unwrap(),
+             "float_array_4_array_3");
+  assert!(!type1.needs_allocation_place_variants());
+
+  let mut type1_ref = type1.with_const(true);
+  type1_ref.indirection = CppTypeIndirection::Ref;
+  assert!(type1_ref.is_const);
+  assert_eq!(type1_ref.to_cpp_code(None).unwrap(),
+             "const float (&)[3][4]");
+  assert_eq!(type1_ref.to_cpp_declaration("get_m()").unwrap(),
+             "const float (&get_m())[3][4]");
+
+  // arrays decay to pointers in arguments
+  let ffi1 = type1.to_cpp_ffi_type(CppTypeRole::NotReturnType).unwrap();
+  assert_eq!(&ffi1.original_type, &type1);
+  assert_eq!(ffi1.conversion, CppIndirectionChange::NoChange);
+  let mut row_ptr = row_type.clone();
+  row_ptr.indirection = CppTypeIndirection::Ptr;
+  assert_eq!(&ffi1.ffi_type, &row_ptr);
+  assert_eq!(ffi1.ffi_type.to_cpp_code(None).unwrap(), "float (*)[4]");
+  assert!(type1.to_cpp_ffi_type(CppTypeRole::ReturnType).is_err());
+
+  let ffi2 = type1_ref.to_cpp_ffi_type(CppTypeRole::ReturnType).unwrap();
+  assert_eq!(ffi2.conversion, CppIndirectionChange::ReferenceToPointer);
+  assert_eq!(ffi2.ffi_type.to_cpp_declaration("func()").unwrap(),
+             "const float (*func())[3][4]");
+}
+
 #[test]
 fn instantiate1() {
   let type1 = CppType {
@@ -1,9 +1,16 @@
 mod cpp_type;
 mod cpp_method;
 mod cpp_ffi_data;
+mod cpp_ffi_generator;
+mod cpp_code_generator;
 mod cpp_operator;
 mod cpp_parser;
+mod cpp_template_checker;
+mod rust_type;
 mod full_run;
+mod pipeline;
+mod rust_generator;
+mod rust_code_generator;
 
 use std::path::{Path, PathBuf};
 use common::file_utils::{create_dir_all, PathBufWithAdded};
@@ -0,0 +1,200 @@
+//! Helpers running the generator's stages on a small C++ header.
+
+use config::{Config, CrateProperties};
+use cpp_data::CppDataWithDeps;
+use cpp_ffi_data::CppFfiHeaderData;
+use common::file_utils::{create_dir, create_dir_all, create_file, file_to_string,
+                         PathBufWithAdded};
+use common::errors::fancy_unwrap;
+use common::string_utils::JoinWithSeparator;
+use launcher::{load_or_create_cpp_data, run_cpp_ffi_generator, rust_generator_input_data};
+use rust_code_generator::{self, RustCodeGeneratorConfig};
+use rust_generator::RustGeneratorOutput;
+use rust_info::{RustMethod, RustModule, RustTypeDeclaration, RustTypeDeclarationKind,
+                TraitImpl};
+use rust_type::RustType;
+use tests::TempTestDir;
+
+/// Creates a config for a library consisting of a single header
+/// containing `code`. The header and the cache are placed in `dir`.
+pub fn create_config(dir: &TempTestDir, code: &str) -> Config {
+  let include_dir = dir.path().with_added("include");
+  create_dir(&include_dir).unwrap();
+  let include_name = "myfakelib.h";
+  {
+    let mut include_file = create_file(include_dir.with_added(&include_name)).unwrap();
+    include_file.write(code).unwrap();
+    include_file.write("\n").unwrap();
+  }
+  let mut config = Config::new(dir.path().with_added("crate"),
+                               dir.path().with_added("cache"),
+                               CrateProperties::new("rust_fakelib", "0.0.0"));
+  create_dir(config.cache_dir_path()).unwrap();
+  config.add_include_path(include_dir);
+  config.add_include_directive(include_name);
+  config.add_cpp_parser_argument("-std=gnu++11");
+  config.set_write_cache(false);
+  config.set_quiet_mode(true);
+  config
+}
+
+/// Runs the C++ parser, the post-processor and the C++ wrapper generator.
+pub fn run_ffi_generator(config: &Config) -> (CppDataWithDeps<'static>, Vec<CppFfiHeaderData>) {
+  let cpp_data = fancy_unwrap(load_or_create_cpp_data(config, Vec::new()));
+  let headers = fancy_unwrap(run_cpp_ffi_generator(config, &cpp_data, "rust_fakelib_c".into()));
+  (cpp_data, headers)
+}
+
+/// Runs all stages of the generator up to the Rust generator.
+pub fn run_rust_generator(config: &Config) -> RustGeneratorOutput {
+  let (cpp_data, headers) = run_ffi_generator(config);
+  fancy_unwrap(rust_generator_input_data(config, &cpp_data, headers, &[]).run())
+}
+
+/// Runs all stages of the generator and returns the generated code
+/// of all Rust modules with each whitespace sequence replaced by a single space.
+pub fn run_rust_code_generator(config: &Config) -> String {
+  let output = run_rust_generator(config);
+  create_dir_all(config.output_dir_path()).unwrap();
+  let rust_config = RustCodeGeneratorConfig {
+    crate_properties: config.crate_properties().clone(),
+    output_path: config.output_dir_path().clone(),
+    crate_template_path: None,
+    cpp_ffi_lib_name: "rust_fakelib_c".into(),
+    cpp_lib_version: None,
+    generator_dependencies: &[],
+    write_dependencies_local_paths: false,
+  };
+  fancy_unwrap(rust_code_generator::run(rust_config, &output));
+  output
+    .modules
+    .iter()
+    .map(|module| {
+           file_to_string(config
+                            .output_dir_path()
+                            .with_added("src")
+                            .with_added(format!("{}.rs", module.name)))
+               .unwrap()
+         })
+    .join("\n")
+    .split_whitespace()
+    .join(" ")
+}
+
+/// Parses `code` and runs all stages of the generator
+/// up to the Rust generator with default settings.
+pub fn rust_output(code: &str) -> RustGeneratorOutput {
+  let dir = TempTestDir::new("test_generator_pipeline");
+  run_rust_generator(&create_config(&dir, code))
+}
+
+/// Returns the declaration of Rust type named `name`
+/// (the last part of its full name).
+pub fn find_type<'a>(output: &'a RustGeneratorOutput, name: &str) -> &'a RustTypeDeclaration {
+  fn find_in<'a>(modules: &'a [RustModule], name: &str) -> Option<&'a RustTypeDeclaration> {
+    for module in modules {
+      if let Some(r) = module
+           .types
+           .iter()
+           .find(|t| t.name.last_name().unwrap() == name) {
+        return Some(r);
+      }
+      if let Some(r) = find_in(&module.submodules, name) {
+        return Some(r);
+      }
+    }
+    None
+  }
+  find_in(&output.modules, name).unwrap_or_else(|| panic!("type not found: {}", name))
+}
+
+/// Returns trait implementations of Rust type named `name`.
+pub fn trait_impls<'a>(output: &'a RustGeneratorOutput, name: &str) -> &'a [TraitImpl] {
+  match find_type(output, name).kind {
+    RustTypeDeclarationKind::CppTypeWrapper { ref trait_impls, .. } => trait_impls,
+    _ => panic!("not a type wrapper: {}", name),
+  }
+}
+
+/// Returns full names of the traits implemented by Rust type named `name`
+/// (e.g. `"::std::ops::Add"`), in the order they are generated.
+pub fn trait_names(output: &RustGeneratorOutput, name: &str) -> Vec<String> {
+  trait_impls(output, name)
+    .iter()
+    .map(|x| if let RustType::Common { ref base, .. } = x.trait_type {
+           base.full_name(None)
+         } else {
+           panic!("unexpected trait type");
+         })
+    .collect()
+}
+
+/// Returns names of the methods in the `impl` block of Rust type named `name`.
+pub fn method_names(output: &RustGeneratorOutput, name: &str) -> Vec<String> {
+  match find_type(output, name).kind {
+    RustTypeDeclarationKind::CppTypeWrapper { ref methods, .. } => {
+      methods
+        .iter()
+        .map(|m| m.name.last_name().unwrap().clone())
+        .collect()
+    }
+    _ => panic!("not a type wrapper: {}", name),
+  }
+}
+
+/// Returns the free function named `name` (the last part of its full name)
+/// or `None` if there is no such function.
+pub fn try_find_function<'a>(output: &'a RustGeneratorOutput,
+                             name: &str)
+                             -> Option<&'a RustMethod> {
+  fn find_in<'a>(modules: &'a [RustModule], name: &str) -> Option<&'a RustMethod> {
+    for module in modules {
+      if let Some(r) = module
+           .functions
+           .iter()
+           .find(|f| f.name.last_name().unwrap() == name) {
+        return Some(r);
+      }
+      if let Some(r) = find_in(&module.submodules, name) {
+        return Some(r);
+      }
+    }
+    None
+  }
+  find_in(&output.modules, name)
+}
+
+/// Returns the free function named `name` (the last part of its full name).
+pub fn find_function<'a>(output: &'a RustGeneratorOutput, name: &str) -> &'a RustMethod {
+  try_find_function(output, name).unwrap_or_else(|| panic!("function not found: {}", name))
+}
+
+/// Returns trait implementations generated from free functions in all modules.
+pub fn module_trait_impls(output: &RustGeneratorOutput) -> Vec<&TraitImpl> {
+  fn add_from<'a>(modules: &'a [RustModule], result: &mut Vec<&'a TraitImpl>) {
+    for module in modules {
+      result.extend(module.trait_impls.iter());
+      add_from(&module.submodules, result);
+    }
+  }
+  let mut result = Vec::new();
+  add_from(&output.modules, &mut result);
+  result
+}
+
+/// Returns the method named `method_name` in the `impl` block
+/// of Rust type named `type_name`.
+pub fn find_method<'a>(output: &'a RustGeneratorOutput,
+                       type_name: &str,
+                       method_name: &str)
+                       -> &'a RustMethod {
+  match find_type(output, type_name).kind {
+    RustTypeDeclarationKind::CppTypeWrapper { ref methods, .. } => {
+      methods
+        .iter()
+        .find(|m| m.name.last_name().unwrap() == method_name)
+        .unwrap_or_else(|| panic!("method not found: {}::{}", type_name, method_name))
+    }
+    _ => panic!("not a type wrapper: {}", type_name),
+  }
+}
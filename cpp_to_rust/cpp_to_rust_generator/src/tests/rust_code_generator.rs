@@ -0,0 +1,118 @@
+use config::PointerAnnotation;
+use cpp_data::CppTypeAllocationPlace;
+use config::PointerAnnotationTarget::Argument;
+use common::file_utils::PathBufWithAdded;
+use tests::TempTestDir;
+use tests::pipeline::{create_config, run_rust_code_generator};
+
+#[test]
+fn enum_newtype() {
+  let dir = TempTestDir::new("test_rust_code_generator_enum_newtype");
+  let mut config = create_config(&dir,
+                                 "enum Color { Red, Green = 2, Lime = 2 };
+void paint(Color color);");
+  config.set_enums_as_newtypes(true);
+  let code = run_rust_code_generator(&config);
+  assert!(code.contains("#[repr(transparent)] pub struct Color("));
+  assert!(code.contains("pub const Red: Color = Color(0);"));
+  assert!(code.contains("pub const Green: Color = Color(2);"));
+  assert!(code.contains("pub const Lime: Color = Color(2);"));
+  // only the first variant with a value is used as its name
+  assert!(code.contains("2 => Some(\"Green\"),"));
+  assert!(!code.contains("Some(\"Lime\")"));
+  assert!(!code.contains("pub enum Color"));
+  // the newtype is passed to the FFI function as the underlying integer type
+  assert!(code.contains("::std::mem::transmute::<"));
+  assert!(code.contains(">(color)"));
+}
+
+const DEPRECATED_CODE: &'static str = "enum Mode { Mode1, Mode2 __attribute__((deprecated(\"old\"))) };
+[[deprecated(\"use paint\")]] void draw(int x);
+[[deprecated(\"use paint\")]] void draw(double x);
+[[deprecated]] void erase();";
+
+#[test]
+fn deprecated_items() {
+  let dir = TempTestDir::new("test_rust_code_generator_deprecated_items");
+  let config = create_config(&dir, DEPRECATED_CODE);
+  let code = run_rust_code_generator(&config);
+  assert!(code.contains("#[deprecated(note = \"old\")] Mode2 = 1"));
+  assert!(!code.contains("#[deprecated(note = \"old\")] Mode1"));
+  // the attribute of an overloaded function is shared by all variants
+  assert!(code.contains("#[deprecated(note = \"use paint\")] pub fn draw<"));
+  assert!(code.contains("#[deprecated] pub fn erase()"));
+}
+
+#[test]
+fn deprecated_newtype_constants() {
+  let dir = TempTestDir::new("test_rust_code_generator_deprecated_newtype_constants");
+  let mut config = create_config(&dir, DEPRECATED_CODE);
+  config.set_enums_as_newtypes(true);
+  let code = run_rust_code_generator(&config);
+  assert!(code.contains("#[deprecated(note = \"old\")] pub const Mode2: Mode = Mode(1);"));
+  assert!(!code.contains("#[deprecated(note = \"old\")] pub const Mode1"));
+}
+
+#[test]
+fn nullable_pointer_arguments() {
+  let dir = TempTestDir::new("test_rust_code_generator_nullable_pointer_arguments");
+  let mut config = create_config(&dir,
+                                 "class Item {
+public:
+  Item();
+};
+void set_current(Item* item);
+void set_parent(const Item* item);");
+  config.add_pointer_annotation("set_current(Item*)", Argument(0), PointerAnnotation::Nullable);
+  config.add_pointer_annotation("set_parent(const Item*)",
+                                Argument(0),
+                                PointerAnnotation::Nullable);
+  let code = run_rust_code_generator(&config);
+  assert!(code.contains("fn set_current(item: ::std::option::Option<&mut ::"));
+  assert!(code.contains("item.map_or(::std::ptr::null_mut(), |x| x as *mut "));
+  assert!(code.contains("fn set_parent(item: ::std::option::Option<&::"));
+  assert!(code.contains("item.map_or(::std::ptr::null(), |x| x as *const "));
+}
+
+#[test]
+fn debug_from_stream_output() {
+  let dir = TempTestDir::new("test_rust_code_generator_debug_from_stream_output");
+  let mut config = create_config(&dir,
+                                 "#include <ostream>
+class Color {
+public:
+  int red() const;
+};
+std::ostream& operator<<(std::ostream& stream, const Color& color);");
+  config.add_target_include_path(dir.path().with_added("include"));
+  let code = run_rust_code_generator(&config);
+  assert!(code.contains("impl ::std::fmt::Debug for ::"));
+  assert!(code.contains("let buffer = ::std::ptr::null_mut(); let buffer_size = 0;"));
+  assert!(code.contains("::ffi::rust_fakelib_c_Color_write_to_buffer("));
+  assert!(code.contains("let mut text = vec![0u8; size];"));
+  assert!(code.contains("f.write_str(&String::from_utf8_lossy(&text))"));
+  assert!(!code.contains("pub fn write_to_buffer("));
+}
+
+#[test]
+fn begin_end_container() {
+  let dir = TempTestDir::new("test_rust_code_generator_begin_end_container");
+  let mut config = create_config(&dir,
+                                 "class IntSet {
+public:
+  class const_iterator {
+  public:
+    const int& operator*() const;
+    const_iterator& operator++();
+    bool operator==(const const_iterator& other) const;
+  };
+  const_iterator begin() const;
+  const_iterator end() const;
+};");
+  config.set_type_allocation_place(CppTypeAllocationPlace::Stack, "IntSet::const_iterator");
+  let code = run_rust_code_generator(&config);
+  assert!(code.contains("impl ::cpp_utils::container::CppIterable for ::"));
+  assert!(code.contains("impl ::cpp_utils::container::CppIterator for ::"));
+  assert!(code.contains("type IntoIter = ::cpp_utils::container::BeginEndIter<'a, ::"));
+  assert!(code.contains("::cpp_utils::container::BeginEndIter::new(self)"));
+}
@@ -0,0 +1,893 @@
+use config::{Config, ContainerTraits, PointerAnnotation, PointerAnnotationTarget,
+             ReturnValueLifetime, UnsafePolicy};
+use cpp_data::CppTypeAllocationPlace;
+use rust_generator::RustGeneratorOutput;
+use rust_info::{RustMethodArguments, RustMethodUnsafeReason, RustModule, RustTypeDeclarationKind, RustTypeWrapperKind,
+                TraitImpl, TraitImplExtra};
+use rust_type::{CompleteType, RustType, RustTypeIndirection, RustToCTypeConversion};
+use common::file_utils::PathBufWithAdded;
+use tests::TempTestDir;
+use tests::pipeline::{create_config, run_rust_generator, trait_impls, trait_names,
+                      method_names, find_type, find_function, try_find_function,
+                      find_method, module_trait_impls};
+
+#[test]
+fn operator_traits() {
+  let dir = TempTestDir::new("test_rust_generator_operator_traits");
+  let mut config = create_config(&dir,
+                                 "class Point {
+public:
+  Point();
+  Point operator+(const Point& other) const;
+  void operator-(const Point& other) const;
+  Point& operator*=(int value);
+  bool operator==(const Point& other) const;
+  bool operator<(const Point& other);
+  int operator!() const;
+};");
+  config.set_type_allocation_place(CppTypeAllocationPlace::Stack, "Point");
+  let output = run_rust_generator(&config);
+  let traits = trait_names(&output, "Point");
+  assert!(traits.contains(&"::std::ops::Add".to_string()));
+  assert!(traits.contains(&"::std::ops::MulAssign".to_string()));
+  assert!(traits.contains(&"::std::cmp::PartialEq".to_string()));
+  assert!(traits.contains(&"::std::ops::Not".to_string()));
+  // `operator-` doesn't return a value and `operator<` takes `self` by mutable reference
+  assert!(!traits.contains(&"::std::ops::Sub".to_string()));
+  assert!(!traits.contains(&"::std::cmp::PartialOrd".to_string()));
+  let methods = method_names(&output, "Point");
+  assert!(methods.contains(&"op_sub".to_string()));
+  assert!(methods.contains(&"op_lt".to_string()));
+  assert!(!methods.contains(&"op_add".to_string()));
+
+  for trait_impl in trait_impls(&output, "Point") {
+    if let RustType::Common { ref base, ref generic_arguments, .. } = trait_impl.trait_type {
+      match base.last_name().unwrap().as_str() {
+        "Add" => {
+          // `const Point&` self argument makes the trait implemented for `&'a Point`
+          assert!(trait_impl.target_type.is_ref());
+          assert_eq!(trait_impl.associated_types.len(), 1);
+          assert_eq!(trait_impl.associated_types[0].name, "Output");
+          assert!(!trait_impl.associated_types[0].value.is_ref());
+        }
+        "MulAssign" => {
+          // returned reference to self is discarded
+          assert!(!trait_impl.target_type.is_ref());
+          assert_eq!(trait_impl.methods.len(), 1);
+          assert!(trait_impl.associated_types.is_empty());
+        }
+        "PartialEq" => {
+          assert!(!trait_impl.target_type.is_ref());
+          assert_eq!(generic_arguments.as_ref().map(|x| x.len()), Some(1));
+          assert_eq!(trait_impl.extra, None);
+        }
+        _ => {}
+      }
+    }
+  }
+}
+
+#[test]
+fn operator_traits_with_exceptions() {
+  let dir = TempTestDir::new("test_rust_generator_operator_exceptions");
+  let mut config = create_config(&dir,
+                                 "class Point {
+public:
+  Point();
+  Point operator+(const Point& other) const;
+  bool operator<(const Point& other) const;
+};");
+  config.set_type_allocation_place(CppTypeAllocationPlace::Stack, "Point");
+  config.set_catch_exceptions(true);
+  let output = run_rust_generator(&config);
+  let traits = trait_names(&output, "Point");
+  assert!(!traits.contains(&"::std::ops::Add".to_string()));
+  assert!(!traits.contains(&"::std::cmp::PartialOrd".to_string()));
+  let methods = method_names(&output, "Point");
+  assert!(methods.contains(&"op_add".to_string()));
+  assert!(methods.contains(&"op_lt".to_string()));
+  for trait_impl in trait_impls(&output, "Point") {
+    assert!(trait_impl.extra != Some(TraitImplExtra::PartialOrdFromLessThan));
+  }
+}
+
+const CONVERSION_OPERATORS_CODE: &'static str = "class Point {
+public:
+  Point();
+};
+
+class Value {
+public:
+  Value();
+  operator bool();
+  operator bool() const;
+  operator int() const;
+  operator const char*() const;
+  operator const Point&() const;
+  operator Point() const;
+};";
+
+/// Returns `From` trait implementations of `Value` generated from conversion operators.
+fn conversion_impls(output: &RustGeneratorOutput) -> Vec<&TraitImpl> {
+  trait_impls(output, "Value")
+    .iter()
+    .filter(|x| type_last_name(&x.trait_type) == "From")
+    .collect()
+}
+
+#[test]
+fn conversion_operators() {
+  let dir = TempTestDir::new("test_rust_generator_conversion_operators");
+  let mut config = create_config(&dir, CONVERSION_OPERATORS_CODE);
+  config.set_type_allocation_place(CppTypeAllocationPlace::Stack, "Point");
+  config.set_type_allocation_place(CppTypeAllocationPlace::Stack, "Value");
+  let output = run_rust_generator(&config);
+  let impls = conversion_impls(&output);
+  // conversions to pointers and references are not supported
+  let mut targets: Vec<_> = impls
+    .iter()
+    .map(|x| {
+           assert!(!x.target_type.is_ref());
+           type_last_name(&x.target_type)
+         })
+    .collect();
+  targets.sort();
+  assert_eq!(targets, vec!["Point", "bool", "c_int"]);
+  for trait_impl in impls {
+    assert_eq!(trait_impl.methods.len(), 1);
+    assert_eq!(trait_impl.methods[0].name.last_name().unwrap(), "from");
+    if let RustType::Common { generic_arguments: Some(ref args), .. } = trait_impl.trait_type {
+      assert_eq!(type_last_name(&args[0]), "Value");
+      // only the conversion from a const reference is kept
+      // if there are const and non-const overloads
+      assert!(args[0].is_ref());
+      assert!(args[0].is_const().unwrap());
+    } else {
+      panic!("From must have a generic argument");
+    }
+  }
+}
+
+#[test]
+fn conversion_operators_with_exceptions() {
+  let dir = TempTestDir::new("test_rust_generator_conversion_operators_exceptions");
+  let mut config = create_config(&dir, CONVERSION_OPERATORS_CODE);
+  config.set_type_allocation_place(CppTypeAllocationPlace::Stack, "Point");
+  config.set_type_allocation_place(CppTypeAllocationPlace::Stack, "Value");
+  config.set_catch_exceptions(true);
+  let output = run_rust_generator(&config);
+  // `From` can't return a `Result`, so the operators are only available as methods
+  assert!(conversion_impls(&output).is_empty());
+  assert!(method_names(&output, "Value").contains(&"as_bool".to_string()));
+}
+
+fn is_flaggable_enum(output: &RustGeneratorOutput, name: &str) -> bool {
+  match find_type(output, name).kind {
+    RustTypeDeclarationKind::CppTypeWrapper {
+      kind: RustTypeWrapperKind::Enum { is_flaggable, .. }, ..
+    } => is_flaggable,
+    _ => panic!("not an enum: {}", name),
+  }
+}
+
+fn first_argument_type(output: &RustGeneratorOutput, function_name: &str) -> RustType {
+  match find_function(output, function_name).arguments {
+    RustMethodArguments::SingleVariant(ref variant) => {
+      variant.arguments[0]
+        .argument_type
+        .rust_api_type
+        .clone()
+    }
+    _ => panic!("unexpected overloading: {}", function_name),
+  }
+}
+
+fn type_last_name(rust_type: &RustType) -> String {
+  match *rust_type {
+    RustType::Common { ref base, .. } => base.last_name().unwrap().clone(),
+    _ => panic!("unexpected type: {:?}", rust_type),
+  }
+}
+
+#[test]
+fn flags_typedef() {
+  let dir = TempTestDir::new("test_rust_generator_flags_typedef");
+  let mut config = create_config(&dir,
+                                 "enum Feature { FeatureA = 1, FeatureB = 2 };
+typedef unsigned int Features;
+Features operator|(Feature a, Feature b);
+void set_features(Features features);");
+  config.add_flags_typedef("Features", "Feature");
+  let output = run_rust_generator(&config);
+  assert!(is_flaggable_enum(&output, "Feature"));
+  assert_eq!(type_last_name(&first_argument_type(&output, "set_features")),
+             "Flags");
+  // `BitOr` is already implemented for flaggable enums through `EnumOrFlags`
+  for trait_impl in module_trait_impls(&output) {
+    if type_last_name(&trait_impl.target_type) == "Feature" {
+      assert!(type_last_name(&trait_impl.trait_type) != "BitOr");
+    }
+  }
+}
+
+#[test]
+fn flags_typedef_too_wide() {
+  let dir = TempTestDir::new("test_rust_generator_flags_typedef_too_wide");
+  let mut config = create_config(&dir,
+                                 "enum Big : long long { BigA = 1, BigB = 2 };
+typedef long long BigFlags;
+void set_big(BigFlags flags);");
+  config.add_flags_typedef("BigFlags", "Big");
+  let output = run_rust_generator(&config);
+  // `Flags` can't hold 64-bit values, so the typedef stays an integer
+  assert!(!is_flaggable_enum(&output, "Big"));
+  assert!(type_last_name(&first_argument_type(&output, "set_big")) != "Flags");
+}
+
+fn enum_int_type(output: &RustGeneratorOutput, name: &str) -> String {
+  match find_type(output, name).kind {
+    RustTypeDeclarationKind::CppTypeWrapper {
+      kind: RustTypeWrapperKind::Enum { ref int_type, .. }, ..
+    } => int_type.clone(),
+    _ => panic!("not an enum: {}", name),
+  }
+}
+
+#[test]
+fn enum_platform_dependent_types() {
+  let dir = TempTestDir::new("test_rust_generator_enum_platform_types");
+  let config = create_config(&dir,
+                             "enum Wide : long { WideA = -1 };
+enum UWide : unsigned long { UWideA = 1 };
+enum Big { BigA = 1, BigB = 0x100000000 };
+enum Letter : char { LetterA = 'a' };
+enum Switch : bool { SwitchOff, SwitchOn };
+Wide wide_value(Wide value);");
+  let output = run_rust_generator(&config);
+  let long_bits = if cfg!(windows) || cfg!(target_pointer_width = "32") {
+    32
+  } else {
+    64
+  };
+  assert_eq!(enum_int_type(&output, "Wide"), format!("i{}", long_bits));
+  assert_eq!(enum_int_type(&output, "UWide"), format!("u{}", long_bits));
+  if cfg!(target_env = "msvc") {
+    assert_eq!(enum_int_type(&output, "Big"), "i32");
+  } else {
+    assert_eq!(enum_int_type(&output, "Big"), "u64");
+  }
+  let char_type = if ::std::os::raw::c_char::min_value() == 0 {
+    "u8"
+  } else {
+    "i8"
+  };
+  assert_eq!(enum_int_type(&output, "Letter"), char_type);
+  assert_eq!(enum_int_type(&output, "Switch"), "u8");
+
+  // enum values are passed to FFI functions as the underlying integer type
+  match find_function(&output, "wide_value").arguments {
+    RustMethodArguments::SingleVariant(ref variant) => {
+      for complete_type in &[&variant.arguments[0].argument_type, &variant.return_type] {
+        assert_eq!(type_last_name(&complete_type.rust_api_type), "Wide");
+        assert_eq!(type_last_name(&complete_type.rust_ffi_type),
+                   format!("i{}", long_bits));
+        assert_eq!(complete_type.rust_api_to_c_conversion,
+                   RustToCTypeConversion::EnumToInt);
+      }
+    }
+    _ => panic!("unexpected overloading: wide_value"),
+  }
+}
+
+fn return_type(output: &RustGeneratorOutput, function_name: &str) -> RustType {
+  match find_function(output, function_name).arguments {
+    RustMethodArguments::SingleVariant(ref variant) => variant.return_type.rust_api_type.clone(),
+    _ => panic!("unexpected overloading: {}", function_name),
+  }
+}
+
+#[test]
+fn type_aliases() {
+  let dir = TempTestDir::new("test_rust_generator_type_aliases");
+  let mut config = create_config(&dir,
+                                 "class Item {
+public:
+  Item();
+};
+typedef Item* ItemPtr;
+typedef long long Id;
+typedef int& IntRef;
+Id make_id();
+void use_ids(Id id, const Id* ids, ItemPtr item);");
+  config.add_preserved_type_alias("Id");
+  let output = run_rust_generator(&config);
+
+  fn add_names(modules: &[RustModule], names: &mut Vec<String>) {
+    for module in modules {
+      names.extend(module
+                     .type_aliases
+                     .iter()
+                     .map(|alias| alias.name.last_name().unwrap().clone()));
+      add_names(&module.submodules, names);
+    }
+  }
+  let mut alias_names = Vec::new();
+  add_names(&output.modules, &mut alias_names);
+  alias_names.sort();
+  // aliases of references are not supported
+  assert_eq!(alias_names, vec!["Id".to_string(), "ItemPtr".to_string()]);
+
+  assert_eq!(type_last_name(&return_type(&output, "make_id")), "Id");
+  let arguments = match find_function(&output, "use_ids").arguments {
+    RustMethodArguments::SingleVariant(ref variant) => {
+      variant
+        .arguments
+        .iter()
+        .map(|arg| arg.argument_type.rust_api_type.clone())
+        .collect::<Vec<_>>()
+    }
+    _ => panic!("unexpected overloading"),
+  };
+  assert_eq!(type_last_name(&arguments[0]), "Id");
+  // indirection of the original type is kept
+  assert_eq!(type_last_name(&arguments[1]), "Id");
+  if let RustType::Common { ref indirection, ref is_const, .. } = arguments[1] {
+    assert_eq!(indirection, &RustTypeIndirection::Ptr);
+    assert!(*is_const);
+  }
+  // `ItemPtr` is not preserved, so the target type is used
+  assert_eq!(type_last_name(&arguments[2]), "Item");
+  if let RustType::Common { ref indirection, .. } = arguments[2] {
+    assert_eq!(indirection, &RustTypeIndirection::Ptr);
+  }
+}
+
+const POINTER_ANNOTATIONS_CODE: &'static str = "class Item {
+public:
+  Item();
+};
+class Handle {
+public:
+  Handle();
+private:
+  ~Handle();
+};
+class Container {
+public:
+  Container();
+  void setCurrent(Item* item);
+  void setParent(const Item* item);
+  Item* current() const;
+  Item* first();
+  void take(Item* item);
+  Item* release();
+  void takeConst(const Item* item);
+  void takeHandle(Handle* handle);
+};";
+
+/// Adds a pointer annotation to a method of `Container`.
+fn annotate(config: &mut Config,
+            signature: &str,
+            target: PointerAnnotationTarget,
+            annotation: PointerAnnotation) {
+  config.add_pointer_annotation(format!("Container::{}", signature), target, annotation);
+}
+
+fn pointer_annotations_config(dir: &TempTestDir) -> Config {
+  use config::PointerAnnotation::*;
+  use config::PointerAnnotationTarget::*;
+  let mut config = create_config(dir, POINTER_ANNOTATIONS_CODE);
+  annotate(&mut config, "setCurrent(Item*)", Argument(0), Nullable);
+  annotate(&mut config, "setParent(const Item*)", Argument(0), Nullable);
+  annotate(&mut config, "current() const", ReturnValue, Nullable);
+  annotate(&mut config, "first()", ReturnValue, Borrowed);
+  annotate(&mut config, "take(Item*)", Argument(0), Owned);
+  annotate(&mut config, "release()", ReturnValue, Owned);
+  annotate(&mut config, "takeConst(const Item*)", Argument(0), Owned);
+  annotate(&mut config, "takeHandle(Handle*)", Argument(0), Owned);
+  config
+}
+
+fn single_variant_types(output: &RustGeneratorOutput,
+                        method_name: &str)
+                        -> (Vec<CompleteType>, CompleteType) {
+  match find_method(output, "Container", method_name).arguments {
+    RustMethodArguments::SingleVariant(ref variant) => {
+      (variant
+         .arguments
+         .iter()
+         .map(|arg| arg.argument_type.clone())
+         .collect(),
+       variant.return_type.clone())
+    }
+    _ => panic!("unexpected overloading: {}", method_name),
+  }
+}
+
+/// Checks that `rust_type` is `Option<&T>` or `Option<&mut T>`
+/// and returns `T`'s constness.
+fn option_ref_is_const(rust_type: &RustType) -> bool {
+  assert_eq!(type_last_name(rust_type), "Option");
+  match *rust_type {
+    RustType::Common { generic_arguments: Some(ref args), .. } => {
+      assert_eq!(args.len(), 1);
+      assert_eq!(type_last_name(&args[0]), "Item");
+      assert!(args[0].is_ref());
+      args[0].is_const().unwrap()
+    }
+    _ => panic!("Option must have a generic argument"),
+  }
+}
+
+#[test]
+fn pointer_annotations() {
+  let dir = TempTestDir::new("test_rust_generator_pointer_annotations");
+  let output = run_rust_generator(&pointer_annotations_config(&dir));
+
+  let (args, _) = single_variant_types(&output, "set_current");
+  assert_eq!(args[1].rust_api_to_c_conversion,
+             RustToCTypeConversion::OptionRefToPtr);
+  assert!(!option_ref_is_const(&args[1].rust_api_type));
+  let (args, _) = single_variant_types(&output, "set_parent");
+  assert!(option_ref_is_const(&args[1].rust_api_type));
+  let (_, return_type) = single_variant_types(&output, "current");
+  assert_eq!(return_type.rust_api_to_c_conversion,
+             RustToCTypeConversion::OptionRefToPtr);
+  option_ref_is_const(&return_type.rust_api_type);
+
+  let (_, return_type) = single_variant_types(&output, "first");
+  assert_eq!(return_type.rust_api_to_c_conversion,
+             RustToCTypeConversion::RefToPtr);
+  assert!(return_type.rust_api_type.is_ref());
+  assert_eq!(type_last_name(&return_type.rust_api_type), "Item");
+
+  let (args, _) = single_variant_types(&output, "take");
+  assert_eq!(args[1].rust_api_to_c_conversion,
+             RustToCTypeConversion::CppBoxIntoPtr);
+  assert_eq!(type_last_name(&args[1].rust_api_type), "CppBox");
+  let (_, return_type) = single_variant_types(&output, "release");
+  assert_eq!(return_type.rust_api_to_c_conversion,
+             RustToCTypeConversion::CppBoxToPtr);
+  assert_eq!(type_last_name(&return_type.rust_api_type), "CppBox");
+
+  // ownership of const and non-deletable objects can't be transferred,
+  // so methods with such annotations are skipped
+  let methods = method_names(&output, "Container");
+  assert!(!methods.contains(&"take_const".to_string()));
+  assert!(!methods.contains(&"take_handle".to_string()));
+}
+
+fn function_types(output: &RustGeneratorOutput,
+                  function_name: &str)
+                  -> (Vec<CompleteType>, CompleteType) {
+  match find_function(output, function_name).arguments {
+    RustMethodArguments::SingleVariant(ref variant) => {
+      (variant
+         .arguments
+         .iter()
+         .map(|arg| arg.argument_type.clone())
+         .collect(),
+       variant.return_type.clone())
+    }
+    _ => panic!("unexpected overloading: {}", function_name),
+  }
+}
+
+#[test]
+fn return_value_lifetimes() {
+  let dir = TempTestDir::new("test_rust_generator_return_value_lifetimes");
+  let mut config = create_config(&dir,
+                                 "class Item {
+public:
+  Item();
+};
+
+class Container {
+public:
+  Container();
+  const Item& item() const;
+};
+
+const Item& max_item(const Item& a, const Item& b);
+const Item& default_item();
+const Item& second_item(const Item& a, const Item& b);
+const Item& checked_item(int index, const Item& a);
+const Item& global_item();");
+  config.set_return_value_lifetime("second_item(const Item&, const Item&)",
+                                   ReturnValueLifetime::OfArgument(1));
+  config.set_return_value_lifetime("checked_item(int, const Item&)",
+                                   ReturnValueLifetime::OfArgument(0));
+  config.set_return_value_lifetime("global_item()", ReturnValueLifetime::Static);
+  let output = run_rust_generator(&config);
+  let l0 = Some("l0".to_string());
+
+  // the reference returned by a method borrows from `self`
+  match find_method(&output, "Container", "item").arguments {
+    RustMethodArguments::SingleVariant(ref variant) => {
+      assert_eq!(variant.arguments.len(), 1);
+      assert_eq!(variant.arguments[0].name, "self");
+      assert_eq!(variant.arguments[0].argument_type.rust_api_type.lifetime(),
+                 l0.as_ref());
+      assert_eq!(variant.return_type.rust_api_type.lifetime(), l0.as_ref());
+      assert_eq!(type_last_name(&variant.return_type.rust_api_type), "Item");
+    }
+    _ => panic!("unexpected overloading: item"),
+  }
+
+  // all reference arguments of a free function share the lifetime of the return value
+  let (args, return_type) = function_types(&output, "max_item");
+  assert_eq!(args.len(), 2);
+  assert_eq!(args[0].rust_api_type.lifetime(), l0.as_ref());
+  assert_eq!(args[1].rust_api_type.lifetime(), l0.as_ref());
+  assert_eq!(return_type.rust_api_type.lifetime(), l0.as_ref());
+
+  // there is nothing to borrow from, so the raw pointer is kept
+  let (_, return_type) = function_types(&output, "default_item");
+  assert!(!return_type.rust_api_type.is_ref());
+  assert_eq!(return_type.rust_api_type, return_type.rust_ffi_type);
+  assert_eq!(return_type.rust_api_to_c_conversion,
+             RustToCTypeConversion::None);
+
+  // only the configured argument is borrowed from
+  let (args, return_type) = function_types(&output, "second_item");
+  assert_eq!(args[0].rust_api_type.lifetime(), None);
+  assert_eq!(args[1].rust_api_type.lifetime(), l0.as_ref());
+  assert_eq!(return_type.rust_api_type.lifetime(), l0.as_ref());
+
+  // the return value can't borrow from an integer, so the function is skipped
+  assert!(try_find_function(&output, "checked_item").is_none());
+
+  let (args, return_type) = function_types(&output, "global_item");
+  assert!(args.is_empty());
+  assert!(return_type.rust_api_type.is_ref());
+  assert_eq!(return_type.rust_api_type.lifetime(),
+             Some(&"static".to_string()));
+}
+
+/// Runs the generator with the unsafe `policy` and returns
+/// the unsafe reasons of `Point`'s methods `x`, `set_x` and `move_by`
+/// and the traits implemented by `Point`.
+fn unsafe_policy_output(policy: UnsafePolicy)
+                        -> (Vec<Option<RustMethodUnsafeReason>>, Vec<String>) {
+  let dir = TempTestDir::new(&format!("test_rust_generator_unsafe_policy_{:?}", policy));
+  let mut config = create_config(&dir,
+                                 "class Point {
+public:
+  Point();
+  Point(const Point& other);
+  int x() const;
+  void setX(int x);
+  void moveBy(int* delta);
+  Point operator+(const Point& other) const;
+};");
+  config.set_type_allocation_place(CppTypeAllocationPlace::Stack, "Point");
+  config.set_unsafe_policy(policy);
+  config.add_safe_method("Point::x");
+  config.add_safe_method("Point::moveBy(int*)");
+  let output = run_rust_generator(&config);
+  let reasons = ["x", "set_x", "move_by"]
+    .iter()
+    .map(|name| {
+           let method = find_method(&output, "Point", name);
+           assert_eq!(method.variant_docs.len(), 1);
+           let reason = method.variant_docs[0].unsafe_reason.clone();
+           assert_eq!(method.is_unsafe, reason.is_some());
+           reason
+         })
+    .collect();
+  for trait_impl in trait_impls(&output, "Point") {
+    for method in &trait_impl.methods {
+      assert!(!method.is_unsafe);
+    }
+  }
+  (reasons, trait_names(&output, "Point"))
+}
+
+fn raw_pointer_argument() -> Option<RustMethodUnsafeReason> {
+  Some(RustMethodUnsafeReason::RawPointerArgument("delta".to_string()))
+}
+
+#[test]
+fn unsafe_policy_heuristic() {
+  let (reasons, traits) = unsafe_policy_output(UnsafePolicy::Heuristic);
+  assert_eq!(reasons, vec![None, None, raw_pointer_argument()]);
+  assert!(traits.contains(&"::std::ops::Add".to_string()));
+  assert!(traits.contains(&"::std::clone::Clone".to_string()));
+}
+
+#[test]
+fn unsafe_policy_all_unsafe() {
+  let (reasons, traits) = unsafe_policy_output(UnsafePolicy::AllUnsafe);
+  let all_unsafe = Some(RustMethodUnsafeReason::AllUnsafePolicy);
+  assert_eq!(reasons,
+             vec![all_unsafe.clone(), all_unsafe.clone(), all_unsafe]);
+  // trait implementations are not affected by the policy
+  assert!(traits.contains(&"::std::ops::Add".to_string()));
+  assert!(traits.contains(&"::std::clone::Clone".to_string()));
+}
+
+#[test]
+fn unsafe_policy_heuristic_with_allowlist() {
+  let (reasons, traits) = unsafe_policy_output(UnsafePolicy::HeuristicWithAllowlist);
+  // methods taking raw pointers are unsafe even if they are allowed
+  assert_eq!(reasons,
+             vec![None,
+                  Some(RustMethodUnsafeReason::NotInSafeMethods),
+                  raw_pointer_argument()]);
+  assert!(traits.contains(&"::std::ops::Add".to_string()));
+  assert!(traits.contains(&"::std::clone::Clone".to_string()));
+}
+
+#[test]
+fn special_constructor_traits() {
+  let dir = TempTestDir::new("test_rust_generator_special_constructors");
+  let mut config = create_config(&dir,
+                                 "class Point {
+public:
+  Point();
+  Point(const Point& other);
+};
+class Buffer {
+public:
+  Buffer();
+  Buffer(const Buffer& other);
+};
+class Item {
+public:
+  Item(const Item& other, int* copied_count);
+};");
+  config.set_type_allocation_place(CppTypeAllocationPlace::Stack, "Point");
+  config.set_type_allocation_place(CppTypeAllocationPlace::Heap, "Buffer");
+  config.set_type_allocation_place(CppTypeAllocationPlace::Stack, "Item");
+  let output = run_rust_generator(&config);
+  let point_traits = trait_names(&output, "Point");
+  assert!(point_traits.contains(&"::std::clone::Clone".to_string()));
+  assert!(point_traits.contains(&"::std::default::Default".to_string()));
+  let clone_impl = trait_impls(&output, "Point")
+    .iter()
+    .find(|x| type_last_name(&x.trait_type) == "Clone")
+    .unwrap();
+  assert_eq!(clone_impl.methods.len(), 1);
+  assert_eq!(clone_impl.methods[0].name.last_name().unwrap(), "clone");
+  assert!(!clone_impl.target_type.is_ref());
+
+  // `Default` can't be implemented for types returned in `CppBox`,
+  // and the copy constructor makes `CppBox<Buffer>` cloneable
+  let buffer_traits = trait_names(&output, "Buffer");
+  assert!(buffer_traits.contains(&"::cpp_utils::CppBoxClone".to_string()));
+  assert!(!buffer_traits.contains(&"::std::clone::Clone".to_string()));
+  assert!(!buffer_traits.contains(&"::std::default::Default".to_string()));
+
+  // a constructor with additional arguments is not a copy constructor
+  assert!(!trait_names(&output, "Item").contains(&"::std::clone::Clone".to_string()));
+}
+
+/// Returns `extra` fields of `Debug` implementations of Rust type named `name`.
+fn debug_impl_extras(output: &RustGeneratorOutput, name: &str) -> Vec<Option<TraitImplExtra>> {
+  trait_impls(output, name)
+    .iter()
+    .filter(|x| type_last_name(&x.trait_type) == "Debug")
+    .map(|x| x.extra.clone())
+    .collect()
+}
+
+#[test]
+fn debug_traits() {
+  let dir = TempTestDir::new("test_rust_generator_debug_traits");
+  let mut config = create_config(&dir,
+                                 "#include <ostream>
+class Url {
+public:
+  int id() const;
+};
+class Path {
+public:
+  void print() const;
+};
+class Tag {
+public:
+  int id() const;
+};
+class Color {
+public:
+  int red() const;
+};
+std::ostream& operator<<(std::ostream& stream, const Tag& tag);
+std::ostream& operator<<(std::ostream& stream, const Color& color);");
+  config.add_target_include_path(dir.path().with_added("include"));
+  config.add_debug_method("Url::id() const");
+  config.add_debug_method("Path::print() const");
+  config.add_debug_method("Tag::id() const");
+  let output = run_rust_generator(&config);
+  assert_eq!(debug_impl_extras(&output, "Url"),
+             vec![Some(TraitImplExtra::DebugFromMethod)]);
+  // the configured method must return a value
+  assert!(debug_impl_extras(&output, "Path").is_empty());
+  // the configured method is preferred over `operator<<`
+  assert_eq!(debug_impl_extras(&output, "Tag"),
+             vec![Some(TraitImplExtra::DebugFromMethod)]);
+  assert_eq!(debug_impl_extras(&output, "Color"),
+             vec![Some(TraitImplExtra::DebugFromStream)]);
+  let debug_impl = trait_impls(&output, "Color")
+    .iter()
+    .find(|x| type_last_name(&x.trait_type) == "Debug")
+    .unwrap();
+  assert!(!debug_impl.target_type.is_ref());
+  assert_eq!(debug_impl.methods.len(), 1);
+  assert_eq!(debug_impl.methods[0].name.last_name().unwrap(), "fmt");
+  assert!(!debug_impl.methods[0].is_unsafe);
+  // the fictional method used by `Debug` is not exposed
+  assert!(!method_names(&output, "Color").contains(&"write_to_buffer".to_string()));
+  assert!(!method_names(&output, "Tag").contains(&"write_to_buffer".to_string()));
+}
+
+#[test]
+fn eq_classes() {
+  let dir = TempTestDir::new("test_rust_generator_eq_classes");
+  let mut config = create_config(&dir,
+                                 "class Id {
+public:
+  bool operator==(const Id& other) const;
+  int value;
+};
+class Name {
+public:
+  bool operator==(const Name& other) const;
+  int value;
+};
+class Size {
+public:
+  bool operator==(const Size& other) const;
+  double width;
+};");
+  for name in &["Id", "Name", "Size"] {
+    config.set_type_allocation_place(CppTypeAllocationPlace::Stack, *name);
+  }
+  config.add_eq_class("Id");
+  config.add_eq_class("Size");
+  let output = run_rust_generator(&config);
+  for name in &["Id", "Name", "Size"] {
+    assert!(trait_names(&output, name).contains(&"::std::cmp::PartialEq".to_string()));
+  }
+  assert!(trait_names(&output, "Id").contains(&"::std::cmp::Eq".to_string()));
+  // `Eq` is not implemented by default
+  assert!(!trait_names(&output, "Name").contains(&"::std::cmp::Eq".to_string()));
+  // floating point values are not `Eq` even if the class is enabled
+  assert!(!trait_names(&output, "Size").contains(&"::std::cmp::Eq".to_string()));
+}
+
+/// Returns the number of implementations of the trait named `trait_name`
+/// (e.g. `"::std::iter::IntoIterator"`) by Rust type named `name`.
+fn trait_count(output: &RustGeneratorOutput, name: &str, trait_name: &str) -> usize {
+  trait_names(output, name)
+    .iter()
+    .filter(|x| *x == trait_name)
+    .count()
+}
+
+#[test]
+fn container_traits() {
+  let dir = TempTestDir::new("test_rust_generator_container_traits");
+  let mut config = create_config(&dir,
+                                 "class IntList {
+public:
+  IntList();
+  int size() const;
+  const int& at(int index) const;
+  int& operator[](int index);
+  void append(const int& value);
+};
+class ReadOnlyList {
+public:
+  int size() const;
+  const int& at(int index) const;
+};
+class IntSet {
+public:
+  class const_iterator {
+  public:
+    const int& operator*() const;
+    const_iterator& operator++();
+    bool operator==(const const_iterator& other) const;
+  };
+  IntSet();
+  const_iterator begin() const;
+  const_iterator end() const;
+  void append(const int& value);
+};
+class IntQueue {
+public:
+  IntQueue();
+  int size() const;
+  const int& at(int index) const;
+  void append(const int& value);
+};
+class Matrix {
+public:
+  int size() const;
+  const int& at(int index) const;
+};");
+  for name in &["IntList", "IntSet", "IntSet::const_iterator", "IntQueue"] {
+    config.set_type_allocation_place(CppTypeAllocationPlace::Stack, *name);
+  }
+  config.set_container_traits("IntQueue", ContainerTraits::IteratorsOnly);
+  config.set_container_traits("Matrix", ContainerTraits::Disabled);
+  let output = run_rust_generator(&config);
+
+  let container = "::cpp_utils::container::CppContainer";
+  let container_mut = "::cpp_utils::container::CppContainerMut";
+  let container_push = "::cpp_utils::container::CppContainerPush";
+  let into_iterator = "::std::iter::IntoIterator";
+  let extend = "::std::iter::Extend";
+  let from_iterator = "::std::iter::FromIterator";
+
+  // `IntoIterator` is implemented for const and mutable references,
+  // and `Extend` and `FromIterator` accept values and references
+  assert_eq!(trait_count(&output, "IntList", container), 1);
+  assert_eq!(trait_count(&output, "IntList", container_mut), 1);
+  assert_eq!(trait_count(&output, "IntList", container_push), 1);
+  assert_eq!(trait_count(&output, "IntList", into_iterator), 2);
+  assert_eq!(trait_count(&output, "IntList", extend), 2);
+  assert_eq!(trait_count(&output, "IntList", from_iterator), 2);
+  for trait_impl in trait_impls(&output, "IntList") {
+    if type_last_name(&trait_impl.trait_type) == "CppContainer" {
+      let names: Vec<_> = trait_impl
+        .methods
+        .iter()
+        .map(|m| m.name.last_name().unwrap().clone())
+        .collect();
+      assert_eq!(names, vec!["len".to_string(), "get".to_string()]);
+      // access by index is unchecked
+      assert!(!trait_impl.methods[0].is_unsafe);
+      assert!(trait_impl.methods[1].is_unsafe);
+    }
+  }
+
+  // no mutable access and no `append`
+  assert_eq!(trait_count(&output, "ReadOnlyList", container), 1);
+  assert_eq!(trait_count(&output, "ReadOnlyList", into_iterator), 1);
+  assert_eq!(trait_count(&output, "ReadOnlyList", container_mut), 0);
+  assert_eq!(trait_count(&output, "ReadOnlyList", container_push), 0);
+
+  assert_eq!(trait_count(&output, "IntQueue", container_push), 1);
+  assert_eq!(trait_count(&output, "IntQueue", extend), 0);
+  assert_eq!(trait_count(&output, "IntQueue", from_iterator), 0);
+
+  assert_eq!(trait_count(&output, "Matrix", container), 0);
+  assert_eq!(trait_count(&output, "Matrix", into_iterator), 0);
+
+  // `begin()` and `end()` are used if there is no indexed access
+  assert_eq!(trait_count(&output, "IntSet", container), 0);
+  assert_eq!(trait_count(&output, "IntSet", "::cpp_utils::container::CppIterable"), 1);
+  assert_eq!(trait_count(&output, "IntSet", into_iterator), 1);
+  assert_eq!(trait_count(&output, "IntSet", extend), 2);
+  let iterable_impl = trait_impls(&output, "IntSet")
+    .iter()
+    .find(|x| type_last_name(&x.trait_type) == "CppIterable")
+    .unwrap();
+  assert_eq!(iterable_impl.associated_types[0].name, "Iterator");
+  let iterator_name = type_last_name(&iterable_impl.associated_types[0].value);
+  let into_iterator_impl = trait_impls(&output, "IntSet")
+    .iter()
+    .find(|x| type_last_name(&x.trait_type) == "IntoIterator")
+    .unwrap();
+  assert_eq!(into_iterator_impl.extra,
+             Some(TraitImplExtra::IterableIntoIterator));
+  assert!(into_iterator_impl.target_type.is_ref());
+
+  let iterator_impl = trait_impls(&output, &iterator_name)
+    .iter()
+    .find(|x| type_last_name(&x.trait_type) == "CppIterator")
+    .unwrap();
+  assert_eq!(iterator_impl.associated_types[0].name, "Item");
+  assert_eq!(type_last_name(&iterator_impl.associated_types[0].value), "c_int");
+  let names: Vec<_> = iterator_impl
+    .methods
+    .iter()
+    .map(|m| (m.name.last_name().unwrap().clone(), m.is_unsafe))
+    .collect();
+  assert_eq!(names,
+             vec![("current".to_string(), true),
+                  ("increment".to_string(), true),
+                  ("is_equal".to_string(), false)]);
+}
@@ -0,0 +1,46 @@
+use rust_type::*;
+
+fn ref_type(lifetime: Option<&str>) -> RustType {
+  RustType::Common {
+    base: RustName::new(vec!["qt_core".to_string(), "object".to_string(), "Object".to_string()])
+      .unwrap(),
+    generic_arguments: None,
+    is_const: true,
+    is_const2: false,
+    indirection: RustTypeIndirection::Ref { lifetime: lifetime.map(|x| x.to_string()) },
+  }
+}
+
+fn option_type(arg: RustType) -> RustType {
+  RustType::Common {
+    base: RustName::new(vec!["std".to_string(), "option".to_string(), "Option".to_string()])
+      .unwrap(),
+    generic_arguments: Some(vec![arg]),
+    is_const: false,
+    is_const2: false,
+    indirection: RustTypeIndirection::None,
+  }
+}
+
+#[test]
+fn lifetime_of_ref() {
+  let type1 = ref_type(None);
+  assert!(type1.is_ref());
+  assert_eq!(type1.lifetime(), None);
+  let type2 = type1.with_lifetime("l0".to_string());
+  assert_eq!(type2, ref_type(Some("l0")));
+  assert_eq!(type2.lifetime(), Some(&"l0".to_string()));
+  assert_eq!(type2.without_lifetime(), type1);
+}
+
+#[test]
+fn lifetime_of_option_ref() {
+  let type1 = option_type(ref_type(None));
+  assert!(!type1.is_ref());
+  assert_eq!(type1.lifetime(), None);
+  let type2 = type1.with_lifetime("l0".to_string());
+  assert_eq!(type2, option_type(ref_type(Some("l0"))));
+  assert_eq!(type2.lifetime(), Some(&"l0".to_string()));
+  assert_eq!(type2.all_lifetimes(), vec![&"l0".to_string()]);
+  assert_eq!(type2.without_lifetime(), type1);
+}
@@ -10,6 +10,20 @@
 // placement new statements require this
 #include <new>
 
+// std::move is used to pass rvalue reference arguments
+#include <utility>
+
+// std::aligned_storage is used to receive values from Rust callbacks
+#include <type_traits>
+
+// required for reporting caught exceptions
+#include <exception>
+#include <typeinfo>
+#include <string.h>
+
+// required for implementing `Debug` with `operator<<`
+#include <sstream>
+
 // original C++ library includes generated by cpp_to_rust
 {include_directives_code}
 
@@ -23,5 +37,44 @@ void {lib_name_lowercase}_call_destructor(T* x) {{
     x->~T();
 }}
 
+// Writes the text printed by `operator<<` for `value` to `buffer`.
+// At most `buffer_size` bytes are written, and the string is not
+// null-terminated. Returns the full length of the text.
+template<typename T>
+size_t {lib_name_lowercase}_write_to_buffer(const T& value, char* buffer, size_t buffer_size) {{
+    std::ostringstream stream;
+    stream << value;
+    std::string text = stream.str();
+    size_t size = text.size() < buffer_size ? text.size() : buffer_size;
+    if (size > 0) {{
+        memcpy(buffer, text.data(), size);
+    }}
+    return text.size();
+}}
+
+#ifndef CPP_TO_RUST_EXCEPTION_INFO_DEFINED
+#define CPP_TO_RUST_EXCEPTION_INFO_DEFINED
+
+// Information about an exception caught by a wrapper function.
+// The layout must match `cpp_utils::CppExceptionInfo` Rust struct.
+struct cpp_to_rust_exception_info {{
+    int is_thrown;
+    char type_name[256];
+    char what[1024];
+}};
+
+// Writes information about a caught exception to `info`.
+// Strings that don't fit in the buffers are truncated.
+inline void cpp_to_rust_store_exception(cpp_to_rust_exception_info* info,
+                                        const char* type_name,
+                                        const char* what) {{
+    info->is_thrown = 1;
+    strncpy(info->type_name, type_name, sizeof(info->type_name) - 1);
+    info->type_name[sizeof(info->type_name) - 1] = 0;
+    strncpy(info->what, what, sizeof(info->what) - 1);
+    info->what[sizeof(info->what) - 1] = 0;
+}}
+
+#endif // CPP_TO_RUST_EXCEPTION_INFO_DEFINED
 
 #endif // {lib_name_uppercase}_GLOBAL_H
@@ -0,0 +1,26 @@
+// Class generated by cpp_to_rust.
+// See the template at "cpp_to_rust/cpp_to_rust_generator/templates/c_lib/subclass_wrapper.h".
+class {class_name} : public {base_class_name} {{
+public:
+{constructors}
+  ~{class_name}() {{
+    if (m_deleter) {{
+      m_deleter(m_data);
+    }}
+  }}
+
+  void set_data(void* data, void (*deleter)(void*)) {{
+    if (m_deleter) {{
+      m_deleter(m_data);
+    }}
+    m_data = data;
+    m_deleter = deleter;
+  }}
+
+{using_declarations}
+{methods}
+private:
+  void* m_data;
+  void (*m_deleter)(void*);
+{fields}}};
+
@@ -0,0 +1,16 @@
+#include <iostream>
+
+// Hexadecimal digit number `index` of `value`
+#define CPP_TO_RUST_HEX_DIGIT(value, index) \
+  static_cast<char>(((value) >> (4 * (index)) & 0xf) < 10 ? \
+                    '0' + ((value) >> (4 * (index)) & 0xf) : \
+                    'a' + ((value) >> (4 * (index)) & 0xf) - 10)
+
+// Comma-separated characters of the 8-digit hexadecimal representation of `value`.
+// `value` must be parenthesized if it contains commas.
+#define CPP_TO_RUST_HEX(value) \
+  CPP_TO_RUST_HEX_DIGIT(value, 7), CPP_TO_RUST_HEX_DIGIT(value, 6), \
+  CPP_TO_RUST_HEX_DIGIT(value, 5), CPP_TO_RUST_HEX_DIGIT(value, 4), \
+  CPP_TO_RUST_HEX_DIGIT(value, 3), CPP_TO_RUST_HEX_DIGIT(value, 2), \
+  CPP_TO_RUST_HEX_DIGIT(value, 1), CPP_TO_RUST_HEX_DIGIT(value, 0)
+
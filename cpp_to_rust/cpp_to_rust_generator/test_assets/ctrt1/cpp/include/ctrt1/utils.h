@@ -7,4 +7,11 @@ CTRT1_EXPORT const char* ctrt1_version();
 
 CTRT1_EXPORT int ctrt1_abs(int x);
 
+enum class Ctrt1Level : unsigned char {
+  Low = 1,
+  High = 200
+};
+
+CTRT1_EXPORT Ctrt1Level ctrt1_next_level(Ctrt1Level level);
+
 #endif // CTRT1_UTILS_H
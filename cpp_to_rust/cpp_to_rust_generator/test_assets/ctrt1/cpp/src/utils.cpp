@@ -8,6 +8,10 @@ int ctrt1_abs(int x) {
   }
 }
 
+Ctrt1Level ctrt1_next_level(Ctrt1Level level) {
+  return level == Ctrt1Level::Low ? Ctrt1Level::High : Ctrt1Level::Low;
+}
+
 const char* ctrt1_version() {
   return "0.0.1";
 }
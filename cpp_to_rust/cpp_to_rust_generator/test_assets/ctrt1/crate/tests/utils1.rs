@@ -7,3 +7,10 @@ fn utils1() {
   assert_eq!(ctrt1_abs(0), 0);
   assert_eq!(ctrt1_abs(-2), 2);
 }
+
+#[test]
+fn enum_values() {
+  use rust_ctrt1::utils::{ctrt1_next_level, Ctrt1Level};
+  assert_eq!(ctrt1_next_level(Ctrt1Level::Low), Ctrt1Level::High);
+  assert_eq!(ctrt1_next_level(Ctrt1Level::High), Ctrt1Level::Low);
+}
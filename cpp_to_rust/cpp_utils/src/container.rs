@@ -0,0 +1,384 @@
+//! Traits and iterators for C++ container classes.
+//!
+//! `cpp_to_rust` implements these traits for classes that provide indexed access
+//! to their elements (e.g. `size()` and `at(int)` methods of Qt's `QList`
+//! or `std::vector`) and for classes that provide `begin()` and `end()` iterators.
+//! The generated crates also implement `IntoIterator`, `Extend` and `FromIterator`
+//! for such classes using the types and functions of this module.
+
+use std;
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+
+#[cfg(test)]
+mod tests {
+  use super::{CppContainer, CppContainerMut, CppContainerPush, CppIterable, CppIterator,
+              BeginEndIter, Iter, IterMut, extend, from_iter};
+
+  #[derive(Default)]
+  struct Container1 {
+    values: Vec<i32>,
+  }
+
+  impl CppContainer for Container1 {
+    type Item = i32;
+    fn len(&self) -> usize {
+      self.values.len()
+    }
+    unsafe fn get(&self, index: usize) -> &i32 {
+      &self.values[index]
+    }
+  }
+
+  impl CppContainerMut for Container1 {
+    unsafe fn get_mut(&mut self, index: usize) -> &mut i32 {
+      &mut self.values[index]
+    }
+  }
+
+  impl CppContainerPush for Container1 {
+    type Item = i32;
+    fn push(&mut self, value: &i32) {
+      self.values.push(*value);
+    }
+  }
+
+  #[test]
+  fn iter() {
+    let container = Container1 { values: vec![1, 2, 3] };
+    let mut iter = Iter::new(&container);
+    assert_eq!(iter.len(), 3);
+    assert_eq!(iter.next(), Some(&1));
+    assert_eq!(iter.next_back(), Some(&3));
+    assert_eq!(iter.len(), 1);
+    assert_eq!(iter.next(), Some(&2));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+    assert_eq!(container.iter().cloned().collect::<Vec<_>>(), vec![1, 2, 3]);
+  }
+
+  #[test]
+  fn iter_mut() {
+    let mut container = Container1 { values: vec![1, 2, 3] };
+    for value in IterMut::new(&mut container) {
+      *value *= 10;
+    }
+    assert_eq!(container.values, vec![10, 20, 30]);
+    assert_eq!(container.iter_mut().rev().next(), Some(&mut 30));
+  }
+
+  // works like a C++ iterator of `std::vector`
+  struct Iterator1 {
+    ptr: *const i32,
+  }
+
+  impl CppIterator for Iterator1 {
+    type Item = i32;
+    unsafe fn current(&self) -> &i32 {
+      &*self.ptr
+    }
+    unsafe fn increment(&mut self) {
+      self.ptr = self.ptr.offset(1);
+    }
+    fn is_equal(&self, other: &Self) -> bool {
+      self.ptr == other.ptr
+    }
+  }
+
+  impl CppIterable for Container1 {
+    type Iterator = Iterator1;
+    fn begin(&self) -> Iterator1 {
+      Iterator1 { ptr: self.values.as_ptr() }
+    }
+    fn end(&self) -> Iterator1 {
+      Iterator1 { ptr: self.values.as_ptr().wrapping_offset(self.values.len() as isize) }
+    }
+  }
+
+  #[test]
+  fn begin_end_iter() {
+    let container = Container1 { values: vec![1, 2, 3] };
+    assert_eq!(BeginEndIter::new(&container).cloned().collect::<Vec<_>>(),
+               vec![1, 2, 3]);
+    assert_eq!(BeginEndIter::new(&Container1::default()).next(), None);
+  }
+
+  #[test]
+  fn extend_and_from_iter() {
+    let mut container: Container1 = from_iter(vec![1, 2]);
+    assert_eq!(container.values, vec![1, 2]);
+    extend(&mut container, &[3, 4]);
+    assert_eq!(container.values, vec![1, 2, 3, 4]);
+  }
+}
+
+/// A C++ container that provides indexed access to its elements.
+pub trait CppContainer {
+  /// Type of the elements.
+  type Item;
+
+  /// Returns number of elements in the container.
+  fn len(&self) -> usize;
+
+  /// Returns true if the container has no elements.
+  fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Returns a reference to the element at `index`.
+  ///
+  /// # Safety
+  ///
+  /// The index is not checked, and the C++ method may have undefined
+  /// behavior for an invalid index. `index` must be less than `len()`.
+  unsafe fn get(&self, index: usize) -> &Self::Item;
+
+  /// Returns an iterator over references to the elements.
+  fn iter<'a>(&'a self) -> Iter<'a, Self>
+    where Self: Sized
+  {
+    Iter::new(self)
+  }
+}
+
+/// A C++ container that provides mutable indexed access to its elements.
+pub trait CppContainerMut: CppContainer {
+  /// Returns a mutable reference to the element at `index`.
+  ///
+  /// # Safety
+  ///
+  /// The index is not checked, and the C++ method may have undefined
+  /// behavior for an invalid index. `index` must be less than `len()`.
+  ///
+  /// `IterMut` calls this function for each index while holding references
+  /// to the previously returned elements, so an implementation must not
+  /// invalidate or access elements other than the one at `index`.
+  unsafe fn get_mut(&mut self, index: usize) -> &mut Self::Item;
+
+  /// Returns an iterator over mutable references to the elements.
+  fn iter_mut<'a>(&'a mut self) -> IterMut<'a, Self>
+    where Self: Sized
+  {
+    IterMut::new(self)
+  }
+}
+
+/// A C++ container that allows to append elements (e.g. using
+/// `append` or `push_back` C++ method).
+pub trait CppContainerPush {
+  /// Type of the elements.
+  type Item;
+
+  /// Appends a copy of `value` to the end of the container.
+  fn push(&mut self, value: &Self::Item);
+}
+
+/// An iterator over references to the elements of a C++ container.
+pub struct Iter<'a, C: 'a> {
+  container: &'a C,
+  index: usize,
+  end: usize,
+}
+
+impl<'a, C: CppContainer> Iter<'a, C> {
+  /// Creates an iterator over all elements of `container`.
+  pub fn new(container: &'a C) -> Iter<'a, C> {
+    Iter {
+      container: container,
+      index: 0,
+      end: container.len(),
+    }
+  }
+}
+
+impl<'a, C: CppContainer> Iterator for Iter<'a, C> {
+  type Item = &'a C::Item;
+
+  fn next(&mut self) -> Option<&'a C::Item> {
+    if self.index < self.end {
+      let container: &'a C = self.container;
+      let item = unsafe { container.get(self.index) };
+      self.index += 1;
+      Some(item)
+    } else {
+      None
+    }
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let len = self.end - self.index;
+    (len, Some(len))
+  }
+}
+
+impl<'a, C: CppContainer> DoubleEndedIterator for Iter<'a, C> {
+  fn next_back(&mut self) -> Option<&'a C::Item> {
+    if self.index < self.end {
+      self.end -= 1;
+      let container: &'a C = self.container;
+      Some(unsafe { container.get(self.end) })
+    } else {
+      None
+    }
+  }
+}
+
+impl<'a, C: CppContainer> ExactSizeIterator for Iter<'a, C> {}
+
+/// An iterator over mutable references to the elements of a C++ container.
+pub struct IterMut<'a, C: 'a> {
+  // a raw pointer is used because a mutable reference to the container
+  // can't coexist with the references to its elements returned earlier
+  container: *mut C,
+  index: usize,
+  end: usize,
+  _marker: PhantomData<&'a mut C>,
+}
+
+impl<'a, C: CppContainerMut> IterMut<'a, C> {
+  /// Creates an iterator over all elements of `container`.
+  pub fn new(container: &'a mut C) -> IterMut<'a, C> {
+    let end = container.len();
+    IterMut {
+      container: container,
+      index: 0,
+      end: end,
+      _marker: PhantomData,
+    }
+  }
+}
+
+impl<'a, C: CppContainerMut> Iterator for IterMut<'a, C> {
+  type Item = &'a mut C::Item;
+
+  fn next(&mut self) -> Option<&'a mut C::Item> {
+    if self.index < self.end {
+      // each element is only returned once, so the references don't alias
+      let item = unsafe { (*self.container).get_mut(self.index) };
+      self.index += 1;
+      Some(item)
+    } else {
+      None
+    }
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let len = self.end - self.index;
+    (len, Some(len))
+  }
+}
+
+impl<'a, C: CppContainerMut> DoubleEndedIterator for IterMut<'a, C> {
+  fn next_back(&mut self) -> Option<&'a mut C::Item> {
+    if self.index < self.end {
+      self.end -= 1;
+      Some(unsafe { (*self.container).get_mut(self.end) })
+    } else {
+      None
+    }
+  }
+}
+
+impl<'a, C: CppContainerMut> ExactSizeIterator for IterMut<'a, C> {}
+
+/// A C++ iterator (e.g. `const_iterator` of a container class)
+/// that can be dereferenced, incremented and compared.
+pub trait CppIterator {
+  /// Type of the elements.
+  type Item;
+
+  /// Returns a reference to the element the iterator points to
+  /// (C++ `operator*`).
+  ///
+  /// # Safety
+  ///
+  /// The iterator's position is not checked, and the C++ operator may have
+  /// undefined behavior if the iterator doesn't point to an element.
+  /// The iterator must not be equal to the container's end iterator.
+  unsafe fn current(&self) -> &Self::Item;
+
+  /// Moves the iterator to the next element (C++ `operator++`).
+  ///
+  /// # Safety
+  ///
+  /// The iterator's position is not checked, and the C++ operator may have
+  /// undefined behavior if the iterator doesn't point to an element.
+  /// The iterator must not be equal to the container's end iterator.
+  unsafe fn increment(&mut self);
+
+  /// Returns true if both iterators point to the same position
+  /// (C++ `operator==`).
+  fn is_equal(&self, other: &Self) -> bool;
+}
+
+/// A C++ container that provides `begin()` and `end()` const methods.
+pub trait CppIterable {
+  /// Type of the iterators.
+  type Iterator: CppIterator;
+
+  /// Returns an iterator pointing to the first element.
+  fn begin(&self) -> Self::Iterator;
+
+  /// Returns an iterator pointing past the last element.
+  fn end(&self) -> Self::Iterator;
+}
+
+/// An iterator over references to the elements of a C++ container
+/// that provides `begin()` and `end()` methods.
+pub struct BeginEndIter<'a, C: CppIterable + 'a> {
+  current: C::Iterator,
+  end: C::Iterator,
+  _marker: PhantomData<&'a C>,
+}
+
+impl<'a, C: CppIterable> BeginEndIter<'a, C> {
+  /// Creates an iterator over all elements of `container`.
+  pub fn new(container: &'a C) -> BeginEndIter<'a, C> {
+    BeginEndIter {
+      current: container.begin(),
+      end: container.end(),
+      _marker: PhantomData,
+    }
+  }
+}
+
+impl<'a, C: CppIterable> Iterator for BeginEndIter<'a, C> {
+  type Item = &'a <C::Iterator as CppIterator>::Item;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.current.is_equal(&self.end) {
+      None
+    } else {
+      // the element belongs to the container, so the reference
+      // is valid while the container is borrowed
+      let item = unsafe { &*(self.current.current() as *const <C::Iterator as CppIterator>::Item) };
+      unsafe { self.current.increment() };
+      Some(item)
+    }
+  }
+}
+
+/// Appends all values (or references to values) produced by `iter`
+/// to `container`. This function is used to implement `Extend`.
+pub fn extend<C, T, I>(container: &mut C, iter: I)
+  where C: CppContainerPush,
+        T: Borrow<C::Item>,
+        I: IntoIterator<Item = T>
+{
+  for value in iter {
+    container.push(value.borrow());
+  }
+}
+
+/// Creates a container using its default constructor and appends
+/// all values (or references to values) produced by `iter`.
+/// This function is used to implement `FromIterator`.
+pub fn from_iter<C, T, I>(iter: I) -> C
+  where C: CppContainerPush + std::default::Default,
+        T: Borrow<C::Item>,
+        I: IntoIterator<Item = T>
+{
+  let mut container = C::default();
+  extend(&mut container, iter);
+  container
+}
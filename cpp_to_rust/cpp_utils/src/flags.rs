@@ -0,0 +1,286 @@
+//! Types for OR-combinations of C++ enum values.
+//!
+//! `cpp_to_rust` converts Qt's `QFlags<E>` and integer typedefs configured with
+//! `Config::add_flags_typedef` to `Flags<E>`.
+
+use std;
+use std::os::raw::c_int;
+
+#[cfg(test)]
+mod tests {
+  use std::os::raw::c_int;
+  use super::{Flags, FlaggableEnum, EnumOrFlags};
+
+  #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+  enum Enum1 {
+    A = 1,
+    B = 2,
+    C = 4,
+  }
+
+  impl FlaggableEnum for Enum1 {
+    fn to_flag_value(self) -> c_int {
+      self as c_int
+    }
+    fn enum_name() -> &'static str {
+      "Enum1"
+    }
+    fn flag_values() -> &'static [(&'static str, c_int)] {
+      static VALUES: [(&'static str, c_int); 3] = [("A", 1), ("B", 2), ("C", 4)];
+      &VALUES
+    }
+  }
+
+  impl EnumOrFlags<Enum1> for Enum1 {
+    fn to_flags(self) -> Flags<Enum1> {
+      Flags::from_enum(self)
+    }
+  }
+
+  #[test]
+  fn operators() {
+    let mut flags = Flags::from_enum(Enum1::A) | Enum1::B;
+    assert_eq!(flags.to_int(), 3);
+    assert_eq!((flags & Enum1::B).to_int(), 2);
+    assert_eq!((flags ^ Enum1::A).to_int(), 2);
+    assert_eq!((!flags).to_int(), !3);
+    flags |= Enum1::C;
+    assert_eq!(flags.to_int(), 7);
+    flags &= Flags::from_enum(Enum1::A) | Enum1::C;
+    assert_eq!(flags.to_int(), 5);
+    flags ^= Enum1::C;
+    assert_eq!(flags, Flags::from_enum(Enum1::A));
+  }
+
+  #[test]
+  fn contains_insert_remove() {
+    let mut flags = Flags::<Enum1>::default();
+    assert!(flags.is_empty());
+    flags.insert(Enum1::A);
+    flags.insert(Enum1::C);
+    assert!(flags.contains(Enum1::A));
+    assert!(!flags.contains(Enum1::B));
+    assert!(flags.contains(Flags::from_enum(Enum1::A) | Enum1::C));
+    assert!(!flags.contains(Flags::from_enum(Enum1::A) | Enum1::B));
+    flags.remove(Enum1::A);
+    assert_eq!(flags.to_int(), 4);
+  }
+
+  #[test]
+  fn from_iterator() {
+    let flags: Flags<Enum1> = vec![Enum1::A, Enum1::C].into_iter().collect();
+    assert_eq!(flags.to_int(), 5);
+    let mut flags2 = Flags::from(Enum1::B);
+    flags2.extend(vec![Enum1::A]);
+    assert_eq!(flags2.to_int(), 3);
+  }
+
+  #[test]
+  fn debug() {
+    let flags = Flags::from_enum(Enum1::A) | Enum1::C;
+    assert_eq!(format!("{:?}", flags), "Flags<Enum1>(A | C)");
+    assert_eq!(format!("{:?}", Flags::<Enum1>::default()), "Flags<Enum1>(0)");
+    assert_eq!(format!("{:?}", Flags::<Enum1>::from_int(9)),
+               "Flags<Enum1>(A | 0x8)");
+  }
+}
+
+/// An OR-combination of integer values of the enum type `E`.
+///
+/// `Flags` can be constructed from `E` values and combined using
+/// bitwise operators: `Flags::from_enum(E::A) | E::B`.
+///
+/// The value is stored as `c_int`, so enums and flags types
+/// wider than 32 bits are not represented by `Flags`.
+pub struct Flags<E: FlaggableEnum> {
+  value: c_int,
+  _phantom_data: std::marker::PhantomData<E>,
+}
+
+impl<E: FlaggableEnum> Flags<E> {
+  /// Converts integer `value` to `Flags`.
+  pub fn from_int(value: c_int) -> Self {
+    Flags {
+      value: value,
+      _phantom_data: std::marker::PhantomData,
+    }
+  }
+  /// Converts `value` to `Flags` containing that single value.
+  pub fn from_enum(value: E) -> Self {
+    Self::from_int(value.to_flag_value())
+  }
+  /// Converts `Flags` to integer.
+  pub fn to_int(self) -> c_int {
+    self.value
+  }
+  /// Returns `true` if `flag` is enabled in `self`.
+  pub fn test_flag(self, flag: E) -> bool {
+    self.value & flag.to_flag_value() != 0
+  }
+  /// Returns `true` if this value has no flags enabled.
+  pub fn is_empty(self) -> bool {
+    self.value == 0
+  }
+  /// Returns `true` if all flags enabled in `other` are also enabled in `self`.
+  pub fn contains<T: EnumOrFlags<E>>(self, other: T) -> bool {
+    let other = other.to_flags().value;
+    self.value & other == other
+  }
+  /// Enables all flags enabled in `other`.
+  pub fn insert<T: EnumOrFlags<E>>(&mut self, other: T) {
+    self.value |= other.to_flags().value;
+  }
+  /// Disables all flags enabled in `other`.
+  pub fn remove<T: EnumOrFlags<E>>(&mut self, other: T) {
+    self.value &= !other.to_flags().value;
+  }
+}
+
+impl<E: FlaggableEnum> Clone for Flags<E> {
+  fn clone(&self) -> Self {
+    *self
+  }
+}
+
+impl<E: FlaggableEnum> Copy for Flags<E> {}
+
+impl<E: FlaggableEnum> PartialEq for Flags<E> {
+  fn eq(&self, other: &Self) -> bool {
+    self.value == other.value
+  }
+}
+
+impl<E: FlaggableEnum> Eq for Flags<E> {}
+
+impl<E: FlaggableEnum> std::hash::Hash for Flags<E> {
+  fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    self.value.hash(state);
+  }
+}
+
+impl<E: FlaggableEnum, T: EnumOrFlags<E>> std::ops::BitOr<T> for Flags<E> {
+  type Output = Flags<E>;
+  fn bitor(self, rhs: T) -> Flags<E> {
+    Flags::from_int(self.value | rhs.to_flags().value)
+  }
+}
+
+impl<E: FlaggableEnum, T: EnumOrFlags<E>> std::ops::BitAnd<T> for Flags<E> {
+  type Output = Flags<E>;
+  fn bitand(self, rhs: T) -> Flags<E> {
+    Flags::from_int(self.value & rhs.to_flags().value)
+  }
+}
+
+impl<E: FlaggableEnum, T: EnumOrFlags<E>> std::ops::BitXor<T> for Flags<E> {
+  type Output = Flags<E>;
+  fn bitxor(self, rhs: T) -> Flags<E> {
+    Flags::from_int(self.value ^ rhs.to_flags().value)
+  }
+}
+
+impl<E: FlaggableEnum> std::ops::Not for Flags<E> {
+  type Output = Flags<E>;
+  fn not(self) -> Flags<E> {
+    Flags::from_int(!self.value)
+  }
+}
+
+impl<E: FlaggableEnum, T: EnumOrFlags<E>> std::ops::BitOrAssign<T> for Flags<E> {
+  fn bitor_assign(&mut self, rhs: T) {
+    self.value |= rhs.to_flags().value;
+  }
+}
+
+impl<E: FlaggableEnum, T: EnumOrFlags<E>> std::ops::BitAndAssign<T> for Flags<E> {
+  fn bitand_assign(&mut self, rhs: T) {
+    self.value &= rhs.to_flags().value;
+  }
+}
+
+impl<E: FlaggableEnum, T: EnumOrFlags<E>> std::ops::BitXorAssign<T> for Flags<E> {
+  fn bitxor_assign(&mut self, rhs: T) {
+    self.value ^= rhs.to_flags().value;
+  }
+}
+
+impl<E: FlaggableEnum> From<E> for Flags<E> {
+  fn from(value: E) -> Self {
+    Self::from_enum(value)
+  }
+}
+
+impl<E: FlaggableEnum> std::iter::FromIterator<E> for Flags<E> {
+  fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Self {
+    let mut r = Self::default();
+    r.extend(iter);
+    r
+  }
+}
+
+impl<E: FlaggableEnum> std::iter::Extend<E> for Flags<E> {
+  fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+    for item in iter {
+      self.value |= item.to_flag_value();
+    }
+  }
+}
+
+/// Enum type with values suitable for constructing OR-combinations for `Flags`.
+pub trait FlaggableEnum: Sized + Clone {
+  /// Returns integer value of this enum variant.
+  fn to_flag_value(self) -> c_int;
+  /// Returns name of the type for debug output.
+  fn enum_name() -> &'static str;
+  /// Returns names and values of all enum variants.
+  /// Used to list enabled flags in debug output.
+  fn flag_values() -> &'static [(&'static str, c_int)] {
+    &[]
+  }
+}
+
+/// Trait representing types that can be converted to `Flags`.
+pub trait EnumOrFlags<T: FlaggableEnum> {
+  fn to_flags(self) -> Flags<T>;
+}
+
+impl<T: FlaggableEnum> EnumOrFlags<T> for Flags<T> {
+  fn to_flags(self) -> Flags<T> {
+    self
+  }
+}
+
+impl<T: FlaggableEnum> std::fmt::Debug for Flags<T> {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "Flags<{}>(", T::enum_name())?;
+    if self.value == 0 {
+      return write!(f, "0)");
+    }
+    let mut remaining = self.value;
+    let mut is_first = true;
+    for &(name, value) in T::flag_values() {
+      // skip zero values and combinations of already listed flags
+      if value != 0 && self.value & value == value && remaining & value != 0 {
+        if !is_first {
+          write!(f, " | ")?;
+        }
+        write!(f, "{}", name)?;
+        is_first = false;
+        remaining &= !value;
+      }
+    }
+    if remaining != 0 {
+      if !is_first {
+        write!(f, " | ")?;
+      }
+      write!(f, "{:#x}", remaining)?;
+    }
+    write!(f, ")")
+  }
+}
+
+impl<T: FlaggableEnum> Default for Flags<T> {
+  fn default() -> Self {
+    Flags::from_int(0)
+  }
+}
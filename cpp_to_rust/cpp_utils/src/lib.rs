@@ -4,7 +4,7 @@
 mod tests {
   use std::rc::Rc;
   use std::cell::RefCell;
-  use {CppDeletable, Deleter, CppBox};
+  use {CppDeletable, Deleter, CppBox, CppBoxClone, CppExceptionInfo, abort_on_panic};
 
   struct Struct1 {
     value: Rc<RefCell<i32>>,
@@ -30,8 +30,73 @@ mod tests {
     }
     assert!(value1.borrow().clone() == 42);
   }
+
+  #[derive(Debug, PartialEq)]
+  struct Struct2 {
+    value: i32,
+  }
+
+  unsafe extern "C" fn struct2_delete(this_ptr: *mut Struct2) {
+    drop(Box::from_raw(this_ptr));
+  }
+
+  impl CppDeletable for Struct2 {
+    fn deleter() -> Deleter<Self> {
+      struct2_delete
+    }
+  }
+
+  impl CppBoxClone for Struct2 {
+    fn clone_box(&self) -> CppBox<Struct2> {
+      unsafe { CppBox::new(Box::into_raw(Box::new(Struct2 { value: self.value }))) }
+    }
+  }
+
+  #[test]
+  fn test_box_traits() {
+    let box1 = unsafe { CppBox::new(Box::into_raw(Box::new(Struct2 { value: 1 }))) };
+    let box2 = box1.clone();
+    assert!(box1.as_ptr() != box2.as_ptr());
+    assert_eq!(box1, box2);
+    assert_eq!(format!("{:?}", box2), "CppBox(Struct2 { value: 1 })");
+    let null_box: CppBox<Struct2> = CppBox::default();
+    assert!(null_box.clone().is_null());
+    assert!(box1 != null_box);
+    assert_eq!(null_box, CppBox::default());
+    assert_eq!(format!("{:?}", null_box), "CppBox(null)");
+  }
+
+  #[test]
+  fn test_exception_info() {
+    let mut info = CppExceptionInfo::new();
+    assert!(info.to_result().is_ok());
+    info.is_thrown = 1;
+    for (i, c) in b"St13runtime_error".iter().enumerate() {
+      info.type_name[i] = *c as _;
+    }
+    for (i, c) in b"failed".iter().enumerate() {
+      info.what[i] = *c as _;
+    }
+    let exception = info.to_result().unwrap_err();
+    assert_eq!(exception.type_name(), "St13runtime_error");
+    assert_eq!(exception.what(), "failed");
+  }
+
+  #[test]
+  fn test_abort_on_panic() {
+    let mut value = 1;
+    assert_eq!(abort_on_panic(|| {
+                                value += 1;
+                                value * 10
+                              }),
+               20);
+    assert_eq!(value, 2);
+  }
 }
 
+pub mod flags;
+pub mod container;
+
 /// Deleter function type.
 ///
 /// This is usually a C++ function imported via FFI
@@ -165,6 +230,149 @@ impl<T: CppDeletable> Default for CppBox<T> {
   }
 }
 
+/// Indicates that the type can be copied into a new `CppBox`.
+///
+/// This trait is automatically implemented by `cpp_to_rust` for
+/// heap-allocated types that have a public copy constructor.
+/// It allows `CppBox<T>` to implement `Clone`.
+pub trait CppBoxClone: CppDeletable {
+  /// Creates a copy of the object using its C++ copy constructor.
+  fn clone_box(&self) -> CppBox<Self>;
+}
+
+impl<T: CppBoxClone> Clone for CppBox<T> {
+  /// Copies the object in the box. Cloning a null box
+  /// results in another null box.
+  fn clone(&self) -> CppBox<T> {
+    if self.ptr.is_null() {
+      CppBox::default()
+    } else {
+      (**self).clone_box()
+    }
+  }
+}
+
+impl<T: CppDeletable + PartialEq> PartialEq for CppBox<T> {
+  /// Compares objects in the boxes. Null boxes are only equal to each other.
+  fn eq(&self, other: &CppBox<T>) -> bool {
+    unsafe {
+      match (self.ptr.as_ref(), other.ptr.as_ref()) {
+        (Some(a), Some(b)) => a == b,
+        (None, None) => true,
+        _ => false,
+      }
+    }
+  }
+}
+
+impl<T: CppDeletable + Eq> Eq for CppBox<T> {}
+
+impl<T: CppDeletable + std::fmt::Debug> std::fmt::Debug for CppBox<T> {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match unsafe { self.ptr.as_ref() } {
+      Some(value) => f.debug_tuple("CppBox").field(value).finish(),
+      None => write!(f, "CppBox(null)"),
+    }
+  }
+}
+
+/// An exception thrown by a C++ function and caught by its wrapper.
+///
+/// Methods of the generated crates return `Result<T, CppException>`
+/// if catching exceptions was enabled for them in the generator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CppException {
+  type_name: String,
+  what: String,
+}
+
+impl CppException {
+  /// Returns name of the exception's type as reported by `typeid`.
+  /// The name may be mangled depending on the compiler.
+  pub fn type_name(&self) -> &str {
+    &self.type_name
+  }
+
+  /// Returns value of `what()` if the exception is derived from
+  /// `std::exception`, or an empty string otherwise.
+  pub fn what(&self) -> &str {
+    &self.what
+  }
+}
+
+impl std::fmt::Display for CppException {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "C++ exception {}: {}", self.type_name, self.what)
+  }
+}
+
+impl std::error::Error for CppException {
+  fn description(&self) -> &str {
+    &self.what
+  }
+}
+
+/// Information about a C++ exception filled by a wrapper function.
+///
+/// This type is an implementation detail of `cpp_to_rust` and should not be used directly.
+/// Its layout must match `cpp_to_rust_exception_info` struct
+/// of the C++ wrapper library.
+#[repr(C)]
+pub struct CppExceptionInfo {
+  is_thrown: std::os::raw::c_int,
+  type_name: [std::os::raw::c_char; 256],
+  what: [std::os::raw::c_char; 1024],
+}
+
+impl CppExceptionInfo {
+  /// Creates an object indicating that no exception was thrown.
+  pub fn new() -> CppExceptionInfo {
+    CppExceptionInfo {
+      is_thrown: 0,
+      type_name: [0; 256],
+      what: [0; 1024],
+    }
+  }
+
+  /// Returns `Err` if the wrapper function reported an exception.
+  pub fn to_result(&self) -> Result<(), CppException> {
+    if self.is_thrown == 0 {
+      return Ok(());
+    }
+    let to_string = |buffer: &[std::os::raw::c_char]| {
+      let bytes: Vec<u8> = buffer
+        .iter()
+        .take_while(|x| **x != 0)
+        .map(|x| *x as u8)
+        .collect();
+      String::from_utf8_lossy(&bytes).into_owned()
+    };
+    Err(CppException {
+          type_name: to_string(&self.type_name),
+          what: to_string(&self.what),
+        })
+  }
+}
+
+impl Default for CppExceptionInfo {
+  fn default() -> CppExceptionInfo {
+    CppExceptionInfo::new()
+  }
+}
+
+/// Calls `f` and returns its result. Aborts the process if `f` panics.
+///
+/// Callbacks called from C++ code use this function because unwinding
+/// across an `extern "C"` function is undefined behavior.
+///
+/// This function is an implementation detail of `cpp_to_rust` and should not be used directly.
+pub fn abort_on_panic<R, F: FnOnce() -> R>(f: F) -> R {
+  match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+    Ok(value) => value,
+    Err(_) => std::process::abort(),
+  }
+}
+
 /// This module contains `NewUninitialized` trait.
 /// It's an implementation detail of `cpp_to_rust` and should not be used directly.
 pub mod new_uninitialized {
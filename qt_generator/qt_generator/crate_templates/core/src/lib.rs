@@ -5,6 +5,7 @@
 //! This is work in progress, so the API will significantly change in the future.
 //! Some methods are missing, and some are inconvenient to use.
 //! Some methods are unsafe even though they are not marked as unsafe.
+//! Documentation of each method marked as unsafe explains the reason.
 //! Users must carefully track ownership of the objects, as usual Rust guarantees
 //! do not take effect. This will hopefully improve in the future.
 //! Please report any issues to the
@@ -109,14 +110,30 @@
 //!
 //! References (`&T` and `&mut T`) in Qt crates are not very different from raw pointers.
 //! They appear in the same places references were used in C++, but they can't hold any guarantees
-//! Rust usually enforces for references. Lifetimes of references are set trivially: all input
-//! references must be valid for the same lifetime, and output references have the same lifetime
-//! as input references. If there are no input references, output references have `'static`
-//! lifetime.
+//! Rust usually enforces for references. References returned by methods borrow from `self`.
+//! References returned by static methods and free functions borrow from all input references.
+//! If there are no input references, a raw pointer is returned instead, unless the method
+//! is known to return a reference with `'static` lifetime (e.g. `QCoreApplication::instance`).
 //!
 //! It should be expected that raw pointers will be replaced with `CppBox`es and references,
-//! and references will hold their guarantees. However, this requires manual annotation of methods,
-//! so it's not easy to make this improvement.
+//! and references will hold their guarantees. However, this requires manual annotation of methods.
+//! The generator supports such annotations (`Config::add_pointer_annotation`), but only a few
+//! methods (e.g. `QObject::parent`, which returns `Option<&mut QObject>`) are annotated so far.
+//!
+//! Types with a public copy constructor implement `Clone` (or `cpp_utils::CppBoxClone`
+//! if they are stored in the heap, which makes `CppBox<T>` cloneable).
+//! Stack-allocated types with a public default constructor implement `Default`.
+//! `operator==` is exposed as `PartialEq`. Some value types (e.g. `QString`, `QUrl`
+//! and `QDateTime`) also implement `Eq`. Some types (e.g. `QUrl` and `QDateTime`)
+//! implement `Debug` using their `toString` method, and types with
+//! `operator<<(std::ostream&, const T&)` implement `Debug` using that operator.
+//!
+//! Containers with indexed access (e.g. `QList` and `QVector`) implement
+//! `cpp_utils::container::CppContainer`, so references to them can be used in `for` loops.
+//! They also implement `Extend` and `FromIterator` if they have an `append` method.
+//! Classes that inherit these methods (e.g. `QStringList`) don't implement the traits
+//! themselves, but they dereference to the base class, so `for s in &*list` and
+//! `list.iter()` (with `CppContainer` imported) can be used instead.
 
 //!
 
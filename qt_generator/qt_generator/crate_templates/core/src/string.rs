@@ -35,3 +35,10 @@ impl ::string::String {
     }
   }
 }
+
+/// Formats Qt strings like `std` strings
+impl std::fmt::Debug for ::string::String {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    std::fmt::Debug::fmt(&self.to_std_string(), f)
+  }
+}
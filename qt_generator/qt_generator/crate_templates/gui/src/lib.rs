@@ -5,6 +5,7 @@
 //! This is work in progress, so the API will significantly change in the future.
 //! Some methods are missing, and some are inconvenient to use.
 //! Some methods are unsafe even though they are not marked as unsafe.
+//! Documentation of each method marked as unsafe explains the reason.
 //! Users must carefully track ownership of the objects, as usual Rust guarantees
 //! do not take effect. This will hopefully improve in the future.
 //! Please report any issues to the
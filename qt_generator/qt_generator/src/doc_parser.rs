@@ -6,7 +6,7 @@ use std::collections::{hash_map, HashMap, HashSet};
 use cpp_to_rust_generator::common::log;
 use cpp_to_rust_generator::common::errors::{Result, ChainErr, unexpected};
 use cpp_to_rust_generator::cpp_method::CppMethodDoc;
-use cpp_to_rust_generator::cpp_data::CppTypeDoc;
+use cpp_to_rust_generator::cpp_data::{CppTypeDoc, CppDocContent};
 use html_parser::node::Node;
 use html_parser::document::Document;
 use regex::Regex;
@@ -175,7 +175,7 @@ impl DocParser {
               return Err("found empty documentation".into());
             }
             return Ok(CppMethodDoc {
-                        html: item.html.clone(),
+                        content: CppDocContent::Html(item.html.clone()),
                         anchor: item.anchor.clone(),
                         mismatched_declaration: None,
                         url: format!("{}#{}", file_url, item.anchor),
@@ -197,7 +197,7 @@ impl DocParser {
               return Err("found empty documentation".into());
             }
             return Ok(CppMethodDoc {
-                        html: item.html.clone(),
+                        content: CppDocContent::Html(item.html.clone()),
                         anchor: item.anchor.clone(),
                         mismatched_declaration: None,
                         url: format!("{}#{}", file_url, item.anchor),
@@ -221,7 +221,7 @@ impl DocParser {
         return Err("found empty documentation".into());
       }
       return Ok(CppMethodDoc {
-                  html: candidates[0].html.clone(),
+                  content: CppDocContent::Html(candidates[0].html.clone()),
                   anchor: candidates[0].anchor.clone(),
                   url: format!("{}#{}", file_url, candidates[0].anchor),
                   mismatched_declaration: Some(candidates[0].declarations[0].clone()),
@@ -259,7 +259,7 @@ impl DocParser {
         (result.clone(), file_data.file_name.clone())
       };
       return Ok((CppTypeDoc {
-                   html: result.html,
+                   content: CppDocContent::Html(result.html),
                    url: format!("{}{}#{}", self.base_url, file_name, anchor),
                    cross_references: result.cross_references,
                  },
@@ -294,7 +294,7 @@ impl DocParser {
     }
     let (html, cross_references) = process_html(&result, &self.base_url)?;
     Ok((CppTypeDoc {
-          html: html,
+          content: CppDocContent::Html(html),
           url: url,
           cross_references: cross_references.into_iter().collect(),
         },
@@ -4,7 +4,7 @@ use cpp_to_rust_generator::common::errors::Result;
 use cpp_to_rust_generator::common::{log, toml};
 use cpp_to_rust_generator::common::file_utils::{PathBufWithAdded, repo_crate_local_path};
 use cpp_to_rust_generator::config::{Config, CacheUsage, DebugLoggingConfig, exec};
-use cpp_to_rust_generator::cpp_data::CppVisibility;
+use cpp_to_rust_generator::cpp_data::{CppVisibility, CppDocContent};
 use cpp_to_rust_generator::common::cpp_build_config::{CppBuildConfigData, CppLibraryType};
 use cpp_to_rust_generator::common::target;
 use qt_generator_common::{get_installation_data, lib_folder_name, lib_dependencies};
@@ -203,7 +203,7 @@ fn make_config(sublib_name: &str,
             Ok(doc) => {
               // log::debug(format!("Found doc for type: {}", type1.name));
               type1.doc = Some(doc.0);
-              if let CppTypeKind::Enum { ref mut values } = type1.kind {
+              if let CppTypeKind::Enum { ref mut values, .. } = type1.kind {
                 let enum_namespace = if let Some(index) = type1.name.rfind("::") {
                   type1.name[0..index + 2].to_string()
                 } else {
@@ -211,7 +211,7 @@ fn make_config(sublib_name: &str,
                 };
                 for value in values {
                   if let Some(r) = doc.1.iter().find(|x| x.name == value.name) {
-                    value.doc = Some(r.html.clone());
+                    value.doc = Some(CppDocContent::Html(r.html.clone()));
 
                     // let full_name = format!("{}::{}", enum_namespace, &value.name);
                     // println!("full name: {}", full_name);
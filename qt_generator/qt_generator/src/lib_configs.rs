@@ -1,83 +1,11 @@
 //! Generator configurations specific for each Qt module.
 
-use cpp_to_rust_generator::common::errors::{Result, ChainErr};
-use cpp_to_rust_generator::config::{Config, CppTypeAllocationPlace};
+use cpp_to_rust_generator::common::errors::Result;
+use cpp_to_rust_generator::config::{Config, CppTypeAllocationPlace, PointerAnnotation,
+                                    PointerAnnotationTarget, ReturnValueLifetime};
 use cpp_to_rust_generator::cpp_type::{CppType, CppTypeBase, CppBuiltInNumericType,
                                       CppTypeIndirection};
 
-/// Helper method to blacklist all methods of `QList<T>` template instantiation that
-/// don't work if `T` doesn't have `operator==`. `types` is list of such `T` types.
-fn exclude_qlist_eq_based_methods<S: AsRef<str>, I: IntoIterator<Item = S>>(config: &mut Config,
-                                                                            types: I) {
-  let types: Vec<String> = types
-    .into_iter()
-    .map(|x| x.as_ref().to_string())
-    .collect();
-  config.add_cpp_ffi_generator_filter(move |method| {
-    if let Some(ref info) = method.class_membership {
-      if info.class_type.name == "QList" {
-        let args = info
-          .class_type
-          .template_arguments
-          .as_ref()
-          .chain_err(|| "failed to get QList args")?;
-        let arg = args.get(0).chain_err(|| "failed to get QList arg")?;
-        let arg_text = arg.to_cpp_pseudo_code();
-        if types.iter().any(|x| x == &arg_text) {
-          match method.name.as_ref() {
-            "operator==" | "operator!=" | "indexOf" | "lastIndexOf" | "contains" |
-            "startsWith" | "endsWith" | "removeOne" | "removeAll" | "value" | "toVector" |
-            "toSet" => return Ok(false),
-            "count" => {
-              if method.arguments.len() == 1 {
-                return Ok(false);
-              }
-            }
-            _ => {}
-          }
-        }
-      }
-    }
-    Ok(true)
-  });
-}
-
-/// Helper method to blacklist all methods of `QVector<T>` template instantiation that
-/// don't work if `T` doesn't have `operator==`. `types` is list of such `T` types.
-fn exclude_qvector_eq_based_methods<S: AsRef<str>, I: IntoIterator<Item = S>>(config: &mut Config,
-                                                                              types: I) {
-  let types: Vec<String> = types
-    .into_iter()
-    .map(|x| x.as_ref().to_string())
-    .collect();
-  config.add_cpp_ffi_generator_filter(move |method| {
-    if let Some(ref info) = method.class_membership {
-      if info.class_type.name == "QVector" {
-        let args = info
-          .class_type
-          .template_arguments
-          .as_ref()
-          .chain_err(|| "failed to get QVector args")?;
-        let arg = args.get(0).chain_err(|| "failed to get QVector arg")?;
-        let arg_text = arg.to_cpp_pseudo_code();
-        if types.iter().any(|x| x == &arg_text) {
-          match method.name.as_ref() {
-            "operator==" | "operator!=" | "indexOf" | "lastIndexOf" | "contains" |
-            "startsWith" | "endsWith" | "removeOne" | "removeAll" | "toList" => return Ok(false),
-            "count" => {
-              if method.arguments.len() == 1 {
-                return Ok(false);
-              }
-            }
-            _ => {}
-          }
-        }
-      }
-    }
-    Ok(true)
-  });
-}
-
 /// List of QtCore identifiers that should be blacklisted.
 #[cfg_attr(rustfmt, rustfmt_skip)]
 fn core_cpp_parser_blocked_names() -> Vec<&'static str> {
@@ -150,10 +78,6 @@ pub fn core(config: &mut Config) -> Result<()> {
   // but this method is obsolete anyway
   config.add_cpp_parser_blocked_names(vec!["QProcess::pid"]);
 
-  exclude_qvector_eq_based_methods(config, &["QStaticPlugin", "QTimeZone::OffsetData"]);
-  exclude_qlist_eq_based_methods(config,
-                                 &["QAbstractEventDispatcher::TimerInfo", "QCommandLineOption"]);
-
   config.set_types_allocation_place(CppTypeAllocationPlace::Stack,
                                     vec!["QAssociativeIterable",
                                          "QByteArray",
@@ -239,6 +163,27 @@ pub fn core(config: &mut Config) -> Result<()> {
     }
     Ok(true)
   });
+  config.add_pointer_annotation("QObject::parent() const",
+                                 PointerAnnotationTarget::ReturnValue,
+                                 PointerAnnotation::Nullable);
+  config.add_pointer_annotation("QObject::setParent(QObject*)",
+                                 PointerAnnotationTarget::Argument(0),
+                                 PointerAnnotation::Nullable);
+  config.add_pointer_annotation("QCoreApplication::instance()",
+                                 PointerAnnotationTarget::ReturnValue,
+                                 PointerAnnotation::Nullable);
+  config.set_return_value_lifetime("QCoreApplication::instance()",
+                                    ReturnValueLifetime::Static);
+  for method in &["QUrl::toString() const",
+                  "QDate::toString() const",
+                  "QTime::toString() const",
+                  "QDateTime::toString() const"] {
+    config.add_debug_method(*method);
+  }
+  for class_name in &["QString", "QByteArray", "QChar", "QUrl", "QDate", "QTime", "QDateTime"] {
+    config.add_eq_class(*class_name);
+  }
+
   Ok(())
 }
 
@@ -259,31 +204,9 @@ pub fn gui(config: &mut Config) -> Result<()> {
                                            "QOpenGLVersionFunctionsStorage",
                                            "QOpenGLTexture::TextureFormatClass",
                                            "QTextFrameLayoutData"]);
-  exclude_qvector_eq_based_methods(config,
-                                   &["QTextLayout::FormatRange",
-                                     "QAbstractTextDocumentLayout::Selection"]);
-  exclude_qlist_eq_based_methods(config,
-                                 &["QInputMethodEvent::Attribute",
-                                   "QTextLayout::FormatRange",
-                                   "QTouchEvent::TouchPoint"]);
   config.add_cpp_ffi_generator_filter(|method| {
     if let Some(ref info) = method.class_membership {
       match info.class_type.to_cpp_pseudo_code().as_ref() {
-        "QQueue<QInputMethodEvent::Attribute>" |
-        "QQueue<QTextLayout::FormatRange>" |
-        "QQueue<QTouchEvent::TouchPoint>" => {
-          match method.name.as_ref() {
-            "operator==" | "operator!=" => return Ok(false),
-            _ => {}
-          }
-        }
-        "QStack<QInputMethodEvent::Attribute>" |
-        "QStack<QTextLayout::FormatRange>" => {
-          match method.name.as_ref() {
-            "operator==" | "operator!=" | "fromList" => return Ok(false),
-            _ => {}
-          }
-        }
         "QOpenGLVersionFunctionsStorage" => {
           match method.name.as_ref() {
             "QOpenGLVersionFunctionsStorage" |
@@ -320,31 +243,12 @@ pub fn widgets(config: &mut Config) -> Result<()> {
 
   // TODO: Mac specific:
   config.add_cpp_parser_blocked_names(vec!["QMacCocoaViewContainer", "QMacNativeWidget"]);
-
-  exclude_qlist_eq_based_methods(config,
-                                 &["QTableWidgetSelectionRange", "QTextEdit::ExtraSelection"]);
-  config.add_cpp_ffi_generator_filter(|method| {
-    if let Some(ref info) = method.class_membership {
-      match info.class_type.to_cpp_pseudo_code().as_ref() {
-        "QQueue<QTableWidgetSelectionRange>" |
-        "QQueue<QTextEdit::ExtraSelection>" => {
-          match method.name.as_ref() {
-            "operator==" | "operator!=" => return Ok(false),
-            _ => {}
-          }
-        }
-        _ => {}
-      }
-    }
-    Ok(true)
-  });
   Ok(())
 }
 
 /// Qt3DCore specific configuration.
 pub fn core_3d(config: &mut Config) -> Result<()> {
   config.add_cpp_filtered_namespace("Qt3DCore");
-  exclude_qvector_eq_based_methods(config, &["Qt3DCore::QNodeIdTypePair"]);
   Ok(())
 }
 